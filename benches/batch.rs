@@ -0,0 +1,52 @@
+//! Batched seven card scoring against scoring the same hands one at a time:
+//! `cargo bench --bench batch`
+
+use poker::card::*;
+use poker::direct::*;
+use poker::eval::*;
+use poker::eval_batch::*;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::{hint::black_box, time::{Duration, Instant}};
+
+const HANDS: usize = 1_000_000;
+
+/// Best time of a few runs, the first warming up the tables
+fn time(mut run: impl FnMut()) -> Duration {
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut deck = Card::get_deck();
+    let hands: Vec<[Card; 7]> = (0..HANDS)
+        .map(|_| {
+            deck.shuffle(&mut rng);
+            deck[..7].try_into().unwrap()
+        })
+        .collect();
+    let sevens: Sevens = hands.iter().copied().collect();
+    let mut strengths = vec![Strength::default(); HANDS];
+
+    let scalar = time(|| {
+        for hand in &hands {
+            black_box(get_best_score(&(hand[0], hand[1]), &hand[2..]));
+        }
+    });
+    let direct = time(|| {
+        for hand in &hands {
+            black_box(strength(hand));
+        }
+    });
+    let batch = time(|| strength_batch(black_box(&sevens), &mut strengths));
+    let per_hand = |elapsed: Duration| elapsed.as_nanos() as f64 / HANDS as f64;
+    println!("get_best_score  {:6.1} ns a hand", per_hand(scalar));
+    println!("strength        {:6.1} ns a hand", per_hand(direct));
+    println!("strength_batch  {:6.1} ns a hand, {:.1}x get_best_score", per_hand(batch), scalar.as_secs_f64() / batch.as_secs_f64());
+}
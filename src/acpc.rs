@@ -0,0 +1,281 @@
+//! Client side of the Annual Computer Poker Competition protocol, so a Strategy can play matches
+//! against other bots through an ACPC dealer. No limit games only.
+//!
+//! After sending `VERSION:2.0.0` the client is sent a line for every change in a hand's state:
+//! `MATCHSTATE:<position>:<hand number>:<betting>:<cards>`, e.g.
+//! `MATCHSTATE:0:7:r300c/cr900:Ks8h|/Qs7h2c`, where the betting is c (check or call), f and
+//! r<total put in over the hand> with a / between rounds, and the cards are each position's
+//! hole cards (only our own until a showdown) separated by |, then the board. When it's our
+//! turn the reply is the same line followed by `:<action>`.
+//!
+//! Each state is replayed into a HandState from the start of the hand, positions as seats with
+//! the button last: that posts the blinds (big then small heads up, small then big otherwise)
+//! and orders the action as ACPC's standard games do
+
+use crate::card::*;
+use crate::game::*;
+use crate::json::*;
+use crate::strategy::*;
+use std::{fmt::{Display, Formatter}, io::{self, BufRead, BufReader, Write}, net::{TcpStream, ToSocketAddrs}, str::FromStr};
+
+
+/// The game the dealer is running, which the protocol doesn't say
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AcpcGame {
+    pub players: usize,
+    /// Every player starts every hand with this
+    pub stack: Chips,
+    pub small_blind: Chips,
+    pub big_blind: Chips,
+}
+
+impl Default for AcpcGame {
+    /// Heads up no limit as played in the competition
+    fn default() -> Self {
+        AcpcGame { players: 2, stack: 20000, small_blind: 50, big_blind: 100 }
+    }
+}
+
+/// A state of a hand as the dealer sent it
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MatchState {
+    /// Ours
+    pub position: usize,
+    pub hand_number: usize,
+    pub betting: String,
+    /// Each position's hole cards, if shown to us
+    pub holes: Vec<Option<(Card, Card)>>,
+    pub board: Vec<Card>,
+}
+
+impl FromStr for MatchState {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.trim().split(':').collect();
+        let ["MATCHSTATE", position, hand_number, betting, cards] = fields[..] else {
+            return Err("Expected MATCHSTATE:<position>:<hand number>:<betting>:<cards>");
+        };
+        let mut parts = cards.split('/');
+        let holes = parts
+            .next()
+            .unwrap_or_default()
+            .split('|')
+            .map(|hole| match parse_cards(hole)?[..] {
+                [] => Ok(None),
+                [a, b] => Ok(Some((a, b))),
+                _ => Err("Expected two hole cards"),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let board = parse_cards(&parts.collect::<String>())?;
+        let position: usize = position.parse().map_err(|_| "Invalid position")?;
+        if position >= holes.len() || holes[position].is_none() {
+            return Err("Missing our hole cards");
+        }
+        Ok(MatchState {
+            position,
+            hand_number: hand_number.parse().map_err(|_| "Invalid hand number")?,
+            betting: betting.to_string(),
+            holes,
+            board,
+        })
+    }
+}
+
+impl Display for MatchState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let holes: Vec<String> = self.holes.iter().map(|hole| hole.map_or(String::new(), |(a, b)| format_cards(&[a, b]))).collect();
+        write!(f, "MATCHSTATE:{}:{}:{}:{}", self.position, self.hand_number, self.betting, holes.join("|"))?;
+        let mut board = &self.board[..];
+        for n in [3, 1, 1] {
+            if board.is_empty() {
+                break;
+            }
+            write!(f, "/{}", format_cards(&board[..n.min(board.len())]))?;
+            board = &board[n.min(board.len())..];
+        }
+        Ok(())
+    }
+}
+
+impl MatchState {
+    /// The hand so far. Cards not shown to us are made up from what's left of the deck, which
+    /// only matters to the winnings if the hand went to a showdown we didn't see
+    pub fn replay(&self, game: &AcpcGame) -> Result<HandState, &'static str> {
+        let n = game.players;
+        if self.holes.len() != n {
+            return Err("Number of players doesn't match the game");
+        }
+        let shown: Vec<Card> = self.holes.iter().flatten().flat_map(|&(a, b)| [a, b]).chain(self.board.iter().copied()).collect();
+        let mut unseen = Card::DECK.into_iter().filter(|card| !shown.contains(card));
+        let mut deck = Vec::new();
+        for hole in &self.holes {
+            match hole {
+                Some((a, b)) => deck.extend([*a, *b]),
+                None => deck.extend(unseen.by_ref().take(2)),
+            }
+        }
+        deck.extend(&self.board);
+        deck.extend(unseen);
+
+        let mut state = HandState::with_deck(&vec![game.stack; n], n - 1, game.small_blind, game.big_blind, deck);
+        let mut betting = self.betting.chars().peekable();
+        let mut round = 0;
+        while let Some(c) = betting.next() {
+            if c == '/' {
+                round += 1;
+                continue;
+            }
+            let seat = state.to_act().ok_or("Action after the hand is over")?;
+            if usize::from(state.street) != round {
+                return Err("Betting rounds don't match the hand");
+            }
+            let action = match c {
+                'f' => Action::Fold,
+                'c' if state.to_call(seat) == 0 => Action::Check,
+                'c' => Action::Call,
+                'r' => {
+                    let mut digits = String::new();
+                    while let Some(digit) = betting.next_if(char::is_ascii_digit) {
+                        digits.push(digit);
+                    }
+                    let total: Chips = digits.parse().map_err(|_| "Invalid raise")?;
+                    let seat = &state.seats[seat];
+                    let before = seat.contributed - seat.bet;
+                    Action::Raise(total.checked_sub(before).ok_or("Invalid raise")?)
+                }
+                _ => return Err("Invalid betting"),
+            };
+            state.act(action)?;
+        }
+        Ok(state)
+    }
+}
+
+/// An action as ACPC has it, checked against the hand: raises are to the total put in over the
+/// hand, and an all in that can't raise is a call
+pub fn encode_action(state: &HandState, action: Action) -> Result<String, &'static str> {
+    let seat = state.to_act().ok_or("the hand is over")?;
+    state.clone().act(action)?;
+    let before = state.seats[seat].contributed - state.seats[seat].bet;
+    Ok(match action {
+        Action::Fold => "f".to_string(),
+        Action::Check | Action::Call => "c".to_string(),
+        Action::AllIn if state.max_raise_to(seat) <= state.current_bet => "c".to_string(),
+        Action::AllIn => format!("r{}", before + state.max_raise_to(seat)),
+        Action::Raise(to) => format!("r{}", before + to),
+    })
+}
+
+/// The reply to a line from the dealer: None if it isn't our turn. Actions the hand doesn't
+/// allow are replaced by checking or calling
+pub fn respond(line: &str, game: &AcpcGame, strategy: &mut dyn Strategy) -> Result<Option<String>, &'static str> {
+    let match_state: MatchState = line.parse()?;
+    let state = match_state.replay(game)?;
+    if state.to_act() != Some(match_state.position) {
+        return Ok(None);
+    }
+    let action = strategy.act(&state.view(match_state.position));
+    let encoded = encode_action(&state, action).or_else(|_| encode_action(&state, Action::Call)).or_else(|_| encode_action(&state, Action::Check))?;
+    Ok(Some(format!("{}:{}", line.trim(), encoded)))
+}
+
+/// How a match went
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct MatchResult {
+    pub hands: usize,
+    /// Chips won over every hand, less what was put in
+    pub winnings: i64,
+}
+
+impl ToJson for MatchResult {
+    fn to_json(&self) -> Json {
+        Json::object([("hands", self.hands.into()), ("winnings", (self.winnings as f64).into())])
+    }
+}
+
+/// Play a match with the dealer at addr until it hangs up
+pub fn play_match(addr: impl ToSocketAddrs, game: &AcpcGame, strategy: &mut dyn Strategy) -> io::Result<MatchResult> {
+    let invalid = |e: &str| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+    let mut stream = TcpStream::connect(addr)?;
+    stream.write_all(b"VERSION:2.0.0\r\n")?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut result = MatchResult::default();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(result);
+        }
+        // comments and the dealer's own messages
+        if line.starts_with('#') || line.starts_with(';') || line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = respond(&line, game, strategy).map_err(invalid)? {
+            stream.write_all(format!("{}\r\n", reply).as_bytes())?;
+        }
+        let match_state: MatchState = line.parse().map_err(invalid)?;
+        let state = match_state.replay(game).map_err(invalid)?;
+        if let Some(winnings) = state.winnings() {
+            let seat = match_state.position;
+            result.hands += 1;
+            result.winnings += winnings[seat] as i64 - state.seats[seat].contributed as i64;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::street::*;
+    use std::net::TcpListener;
+
+    /// Raises the pot once a street and calls after that
+    struct Raiser;
+
+    impl Strategy for Raiser {
+        fn act(&mut self, state: &GameView) -> Action {
+            match state.min_raise_to {
+                Some(_) if state.street != Street::Preflop && state.to_call == 0 => Action::Raise(state.pot),
+                _ if state.to_call == 0 => Action::Check,
+                _ => Action::Call,
+            }
+        }
+    }
+
+    #[test]
+    fn test_acpc() {
+        let game = AcpcGame::default();
+        let line = "MATCHSTATE:0:0:r300c/cr900:Ks8h|/Qs7h2c";
+        let state: MatchState = line.parse().unwrap();
+        assert_eq!((state.holes[1], state.board.len()), (None, 3));
+        assert_eq!(state.to_string(), line);
+        // the small blind raised to 300 and bet 600 on the flop
+        let hand = state.replay(&game).unwrap();
+        assert_eq!((hand.pot(), hand.to_call(0)), (1200, 600));
+        assert_eq!(respond(line, &game, &mut AlwaysCall), Ok(Some(format!("{}:c", line))));
+        assert_eq!(respond("MATCHSTATE:0:0:r300:Ks8h|", &game, &mut AlwaysCall), Ok(Some("MATCHSTATE:0:0:r300:Ks8h|:c".to_string())));
+        // first to act after the flop, betting 600 into 600 makes 900 in all
+        assert_eq!(respond("MATCHSTATE:0:0:r300c/:Ks8h|/Qs7h2c", &game, &mut Raiser), Ok(Some("MATCHSTATE:0:0:r300c/:Ks8h|/Qs7h2c:r900".to_string())));
+        assert_eq!(respond("MATCHSTATE:1:0:r300:|Ks8h", &game, &mut AlwaysCall), Ok(None));
+        assert!(respond("MATCHSTATE:0:0:r300cc:Ks8h|", &game, &mut AlwaysCall).is_err());
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let dealer = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            assert_eq!(line, "VERSION:2.0.0\r\n");
+            stream.write_all(b"# a comment\r\nMATCHSTATE:0:0:r300:Ks8h|\r\n").unwrap();
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+            assert_eq!(line, "MATCHSTATE:0:0:r300:Ks8h|:c\r\n");
+            stream.write_all(b"MATCHSTATE:0:0:r300c/cc/cc/cc:Ks8h|AsAd/Qs7h2c/3d/4c\r\n").unwrap();
+        });
+        let result = play_match(addr, &game, &mut AlwaysCall).unwrap();
+        dealer.join().unwrap();
+        assert_eq!(result, MatchResult { hands: 1, winnings: -300 });
+    }
+}
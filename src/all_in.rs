@@ -0,0 +1,162 @@
+use crate::card::*;
+use crate::eval::*;
+use crate::history::*;
+use crate::pots::*;
+use crate::street::*;
+use itertools::Itertools;
+use rand::{seq::IndexedRandom, Rng};
+
+
+/// How the pots stood when the last chips went in with cards still to come
+#[derive(Debug, PartialEq, Clone)]
+pub struct AllIn {
+    pub street: Street,
+    /// What each player still in could expect to collect from the pots, after the rake, by
+    /// averaging over the runouts. In the order of the hand's players
+    pub expected: Vec<(String, f64)>,
+}
+
+impl AllIn {
+    pub fn expected(&self, name: &str) -> f64 {
+        self.expected.iter().filter(|(player, _)| player == name).map(|(_, expected)| expected).sum()
+    }
+}
+
+/// The all in of a hand, if the betting ended before the river and went to a showdown where
+/// every hand still in is known (as the hero's or shown). Exact from the flop, and over this
+/// many random runouts before it
+pub fn find_all_in<R: Rng + ?Sized>(hand: &HandRecord, samples: usize, rng: &mut R) -> Option<AllIn> {
+    let street = hand.actions.iter().rev().find(|action| !matches!(action.play, Play::Returned(_)))?.street;
+    if street == Street::River || hand.board.len() < 5 {
+        return None;
+    }
+    let contributed: Vec<u64> = hand.players.iter().map(|player| hand.put_in(&player.name)).collect();
+    let folded: Vec<bool> = hand.players
+        .iter()
+        .zip(&contributed)
+        .map(|(player, &put_in)| {
+            put_in == 0 || hand.actions.iter().any(|action| action.player == player.name && action.play == Play::Fold)
+        })
+        .collect();
+    let holes: Vec<Option<(Card, Card)>> = hand.players
+        .iter()
+        .zip(&folded)
+        .map(|(player, &folded)| {
+            let known = hand.hero.iter().chain(&hand.shown).find(|(name, _)| *name == player.name);
+            (!folded).then(|| known.map(|&(_, hole)| hole)).flatten()
+        })
+        .collect();
+    if folded.iter().filter(|&&folded| !folded).count() < 2 || (0..holes.len()).any(|i| !folded[i] && holes[i].is_none()) {
+        return None;
+    }
+
+    let board = &hand.board[..street.board_len()];
+    let known: Vec<Card> = holes.iter().flatten().flat_map(|&(a, b)| [a, b]).collect();
+    let deck = deck_without(&[board, &known].concat());
+    let pots = side_pots(&contributed, &folded);
+    let mut collected = vec![0.0; hand.players.len()];
+    let mut runouts = 0;
+    let mut showdown = |runout: &[Card]| {
+        let community = [board, runout].concat();
+        let scores: Vec<_> = holes.iter().map(|hole| hole.map(|pair| get_best_score(&pair, &community))).collect();
+        // the split doesn't matter to the average beyond a chip, so the odd chips go anywhere
+        for (total, won) in collected.iter_mut().zip(award_pots(&pots, &scores, OddChip::LeftOfButton(0))) {
+            *total += won as f64;
+        }
+        runouts += 1;
+    };
+    if board.len() >= 3 {
+        deck.iter().copied().combinations(5 - board.len()).for_each(|runout| showdown(&runout));
+    } else {
+        for _ in 0..samples {
+            let runout: Vec<Card> = deck.choose_multiple(rng, 5).copied().collect();
+            showdown(&runout);
+        }
+    }
+
+    let pot: u64 = contributed.iter().sum();
+    let raked: u64 = hand.collected.iter().map(|(_, amount)| amount).sum();
+    let kept = if pot > 0 && raked > 0 { raked as f64 / pot as f64 } else { 1.0 };
+    let expected = hand.players
+        .iter()
+        .zip(&collected)
+        .zip(&folded)
+        .filter(|(_, folded)| !**folded)
+        .map(|((player, &won), _)| (player.name.clone(), won / runouts as f64 * kept))
+        .collect();
+    Some(AllIn { street, expected })
+}
+
+/// One hand on the EV line: what the player actually won and what they'd have won on average
+/// had any all in been run out every way
+#[derive(Debug, PartialEq, Clone)]
+pub struct EvPoint {
+    pub id: String,
+    pub actual: i64,
+    /// The same as actual without an all in
+    pub expected: f64,
+    pub all_in: bool,
+}
+
+/// Actual and all in adjusted winnings for the player, hand by hand over the hands they played
+pub fn ev_line<R: Rng + ?Sized>(hands: &[HandRecord], name: &str, samples: usize, rng: &mut R) -> Vec<EvPoint> {
+    hands
+        .iter()
+        .filter(|hand| hand.player(name).is_some())
+        .map(|hand| {
+            let actual = hand.net(name);
+            match find_all_in(hand, samples, rng) {
+                Some(all_in) if all_in.expected.iter().any(|(player, _)| player == name) => {
+                    let expected = all_in.expected(name) - hand.put_in(name) as f64;
+                    EvPoint { id: hand.id.clone(), actual, expected, all_in: true }
+                }
+                _ => EvPoint { id: hand.id.clone(), actual, expected: actual as f64, all_in: false },
+            }
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND: &str = "\
+PokerStars Hand #7: Hold'em No Limit (50/100) - 2020/01/01 12:00:00 ET
+Table 'Beta' 6-max Seat #1 is the button
+Seat 1: alice (10000 in chips)
+Seat 2: bob (10000 in chips)
+alice: posts small blind 50
+bob: posts big blind 100
+*** HOLE CARDS ***
+Dealt to alice [Ah Ad]
+alice: raises 200 to 300
+bob: calls 200
+*** FLOP *** [Ks 7c 2d]
+bob: bets 400
+alice: raises 9300 to 9700 and is all-in
+bob: calls 9300
+*** TURN *** [Ks 7c 2d] [Kh]
+*** RIVER *** [Ks 7c 2d Kh] [Kd]
+*** SHOW DOWN ***
+bob: shows [Kc Qc] (four of a kind, Kings)
+alice: shows [Ah Ad] (a full house, Aces full of Kings)
+bob collected 19900 from pot
+*** SUMMARY ***
+";
+
+    #[test]
+    fn test_ev_line() {
+        let hand = PokerStars.parse_hand(HAND).unwrap();
+        let all_in = find_all_in(&hand, 0, &mut rand::rng()).unwrap();
+        assert_eq!(all_in.street, Street::Flop);
+        // king queen has five outs twice to the aces, so about a fifth of the pot
+        let bob = all_in.expected("bob");
+        assert!(bob > 0.15 * 19900.0 && bob < 0.25 * 19900.0);
+        assert!((all_in.expected("alice") + bob - 19900.0).abs() < 1e-6);
+
+        let line = ev_line(&[hand], "alice", 0, &mut rand::rng());
+        assert_eq!(line[0].actual, -10000);
+        assert!(line[0].all_in && line[0].expected > 5000.0 && line[0].expected < 7000.0);
+    }
+}
@@ -0,0 +1,104 @@
+//! Proptest strategies, with the `proptest` feature: Arbitrary for the card types, classes and
+//! ranges, and strategies for cards that have to be distinct, like a board or a whole deal
+
+use crate::card::*;
+use crate::class::*;
+use crate::range::*;
+use proptest::{prelude::*, sample::{select, subsequence, SizeRange}};
+
+
+impl Arbitrary for Rank {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Rank>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        select(Rank::ALL_RANKS.to_vec()).boxed()
+    }
+}
+
+impl Arbitrary for Suit {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Suit>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        select(Suit::ALL_SUITS.to_vec()).boxed()
+    }
+}
+
+impl Arbitrary for Card {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Card>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        select(Card::DECK.to_vec()).boxed()
+    }
+}
+
+impl Arbitrary for HandClass {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<HandClass>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (0..HandClass::COUNT).prop_map(HandClass::from_index).boxed()
+    }
+}
+
+/// Up to twenty classes, each at a weight of a quarter, a half, three quarters or all of it
+impl Arbitrary for HandRange {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<HandRange>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop::collection::vec((any::<HandClass>(), select(vec![0.25, 0.5, 0.75, 1.0])), 0..20)
+            .prop_map(|classes| {
+                let mut range = HandRange::new();
+                for (class, weight) in classes {
+                    for pair in class.combos() {
+                        range.insert(pair, weight);
+                    }
+                }
+                range
+            })
+            .boxed()
+    }
+}
+
+/// Different cards, as many as size allows, in any order
+pub fn distinct_cards(size: impl Into<SizeRange>) -> impl Strategy<Value = Vec<Card>> {
+    subsequence(Card::DECK.to_vec(), size).prop_shuffle()
+}
+
+/// A board from preflop to the river: none, three, four or five different cards
+pub fn boards() -> impl Strategy<Value = Vec<Card>> {
+    select(vec![0, 3, 4, 5]).prop_flat_map(distinct_cards)
+}
+
+/// Hole cards for each of the players and a board, no card dealt twice
+pub fn deals(players: usize) -> impl Strategy<Value = (Vec<(Card, Card)>, Vec<Card>)> {
+    select(vec![0, 3, 4, 5]).prop_flat_map(move |board| {
+        distinct_cards(2 * players + board).prop_map(move |cards| {
+            let pairs = cards[..2 * players].chunks(2).map(|pair| (pair[0], pair[1])).collect();
+            (pairs, cards[2 * players..].to_vec())
+        })
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::*;
+    use crate::eval_batch::*;
+
+    proptest! {
+        #[test]
+        fn test_arbitrary(card: Card, range: HandRange, board in boards(), (pairs, deal_board) in deals(3), hand in distinct_cards(7)) {
+            prop_assert_eq!(card.symbol().parse::<Card>(), Ok(card));
+            prop_assert_eq!(range.to_string().parse::<HandRange>(), Ok(range));
+            prop_assert_eq!(parse_cards(&format_cards(&board)), Ok(board));
+            prop_assert!(is_valid_deal(&deal_board, &pairs));
+            let mask = hand.iter().fold(0, |mask, &card| mask | card_bit(card));
+            prop_assert_eq!(score_mask(mask), get_best_score(&(hand[0], hand[1]), &hand[2..]));
+        }
+    }
+}
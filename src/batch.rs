@@ -0,0 +1,198 @@
+//! Batch equities: many spots read from a CSV or JSON lines file, worked out across threads a
+//! spot at a time, and written back out with their results in the same format, e.g.
+//!
+//! ```text
+//! hand,board,range,opponents
+//! AhKh,2c7d9s,"TT+,AQs+",1
+//! QsQd,,,3
+//! ```
+//!
+//! Only the hand is needed; the board defaults to preflop, the range to any two cards and
+//! opponents to 1, each dealt from the same range
+
+use crate::card::*;
+use crate::eval::*;
+use crate::json::*;
+use crate::monte_carlo::*;
+use crate::range::*;
+use std::sync::{atomic::{AtomicUsize, Ordering}, Mutex};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BatchSpot {
+    pub hand: (Card, Card),
+    pub board: Vec<Card>,
+    pub range: HandRange,
+    pub opponents: usize,
+}
+
+impl BatchSpot {
+    fn new(hand: &str, board: &str, range: &str, opponents: &str) -> Result<BatchSpot, &'static str> {
+        let hand = match parse_cards(hand)?[..] {
+            [a, b] => (a, b),
+            _ => return Err("Expected two hole cards"),
+        };
+        let board = parse_cards(board)?;
+        let range = if range.trim().is_empty() { HandRange::full() } else { range.parse()? };
+        let opponents = if opponents.trim().is_empty() { 1 } else { opponents.trim().parse().map_err(|_| "Invalid opponents")? };
+        if !is_valid_deal(&board, &[hand]) || !matches!(board.len(), 0 | 3 | 4 | 5) {
+            return Err("Invalid deal");
+        }
+        if !(1..=9).contains(&opponents) {
+            return Err("Opponents has to be 1-9");
+        }
+        Ok(BatchSpot { hand, board, range, opponents })
+    }
+
+    pub fn equity<R: Rng + ?Sized>(&self, rule: &StopRule, rng: &mut R) -> Result<Estimate, &'static str> {
+        eval_vs_ranges(&self.hand, &vec![self.range.clone(); self.opponents], &self.board, &[], rule, rng)
+    }
+}
+
+/// Spots from CSV with a header naming the columns (hand, and optionally board, range and
+/// opponents, in any order); fields with commas, like most ranges, go in double quotes
+pub fn parse_batch_csv(text: &str) -> Result<Vec<BatchSpot>, String> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let header = lines.next().map(|(_, line)| csv_fields(line)).unwrap_or_default();
+    let column = |name: &str| header.iter().position(|field| field.eq_ignore_ascii_case(name));
+    let hand = column("hand").ok_or("missing a hand column")?;
+    let (board, range, opponents) = (column("board"), column("range"), column("opponents"));
+
+    lines
+        .map(|(number, line)| {
+            let fields = csv_fields(line);
+            let field = |i: Option<usize>| i.and_then(|i| fields.get(i)).map_or("", String::as_str);
+            BatchSpot::new(field(Some(hand)), field(board), field(range), field(opponents))
+                .map_err(|e| format!("line {}: {}", number + 1, e))
+        })
+        .collect()
+}
+
+/// Spots from JSON lines, an object per line with the same keys as the CSV columns
+pub fn parse_batch_jsonl(text: &str) -> Result<Vec<BatchSpot>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            let invalid = |e: &str| format!("line {}: {}", number + 1, e);
+            let object = Json::parse(line).map_err(invalid)?;
+            let field = |key: &str| match object.get(key) {
+                Some(Json::Number(x)) => x.to_string(),
+                Some(value) => value.as_str().unwrap_or_default().to_string(),
+                None => String::new(),
+            };
+            BatchSpot::new(&field("hand"), &field("board"), &field("range"), &field("opponents")).map_err(invalid)
+        })
+        .collect()
+}
+
+/// Every spot's equity, in order, with the spots shared out between threads. An error naming the
+/// first spot whose ranges can't be dealt, if any
+pub fn run_batch<R: Rng + ?Sized>(spots: &[BatchSpot], rule: &StopRule, threads: usize, rng: &mut R) -> Result<Vec<Estimate>, String> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; spots.len()]);
+    let seeds: Vec<u64> = (0..threads.max(1)).map(|_| rng.random()).collect();
+    std::thread::scope(|scope| {
+        for &seed in &seeds {
+            let (next, results) = (&next, &results);
+            scope.spawn(move || {
+                let mut rng = StdRng::seed_from_u64(seed);
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(spot) = spots.get(i) else {
+                        return;
+                    };
+                    let estimate = spot.equity(rule, &mut rng);
+                    results.lock().unwrap()[i] = Some(estimate);
+                }
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        .map(|(i, estimate)| estimate.expect("a spot was skipped").map_err(|e| format!("spot {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// The spots and their results as CSV, with a header
+pub fn format_batch_csv(spots: &[BatchSpot], results: &[Estimate]) -> String {
+    let mut out = String::from("hand,board,range,opponents,equity,std_error,samples\n");
+    for (spot, estimate) in spots.iter().zip(results) {
+        let range = spot.range.to_string();
+        out += &format!("{},{},{},{},{},{},{}\n", format_cards(&[spot.hand.0, spot.hand.1]), format_cards(&spot.board),
+                        if range.contains(',') { format!("\"{}\"", range) } else { range }, spot.opponents,
+                        estimate.equity, estimate.std_error, estimate.tally.samples());
+    }
+    out
+}
+
+/// The spots and their results as JSON lines
+pub fn format_batch_jsonl(spots: &[BatchSpot], results: &[Estimate]) -> String {
+    spots
+        .iter()
+        .zip(results)
+        .map(|(spot, estimate)| {
+            Json::object([
+                ("hand", format_cards(&[spot.hand.0, spot.hand.1]).into()),
+                ("board", format_cards(&spot.board).into()),
+                ("range", spot.range.to_string().into()),
+                ("opponents", spot.opponents.into()),
+                ("equity", estimate.equity.into()),
+                ("std_error", estimate.std_error.into()),
+                ("samples", estimate.tally.samples().into()),
+            ]).to_string() + "\n"
+        })
+        .collect()
+}
+
+/// Fields of a CSV line, unquoting those in double quotes (where "" is a quote)
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields.iter().map(|field| field.trim().to_string()).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch() {
+        let csv = "hand,board,range,opponents\nAhKh,2c7d9s,\"TT+,AQs+\",1\n\nQsQd,,,3\n";
+        let spots = parse_batch_csv(csv).unwrap();
+        assert_eq!(spots.len(), 2);
+        assert_eq!(spots[0].range, "TT+,AQs+".parse().unwrap());
+        assert_eq!((spots[1].board.len(), spots[1].range.len(), spots[1].opponents), (0, 1326, 3));
+        let jsonl = "{\"hand\": \"AhKh\", \"board\": \"2c7d9s\", \"range\": \"TT+,AQs+\", \"opponents\": 1}\n{\"hand\": \"QsQd\", \"opponents\": 3}\n";
+        assert_eq!(parse_batch_jsonl(jsonl).unwrap(), spots);
+        assert!(parse_batch_csv("hand,opponents\nAhKh,12\n").is_err());
+        assert!(parse_batch_csv("board\n2c7d9s\n").is_err());
+
+        let rule = StopRule { half_width: 0.0, max_samples: 2000, ..StopRule::default() };
+        let results = run_batch(&spots, &rule, 2, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(results[1].tally.samples(), 2000);
+        // queens against three random hands win about 50% of pots
+        assert!((results[1].equity - 0.5).abs() < 0.05);
+        let out = format_batch_csv(&spots, &results);
+        assert!(out.lines().nth(1).unwrap().starts_with("AhKh,2c7d9s,\"TT+,AQs+\",1,"));
+        assert_eq!(parse_batch_csv(&out).unwrap(), spots);
+        assert_eq!(parse_batch_jsonl(&format_batch_jsonl(&spots, &results)).unwrap(), spots);
+    }
+}
@@ -0,0 +1,201 @@
+//! Hand abstraction for the solver: hands on a board are bucketed by how their equity against
+//! a range is spread over the runouts (distribution-aware abstraction), so a draw and a weak
+//! made hand with the same average equity end up apart. The histograms are clustered by
+//! k-means under the earth mover's distance
+
+use crate::card::*;
+use crate::eval::*;
+use crate::json::*;
+use crate::range::*;
+use std::collections::BTreeMap;
+use itertools::Itertools;
+use rand::{seq::IndexedRandom, Rng};
+
+
+/// Histogram of the pair's equity against the range on the river, over every runout of the board
+/// (or this many random ones when there are more), with bins equal slices of 0-1
+pub fn equity_histograms<R: Rng + ?Sized>(pairs: &[(Card, Card)], board: &[Card], range: &HandRange, bins: usize,
+                                         runouts: usize, rng: &mut R) -> Vec<Vec<f64>> {
+    let deck = deck_without(board);
+    let all: Vec<Vec<Card>> = deck.iter().copied().combinations(5 - board.len()).collect();
+    let runouts: Vec<&Vec<Card>> = if all.len() <= runouts { all.iter().collect() } else { all.choose_multiple(rng, runouts).collect() };
+
+    let mut histograms = vec![vec![0.0; bins]; pairs.len()];
+    let mut community = board.to_vec();
+    for runout in runouts {
+        community.extend_from_slice(runout);
+        // the range's scores once, then each pair against them
+        let villains: Vec<((Card, Card), f64, _)> = range
+            .without(&community)
+            .combos()
+            .map(|(pair, weight)| (pair, weight, get_best_score(&pair, &community)))
+            .collect();
+        for (pair, histogram) in pairs.iter().zip(&mut histograms) {
+            if community.contains(&pair.0) || community.contains(&pair.1) {
+                continue;
+            }
+            let score = get_best_score(pair, &community);
+            let (mut points, mut total) = (0.0, 0.0);
+            for (villain, weight, villain_score) in &villains {
+                if [villain.0, villain.1].iter().any(|card| *card == pair.0 || *card == pair.1) {
+                    continue;
+                }
+                // lower scores are better
+                points += weight * match score.cmp(villain_score) {
+                    std::cmp::Ordering::Less => 2.0,
+                    std::cmp::Ordering::Equal => 1.0,
+                    std::cmp::Ordering::Greater => 0.0,
+                };
+                total += 2.0 * weight;
+            }
+            if total > 0.0 {
+                let equity = points / total;
+                histogram[((equity * bins as f64) as usize).min(bins - 1)] += 1.0;
+            }
+        }
+        community.truncate(board.len());
+    }
+    for histogram in &mut histograms {
+        let total: f64 = histogram.iter().sum();
+        if total > 0.0 {
+            histogram.iter_mut().for_each(|count| *count /= total);
+        }
+    }
+    histograms
+}
+
+/// Earth mover's distance between two histograms over the same bins: how much probability has
+/// to move how far to turn one into the other, which in one dimension is the gap between their
+/// running totals
+pub fn earth_movers(a: &[f64], b: &[f64]) -> f64 {
+    let (mut carried, mut distance) = (0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        carried += x - y;
+        distance += f64::abs(carried);
+    }
+    distance
+}
+
+/// k-means under the earth mover's distance, started with k-means++. Returns the cluster of
+/// each point and the cluster centres
+pub fn k_means<R: Rng + ?Sized>(points: &[Vec<f64>], k: usize, iterations: usize, rng: &mut R) -> (Vec<usize>, Vec<Vec<f64>>) {
+    let k = k.min(points.len()).max(1);
+    let nearest = |point: &Vec<f64>, centres: &[Vec<f64>]| {
+        centres
+            .iter()
+            .map(|centre| earth_movers(point, centre))
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0))
+    };
+
+    // each new centre is picked with probability by its squared distance from the nearest one
+    let mut centres = vec![points.choose(rng).expect("nothing to cluster").clone()];
+    while centres.len() < k {
+        let weights: Vec<f64> = points.iter().map(|point| nearest(point, &centres).1.powi(2)).collect();
+        let total: f64 = weights.iter().sum();
+        if total == 0.0 {
+            break;
+        }
+        let mut pick = rng.random::<f64>() * total;
+        let i = weights.iter().position(|w| {
+            pick -= w;
+            pick < 0.0
+        });
+        centres.push(points[i.unwrap_or(points.len() - 1)].clone());
+    }
+
+    let mut clusters = vec![0; points.len()];
+    for _ in 0..iterations {
+        let assigned: Vec<usize> = points.iter().map(|point| nearest(point, &centres).0).collect();
+        let settled = assigned == clusters;
+        clusters = assigned;
+        for (c, centre) in centres.iter_mut().enumerate() {
+            let members: Vec<&Vec<f64>> = points.iter().zip(&clusters).filter(|&(_, &cluster)| cluster == c).map(|(point, _)| point).collect();
+            if !members.is_empty() {
+                *centre = (0..centre.len()).map(|bin| members.iter().map(|point| point[bin]).sum::<f64>() / members.len() as f64).collect();
+            }
+        }
+        if settled {
+            break;
+        }
+    }
+    (clusters, centres)
+}
+
+/// Every combo that can be dealt on a board, mapped to its bucket
+#[derive(Debug, PartialEq, Clone)]
+pub struct Abstraction {
+    pub board: Vec<Card>,
+    pub buckets: BTreeMap<(Card, Card), usize>,
+    /// The equity histogram at the centre of each bucket
+    pub centres: Vec<Vec<f64>>,
+}
+
+impl Abstraction {
+    /// Bucket every combo off the board into k, by equity histograms against the range
+    pub fn new<R: Rng + ?Sized>(board: &[Card], range: &HandRange, k: usize, bins: usize, runouts: usize, rng: &mut R) -> Abstraction {
+        let pairs: Vec<(Card, Card)> = deck_without(board).into_iter().tuple_combinations().map(|(a, b)| (a.max(b), a.min(b))).collect();
+        let histograms = equity_histograms(&pairs, board, range, bins, runouts, rng);
+        let (clusters, centres) = k_means(&histograms, k, 50, rng);
+        Abstraction { board: board.to_vec(), buckets: pairs.into_iter().zip(clusters).collect(), centres }
+    }
+
+    /// None for combos holding a board card
+    pub fn bucket(&self, pair: &(Card, Card)) -> Option<usize> {
+        self.buckets.get(&(pair.0.max(pair.1), pair.0.min(pair.1))).copied()
+    }
+
+    /// Average equity of each bucket's centre, taking each bin at its middle
+    pub fn equities(&self) -> Vec<f64> {
+        self.centres
+            .iter()
+            .map(|centre| centre.iter().enumerate().map(|(bin, p)| p * (bin as f64 + 0.5) / centre.len() as f64).sum())
+            .collect()
+    }
+}
+
+impl ToJson for Abstraction {
+    /// The buckets from the best equity down, each with its combos
+    fn to_json(&self) -> Json {
+        let equities = self.equities();
+        let buckets = (0..equities.len())
+            .sorted_by(|&a, &b| equities[b].total_cmp(&equities[a]))
+            .map(|bucket| Json::object([
+                ("equity", equities[bucket].into()),
+                ("hands", self.buckets.iter().filter(|&(_, &b)| b == bucket).map(|(pair, _)| format!("{}{}", pair.0, pair.1)).collect::<Vec<_>>().into()),
+            ]))
+            .collect();
+        Json::Array(buckets)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets() {
+        assert_eq!(earth_movers(&[1.0, 0.0, 0.0], &[0.0, 0.0, 1.0]), 2.0);
+        assert_eq!(earth_movers(&[0.5, 0.5], &[0.5, 0.5]), 0.0);
+
+        // on the turn a flush draw is spread out where a weak pair is bunched up, though they
+        // have about the same equity
+        let board = parse_cards("Ks9h4h2c").unwrap();
+        let draw = (Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        let pair = (Card::new(Rank::Four, Suit::Diamonds), Card::new(Rank::Three, Suit::Diamonds));
+        let range = HandRange::full();
+        let histograms = equity_histograms(&[draw, pair], &board, &range, 5, 100, &mut rand::rng());
+        assert!(histograms[0][1] > 0.2 && histograms[0][4] > 0.2);
+        assert!(histograms[1][1] == 0.0 && histograms[1][2] > 0.5);
+
+        // a quarter of the combos is plenty to bucket against
+        let sparse = HandRange::from_combos(range.without(&board).combos().step_by(4).map(|(pair, _)| pair));
+        let abstraction = Abstraction::new(&board, &sparse, 6, 5, 100, &mut rand::rng());
+        assert_eq!(abstraction.buckets.len(), 48 * 47 / 2);
+        assert!(abstraction.bucket(&draw).is_some() && abstraction.bucket(&(board[0], board[1])).is_none());
+        let equities = abstraction.equities();
+        assert!(equities[abstraction.bucket(&(Card::new(Rank::King, Suit::Hearts), Card::new(Rank::King, Suit::Clubs))).unwrap()] > 0.8);
+    }
+}
@@ -0,0 +1,152 @@
+//! Charts/strategies keyed by effective stack depth (in big blinds)
+//! so a simulation can pick the one appropriate for the current stacks
+
+/// Something that can be blended between two stack depths
+/// t = 0 gives self, t = 1 gives other
+pub trait Interpolate {
+    fn interpolate(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl<T: Interpolate> Interpolate for Vec<T> {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        debug_assert_eq!(self.len(), other.len());
+        self.iter()
+            .zip(other)
+            .map(|(a, b)| a.interpolate(b, t))
+            .collect()
+    }
+}
+
+impl<T: Interpolate, const N: usize> Interpolate for [T; N] {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        std::array::from_fn(|i| self[i].interpolate(&other[i], t))
+    }
+}
+
+/// Typical tournament depths charts are published for
+pub const STANDARD_DEPTHS: [f64; 4] = [10.0, 20.0, 40.0, 100.0];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Lookup {
+    Nearest,
+    Interpolate,
+}
+
+/// Strategies sorted by stack depth in big blinds
+#[derive(Debug, Clone)]
+pub struct DepthBundle<T> {
+    entries: Vec<(f64, T)>,
+}
+
+impl<T> Default for DepthBundle<T> {
+    fn default() -> Self {
+        DepthBundle { entries: Vec::new() }
+    }
+}
+
+impl<T> DepthBundle<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add (or replace) the strategy for a depth
+    pub fn insert(&mut self, depth_bb: f64, strategy: T) {
+        debug_assert!(depth_bb.is_finite() && depth_bb > 0.0);
+        match self.entries.binary_search_by(|(d, _)| d.total_cmp(&depth_bb)) {
+            Ok(i) => self.entries[i].1 = strategy,
+            Err(i) => self.entries.insert(i, (depth_bb, strategy)),
+        }
+    }
+
+    pub fn depths(&self) -> impl Iterator<Item = f64> + '_ {
+        self.entries.iter().map(|(d, _)| *d)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Strategy stored at the depth closest to depth_bb
+    /// ties go to the shallower stack
+    pub fn nearest(&self, depth_bb: f64) -> Option<&T> {
+        self.entries
+            .iter()
+            .min_by(|(a, _), (b, _)| (a - depth_bb).abs().total_cmp(&(b - depth_bb).abs()))
+            .map(|(_, strategy)| strategy)
+    }
+
+    /// Stored strategies either side of depth_bb with how far between them it sits
+    /// clamps to the shallowest/deepest entry outside the stored range
+    fn bracket(&self, depth_bb: f64) -> Option<(&T, &T, f64)> {
+        let first = self.entries.first()?;
+        let last = self.entries.last()?;
+        if depth_bb <= first.0 {
+            return Some((&first.1, &first.1, 0.0));
+        }
+        if depth_bb >= last.0 {
+            return Some((&last.1, &last.1, 0.0));
+        }
+        let i = self.entries.partition_point(|(d, _)| *d <= depth_bb);
+        let (lo_depth, lo) = &self.entries[i - 1];
+        let (hi_depth, hi) = &self.entries[i];
+        Some((lo, hi, (depth_bb - lo_depth) / (hi_depth - lo_depth)))
+    }
+}
+
+impl<T: Interpolate + Clone> DepthBundle<T> {
+    /// Linear blend of the strategies either side of depth_bb
+    pub fn interpolated(&self, depth_bb: f64) -> Option<T> {
+        self.bracket(depth_bb)
+            .map(|(lo, hi, t)| if t == 0.0 { lo.clone() } else { lo.interpolate(hi, t) })
+    }
+
+    pub fn get(&self, depth_bb: f64, lookup: Lookup) -> Option<T> {
+        match lookup {
+            Lookup::Nearest => self.nearest(depth_bb).cloned(),
+            Lookup::Interpolate => self.interpolated(depth_bb),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle() -> DepthBundle<f64> {
+        let mut bundle = DepthBundle::new();
+        for (depth, value) in STANDARD_DEPTHS.iter().zip([0.5, 0.3, 0.2, 0.1]) {
+            bundle.insert(*depth, value);
+        }
+        bundle
+    }
+
+    #[test]
+    fn test_nearest() {
+        let bundle = bundle();
+        assert_eq!(bundle.nearest(12.0), Some(&0.5));
+        assert_eq!(bundle.nearest(15.0), Some(&0.5));
+        assert_eq!(bundle.nearest(31.0), Some(&0.2));
+        assert_eq!(bundle.nearest(500.0), Some(&0.1));
+        assert_eq!(DepthBundle::<f64>::new().nearest(10.0), None);
+    }
+
+    #[test]
+    fn test_interpolated() {
+        let bundle = bundle();
+        assert_eq!(bundle.interpolated(5.0), Some(0.5));
+        assert_eq!(bundle.interpolated(20.0), Some(0.3));
+        assert!((bundle.interpolated(30.0).unwrap() - 0.25).abs() < 1e-9);
+        assert_eq!(bundle.interpolated(200.0), Some(0.1));
+    }
+}
@@ -0,0 +1,26 @@
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+
+
+/// Shared flag for asking a long evaluation to stop early.
+/// Clones share the flag, so one can be handed to the evaluation and another kept to cancel with
+#[derive(Debug, Default, Clone)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Whether an optional token has been cancelled
+pub fn is_cancelled(cancel: Option<&CancelToken>) -> bool {
+    cancel.is_some_and(CancelToken::is_cancelled)
+}
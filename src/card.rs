@@ -1,6 +1,7 @@
-use std::{fmt::{Display, Formatter}};
+use alloc::{string::String, vec::Vec, format};
+use core::{fmt::{Display, Formatter}, str::FromStr};
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 pub enum Rank {
     Two,
     Three,
@@ -23,25 +24,16 @@ impl Rank {
         Rank::Seven, Rank::Eight, Rank::Nine,  Rank::Ten,   Rank::Jack,
         Rank::Queen, Rank::King,  Rank::Ace,
     ];
+
+    /// Two is 0 up to Ace at 12, as usize::from
+    pub const fn index(self) -> usize {
+        self as usize
+    }
 }
 
 impl From<Rank> for usize {
     fn from(rank: Rank) -> Self {
-        match rank {
-            Rank::Two => 0,
-            Rank::Three => 1,
-            Rank::Four => 2,
-            Rank::Five => 3,
-            Rank::Six => 4,
-            Rank::Seven => 5,
-            Rank::Eight => 6,
-            Rank::Nine => 7,
-            Rank::Ten => 8,
-            Rank::Jack => 9,
-            Rank::Queen => 10,
-            Rank::King => 11,
-            Rank::Ace => 12,
-        }
+        rank.index()
     }
 }
 
@@ -68,8 +60,52 @@ impl TryFrom<usize> for Rank {
     }
 }
 
+impl TryFrom<char> for Rank {
+    type Error = &'static str;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value.to_ascii_uppercase() {
+            '2' => Ok(Rank::Two),
+            '3' => Ok(Rank::Three),
+            '4' => Ok(Rank::Four),
+            '5' => Ok(Rank::Five),
+            '6' => Ok(Rank::Six),
+            '7' => Ok(Rank::Seven),
+            '8' => Ok(Rank::Eight),
+            '9' => Ok(Rank::Nine),
+            'T' => Ok(Rank::Ten),
+            'J' => Ok(Rank::Jack),
+            'Q' => Ok(Rank::Queen),
+            'K' => Ok(Rank::King),
+            'A' => Ok(Rank::Ace),
+            _ => Err("Invalid rank character"),
+        }
+    }
+}
+
+impl Rank {
+    /// Single character used in hand notation (T for Ten)
+    pub fn symbol(&self) -> char {
+        match self {
+            Rank::Two => '2',
+            Rank::Three => '3',
+            Rank::Four => '4',
+            Rank::Five => '5',
+            Rank::Six => '6',
+            Rank::Seven => '7',
+            Rank::Eight => '8',
+            Rank::Nine => '9',
+            Rank::Ten => 'T',
+            Rank::Jack => 'J',
+            Rank::Queen => 'Q',
+            Rank::King => 'K',
+            Rank::Ace => 'A',
+        }
+    }
+}
+
 impl Display for Rank {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", match self {
             Rank::Two => "2",
             Rank::Three => "3",
@@ -89,7 +125,7 @@ impl Display for Rank {
 }
 
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 pub enum Suit {
     Hearts,
     Diamonds,
@@ -104,16 +140,16 @@ impl Suit {
         Suit::Clubs,
         Suit::Spades,
     ];
+
+    /// Hearts, diamonds, clubs then spades from 0, as usize::from
+    pub const fn index(self) -> usize {
+        self as usize
+    }
 }
 
 impl From<Suit> for usize {
     fn from(suit: Suit) -> Self {
-        match suit {
-            Suit::Hearts => 0,
-            Suit::Diamonds => 1,
-            Suit::Clubs => 2,
-            Suit::Spades => 3,
-        }
+        suit.index()
     }
 }
 
@@ -131,8 +167,34 @@ impl TryFrom<usize> for Suit {
     }
 }
 
+impl TryFrom<char> for Suit {
+    type Error = &'static str;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase() {
+            'h' | '♥' => Ok(Suit::Hearts),
+            'd' | '♦' => Ok(Suit::Diamonds),
+            'c' | '♣' => Ok(Suit::Clubs),
+            's' | '♠' => Ok(Suit::Spades),
+            _ => Err("Invalid suit character"),
+        }
+    }
+}
+
+impl Suit {
+    /// Single letter used in hand notation
+    pub fn symbol(&self) -> char {
+        match self {
+            Suit::Hearts => 'h',
+            Suit::Diamonds => 'd',
+            Suit::Clubs => 'c',
+            Suit::Spades => 's',
+        }
+    }
+}
+
 impl Display for Suit {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", match self {
             Suit::Hearts => "♥",
             Suit::Diamonds => "♦",
@@ -143,35 +205,101 @@ impl Display for Suit {
 }
 
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 pub struct Card {
     pub rank: Rank,
     pub suit: Suit,
 }
 
 impl Card {
-    pub fn new(rank: Rank, suit: Suit) -> Self {
+    /// Every card, ordered by rank then suit, so each is at its index
+    pub const DECK: [Card; 52] = {
+        let mut cards = [Card::new(Rank::Two, Suit::Hearts); 52];
+        let mut index = 0;
+        while index < 52 {
+            cards[index] = Card::new(Rank::ALL_RANKS[index / 4], Suit::ALL_SUITS[index % 4]);
+            index += 1;
+        }
+        cards
+    };
+
+    pub const fn new(rank: Rank, suit: Suit) -> Self {
         Card { rank, suit }
     }
 
+    /// rank * 4 + suit, as usize::from
+    pub const fn index(self) -> usize {
+        self.rank.index() * 4 + self.suit.index()
+    }
+
     /// Deck of cards, ordered by rank then suit
     pub fn get_deck() -> Vec<Card> {
-        let mut cards = Vec::new();
-        for rank in Rank::ALL_RANKS {
-            for suit in Suit::ALL_SUITS {
-                cards.push(Card {
-                    rank: rank,
-                    suit: suit,
-                });
-            }
+        Card::DECK.to_vec()
+    }
+}
+
+/// Which cards are dealt from
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub enum Deck {
+    /// All 52 cards
+    #[default]
+    Full,
+    /// Short deck (six plus): the 36 cards from six to ace
+    Short,
+}
+
+impl Deck {
+    /// Ranks in the deck, lowest first
+    pub const fn ranks(self) -> &'static [Rank] {
+        match self {
+            Deck::Full => &Rank::ALL_RANKS,
+            Deck::Short => Rank::ALL_RANKS.split_at(4).1,
+        }
+    }
+
+    /// The cards, ordered by rank then suit like Card::DECK
+    pub fn cards(self) -> Vec<Card> {
+        Card::DECK.into_iter().filter(|card| self.ranks().contains(&card.rank)).collect()
+    }
+}
+
+impl Card {
+    /// Plain ascii notation, e.g. "Ah", "Td"
+    pub fn symbol(&self) -> String {
+        format!("{}{}", self.rank.symbol(), self.suit.symbol())
+    }
+}
+
+/// Parse a run of cards like "AhKd", "Ah Kd" or "ah,kd"
+pub fn parse_cards(s: &str) -> Result<Vec<Card>, &'static str> {
+    let chars: Vec<char> = s.chars().filter(|c| !c.is_whitespace() && *c != ',').collect();
+    if !chars.len().is_multiple_of(2) {
+        return Err("Invalid card list");
+    }
+    chars.chunks(2)
+        .map(|c| Ok(Card::new(Rank::try_from(c[0])?, Suit::try_from(c[1])?)))
+        .collect()
+}
+
+/// Ascii notation for a run of cards, the inverse of parse_cards
+pub fn format_cards(cards: &[Card]) -> String {
+    cards.iter().map(Card::symbol).collect()
+}
+
+impl FromStr for Card {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_cards(s)?[..] {
+            [card] => Ok(card),
+            _ => Err("Invalid card"),
         }
-        cards
     }
 }
 
 impl From<Card> for usize {
     fn from(card: Card) -> Self {
-        (usize::from(card.rank) * 4) + usize::from(card.suit)
+        card.index()
     }
 }
 
@@ -179,18 +307,29 @@ impl TryFrom<usize> for Card {
     type Error = &'static str;
 
     fn try_from(value: usize) -> Result<Self, Self::Error> {
-        if value > 51 {
-            return Err("Invalid card value");
-        }
-        let rank = Rank::try_from(value / 4)?;
-        let suit = Suit::try_from(value % 4)?;
-        Ok(Card { rank, suit })
+        Card::DECK.get(value).copied().ok_or("Invalid card value")
     }
 }
 
 impl Display for Card {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}{}", self.rank, self.suit)
     }
 }
 
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deck() {
+        for (index, card) in Card::DECK.into_iter().enumerate() {
+            assert_eq!(card.index(), index);
+            assert_eq!(Card::try_from(index), Ok(card));
+        }
+        assert_eq!(Card::try_from(52), Err("Invalid card value"));
+        assert_eq!(Deck::Short.cards().len(), 36);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::card::*;
+use crate::eval::*;
+use crate::score::*;
+use std::{fmt::{Display, Formatter}, str::FromStr};
+
+
+/// Made hand category, worst first
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum Category {
+    HighCard,
+    Pair,
+    TwoPair,
+    Trips,
+    Straight,
+    Flush,
+    FullHouse,
+    Quads,
+    StraightFlush,
+}
+
+impl Category {
+    pub const ALL_CATEGORIES: [Category; 9] = [
+        Category::HighCard,
+        Category::Pair,
+        Category::TwoPair,
+        Category::Trips,
+        Category::Straight,
+        Category::Flush,
+        Category::FullHouse,
+        Category::Quads,
+        Category::StraightFlush,
+    ];
+
+    pub fn from_score(score: Score) -> Category {
+        score.category()
+    }
+
+    /// Category of the best 5 card hand from the pair and board (3-5 cards)
+    pub fn of(pair: &(Card, Card), board: &[Card]) -> Category {
+        Category::from_score(get_best_score(pair, board))
+    }
+}
+
+impl From<Category> for usize {
+    fn from(category: Category) -> Self {
+        category as usize
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Category::HighCard => "high card",
+            Category::Pair => "pair",
+            Category::TwoPair => "two pair",
+            Category::Trips => "three of a kind",
+            Category::Straight => "straight",
+            Category::Flush => "flush",
+            Category::FullHouse => "full house",
+            Category::Quads => "four of a kind",
+            Category::StraightFlush => "straight flush",
+        })
+    }
+}
+
+impl FromStr for Category {
+    type Err = &'static str;
+
+    /// The Display names, e.g. "two pair"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Category::ALL_CATEGORIES
+            .into_iter()
+            .find(|category| category.to_string().eq_ignore_ascii_case(s))
+            .ok_or("Expected a hand category, e.g. two pair")
+    }
+}
+
+
+/// Drawing potential with cards still to come
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum Draw {
+    Nothing,
+    Gutshot,
+    OpenEnded,
+    Flush,
+    /// flush draw and a straight draw
+    Combo,
+}
+
+impl Draw {
+    pub const ALL_DRAWS: [Draw; 5] = [Draw::Nothing, Draw::Gutshot, Draw::OpenEnded, Draw::Flush, Draw::Combo];
+
+    /// Draws to a hand better than the pair currently makes, that use a hole card
+    pub fn of(pair: &(Card, Card), board: &[Card]) -> Draw {
+        let made = Category::of(pair, board);
+        let flush = made < Category::Flush && is_flush_draw(pair, board);
+        let straight = if made < Category::Straight { straight_outs(pair, board) } else { 0 };
+        match (flush, straight) {
+            (true, 0) => Draw::Flush,
+            (true, _) => Draw::Combo,
+            (false, 0) => Draw::Nothing,
+            (false, 1) => Draw::Gutshot,
+            (false, _) => Draw::OpenEnded,
+        }
+    }
+}
+
+impl Display for Draw {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Draw::Nothing => "no draw",
+            Draw::Gutshot => "gutshot",
+            Draw::OpenEnded => "open-ended straight draw",
+            Draw::Flush => "flush draw",
+            Draw::Combo => "combo draw",
+        })
+    }
+}
+
+/// Exactly 4 of a suit, including at least one hole card
+fn is_flush_draw(pair: &(Card, Card), board: &[Card]) -> bool {
+    Suit::ALL_SUITS.iter().any(|&suit| {
+        let count = board.iter().chain([&pair.0, &pair.1]).filter(|card| card.suit == suit).count();
+        count == 4 && (pair.0.suit == suit || pair.1.suit == suit)
+    })
+}
+
+/// Ranks (not cards) that would complete a straight using a hole card
+fn straight_outs(pair: &(Card, Card), board: &[Card]) -> usize {
+    let ranks: Vec<Rank> = board.iter().chain([&pair.0, &pair.1]).map(|card| card.rank).collect();
+
+    Rank::ALL_RANKS
+        .iter()
+        .filter(|out| !ranks.contains(out))
+        .filter(|&&out| {
+            // some straight through the out has its other 4 ranks present and uses a hole card
+            (usize::from(Rank::Five)..=usize::from(Rank::Ace)).any(|high| {
+                let straight = straight_ranks(Rank::try_from(high).unwrap());
+                straight.contains(&out)
+                    && straight.iter().all(|rank| *rank == out || ranks.contains(rank))
+                    && (straight.contains(&pair.0.rank) || straight.contains(&pair.1.rank))
+            })
+        })
+        .count()
+}
+
+/// Cards that would make the pair a better category as the next card off (flop or turn),
+/// out of those not on the board or in dead. Anything that lifts the category counts, even a
+/// card that only pairs the board
+pub fn outs(pair: &(Card, Card), board: &[Card], dead: &[Card]) -> Vec<Card> {
+    debug_assert!(board.len() == 3 || board.len() == 4);
+    let made = Category::of(pair, board);
+    let mut next = board.to_vec();
+    deck_without(&[board, dead, &[pair.0, pair.1]].concat())
+        .into_iter()
+        .filter(|&card| {
+            next.push(card);
+            let better = Category::of(pair, &next) > made;
+            next.pop();
+            better
+        })
+        .collect()
+}
+
+/// The 5 ranks of the straight with the given high card (Five means the wheel)
+fn straight_ranks(high: Rank) -> [Rank; 5] {
+    let high = usize::from(high);
+    std::array::from_fn(|i| {
+        if high == usize::from(Rank::Five) && i == 4 {
+            Rank::Ace
+        } else {
+            Rank::try_from(high - i).unwrap()
+        }
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::*;
+
+    fn cards(s: &[(Rank, Suit)]) -> Vec<Card> {
+        s.iter().map(|&(rank, suit)| Card::new(rank, suit)).collect()
+    }
+
+    #[test]
+    fn test_from_score() {
+        let royal = cards(&[(Rank::Ace, Suit::Spades), (Rank::King, Suit::Spades), (Rank::Queen, Suit::Spades),
+                            (Rank::Jack, Suit::Spades), (Rank::Ten, Suit::Spades)]);
+        assert_eq!(Category::from_score(SCORES[&Hand::new(&royal)]), Category::StraightFlush);
+
+        let wheel = cards(&[(Rank::Ace, Suit::Spades), (Rank::Two, Suit::Hearts), (Rank::Three, Suit::Spades),
+                            (Rank::Four, Suit::Spades), (Rank::Five, Suit::Spades)]);
+        assert_eq!(Category::from_score(SCORES[&Hand::new(&wheel)]), Category::Straight);
+
+        let worst = cards(&[(Rank::Seven, Suit::Spades), (Rank::Five, Suit::Hearts), (Rank::Four, Suit::Spades),
+                            (Rank::Three, Suit::Spades), (Rank::Two, Suit::Spades)]);
+        assert_eq!(Category::from_score(SCORES[&Hand::new(&worst)]), Category::HighCard);
+        assert_eq!(Category::from_score(Score::from_index(7461).unwrap()), Category::HighCard);
+        assert_eq!(Category::from_score(Score::from_index(3324).unwrap()), Category::TwoPair);
+    }
+
+    #[test]
+    fn test_draws() {
+        let board = cards(&[(Rank::Ace, Suit::Hearts), (Rank::King, Suit::Hearts), (Rank::Four, Suit::Spades)]);
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        // 4 hearts and 2345 for the wheel
+        assert_eq!(Draw::of(&pair, &board), Draw::Combo);
+
+        let pair = (Card::new(Rank::Queen, Suit::Clubs), Card::new(Rank::Jack, Suit::Clubs));
+        assert_eq!(Draw::of(&pair, &board), Draw::Gutshot);
+
+        let board = cards(&[(Rank::Nine, Suit::Hearts), (Rank::Eight, Suit::Clubs), (Rank::Two, Suit::Spades)]);
+        let pair = (Card::new(Rank::Seven, Suit::Diamonds), Card::new(Rank::Six, Suit::Hearts));
+        assert_eq!(Draw::of(&pair, &board), Draw::OpenEnded);
+
+        let pair = (Card::new(Rank::Ace, Suit::Diamonds), Card::new(Rank::Ace, Suit::Hearts));
+        assert_eq!(Draw::of(&pair, &board), Draw::Nothing);
+        // two aces for trips, or pairing the board for two pair
+        assert_eq!(outs(&pair, &board, &[]).len(), 2 + 9);
+
+        // eight to the straight, six pairing a hole card and nine pairing the board
+        let pair = (Card::new(Rank::Seven, Suit::Diamonds), Card::new(Rank::Six, Suit::Clubs));
+        assert_eq!(outs(&pair, &board, &[]).len(), 8 + 6 + 9);
+    }
+}
@@ -0,0 +1,186 @@
+//! Chaos mode for self-play: hands where the odd cases the rules have to get right come up far
+//! more often than they would, to shake out bugs in the bots and the pot logic. Every case is
+//! still a legal hand, and the chips at the table are checked after each one
+
+use crate::card::*;
+use crate::game::*;
+use crate::strategy::*;
+use crate::table::*;
+use rand::{seq::SliceRandom, Rng};
+
+
+/// An odd case forced on a hand
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ChaosCase {
+    /// The big blind has less than the blind to post, so is all in for less
+    ShortBlind,
+    /// Everyone goes all in preflop, whatever their bot wants
+    AllIn,
+    /// Everyone goes all in preflop on a royal flush board, so every pot and side pot is split
+    /// between everyone in it
+    SplitPots,
+}
+
+/// How often each case is forced, 0-1, at most one a hand
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Chaos {
+    pub short_blind: f64,
+    pub all_in: f64,
+    pub split_pots: f64,
+}
+
+impl Default for Chaos {
+    fn default() -> Self {
+        Chaos { short_blind: 0.1, all_in: 0.1, split_pots: 0.1 }
+    }
+}
+
+/// A hand dealt in chaos mode, with the chips a short blind left off the table
+#[derive(Debug, Clone)]
+pub struct ChaosHand {
+    pub hand: HandState,
+    pub case: Option<ChaosCase>,
+    /// The table seat of each seat in the hand
+    pub seats: Vec<usize>,
+    held_back: Option<(usize, Chips)>,
+}
+
+impl Chaos {
+    fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<ChaosCase> {
+        let roll: f64 = rng.random();
+        [(ChaosCase::ShortBlind, self.short_blind), (ChaosCase::AllIn, self.all_in), (ChaosCase::SplitPots, self.split_pots)]
+            .into_iter()
+            .scan(0.0, |total, (case, rate)| {
+                *total += rate;
+                Some((case, *total))
+            })
+            .find(|&(_, total)| roll < total)
+            .map(|(case, _)| case)
+    }
+
+    /// Deal a hand at the table as Table::deal does, maybe with a case forced on it
+    pub fn deal<R: Rng + ?Sized>(&self, table: &mut Table, rng: &mut R) -> Result<ChaosHand, &'static str> {
+        let (mut stacks, button) = table.dealt_in()?;
+        let seats = table.active();
+        let mut deck = Card::get_deck();
+        deck.shuffle(rng);
+        let mut case = self.pick(rng);
+        let mut held_back = None;
+
+        match case {
+            Some(ChaosCase::ShortBlind) if table.big_blind > 1 => {
+                let (_, big) = blind_seats(stacks.len(), button);
+                let short = rng.random_range(1..table.big_blind).min(stacks[big]);
+                held_back = Some((big, stacks[big] - short));
+                stacks[big] = short;
+            }
+            Some(ChaosCase::ShortBlind) => case = None,
+            Some(ChaosCase::SplitPots) => {
+                // the board goes straight after the hole cards, with no burns
+                let board = parse_cards("AsKsQsJsTs").unwrap();
+                deck.retain(|card| !board.contains(card));
+                let holes = 2 * stacks.len();
+                deck.splice(holes..holes, board);
+            }
+            _ => {}
+        }
+        let hand = HandState::with_deck(&stacks, button, table.small_blind, table.big_blind, deck);
+        Ok(ChaosHand { hand, case, seats, held_back })
+    }
+
+    /// Play a chaos hand out, asking the bot at each table seat what to do unless the case says
+    /// otherwise
+    pub fn play(&self, hand: &mut ChaosHand, bots: &mut [Box<dyn Strategy>]) -> Result<(), &'static str> {
+        let forced = matches!(hand.case, Some(ChaosCase::AllIn | ChaosCase::SplitPots));
+        while let Some(seat) = hand.hand.to_act() {
+            let action = bots[hand.seats[seat]].act(&hand.hand.view(seat));
+            if !forced {
+                hand.hand.act(action)?;
+            } else if hand.hand.act(Action::AllIn).is_err() {
+                // a short all in earlier means there's no raising, only calling it
+                hand.hand.act(Action::Call)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Take the stacks from a finished chaos hand, giving back the chips a short blind was dealt
+    /// without
+    pub fn finish(table: &mut Table, hand: &ChaosHand) {
+        table.finish(&hand.hand);
+        if let Some((seat, chips)) = hand.held_back {
+            table.seats[hand.seats[seat]].as_mut().unwrap().stack += chips;
+        }
+    }
+}
+
+/// How many hands of a chaos match had each case forced on them
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ChaosCounts {
+    pub hands: usize,
+    pub short_blinds: usize,
+    pub all_ins: usize,
+    pub split_pots: usize,
+}
+
+/// run_match in chaos mode, failing if a hand can't be played or the chips at the table change
+pub fn run_chaos_match<R: Rng + ?Sized>(table: &mut Table, bots: &mut [Box<dyn Strategy>], hands: usize,
+                                        chaos: &Chaos, rng: &mut R) -> Result<ChaosCounts, &'static str> {
+    debug_assert_eq!(table.seats.len(), bots.len());
+    let chips = |table: &Table| table.seats.iter().flatten().map(|player| player.stack).sum::<Chips>();
+    let total = chips(table);
+    let mut counts = ChaosCounts::default();
+    while counts.hands < hands && table.active().len() >= 2 {
+        let mut hand = chaos.deal(table, rng)?;
+        chaos.play(&mut hand, bots)?;
+        Chaos::finish(table, &hand);
+        if chips(table) != total {
+            return Err("chips went missing");
+        }
+
+        counts.hands += 1;
+        match hand.case {
+            Some(ChaosCase::ShortBlind) => counts.short_blinds += 1,
+            Some(ChaosCase::AllIn) => counts.all_ins += 1,
+            Some(ChaosCase::SplitPots) => counts.split_pots += 1,
+            None => {}
+        }
+    }
+    Ok(counts)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_chaos() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut table = Table::new(4, 1, 2);
+        for (seat, stack) in [40, 60, 60, 90].into_iter().enumerate() {
+            table.sit(seat, Player { name: seat.to_string(), stack }).unwrap();
+        }
+        // the split pots are checked by hand: everyone gets back what they put in
+        let split = Chaos { short_blind: 0.0, all_in: 0.0, split_pots: 1.0 };
+        let mut hand = split.deal(&mut table, &mut rng).unwrap();
+        let mut bots: Vec<Box<dyn Strategy>> = vec![
+            Box::new(AlwaysCall),
+            Box::new(PushFold { threshold: 0.55, payouts: None }),
+            Box::new(AlwaysCall),
+            Box::new(PushFold { threshold: 0.5, payouts: None }),
+        ];
+        split.play(&mut hand, &mut bots).unwrap();
+        assert_eq!(hand.hand.winnings(), Some(&[40, 60, 60, 90][..]));
+
+        let short = Chaos { short_blind: 1.0, all_in: 0.0, split_pots: 0.0 };
+        let hand = short.deal(&mut table, &mut rng).unwrap();
+        let (_, big) = blind_seats(4, hand.hand.button);
+        assert!(hand.hand.seats[big].is_all_in() && hand.hand.seats[big].contributed == 1);
+
+        let chaos = Chaos { short_blind: 0.3, all_in: 0.3, split_pots: 0.3 };
+        let counts = run_chaos_match(&mut table, &mut bots, 50, &chaos, &mut rng).unwrap();
+        assert!(counts.hands > 0 && counts.short_blinds + counts.all_ins + counts.split_pots > 0);
+    }
+}
@@ -0,0 +1,94 @@
+//! Preflop charts from other tools, read into a HandRange: CSV or JSON tables of hand class to
+//! how often an action is taken, as well as the range strings HandRange parses itself
+
+use crate::json::*;
+use crate::range::*;
+
+
+/// A CSV chart, a hand (AKs, or anything parse_hands reads) then frequencies (0-1) on each line.
+/// A header line naming the columns is optional; given one, the action picks the column,
+/// otherwise the first frequency is used
+pub fn parse_chart_csv(text: &str, action: Option<&str>) -> Result<HandRange, String> {
+    let mut range = HandRange::new();
+    let mut column = 1;
+    for (number, line) in text.lines().enumerate() {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.iter().all(|field| field.is_empty()) {
+            continue;
+        }
+        let invalid = |e: &str| format!("line {}: {}", number + 1, e);
+        if number == 0 && fields.get(1).is_some_and(|field| field.parse::<f64>().is_err()) {
+            if let Some(action) = action {
+                column = fields.iter().position(|field| field.eq_ignore_ascii_case(action)).ok_or_else(|| invalid("no column for the action"))?;
+            }
+            continue;
+        }
+        let frequency: f64 = fields
+            .get(column)
+            .ok_or_else(|| invalid("missing frequency"))?
+            .parse()
+            .map_err(|_| invalid("invalid frequency"))?;
+        insert(&mut range, fields[0], frequency).map_err(invalid)?;
+    }
+    Ok(range)
+}
+
+/// A JSON chart, an object of hand to frequency, e.g. {"AKs": 1, "AQo": 0.5}, or of hand to an
+/// object of action to frequency, e.g. {"AKs": {"raise": 0.75, "call": 0.25}}, which needs the action
+pub fn parse_chart_json(text: &str, action: Option<&str>) -> Result<HandRange, String> {
+    let Json::Object(hands) = Json::parse(text)? else {
+        return Err("Expected an object of hands".to_string());
+    };
+    let mut range = HandRange::new();
+    for (hands, value) in &hands {
+        let invalid = |e: &str| format!("{}: {}", hands, e);
+        let frequency = match (value, action) {
+            (Json::Number(frequency), _) => *frequency,
+            // an action left out of a hand's mix is never taken
+            (Json::Object(_), Some(action)) => value.get(action).map_or(Some(0.0), Json::as_f64).ok_or_else(|| invalid("invalid frequency"))?,
+            (Json::Object(_), None) => return Err(invalid("pick an action from the mix")),
+            _ => return Err(invalid("expected a frequency")),
+        };
+        insert(&mut range, hands, frequency).map_err(invalid)?;
+    }
+    Ok(range)
+}
+
+fn insert(range: &mut HandRange, hands: &str, frequency: f64) -> Result<(), &'static str> {
+    if !(0.0..=1.0).contains(&frequency) {
+        return Err("frequencies have to be between 0 and 1");
+    }
+    for pair in parse_hands(hands)? {
+        range.insert(pair, frequency);
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::*;
+
+    #[test]
+    fn test_import_charts() {
+        let range: HandRange = "TT+, AKs, AQo:0.5, A5s-A3s, KhQh".parse().unwrap();
+        assert_eq!(range.len(), 5 * 6 + 4 + 12 + 3 * 4 + 1);
+        assert_eq!(range.weight(&(Card::new(Rank::Ace, Suit::Clubs), Card::new(Rank::Queen, Suit::Hearts))), 0.5);
+        assert_eq!("A9s+".parse::<HandRange>().unwrap().len(), 5 * 4);
+        assert_eq!("AK".parse::<HandRange>().unwrap().len(), 16);
+        assert_eq!("66-99".parse::<HandRange>().unwrap(), "99-66".parse().unwrap());
+        assert!("AKs-QJs".parse::<HandRange>().is_err() && "AKs:2".parse::<HandRange>().is_err());
+
+        let csv = "hand,raise,call\nAA,1,0\nAKs,0.75,0.25\nAKo,0,1\n";
+        assert_eq!(parse_chart_csv(csv, Some("call")).unwrap(), "AKs:0.25,AKo".parse().unwrap());
+        assert_eq!(parse_chart_csv(csv, None).unwrap(), "AA,AKs:0.75".parse().unwrap());
+        assert_eq!(parse_chart_csv("QQ+,1\n", None).unwrap(), "QQ+".parse().unwrap());
+        assert!(parse_chart_csv(csv, Some("fold")).is_err());
+
+        let json = r#"{"AA": {"raise": 1}, "AKs": {"raise": 0.75, "call": 0.25}, "AKo": {"call": 1}}"#;
+        assert_eq!(parse_chart_json(json, Some("raise")).unwrap(), "AA,AKs:0.75".parse().unwrap());
+        assert_eq!(parse_chart_json(r#"{"KK+": 1, "JJ": 0.5}"#, None).unwrap(), "KK+,JJ:0.5".parse().unwrap());
+        assert!(parse_chart_json(json, None).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use crate::card::*;
+use std::{fmt::{Display, Formatter}, str::FromStr};
+use itertools::Itertools;
+
+
+/// One of the 169 canonical starting hands (AKs, AKo, QQ, ...)
+/// high >= low, pairs are never suited
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct HandClass {
+    pub high: Rank,
+    pub low: Rank,
+    pub suited: bool,
+}
+
+impl HandClass {
+    pub const COUNT: usize = 169;
+
+    pub fn new(a: Rank, b: Rank, suited: bool) -> Self {
+        debug_assert!(a != b || !suited, "pairs can't be suited");
+        HandClass {
+            high: a.max(b),
+            low: a.min(b),
+            suited,
+        }
+    }
+
+    pub fn from_pair(pair: &(Card, Card)) -> Self {
+        HandClass::new(pair.0.rank, pair.1.rank, pair.0.suit == pair.1.suit)
+    }
+
+    pub fn is_pair(&self) -> bool {
+        self.high == self.low
+    }
+
+    /// Position in the standard 13x13 grid (row, col)
+    /// Aces in row/col 0, pairs on the diagonal, suited above it
+    pub fn grid(&self) -> (usize, usize) {
+        let high = usize::from(Rank::Ace) - usize::from(self.high);
+        let low = usize::from(Rank::Ace) - usize::from(self.low);
+        if self.suited {
+            (high, low)
+        } else {
+            (low, high)
+        }
+    }
+
+    pub fn from_grid(row: usize, col: usize) -> Self {
+        debug_assert!(row < 13 && col < 13);
+        let a = Rank::try_from(usize::from(Rank::Ace) - row).unwrap();
+        let b = Rank::try_from(usize::from(Rank::Ace) - col).unwrap();
+        HandClass::new(a, b, row < col)
+    }
+
+    /// Index into tables of 169 entries, row major over the grid
+    pub fn index(&self) -> usize {
+        let (row, col) = self.grid();
+        row * 13 + col
+    }
+
+    pub fn from_index(index: usize) -> Self {
+        debug_assert!(index < HandClass::COUNT);
+        HandClass::from_grid(index / 13, index % 13)
+    }
+
+    /// All 169 classes in index order
+    pub fn all() -> impl Iterator<Item = HandClass> {
+        (0..HandClass::COUNT).map(HandClass::from_index)
+    }
+
+    /// Every concrete combo in the class: 6 for pairs, 4 suited, 12 offsuit
+    pub fn combos(&self) -> Vec<(Card, Card)> {
+        if self.is_pair() {
+            return Suit::ALL_SUITS
+                .iter()
+                .tuple_combinations()
+                .map(|(&a, &b)| (Card::new(self.high, a), Card::new(self.low, b)))
+                .collect();
+        }
+        let mut combos = Vec::new();
+        for a in Suit::ALL_SUITS {
+            for b in Suit::ALL_SUITS {
+                if (a == b) == self.suited {
+                    combos.push((Card::new(self.high, a), Card::new(self.low, b)));
+                }
+            }
+        }
+        combos
+    }
+
+    /// The combos none of the dead cards are in
+    pub fn live_combos(&self, dead: &[Card]) -> Vec<(Card, Card)> {
+        self.combos().into_iter().filter(|pair| !dead.contains(&pair.0) && !dead.contains(&pair.1)).collect()
+    }
+
+    /// How many combos are left with the dead cards out, counted from the suits left of each rank
+    pub fn combo_count(&self, dead: &[Card]) -> usize {
+        let live = |rank: Rank| Suit::ALL_SUITS.iter().filter(|&&suit| !dead.contains(&Card::new(rank, suit))).count();
+        let both = Suit::ALL_SUITS
+            .iter()
+            .filter(|&&suit| !dead.contains(&Card::new(self.high, suit)) && !dead.contains(&Card::new(self.low, suit)))
+            .count();
+        match (self.is_pair(), self.suited) {
+            (true, _) => live(self.high) * live(self.high).saturating_sub(1) / 2,
+            (false, true) => both,
+            (false, false) => live(self.high) * live(self.low) - both,
+        }
+    }
+
+    pub fn contains(&self, pair: &(Card, Card)) -> bool {
+        pair.0 != pair.1 && HandClass::from_pair(pair) == *self
+    }
+
+    /// A single concrete combo from the class
+    pub fn representative(&self) -> (Card, Card) {
+        self.combos()[0]
+    }
+}
+
+impl Display for HandClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.high.symbol(), self.low.symbol())?;
+        if !self.is_pair() {
+            write!(f, "{}", if self.suited { "s" } else { "o" })?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for HandClass {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.trim().chars().collect();
+        let (a, b) = match chars[..] {
+            [a, b] | [a, b, _] => (Rank::try_from(a)?, Rank::try_from(b)?),
+            _ => return Err("Invalid hand class"),
+        };
+        let suited = match chars.get(2).map(|c| c.to_ascii_lowercase()) {
+            None if a == b => false,
+            Some('s') if a != b => true,
+            Some('o') if a != b => false,
+            _ => return Err("Invalid hand class"),
+        };
+        Ok(HandClass::new(a, b, suited))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_round_trip() {
+        for (i, class) in HandClass::all().enumerate() {
+            assert_eq!(class.index(), i);
+        }
+        assert_eq!(HandClass::all().map(|class| class.combos().len()).sum::<usize>(), 1326);
+    }
+
+    #[test]
+    fn test_combo_count() {
+        let dead = parse_cards("AhKhKd7c").unwrap();
+        for class in HandClass::all() {
+            assert_eq!(class.combo_count(&dead), class.live_combos(&dead).len(), "{}", class);
+            assert_eq!(class.combo_count(&[]), class.combos().len());
+            assert!(class.combos().iter().all(|pair| class.contains(pair)));
+        }
+        let ako: HandClass = "AKo".parse().unwrap();
+        assert_eq!(ako.combo_count(&dead), 4);
+        assert!(!ako.contains(&(dead[0], dead[1])));
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for class in HandClass::all() {
+            assert_eq!(class.to_string().parse::<HandClass>(), Ok(class));
+        }
+        assert_eq!("AKs".parse::<HandClass>(), Ok(HandClass::new(Rank::Ace, Rank::King, true)));
+        assert_eq!("kao".parse::<HandClass>().unwrap().to_string(), "AKo");
+        assert!("QQs".parse::<HandClass>().is_err());
+        assert!("AK".parse::<HandClass>().is_err());
+        assert!("AKx".parse::<HandClass>().is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! Coordinator/worker mode for big exhaustive evaluations: the runout index space
+//! (see unrank_combination) is split into chunks handed out to workers over TCP,
+//! chunks from workers that drop or time out are handed out again, and the tallies merged.
+//!
+//! Line protocol:
+//! - coordinator -> worker: `JOB <pair> <board or -> <start> <end>` or `DONE`
+//! - worker -> coordinator: `RESULT <start> <end> <win> <lose>`
+
+use crate::card::*;
+use crate::combinatorics::*;
+use crate::eval::*;
+use std::{collections::VecDeque, io::{self, BufRead, BufReader, Write}, net::{TcpListener, TcpStream, ToSocketAddrs}, ops::Range, sync::{Condvar, Mutex}, time::Duration};
+
+
+/// An eval_with_community computation to split up
+#[derive(Debug, PartialEq, Clone)]
+pub struct Job {
+    pub pair: (Card, Card),
+    pub board: Vec<Card>,
+}
+
+impl Job {
+    /// Size of the runout index space
+    pub fn runouts(&self) -> usize {
+        runout_count(self.board.len(), 50 - self.board.len())
+    }
+
+    pub fn chunks(&self, chunk_size: usize) -> VecDeque<Range<usize>> {
+        let total = self.runouts();
+        (0..total)
+            .step_by(chunk_size.max(1))
+            .map(|start| start..(start + chunk_size.max(1)).min(total))
+            .collect()
+    }
+
+    fn encode(&self, range: &Range<usize>) -> String {
+        let board = if self.board.is_empty() { "-".to_string() } else { format_cards(&self.board) };
+        format!("JOB {} {} {} {}\n", format_cards(&[self.pair.0, self.pair.1]), board, range.start, range.end)
+    }
+
+    /// None for anything malformed, including jobs no worker could evaluate: cards dealt twice or
+    /// runouts that don't exist
+    fn decode(line: &str) -> Option<(Job, Range<usize>)> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let ["JOB", pair, board, start, end] = fields[..] else {
+            return None;
+        };
+        let pair = match parse_cards(pair).ok()?[..] {
+            [a, b] => (a, b),
+            _ => return None,
+        };
+        let board = if board == "-" { Vec::new() } else { parse_cards(board).ok()? };
+        if !is_valid_deal_with(&board, &[pair], &[]) {
+            return None;
+        }
+        let job = Job { pair, board };
+        let range: Range<usize> = start.parse().ok()?..end.parse().ok()?;
+        (range.start <= range.end && range.end <= job.runouts()).then_some((job, range))
+    }
+}
+
+struct Progress {
+    pending: VecDeque<Range<usize>>,
+    outstanding: usize,
+    win_count: usize,
+    lose_count: usize,
+}
+
+/// Hand out the job to workers connecting on listener until every chunk is done
+/// returns (win_count, lose_count), the same as eval_with_community
+pub fn coordinate(listener: TcpListener, job: &Job, chunk_size: usize, timeout: Duration) -> io::Result<(usize, usize)> {
+    let pending = job.chunks(chunk_size);
+    let progress = Mutex::new(Progress {
+        outstanding: pending.len(),
+        pending,
+        win_count: 0,
+        lose_count: 0,
+    });
+    let changed = Condvar::new();
+
+    listener.set_nonblocking(true)?;
+    std::thread::scope(|scope| -> io::Result<()> {
+        while progress.lock().unwrap().outstanding > 0 {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let (progress, changed) = (&progress, &changed);
+                    scope.spawn(move || serve_worker(stream, job, progress, changed, timeout));
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => std::thread::sleep(Duration::from_millis(10)),
+                Err(e) => return Err(e),
+            }
+        }
+        changed.notify_all();
+        Ok(())
+    })?;
+
+    let progress = progress.into_inner().unwrap();
+    Ok((progress.win_count, progress.lose_count))
+}
+
+/// Feed chunks to one worker until everything is done or the worker fails
+fn serve_worker(stream: TcpStream, job: &Job, progress: &Mutex<Progress>, changed: &Condvar, timeout: Duration) {
+    let send_chunk = |stream: &TcpStream, reader: &mut BufReader<&TcpStream>, range: &Range<usize>| -> Option<(usize, usize)> {
+        (&*stream).write_all(job.encode(range).as_bytes()).ok()?;
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let ["RESULT", start, end, win, lose] = fields[..] else {
+            return None;
+        };
+        if start.parse::<usize>().ok()? != range.start || end.parse::<usize>().ok()? != range.end {
+            return None;
+        }
+        Some((win.parse().ok()?, lose.parse().ok()?))
+    };
+
+    if stream.set_nonblocking(false).is_err() || stream.set_read_timeout(Some(timeout)).is_err() {
+        return;
+    }
+    let mut reader = BufReader::new(&stream);
+    loop {
+        let range = {
+            let mut state = progress.lock().unwrap();
+            // chunks out with other workers may still come back if those workers fail
+            while state.pending.is_empty() && state.outstanding > 0 {
+                state = changed.wait_timeout(state, Duration::from_millis(100)).unwrap().0;
+            }
+            match state.pending.pop_front() {
+                Some(range) => range,
+                None => {
+                    let _ = (&stream).write_all(b"DONE\n");
+                    return;
+                }
+            }
+        };
+
+        match send_chunk(&stream, &mut reader, &range) {
+            Some((win, lose)) => {
+                let mut state = progress.lock().unwrap();
+                state.win_count += win;
+                state.lose_count += lose;
+                state.outstanding -= 1;
+            }
+            None => {
+                // give the chunk to someone else and drop this worker
+                progress.lock().unwrap().pending.push_back(range);
+                changed.notify_all();
+                return;
+            }
+        }
+        changed.notify_all();
+    }
+}
+
+/// Connect to a coordinator and evaluate chunks until told it's done
+/// returns the number of chunks evaluated
+pub fn work(addr: impl ToSocketAddrs) -> io::Result<usize> {
+    let stream = TcpStream::connect(addr)?;
+    let mut reader = BufReader::new(&stream);
+    let mut chunks = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim() == "DONE" {
+            return Ok(chunks);
+        }
+        let (job, range) = Job::decode(&line)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "bad job"))?;
+        let (win, lose) = eval_runout_range(&job.board, &job.pair, range.clone());
+        (&stream).write_all(format!("RESULT {} {} {} {}\n", range.start, range.end, win, lose).as_bytes())?;
+        chunks += 1;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_matches_local() {
+        let job = Job {
+            pair: (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts)),
+            board: parse_cards("AhKh4s9c").unwrap(),
+        };
+        assert_eq!(Job::decode(&job.encode(&(0..46))), Some((job.clone(), 0..46)));
+        assert_eq!(Job::decode("JOB 2h3h 2hKh4s9c 0 46"), None);
+        assert_eq!(Job::decode("JOB 2h3h AhKh4s9c 0 47"), None);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let result = std::thread::scope(|scope| {
+            let coordinator = scope.spawn(|| coordinate(listener, &job, 10, Duration::from_secs(60)));
+
+            // a worker that takes a chunk and disappears, so the chunk has to be retried
+            let flaky = TcpStream::connect(addr).unwrap();
+            let mut line = String::new();
+            BufReader::new(&flaky).read_line(&mut line).unwrap();
+            assert!(line.starts_with("JOB"));
+            drop(flaky);
+
+            let workers: Vec<_> = (0..2).map(|_| scope.spawn(move || work(addr).unwrap())).collect();
+            let chunks: usize = workers.into_iter().map(|w| w.join().unwrap()).sum();
+            assert_eq!(chunks, 5);
+            coordinator.join().unwrap().unwrap()
+        });
+
+        assert_eq!(result, eval_with_community(job.board.clone(), &job.pair));
+    }
+}
@@ -0,0 +1,132 @@
+//! Counting with card removal: n choose k, combos of a class or boards still possible once some
+//! cards are known, the chance of hitting outs, and ranking and unranking combinations so index
+//! spaces can be split up
+
+use crate::card::*;
+use crate::class::*;
+use crate::street::*;
+
+
+/// n choose k
+pub fn choose(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k.min(n - k)).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+/// The index-th k element combination of 0..n, in the same (lexicographic) order
+/// itertools' combinations produces them, so index spaces can be split into chunks
+pub fn unrank_combination(index: usize, n: usize, k: usize) -> Vec<usize> {
+    let mut combo = vec![0; k];
+    unrank_combination_into(index, n, &mut combo);
+    combo
+}
+
+/// unrank_combination into a slice as long as the combination, for loops that can't allocate
+pub fn unrank_combination_into(mut index: usize, n: usize, combo: &mut [usize]) {
+    let k = combo.len();
+    debug_assert!(index < choose(n, k));
+    let mut x = 0;
+    for (i, slot) in combo.iter_mut().enumerate() {
+        // skip past every combination starting with a smaller element
+        loop {
+            let count = choose(n - x - 1, k - i - 1);
+            if index < count {
+                break;
+            }
+            index -= count;
+            x += 1;
+        }
+        *slot = x;
+        x += 1;
+    }
+}
+
+/// Call f with every k element combination of 0..n (k at most 5) in lexicographic order, the
+/// indices updated in place rather than each combination allocated
+pub fn for_each_combination(n: usize, k: usize, mut f: impl FnMut(&[usize])) {
+    debug_assert!(k <= 5);
+    if k > n {
+        return;
+    }
+    let mut combo = [0, 1, 2, 3, 4];
+    loop {
+        f(&combo[..k]);
+        // the last index that can still move up, everything after it following on from it
+        let Some(i) = (0..k).rev().find(|&i| combo[i] < n - k + i) else {
+            return;
+        };
+        combo[i] += 1;
+        for j in i + 1..k {
+            combo[j] = combo[j - 1] + 1;
+        }
+    }
+}
+
+/// Inverse of unrank_combination, combo must be sorted
+pub fn rank_combination(combo: &[usize], n: usize) -> usize {
+    let k = combo.len();
+    let mut index = 0;
+    let mut x = 0;
+    for (i, &c) in combo.iter().enumerate() {
+        while x < c {
+            index += choose(n - x - 1, k - i - 1);
+            x += 1;
+        }
+        x += 1;
+    }
+    index
+}
+
+/// Number of ways to complete a board of board_len (0-5) cards from `unseen` cards
+pub fn runout_count(board_len: usize, unseen: usize) -> usize {
+    debug_assert!(board_len <= 5);
+    choose(unseen, 5 - board_len)
+}
+
+/// Combos of the class none of the dead cards are in
+pub fn combos_of(class: HandClass, dead: &[Card]) -> usize {
+    class.combo_count(dead)
+}
+
+/// Whole boards for the street (e.g. all three flop cards) that can be dealt around the dead cards
+pub fn boards_remaining(street: Street, dead: &[Card]) -> usize {
+    choose(52 - dead.len(), street.board_len())
+}
+
+/// Chance of at least one of the outs among the next cards drawn from the unseen ones
+pub fn hit_probability(outs: usize, unseen: usize, cards: usize) -> f64 {
+    1.0 - choose(unseen - outs, cards) as f64 / choose(unseen, cards) as f64
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_counts() {
+        let dead = parse_cards("AhKh").unwrap();
+        assert_eq!(boards_remaining(Street::Flop, &dead), 19600);
+        assert_eq!(boards_remaining(Street::River, &[]), 2598960);
+        assert_eq!(combos_of("AKs".parse().unwrap(), &dead), 3);
+        // nine hearts with two cards to come
+        assert!((hit_probability(9, 47, 2) - (1.0 - (38.0 * 37.0) / (47.0 * 46.0))).abs() < 1e-12);
+        assert_eq!(hit_probability(0, 47, 2), 0.0);
+    }
+
+    #[test]
+    fn test_unrank_combination() {
+        for (index, combo) in (0..7).combinations(3).enumerate() {
+            assert_eq!(unrank_combination(index, 7, 3), combo);
+            assert_eq!(rank_combination(&combo, 7), index);
+        }
+        for k in 0..=5 {
+            let mut combos = Vec::new();
+            for_each_combination(7, k, |combo| combos.push(combo.to_vec()));
+            assert_eq!(combos, (0..7).combinations(k).collect::<Vec<_>>());
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//! How a range hits a board, in combos: each combo the board doesn't block is put into one
+//! made hand (sets, overpairs, top pair, ...) and one draw, weighted by the range, the way
+//! the range tools lay it out. Blockers compares that with and without the cards someone holds
+
+use crate::card::*;
+use crate::category::*;
+use crate::json::*;
+use crate::range::*;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+
+/// Made hand relative to the board, best first. Pairs go by what the hole cards pair up with
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum MadeHand {
+    StraightFlush,
+    Quads,
+    FullHouse,
+    Flush,
+    Straight,
+    /// A pocket pair that hit the board
+    Set,
+    /// One hole card with a pair on the board
+    Trips,
+    TwoPair,
+    Overpair,
+    TopPair,
+    /// A pocket pair between the top two ranks on the board
+    UnderTopPair,
+    MiddlePair,
+    /// Bottom pair, or a pocket pair below the second rank on the board
+    WeakPair,
+    AceHigh,
+    Nothing,
+}
+
+impl MadeHand {
+    pub const ALL_MADE_HANDS: [MadeHand; 15] = [
+        MadeHand::StraightFlush,
+        MadeHand::Quads,
+        MadeHand::FullHouse,
+        MadeHand::Flush,
+        MadeHand::Straight,
+        MadeHand::Set,
+        MadeHand::Trips,
+        MadeHand::TwoPair,
+        MadeHand::Overpair,
+        MadeHand::TopPair,
+        MadeHand::UnderTopPair,
+        MadeHand::MiddlePair,
+        MadeHand::WeakPair,
+        MadeHand::AceHigh,
+        MadeHand::Nothing,
+    ];
+
+    /// The pair's made hand on a board of 3-5 cards
+    pub fn of(pair: &(Card, Card), board: &[Card]) -> MadeHand {
+        match Category::of(pair, board) {
+            Category::StraightFlush => return MadeHand::StraightFlush,
+            Category::Quads => return MadeHand::Quads,
+            Category::FullHouse => return MadeHand::FullHouse,
+            Category::Flush => return MadeHand::Flush,
+            Category::Straight => return MadeHand::Straight,
+            _ => {}
+        }
+
+        let on_board = |rank: Rank| board.iter().filter(|card| card.rank == rank).count();
+        let mut ranks: Vec<Rank> = board.iter().map(|card| card.rank).collect();
+        ranks.sort_unstable_by(|a, b| b.cmp(a));
+        ranks.dedup();
+        let place = |rank: Rank| ranks.iter().position(|&r| r == rank);
+
+        if pair.0.rank == pair.1.rank {
+            let rank = pair.0.rank;
+            return match place(rank) {
+                Some(_) => MadeHand::Set,
+                None if rank > ranks[0] => MadeHand::Overpair,
+                None if ranks.len() < 2 || rank > ranks[1] => MadeHand::UnderTopPair,
+                None => MadeHand::WeakPair,
+            };
+        }
+        let hits: Vec<Rank> = [pair.0.rank, pair.1.rank].into_iter().filter(|&rank| on_board(rank) > 0).collect();
+        if hits.iter().any(|&rank| on_board(rank) >= 2) {
+            return MadeHand::Trips;
+        }
+        match hits[..] {
+            [_, _] => MadeHand::TwoPair,
+            [rank] => match place(rank) {
+                Some(0) => MadeHand::TopPair,
+                Some(1) => MadeHand::MiddlePair,
+                _ => MadeHand::WeakPair,
+            },
+            _ if pair.0.rank == Rank::Ace || pair.1.rank == Rank::Ace => MadeHand::AceHigh,
+            _ => MadeHand::Nothing,
+        }
+    }
+}
+
+impl Display for MadeHand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            MadeHand::StraightFlush => "straight flush",
+            MadeHand::Quads => "four of a kind",
+            MadeHand::FullHouse => "full house",
+            MadeHand::Flush => "flush",
+            MadeHand::Straight => "straight",
+            MadeHand::Set => "set",
+            MadeHand::Trips => "trips",
+            MadeHand::TwoPair => "two pair",
+            MadeHand::Overpair => "overpair",
+            MadeHand::TopPair => "top pair",
+            MadeHand::UnderTopPair => "pocket pair below top pair",
+            MadeHand::MiddlePair => "middle pair",
+            MadeHand::WeakPair => "weak pair",
+            MadeHand::AceHigh => "ace high",
+            MadeHand::Nothing => "no made hand",
+        })
+    }
+}
+
+/// Weighted combos of a range in each made hand and draw on a board
+#[derive(Debug, PartialEq, Clone)]
+pub struct ComboReport {
+    /// Weight of the combos the board leaves
+    pub total: f64,
+    pub made: BTreeMap<MadeHand, f64>,
+    /// Draws on the flop and turn; on the river every combo is Draw::Nothing
+    pub draws: BTreeMap<Draw, f64>,
+}
+
+impl ComboReport {
+    pub fn new(range: &HandRange, board: &[Card]) -> ComboReport {
+        debug_assert!((3..=5).contains(&board.len()));
+        let mut report = ComboReport { total: 0.0, made: BTreeMap::new(), draws: BTreeMap::new() };
+        for (pair, weight) in range.without(board).combos() {
+            let draw = if board.len() < 5 { Draw::of(&pair, board) } else { Draw::Nothing };
+            report.total += weight;
+            *report.made.entry(MadeHand::of(&pair, board)).or_default() += weight;
+            *report.draws.entry(draw).or_default() += weight;
+        }
+        report
+    }
+
+    pub fn made(&self, made: MadeHand) -> f64 {
+        self.made.get(&made).copied().unwrap_or(0.0)
+    }
+
+    pub fn draw(&self, draw: Draw) -> f64 {
+        self.draws.get(&draw).copied().unwrap_or(0.0)
+    }
+}
+
+impl Display for ComboReport {
+    /// Combos and share of the range for everything the range has some of
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let share = |combos: f64| if self.total > 0.0 { combos / self.total * 100.0 } else { 0.0 };
+        for (made, &combos) in &self.made {
+            writeln!(f, "{:<28} {:>7.1} {:>6.1}%", made.to_string(), combos, share(combos))?;
+        }
+        for (draw, &combos) in self.draws.iter().filter(|&(&draw, _)| draw != Draw::Nothing) {
+            writeln!(f, "{:<28} {:>7.1} {:>6.1}%", draw.to_string(), combos, share(combos))?;
+        }
+        write!(f, "{:<28} {:>7.1}", "total", self.total)
+    }
+}
+
+impl ToJson for ComboReport {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("total", self.total.into()),
+            ("made", Json::Object(self.made.iter().map(|(made, &combos)| (made.to_string(), combos.into())).collect())),
+            ("draws", Json::Object(self.draws.iter().map(|(draw, &combos)| (draw.to_string(), combos.into())).collect())),
+        ])
+    }
+}
+
+/// What holding some cards does to a range on a board: its combos in each made hand and draw
+/// with only the board taken out, and with the held cards taken out too
+#[derive(Debug, PartialEq, Clone)]
+pub struct Blockers {
+    pub before: ComboReport,
+    pub after: ComboReport,
+}
+
+impl Blockers {
+    pub fn new(range: &HandRange, board: &[Card], held: &[Card]) -> Blockers {
+        Blockers {
+            before: ComboReport::new(range, board),
+            after: ComboReport::new(&range.without(held), board),
+        }
+    }
+
+    /// Share (0-1) of the made hand's combos the held cards take out
+    pub fn removed(&self, made: MadeHand) -> f64 {
+        let before = self.before.made(made);
+        if before > 0.0 { 1.0 - self.after.made(made) / before } else { 0.0 }
+    }
+
+    pub fn removed_draw(&self, draw: Draw) -> f64 {
+        let before = self.before.draw(draw);
+        if before > 0.0 { 1.0 - self.after.draw(draw) / before } else { 0.0 }
+    }
+}
+
+impl Display for Blockers {
+    /// Combos before and after, and the share taken out, for everything the range had some of
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for &made in self.before.made.keys() {
+            writeln!(f, "{:<28} {:>7.1} {:>7.1} {:>6.1}%", made.to_string(), self.before.made(made), self.after.made(made), self.removed(made) * 100.0)?;
+        }
+        for &draw in self.before.draws.keys().filter(|&&draw| draw != Draw::Nothing) {
+            writeln!(f, "{:<28} {:>7.1} {:>7.1} {:>6.1}%", draw.to_string(), self.before.draw(draw), self.after.draw(draw), self.removed_draw(draw) * 100.0)?;
+        }
+        write!(f, "{:<28} {:>7.1} {:>7.1}", "total", self.before.total, self.after.total)
+    }
+}
+
+impl ToJson for Blockers {
+    fn to_json(&self) -> Json {
+        Json::object([("before", self.before.to_json()), ("after", self.after.to_json())])
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::*;
+
+    #[test]
+    fn test_combo_report() {
+        let board = parse_cards("Kh9h4c").unwrap();
+        let class = |s: &str| s.parse::<HandClass>().unwrap();
+        let report = ComboReport::new(&HandRange::full(), &board);
+        assert_eq!(report.total, (49 * 48 / 2) as f64);
+        // 3 combos of each pocket pair that hit, 6 of each that didn't
+        assert_eq!(report.made(MadeHand::Set), 3.0 * 3.0);
+        assert_eq!(report.made(MadeHand::Overpair), 6.0);
+        assert_eq!(report.made(MadeHand::UnderTopPair), 6.0 * 3.0);
+        // any two of the 11 hearts left, with or without a straight draw
+        assert_eq!(report.draw(Draw::Flush) + report.draw(Draw::Combo), (11 * 10 / 2) as f64);
+
+        let range = HandRange::from_classes([class("AA"), class("KQs"), class("99"), class("T8s"), class("A5s")]);
+        let report = ComboReport::new(&range, &board);
+        assert_eq!(report.made(MadeHand::Overpair), 6.0);
+        assert_eq!(report.made(MadeHand::TopPair), 3.0);
+        assert_eq!(report.made(MadeHand::Set), 3.0);
+        assert_eq!(report.made(MadeHand::AceHigh), 4.0);
+        assert_eq!(report.made(MadeHand::Nothing), 4.0);
+        // Th8h and Ah5h, neither with a straight draw
+        assert_eq!((report.draw(Draw::Flush), report.draw(Draw::Combo)), (2.0, 0.0));
+        assert_eq!(report.total, 20.0);
+        assert!(report.to_string().contains("set"));
+
+        // the ace of hearts takes out the flush draws with it, 10 of the 55
+        let blockers = Blockers::new(&HandRange::full(), &board, &[Card::new(Rank::Ace, Suit::Hearts)]);
+        let draws = |report: &ComboReport| report.draw(Draw::Flush) + report.draw(Draw::Combo);
+        assert_eq!(draws(&blockers.before) - draws(&blockers.after), 10.0);
+        assert_eq!(blockers.removed(MadeHand::Overpair), 0.5);
+        assert_eq!(blockers.removed(MadeHand::Set), 0.0);
+        assert_eq!(blockers.after.total, (48 * 47 / 2) as f64);
+    }
+}
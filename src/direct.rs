@@ -0,0 +1,117 @@
+//! Scoring hands straight from their cards, with no tables, so it needs nothing but core and
+//! builds without the `std` feature, e.g. for embedded targets or WASM with no system
+//! underneath. The same scheme as the GPU shader: each rank count is kept as a mask of ranks
+//! and the best five cards come out of shifts, ands and bit counts
+
+use crate::card::*;
+
+/// How good a hand is, higher being better (unlike Score):
+/// category << 26 | primary << 13 | secondary, where primary and secondary are masks of ranks
+/// (or one more than a straight's high rank). Masks with as many ranks compare the way kickers do
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Strength(u32);
+
+impl Strength {
+    /// 0 for high card up to 8 for a straight flush, the order of Category
+    pub fn category(self) -> u32 {
+        self.0 >> 26
+    }
+
+    /// The bits as above, what the GPU shader compares
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+}
+
+/// Strength of the best five of five to seven cards
+pub fn strength(cards: &[Card]) -> Strength {
+    assert!((5..=7).contains(&cards.len()), "five to seven cards");
+    let mut suits = [0u32; 4];
+    for card in cards {
+        suits[usize::from(card.suit)] |= 1 << usize::from(card.rank);
+    }
+    strength_of_suits(suits)
+}
+
+/// Strength from each suit's mask of ranks
+pub fn strength_of_suits(suits: [u32; 4]) -> Strength {
+    // with seven cards or fewer a flush rules out quads and full houses
+    for ranks in suits {
+        if ranks.count_ones() >= 5 {
+            return Strength(match straight(ranks) {
+                0 => 5 << 26 | top(ranks, 5) << 13,
+                high => 8 << 26 | high,
+            });
+        }
+    }
+
+    // how many suits have each rank, added up a bit at a time across the masks
+    let [a, b, c, d] = suits;
+    let (low_ab, high_ab, low_cd, high_cd) = (a ^ b, a & b, c ^ d, c & d);
+    let ones = low_ab ^ low_cd;
+    let carry = low_ab & low_cd;
+    let twos = high_ab ^ high_cd ^ carry;
+    let fours = (high_ab & high_cd) | ((high_ab ^ high_cd) & carry);
+    let held = [0, ones & !twos, twos & !ones, ones & twos, fours];
+    let present = held[1] | held[2] | held[3] | held[4];
+    if held[4] != 0 {
+        let quad = top(held[4], 1);
+        return Strength(7 << 26 | quad << 13 | top(present & !quad, 1));
+    }
+    let trip = top(held[3], 1);
+    let full = top((held[3] | held[2]) & !trip, 1);
+    if trip != 0 && full != 0 {
+        return Strength(6 << 26 | trip << 13 | full);
+    }
+    let high = straight(present);
+    if high != 0 {
+        return Strength(4 << 26 | high);
+    }
+    if trip != 0 {
+        return Strength(3 << 26 | trip << 13 | top(present & !trip, 2));
+    }
+    let paired = top(held[2], 2);
+    let pairs = paired.count_ones();
+    Strength(pairs << 26 | paired << 13 | top(present & !paired, 5 - 2 * pairs))
+}
+
+/// The n highest ranks of a mask
+fn top(ranks: u32, n: u32) -> u32 {
+    let mut kept = ranks;
+    while kept.count_ones() > n {
+        kept &= kept - 1;
+    }
+    kept
+}
+
+/// One more than the high rank of the best straight, or 0 without one
+fn straight(ranks: u32) -> u32 {
+    // the ace under the two too, then the lowest rank of every run of five
+    let ranks = ranks << 1 | ranks >> 12;
+    let runs = ranks & ranks >> 1 & ranks >> 2 & ranks >> 3 & ranks >> 4;
+    if runs == 0 { 0 } else { 35 - runs.leading_zeros() }
+}
+
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::category::*;
+    use crate::eval::*;
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+    #[test]
+    fn test_strength() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut deck = Card::get_deck();
+        for _ in 0..20_000 {
+            deck.shuffle(&mut rng);
+            let n = 5 + deck[20].rank as usize % 3;
+            let (ours, theirs) = (&deck[..n], &deck[10..10 + n]);
+            let (ours_score, theirs_score) = (get_best_score(&(ours[0], ours[1]), &ours[2..]), get_best_score(&(theirs[0], theirs[1]), &theirs[2..]));
+            // scores are lower for better hands
+            assert_eq!(strength(ours).cmp(&strength(theirs)), theirs_score.cmp(&ours_score), "{} {}", format_cards(ours), format_cards(theirs));
+            assert_eq!(strength(ours).category(), Category::from_score(ours_score) as u32, "{}", format_cards(ours));
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Equity against each combo of a range rather than the average over all of them, so a
+//! polarized spot (crushing half the range, crushed by the rest) can be told from a merged one
+//! where every combo is close
+
+use crate::card::*;
+use crate::eval::*;
+use crate::json::*;
+use crate::range::*;
+use itertools::Itertools;
+
+
+/// The pair's exact equity against every combo of the range, worst first
+#[derive(Debug, PartialEq, Clone)]
+pub struct EquityDistribution {
+    pub equities: Vec<((Card, Card), f64, f64)>,
+}
+
+impl EquityDistribution {
+    /// Over every runout of the board, so quick from the flop on (like equity_vs_range)
+    pub fn new(pair: &(Card, Card), range: &HandRange, board: &[Card], dead: &[Card]) -> EquityDistribution {
+        debug_assert!(is_valid_deal_with(board, &[*pair], dead));
+        let range = range.without(&[board, dead, &[pair.0, pair.1]].concat());
+        let deck = deck_without(&[board, dead, &[pair.0, pair.1]].concat());
+        let combos: Vec<((Card, Card), f64)> = range.combos().collect();
+
+        // 2 points a win, 1 a tie
+        let mut points = vec![0usize; combos.len()];
+        let mut showdowns = vec![0usize; combos.len()];
+        let mut community = board.to_vec();
+        for remainder in deck.iter().copied().combinations(5 - board.len()) {
+            community.extend_from_slice(&remainder);
+            let my_score = get_best_score(pair, &community);
+            for (i, (evil_pair, _)) in combos.iter().enumerate() {
+                if remainder.contains(&evil_pair.0) || remainder.contains(&evil_pair.1) {
+                    continue;
+                }
+                points[i] += match my_score.cmp(&get_best_score(evil_pair, &community)) {
+                    std::cmp::Ordering::Less => 2,
+                    std::cmp::Ordering::Equal => 1,
+                    std::cmp::Ordering::Greater => 0,
+                };
+                showdowns[i] += 1;
+            }
+            community.truncate(board.len());
+        }
+
+        let mut equities: Vec<((Card, Card), f64, f64)> = combos
+            .into_iter()
+            .zip(points.into_iter().zip(showdowns))
+            .map(|((pair, weight), (points, showdowns))| (pair, weight, points as f64 / (2 * showdowns) as f64))
+            .collect();
+        equities.sort_by(|a, b| a.2.total_cmp(&b.2));
+        EquityDistribution { equities }
+    }
+
+    fn total_weight(&self) -> f64 {
+        self.equities.iter().map(|&(_, weight, _)| weight).sum()
+    }
+
+    /// The average equity, what equity_vs_range gives
+    pub fn mean(&self) -> f64 {
+        self.equities.iter().map(|&(_, weight, equity)| weight * equity).sum::<f64>() / self.total_weight()
+    }
+
+    /// The equity that this fraction (0-1) of the range's weight is at or below
+    pub fn percentile(&self, fraction: f64) -> f64 {
+        debug_assert!((0.0..=1.0).contains(&fraction));
+        let mut left = fraction * self.total_weight();
+        for &(_, weight, equity) in &self.equities {
+            left -= weight;
+            if left <= 0.0 {
+                return equity;
+            }
+        }
+        self.equities.last().map_or(0.0, |&(_, _, equity)| equity)
+    }
+
+    pub fn median(&self) -> f64 {
+        self.percentile(0.5)
+    }
+
+    /// Share of the range's weight in each of this many equal slices of 0-1
+    pub fn histogram(&self, bins: usize) -> Vec<f64> {
+        let mut histogram = vec![0.0; bins];
+        for &(_, weight, equity) in &self.equities {
+            histogram[((equity * bins as f64) as usize).min(bins - 1)] += weight;
+        }
+        let total = self.total_weight();
+        histogram.iter_mut().for_each(|share| *share /= total);
+        histogram
+    }
+
+    /// The histogram as a bar per bin, the fullest bin as wide as given
+    pub fn render(&self, bins: usize, width: usize) -> String {
+        let histogram = self.histogram(bins);
+        let most = histogram.iter().copied().fold(0.0, f64::max);
+        let mut out = String::new();
+        for (bin, share) in histogram.iter().enumerate() {
+            let bar = if most > 0.0 { (share / most * width as f64).round() as usize } else { 0 };
+            out += &format!("{:>3.0}-{:>3.0}% {:>5.1}% {}\n", bin as f64 / bins as f64 * 100.0, (bin + 1) as f64 / bins as f64 * 100.0,
+                            share * 100.0, "█".repeat(bar));
+        }
+        out
+    }
+}
+
+impl ToJson for EquityDistribution {
+    /// Each combo's weight and equity, worst first, with the mean and median
+    fn to_json(&self) -> Json {
+        let combos = self.equities
+            .iter()
+            .map(|&(pair, weight, equity)| Json::object([
+                ("hand", format_cards(&[pair.0, pair.1]).into()),
+                ("weight", weight.into()),
+                ("equity", equity.into()),
+            ]))
+            .collect();
+        Json::object([("mean", self.mean().into()), ("median", self.median().into()), ("combos", Json::Array(combos))])
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::*;
+
+    #[test]
+    fn test_equity_distribution() {
+        // a bluff catcher on the river is polarized against sets and air
+        let board = parse_cards("Kh9c4d2s7h").unwrap();
+        let pair = (Card::new(Rank::King, Suit::Spades), Card::new(Rank::Queen, Suit::Spades));
+        let class = |s: &str| s.parse::<HandClass>().unwrap();
+        let range = HandRange::from_classes([class("99"), class("65s"), class("JTo")]);
+        let distribution = EquityDistribution::new(&pair, &range, &board, &[]);
+        assert_eq!(distribution.percentile(0.1), 0.0);
+        assert_eq!(distribution.percentile(0.9), 1.0);
+        assert!((distribution.mean() - equity_vs_range(&pair, &range, &board, &[])).abs() < 1e-12);
+        let histogram = distribution.histogram(4);
+        assert_eq!(histogram[1] + histogram[2], 0.0);
+        // the 3 sets out of 3 + 4 + 12 combos
+        assert!((histogram[0] - 3.0 / 19.0).abs() < 1e-12);
+        assert_eq!(distribution.render(4, 10).lines().count(), 4);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::card::*;
+use crate::eval::*;
+use crate::json::*;
+use itertools::Itertools;
+use rand::{seq::IndexedRandom, Rng};
+
+
+/// How double board pots went, counted per deal (weighted when enumerating). Half the pot goes
+/// to each board, so winning both scoops and losing both loses it all
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct DoubleBoard {
+    pub deals: usize,
+    pub scoops: usize,
+    pub losses: usize,
+    /// Pot won over every deal, in quarter pots (a half tied on one board is a quarter)
+    pub quarters: usize,
+}
+
+impl DoubleBoard {
+    /// Deals where some but not all of the pot was won
+    pub fn splits(&self) -> usize {
+        self.deals - self.scoops - self.losses
+    }
+
+    pub fn scoop(&self) -> f64 {
+        self.scoops as f64 / self.deals as f64
+    }
+
+    pub fn split(&self) -> f64 {
+        self.splits() as f64 / self.deals as f64
+    }
+
+    pub fn lose(&self) -> f64 {
+        self.losses as f64 / self.deals as f64
+    }
+
+    /// Share of the pot won on average
+    pub fn equity(&self) -> f64 {
+        self.quarters as f64 / (4 * self.deals) as f64
+    }
+
+    pub fn add(&mut self, other: DoubleBoard) {
+        self.deals += other.deals;
+        self.scoops += other.scoops;
+        self.losses += other.losses;
+        self.quarters += other.quarters;
+    }
+
+    /// One showdown on two full boards, counted weight times
+    fn showdown(pair: &(Card, Card), evil_pair: &(Card, Card), boards: [&[Card]; 2], weight: usize) -> DoubleBoard {
+        let quarters: usize = boards
+            .iter()
+            .map(|board| match get_best_score(pair, board).cmp(&get_best_score(evil_pair, board)) {
+                std::cmp::Ordering::Less => 2,
+                std::cmp::Ordering::Equal => 1,
+                std::cmp::Ordering::Greater => 0,
+            })
+            .sum();
+        DoubleBoard {
+            deals: weight,
+            scoops: if quarters == 4 { weight } else { 0 },
+            losses: if quarters == 0 { weight } else { 0 },
+            quarters: weight * quarters,
+        }
+    }
+}
+
+impl ToJson for DoubleBoard {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("deals", self.deals.into()),
+            ("equity", self.equity().into()),
+            ("scoop", self.scoop().into()),
+            ("split", self.split().into()),
+            ("lose", self.lose().into()),
+        ])
+    }
+}
+
+/// Exact double board result of pair against a known evil_pair over every runout of both boards,
+/// which come out of the same deck. Only quick with at least the turn on both
+pub fn eval_double_board_heads_up(pair: &(Card, Card), evil_pair: &(Card, Card), boards: [&[Card]; 2], dead: &[Card]) -> DoubleBoard {
+    debug_assert!(is_valid_deal_with(boards[0], &[*pair, *evil_pair], &[boards[1], dead].concat()) && boards[1].len() <= 5);
+    let hands = [pair.0, pair.1, evil_pair.0, evil_pair.1];
+    let deck = deck_without(&[boards[0], boards[1], dead, &hands].concat());
+    let mut result = DoubleBoard::default();
+
+    // suits only swap on the first board if the second doesn't care either
+    let fixed: [&[Card]; 5] = [&hands[..2], &hands[2..], boards[0], boards[1], dead];
+    let mut second = boards[1].to_vec();
+    for_each_runout(boards[0], &deck, &fixed, |first, remainder, weight| {
+        for runout in deck.iter().filter(|card| !remainder.contains(card)).copied().combinations(5 - boards[1].len()) {
+            second.extend_from_slice(&runout);
+            result.add(DoubleBoard::showdown(pair, evil_pair, [first, &second], weight));
+            second.truncate(boards[1].len());
+        }
+    });
+    result
+}
+
+/// Double board against what's known of the villain from n random deals of the evil pair and both boards
+pub fn eval_double_board<R: Rng + ?Sized>(pair: &(Card, Card), villain: &Villain, boards: [&[Card]; 2], dead: &[Card], n: usize, rng: &mut R) -> DoubleBoard {
+    let known = villain.known_cards();
+    debug_assert!(is_valid_deal_with(boards[0], &[*pair], &[boards[1], &known, dead].concat()) && boards[1].len() <= 5);
+    let deck = deck_without(&[boards[0], boards[1], &known, dead, &[pair.0, pair.1]].concat());
+    let needed = [2 - known.len(), 5 - boards[0].len(), 5 - boards[1].len()];
+
+    let mut result = DoubleBoard::default();
+    for _ in 0..n {
+        let cards: Vec<Card> = deck.choose_multiple(rng, needed.iter().sum()).copied().collect();
+        let (evil, rest) = cards.split_at(needed[0]);
+        let (first, second) = rest.split_at(needed[1]);
+        let evil: Vec<Card> = known.iter().chain(evil).copied().collect();
+        let first = [boards[0], first].concat();
+        let second = [boards[1], second].concat();
+        result.add(DoubleBoard::showdown(pair, &(evil[0], evil[1]), [&first, &second], 1));
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_board() {
+        let aces = (Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::Ace, Suit::Diamonds));
+        let kings = (Card::new(Rank::King, Suit::Spades), Card::new(Rank::King, Suit::Hearts));
+        let first = parse_cards("AsKd7h2c").unwrap();
+        let second = parse_cards("QsJc4d3h").unwrap();
+        let result = eval_double_board_heads_up(&aces, &kings, [&first, &second], &[]);
+        // one card each from the 40 left
+        assert_eq!(result.deals, 40 * 39);
+        assert_eq!(result.scoops + result.splits() + result.losses, result.deals);
+        // the kings need the last king on both boards at once, which can't happen
+        assert_eq!(result.losses, 0);
+        assert!(result.scoop() > 0.9);
+
+        let result = eval_double_board(&aces, &Villain::Known(kings.0, kings.1), [&first, &second], &[], 200, &mut rand::rng());
+        assert_eq!(result.deals, 200);
+        assert_eq!(result.losses, 0);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::card::*;
+use crate::category::*;
+use crate::eval::*;
+use crate::json::*;
+use crate::low::*;
+use crate::monte_carlo::*;
+use std::time::Instant;
+use itertools::Itertools;
+use rand::{seq::{IndexedRandom, SliceRandom}, Rng};
+
+
+/// Cards in a draw hand
+pub const DRAW_CARDS: usize = 5;
+
+fn is_valid_draw(keep: &[Card], villain_keep: &[Card], dead: &[Card]) -> bool {
+    keep.len() <= DRAW_CARDS && villain_keep.len() <= DRAW_CARDS && [keep, villain_keep, dead].concat().iter().all_unique()
+}
+
+/// Exact deuce-to-seven single draw equity. Each player keeps some cards and draws the rest from
+/// what's left, a pat hand keeping all five. Dead cards are out of play, like your own discards.
+/// Quick up to two cards drawn each, returns (win_count, tie_count, lose_count)
+pub fn eval_deuce_seven_heads_up(keep: &[Card], villain_keep: &[Card], dead: &[Card]) -> (usize, usize, usize) {
+    debug_assert!(is_valid_draw(keep, villain_keep, dead));
+    let deck = deck_without(&[keep, villain_keep, dead].concat());
+    let mut hands = vec![keep.to_vec(), villain_keep.to_vec()];
+    let mut tally = Tally::default();
+    for_each_completion(&mut hands, DRAW_CARDS, &deck, |hands| {
+        match DeuceSeven::of(&hands[0]).cmp(&DeuceSeven::of(&hands[1])) {
+            std::cmp::Ordering::Less => tally.wins += 1,
+            std::cmp::Ordering::Equal => tally.ties += 1,
+            std::cmp::Ordering::Greater => tally.losses += 1,
+        }
+    });
+    (tally.wins, tally.ties, tally.losses)
+}
+
+/// Monte carlo deuce-to-seven single draw equity, as eval_deuce_seven_heads_up but drawing at
+/// random until the stop rule is met, for when both players draw several cards
+pub fn eval_deuce_seven<R: Rng + ?Sized>(keep: &[Card], villain_keep: &[Card], dead: &[Card], rule: &StopRule, rng: &mut R) -> Estimate {
+    debug_assert!(is_valid_draw(keep, villain_keep, dead));
+    let mut deck = deck_without(&[keep, villain_keep, dead].concat());
+    let draws = DRAW_CARDS - keep.len();
+
+    let start = Instant::now();
+    let mut tally = Tally::default();
+    loop {
+        for _ in 0..(rule.max_samples - tally.samples()).min(256) {
+            let (cards, _) = deck.partial_shuffle(rng, 2 * DRAW_CARDS - keep.len() - villain_keep.len());
+            let (drawn, villain_drawn) = cards.split_at(draws);
+            let hand = [keep, drawn].concat();
+            let villain_hand = [villain_keep, villain_drawn].concat();
+            match DeuceSeven::of(&hand).cmp(&DeuceSeven::of(&villain_hand)) {
+                std::cmp::Ordering::Less => tally.wins += 1,
+                std::cmp::Ordering::Equal => tally.ties += 1,
+                std::cmp::Ordering::Greater => tally.losses += 1,
+            }
+        }
+        let estimate = Estimate::wilson(tally, rule.confidence);
+        if rule.is_done(&estimate, start) {
+            return estimate;
+        }
+    }
+}
+
+/// Category of a made five card draw hand, scored as it stands
+pub fn draw_category(hand: &[Card]) -> Category {
+    Game::FiveCardDraw.best_score(hand, &[]).category()
+}
+
+/// Throw away the discards and replace them from the deck, leaving out dead cards (like other
+/// players' hands or discards, when they're known)
+pub fn draw<R: Rng + ?Sized>(hand: &[Card], discard: &[Card], dead: &[Card], rng: &mut R) -> Vec<Card> {
+    debug_assert!(discard.iter().all(|card| hand.contains(card)));
+    let deck = deck_without(&[hand, dead].concat());
+    hand.iter()
+        .copied()
+        .filter(|card| !discard.contains(card))
+        .chain(deck.choose_multiple(rng, discard.len()).copied())
+        .collect()
+}
+
+/// How every possible draw to a hand comes out, by category
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DrawOutcomes {
+    /// Category of the hand before drawing
+    pub before: Category,
+    /// Draws ending in each category, indexed by category
+    pub counts: [usize; 9],
+}
+
+impl DrawOutcomes {
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Chance of ending with exactly this category
+    pub fn probability(&self, category: Category) -> f64 {
+        self.counts[usize::from(category)] as f64 / self.total() as f64
+    }
+
+    /// Chance of ending in a better category than before the draw
+    pub fn improve(&self) -> f64 {
+        self.counts[usize::from(self.before) + 1..].iter().sum::<usize>() as f64 / self.total() as f64
+    }
+}
+
+impl ToJson for DrawOutcomes {
+    /// Chances by the names categories display as, leaving out those no draw ends in
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("before", self.before.to_string().into()),
+            ("draws", self.total().into()),
+            ("categories", Json::Object(Category::ALL_CATEGORIES.iter().rev()
+                .filter(|&&category| self.counts[usize::from(category)] > 0)
+                .map(|&category| (category.to_string(), self.probability(category).into()))
+                .collect())),
+            ("improves", self.improve().into()),
+        ])
+    }
+}
+
+/// Every way to replace the discards from the deck (without the hand or dead cards), counted by
+/// the category of the new hand. At most about a million draws, discarding all five
+pub fn draw_outcomes(hand: &[Card], discard: &[Card], dead: &[Card]) -> DrawOutcomes {
+    debug_assert!(hand.len() == DRAW_CARDS && discard.iter().all(|card| hand.contains(card)));
+    let deck = deck_without(&[hand, dead].concat());
+    let keep: Vec<Card> = hand.iter().copied().filter(|card| !discard.contains(card)).collect();
+    let mut outcomes = DrawOutcomes { before: draw_category(hand), counts: [0; 9] };
+    for_each_completion(&mut [keep], DRAW_CARDS, &deck, |hands| {
+        outcomes.counts[usize::from(draw_category(&hands[0]))] += 1;
+    });
+    outcomes
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_deuce_seven() {
+        // drawing one to 7-5-4-3 against a pat 8-6: only a deuce wins, a six makes a straight
+        let keep = parse_cards("7c5d4s3h").unwrap();
+        let pat = parse_cards("8h6c5h4d2s").unwrap();
+        assert_eq!(eval_deuce_seven_heads_up(&keep, &pat, &[]), (3, 0, 40));
+        // with a deuce among the discards
+        assert_eq!(eval_deuce_seven_heads_up(&keep, &pat, &parse_cards("2cKd").unwrap()), (2, 0, 39));
+
+        let rule = StopRule { half_width: 0.0, max_samples: 500, ..StopRule::default() };
+        let estimate = eval_deuce_seven(&pat, &keep, &[], &rule, &mut rand::rng());
+        assert_eq!(estimate.tally.samples(), 500);
+        assert!(estimate.equity > 0.8);
+    }
+
+    #[test]
+    fn test_draw_outcomes() {
+        let hand = parse_cards("AhKhQh7h2c").unwrap();
+        assert_eq!(draw_category(&hand), Category::HighCard);
+
+        // nine hearts left of 47 cards
+        let outcomes = draw_outcomes(&hand, &parse_cards("2c").unwrap(), &[]);
+        assert_eq!(outcomes.total(), 47);
+        assert_eq!(outcomes.counts[usize::from(Category::Flush)], 9);
+        // or pairing one of four ranks
+        assert_eq!(outcomes.counts[usize::from(Category::Pair)], 12);
+        assert!((outcomes.improve() - 21.0 / 47.0).abs() < 1e-9);
+
+        // three to trips drawing two
+        let outcomes = draw_outcomes(&parse_cards("9c9d9s4h2c").unwrap(), &parse_cards("4h2c").unwrap(), &[]);
+        assert_eq!(outcomes.total(), 1081);
+        assert_eq!(outcomes.counts[usize::from(Category::Quads)], 46);
+        assert_eq!(outcomes.before, Category::Trips);
+
+        let drawn = draw(&hand, &parse_cards("2c7h").unwrap(), &[], &mut rand::rng());
+        assert_eq!(drawn.len(), 5);
+        assert!(drawn.iter().all_unique() && drawn[..3] == hand[..3]);
+    }
+}
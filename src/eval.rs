@@ -0,0 +1,710 @@
+use crate::cancel::*;
+use crate::card::*;
+use crate::combinatorics::*;
+use crate::hand::*;
+use crate::iso::*;
+use crate::eval_batch::*;
+use crate::score::*;
+use itertools::Itertools;
+
+use std::{collections::HashMap, fmt::{Display, Formatter}, ops::{Range, RangeInclusive}, str::FromStr, sync::{atomic::{AtomicUsize, Ordering}, LazyLock, Mutex}};
+use rand::{seq::IteratorRandom, rng};
+
+pub static SCORES: LazyLock<HandMap<Score>> = LazyLock::new(|| scores_for(Deck::Full));
+/// Short deck (six plus) scores, for Game::ShortDeck
+pub static SHORT_DECK_SCORES: LazyLock<HandMap<Score>> = LazyLock::new(|| scores_for(Deck::Short));
+
+/// The raw tables behind the scores, full deck then short, kept for their categories
+pub(crate) static SCORE_TABLES: [LazyLock<ScoreTable>; 2] = [
+    LazyLock::new(|| create_score_table(Deck::Full)),
+    LazyLock::new(|| create_score_table(Deck::Short)),
+];
+
+fn scores_for(deck: Deck) -> HandMap<Score> {
+    SCORE_TABLES[usize::from(deck == Deck::Short)].scores
+        .iter()
+        .map(|(&hand, &raw)| (hand, Score::new(raw, deck)))
+        .collect()
+}
+
+/// Best (lowest) score of any 5 cards from the pair and the community cards
+pub fn get_best_score(pair: &(Card, Card), community: &[Card]) -> Score {
+    Game::Holdem.best_score(&[pair.0, pair.1], community)
+}
+
+/// Rules for making a five card hand out of hole cards and the board
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Game {
+    /// Two hole cards, any five of them and the board
+    #[default]
+    Holdem,
+    /// Four hole cards, exactly two of them with exactly three from the board
+    Omaha,
+    /// Hold'em with a short deck (six plus), see create_score_table for how hands rank
+    ShortDeck,
+    /// Five card draw: five hole cards and no board, the hand is just the five
+    FiveCardDraw,
+}
+
+impl Game {
+    pub const ALL_GAMES: [Game; 4] = [Game::Holdem, Game::Omaha, Game::ShortDeck, Game::FiveCardDraw];
+}
+
+/// What sets one game apart from another when it comes to showdowns: the deck, how many hole cards
+/// there are and how many of them a hand has to use, and how five card hands rank. Everything
+/// else, dealing runouts and enumerating or sampling what the villain holds, is shared
+pub trait GameRules {
+    fn deck(&self) -> Deck;
+
+    fn hole_cards(&self) -> usize;
+
+    /// Fewest and most hole cards a five card hand can use, the rest coming from the board
+    fn hole_cards_used(&self) -> RangeInclusive<usize>;
+
+    /// Score of every five card hand, which decides how they rank
+    fn scores(&self) -> &'static HandMap<Score>;
+
+    /// Best (lowest) score of a five card hand the rules allow
+    fn best_score(&self, hole: &[Card], community: &[Card]) -> Score {
+        debug_assert_eq!(hole.len(), self.hole_cards());
+        let scores = self.scores();
+        let mut hand = [hole[0]; 5];
+        let mut best: Option<Score> = None;
+        for used in self.hole_cards_used() {
+            for_each_combination(hole.len(), used, |from_hole| {
+                for (slot, &i) in from_hole.iter().enumerate() {
+                    hand[slot] = hole[i];
+                }
+                for_each_combination(community.len(), 5 - used, |from_board| {
+                    for (slot, &i) in from_board.iter().enumerate() {
+                        hand[used + slot] = community[i];
+                    }
+                    let score = scores[&Hand::new(&hand)];
+                    best = Some(best.map_or(score, |best| best.min(score)));
+                });
+            });
+        }
+        best.expect("not enough cards for a five card hand")
+    }
+}
+
+impl GameRules for Game {
+    fn deck(&self) -> Deck {
+        match self {
+            Game::Holdem | Game::Omaha | Game::FiveCardDraw => Deck::Full,
+            Game::ShortDeck => Deck::Short,
+        }
+    }
+
+    fn hole_cards(&self) -> usize {
+        match self {
+            Game::Holdem | Game::ShortDeck => 2,
+            Game::Omaha => 4,
+            Game::FiveCardDraw => 5,
+        }
+    }
+
+    fn hole_cards_used(&self) -> RangeInclusive<usize> {
+        match self {
+            Game::Holdem | Game::ShortDeck => 0..=2,
+            Game::Omaha => 2..=2,
+            Game::FiveCardDraw => 5..=5,
+        }
+    }
+
+    fn scores(&self) -> &'static HandMap<Score> {
+        match self.deck() {
+            Deck::Full => &SCORES,
+            Deck::Short => &SHORT_DECK_SCORES,
+        }
+    }
+}
+
+impl Display for Game {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Game::Holdem => "holdem",
+            Game::Omaha => "omaha",
+            Game::ShortDeck => "short-deck",
+            Game::FiveCardDraw => "draw",
+        })
+    }
+}
+
+impl FromStr for Game {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Game::ALL_GAMES
+            .into_iter()
+            .find(|game| game.to_string() == s)
+            .ok_or("Expected holdem, omaha, short-deck or draw")
+    }
+}
+
+
+/// is_valid_deal with some other cards (dead, exposed) that mustn't clash either
+pub fn is_valid_deal_with(board: &[Card], pairs: &[(Card, Card)], others: &[Card]) -> bool {
+    let cards: Vec<Card> = others.iter()
+        .copied()
+        .chain(pairs.iter().flat_map(|pair| [pair.0, pair.1]))
+        .collect();
+    is_valid_deal(board, pairs) && [board, &cards].concat().iter().all_unique()
+}
+
+/// The deck with every card in used taken out
+pub fn deck_without(used: &[Card]) -> Vec<Card> {
+    let mut deck = Card::get_deck();
+    deck.retain(|card| !used.contains(card));
+    deck
+}
+
+/// At most 5 board cards and no card dealt twice
+pub fn is_valid_deal(board: &[Card], pairs: &[(Card, Card)]) -> bool {
+    let cards: Vec<Card> = board.iter()
+        .copied()
+        .chain(pairs.iter().flat_map(|pair| [pair.0, pair.1]))
+        .collect();
+    board.len() <= 5 && cards.iter().all_unique()
+}
+
+/// Deal the rest of a board of any length (0-5) from deck in every possible way,
+/// calling f with the full 5 card board, the cards dealt and how many runouts it stands for.
+/// Runouts that only differ by swapping suits none of the fixed groups (hands, board) care about
+/// score the same, so only one of each is visited
+pub fn for_each_runout(board: &[Card], deck: &[Card], fixed: &[&[Card]], mut f: impl FnMut(&[Card], &[Card], usize)) {
+    debug_assert!(board.len() <= 5);
+    let perms = stabilizer(fixed);
+
+    let mut community = board.to_vec();
+    let n = community.len();
+
+    for remainder in deck.iter().copied().combinations(5 - n) {
+        let Some(weight) = orbit_weight(&remainder, &perms) else {
+            continue;
+        };
+        community.extend_from_slice(&remainder);
+        f(&community, &remainder, weight);
+        community.truncate(n);
+    }
+}
+
+
+/// Every way to deal each hand the cards it's missing to reach size from deck, calling f with the
+/// hands once all are full. For games without a board, where each player's unseen cards are their own
+pub fn for_each_completion(hands: &mut [Vec<Card>], size: usize, deck: &[Card], mut f: impl FnMut(&[Vec<Card>])) {
+    complete_from(hands, 0, size, deck, &mut f);
+}
+
+fn complete_from(hands: &mut [Vec<Card>], i: usize, size: usize, deck: &[Card], f: &mut impl FnMut(&[Vec<Card>])) {
+    let Some(hand) = hands.get(i) else {
+        f(hands);
+        return;
+    };
+    let have = hand.len();
+    for missing in deck.iter().copied().combinations(size - have) {
+        let rest: Vec<Card> = deck.iter().copied().filter(|card| !missing.contains(card)).collect();
+        hands[i].extend_from_slice(&missing);
+        complete_from(hands, i + 1, size, &rest, f);
+        hands[i].truncate(have);
+    }
+}
+
+/// exhaustive search over every runout and evil pair, from any board length (0-5)
+/// though it's only quick with at least the flop on the board
+/// returns (win_count, lose_count)
+pub fn eval_with_community(community: Vec<Card>, pair: &(Card, Card)) -> (usize, usize) {
+    let count = runout_count(community.len(), 50 - community.len());
+    eval_runout_range(&community, pair, 0..count)
+}
+
+/// eval_with_community over only the runouts whose index (see unrank_combination)
+/// is in range, so the work can be split up and the tallies added back together
+pub fn eval_runout_range(community: &[Card], pair: &(Card, Card), range: Range<usize>) -> (usize, usize) {
+    let (win_count, tie_count, lose_count) = tally_runout_range(community, pair, range);
+    (win_count, tie_count + lose_count)
+}
+
+/// eval_runout_range keeping ties apart from losses
+/// returns (win_count, tie_count, lose_count)
+pub fn tally_runout_range(community: &[Card], pair: &(Card, Card), range: Range<usize>) -> (usize, usize, usize) {
+    tally_runout_range_vs(community, pair, &Villain::Random, &[], range, None)
+}
+
+/// What's known about the evil pair
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Villain {
+    /// Any two cards
+    #[default]
+    Random,
+    /// One card is showing (e.g. a stud upcard), the other could be anything
+    Exposed(Card),
+    /// Both cards are known, as with eval_heads_up
+    Known(Card, Card),
+}
+
+impl Villain {
+    pub fn known_cards(&self) -> Vec<Card> {
+        match self {
+            Villain::Random => Vec::new(),
+            Villain::Exposed(card) => vec![*card],
+            Villain::Known(a, b) => vec![*a, *b],
+        }
+    }
+
+    /// Size of the runout index space for a board, after the pair, known and dead cards are taken out
+    pub fn runouts(&self, board_len: usize, dead: usize) -> usize {
+        runout_count(board_len, 50 - board_len - self.known_cards().len() - dead)
+    }
+
+    /// Evil pairs left once the whole board is out
+    pub fn evil_pairs_per_runout(&self, dead: usize) -> usize {
+        let unseen = 45 - self.known_cards().len() - dead;
+        match self {
+            Villain::Random => choose(unseen, 2),
+            Villain::Exposed(_) => unseen,
+            Villain::Known(_, _) => 1,
+        }
+    }
+
+    /// Every evil pair still possible with these cards left in the deck
+    pub fn evil_pairs(&self, deck: &[Card]) -> Vec<(Card, Card)> {
+        match self {
+            Villain::Random => deck.iter().copied().tuple_combinations().collect(),
+            Villain::Exposed(exposed) => deck.iter().map(|&card| (*exposed, card)).collect(),
+            Villain::Known(a, b) => vec![(*a, *b)],
+        }
+    }
+}
+
+/// tally_runout_range against a villain who may have a card showing, with dead cards
+/// (mucked or seen elsewhere) out of the deck. The runout index space is over the deck with
+/// those cards removed too.
+/// Once cancel is cancelled it stops before the next runout, returning what it has so far
+pub fn tally_runout_range_vs(community: &[Card], pair: &(Card, Card), villain: &Villain, dead: &[Card], range: Range<usize>,
+                             cancel: Option<&CancelToken>) -> (usize, usize, usize) {
+    let known = villain.known_cards();
+    debug_assert!(is_valid_deal_with(community, &[*pair], &[&known, dead].concat()));
+    let mut win_count: usize = 0;
+    let mut tie_count: usize = 0;
+    let mut lose_count: usize = 0;
+
+    let deck = deck_without(&[community, &known, dead, &[pair.0, pair.1]].concat());
+
+    let evil_pairs = villain.evil_pairs(&deck);
+
+    // with the turn or river out there are too few runouts for anything but scoring every one
+    let community_mask = community.iter().fold(0, |mask, &card| mask | card_bit(card));
+    match community.len() {
+        5 if range.contains(&0) => return tally_board(community_mask, pair, &evil_pairs),
+        5 => return (0, 0, 0),
+        4 => {
+            for index in range {
+                if is_cancelled(cancel) {
+                    break;
+                }
+                let (win, tie, lose) = tally_board(community_mask | card_bit(deck[index]), pair, &evil_pairs);
+                (win_count, tie_count, lose_count) = (win_count + win, tie_count + tie, lose_count + lose);
+            }
+            return (win_count, tie_count, lose_count);
+        }
+        _ => {}
+    }
+
+    // runouts that only differ by swapping suits the hand and board don't care about score the same
+    let perms = stabilizer(&[&[pair.0, pair.1], community, &known, dead]);
+
+    let mut board = community.to_vec();
+    let n = board.len();
+    let (mut indices, mut remainder) = ([0; 5], [pair.0; 5]);
+
+    for index in range {
+        if is_cancelled(cancel) {
+            break;
+        }
+        unrank_combination_into(index, deck.len(), &mut indices[..5 - n]);
+        for (card, &i) in remainder.iter_mut().zip(&indices[..5 - n]) {
+            *card = deck[i];
+        }
+        let remainder = &remainder[..5 - n];
+        let Some(weight) = orbit_weight(remainder, &perms) else {
+            continue;
+        };
+        board.extend_from_slice(remainder);
+
+        let mut scores = RunoutScores::new(&board);
+        let my_score = scores.best(pair);
+
+        for evil_pair in &evil_pairs {
+            // Skip if evil_pair contains a card from the runout
+            if remainder.contains(&evil_pair.0) || remainder.contains(&evil_pair.1) {
+                continue;
+            }
+            match my_score.cmp(&scores.best(evil_pair)) {
+                std::cmp::Ordering::Less => win_count += weight,
+                std::cmp::Ordering::Equal => tie_count += weight,
+                std::cmp::Ordering::Greater => lose_count += weight,
+            }
+        }
+        board.truncate(n);
+    }
+    (win_count, tie_count, lose_count)
+}
+
+/// pair against every evil pair that doesn't use one of the board's cards, the whole board given
+/// as a mask of cards (see card_bit)
+fn tally_board(board: u64, pair: &(Card, Card), evil_pairs: &[(Card, Card)]) -> (usize, usize, usize) {
+    let my_score = score_mask(board | card_bit(pair.0) | card_bit(pair.1));
+    let mut tally = (0, 0, 0);
+    for evil_pair in evil_pairs {
+        let evil_hole = card_bit(evil_pair.0) | card_bit(evil_pair.1);
+        if evil_hole & board != 0 {
+            continue;
+        }
+        match my_score.cmp(&score_mask(board | evil_hole)) {
+            std::cmp::Ordering::Less => tally.0 += 1,
+            std::cmp::Ordering::Equal => tally.1 += 1,
+            std::cmp::Ordering::Greater => tally.2 += 1,
+        }
+    }
+    tally
+}
+
+/// get_best_score for every pair on one complete board, sharing the work between them: the board
+/// on its own and each card with four of the board are only scored once (the latter when first
+/// needed), leaving the ten hands with both hole cards for each pair instead of all 21
+pub struct RunoutScores {
+    board: [Card; 5],
+    board_score: Score,
+    singles: [Option<Score>; 52],
+}
+
+impl RunoutScores {
+    /// Each three of the board's cards
+    const THREES: [[usize; 3]; 10] = [[0, 1, 2], [0, 1, 3], [0, 1, 4], [0, 2, 3], [0, 2, 4],
+                                      [0, 3, 4], [1, 2, 3], [1, 2, 4], [1, 3, 4], [2, 3, 4]];
+
+    pub fn new(board: &[Card]) -> RunoutScores {
+        let board: [Card; 5] = board.try_into().expect("the whole board has to be out");
+        RunoutScores { board, board_score: SCORES[&Hand::new(&board)], singles: [None; 52] }
+    }
+
+    /// Best score of any five of the pair and the board
+    pub fn best(&mut self, pair: &(Card, Card)) -> Score {
+        let b = self.board;
+        let mut best = self.board_score.min(self.single(pair.0)).min(self.single(pair.1));
+        for [i, j, k] in Self::THREES {
+            best = best.min(SCORES[&Hand::from_cards([pair.0, pair.1, b[i], b[j], b[k]])]);
+        }
+        best
+    }
+
+    /// Best score of the card with four of the board
+    fn single(&mut self, card: Card) -> Score {
+        let b = self.board;
+        *self.singles[usize::from(card)].get_or_insert_with(|| {
+            // leaving out each of the board's cards in turn
+            (0..5)
+                .map(|i| SCORES[&Hand::from_cards([card, b[(i + 1) % 5], b[(i + 2) % 5], b[(i + 3) % 5], b[(i + 4) % 5]])])
+                .min()
+                .unwrap()
+        })
+    }
+}
+
+/// Exact result of pair against a known evil_pair over every runout of the community cards
+/// returns (win_count, tie_count, lose_count)
+pub fn eval_heads_up(pair: &(Card, Card), evil_pair: &(Card, Card), community: &[Card]) -> (usize, usize, usize) {
+    eval_heads_up_with_dead(pair, evil_pair, community, &[])
+}
+
+/// eval_heads_up with dead cards that can't come on the board
+pub fn eval_heads_up_with_dead(pair: &(Card, Card), evil_pair: &(Card, Card), community: &[Card], dead: &[Card]) -> (usize, usize, usize) {
+    debug_assert!(is_valid_deal_with(community, &[*pair, *evil_pair], dead));
+    eval_game_heads_up(&Game::Holdem, &[pair.0, pair.1], &[evil_pair.0, evil_pair.1], community, dead)
+}
+
+/// The game's deck without the used cards
+pub fn game_deck_without(game: &impl GameRules, used: &[Card]) -> Vec<Card> {
+    let mut deck = game.deck().cards();
+    deck.retain(|card| !used.contains(card));
+    deck
+}
+
+/// Exact result of hole cards against known evil ones over every runout of the community cards,
+/// under any game's rules, with dead cards that can't come on the board
+/// returns (win_count, tie_count, lose_count)
+pub fn eval_game_heads_up(game: &impl GameRules, hole: &[Card], evil_hole: &[Card], community: &[Card], dead: &[Card]) -> (usize, usize, usize) {
+    debug_assert!(is_valid_deal_with(community, &[], &[hole, evil_hole, dead].concat()));
+    let mut win_count: usize = 0;
+    let mut tie_count: usize = 0;
+    let mut lose_count: usize = 0;
+
+    let deck = game_deck_without(game, &[community, dead, hole, evil_hole].concat());
+
+    let fixed: [&[Card]; 4] = [hole, evil_hole, community, dead];
+    for_each_runout(community, &deck, &fixed, |board, _, weight| {
+        match game.best_score(hole, board).cmp(&game.best_score(evil_hole, board)) {
+            std::cmp::Ordering::Less => win_count += weight,
+            std::cmp::Ordering::Equal => tie_count += weight,
+            std::cmp::Ordering::Greater => lose_count += weight,
+        }
+    });
+    (win_count, tie_count, lose_count)
+}
+
+/// Exact result of pair against a random hand before the flop: every evil pair on every board,
+/// about two billion showdowns. Evil pairs that are the same as another up to suits (relative to
+/// pair) are only enumerated once, which leaves a few hundred, shared out between threads.
+/// Results are kept, so asking again for the hand (or the same one in other suits) is free
+/// returns (win_count, tie_count, lose_count)
+pub fn eval_preflop_exact(pair: &(Card, Card)) -> (usize, usize, usize) {
+    // by canonical hand
+    type Known = HashMap<Vec<Card>, (usize, usize, usize)>;
+    static KNOWN: LazyLock<Mutex<Known>> = LazyLock::new(Default::default);
+    let key = canonicalize(&[pair.0, pair.1]);
+    if let Some(&tally) = KNOWN.lock().unwrap().get(&key) {
+        return tally;
+    }
+
+    let hole = vec![pair.0, pair.1];
+    let evil_pairs = dedupe(deck_without(&hole).into_iter().tuple_combinations::<(Card, Card)>(), |evil_pair| {
+        vec![hole.clone(), vec![evil_pair.0, evil_pair.1]]
+    });
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let next = AtomicUsize::new(0);
+    let tally = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads).map(|_| scope.spawn(|| {
+            let mut tally = (0, 0, 0);
+            while let Some(&(evil_pair, weight)) = evil_pairs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                let (win, tie, lose) = eval_preflop_heads_up(pair, &evil_pair);
+                tally = (tally.0 + weight * win, tally.1 + weight * tie, tally.2 + weight * lose);
+            }
+            tally
+        })).collect();
+        workers.into_iter().map(|worker| worker.join().unwrap()).fold((0, 0, 0), |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2))
+    });
+    KNOWN.lock().unwrap().insert(key, tally);
+    tally
+}
+
+/// eval_heads_up with no board, each board's cards masked once for both hands (see score_mask)
+fn eval_preflop_heads_up(pair: &(Card, Card), evil_pair: &(Card, Card)) -> (usize, usize, usize) {
+    let deck: Vec<u64> = deck_without(&[pair.0, pair.1, evil_pair.0, evil_pair.1]).into_iter().map(card_bit).collect();
+    let hole = card_bit(pair.0) | card_bit(pair.1);
+    let evil_hole = card_bit(evil_pair.0) | card_bit(evil_pair.1);
+    let mut tally = (0, 0, 0);
+    for_each_combination(deck.len(), 5, |board| {
+        let board = board.iter().fold(0, |mask, &card| mask | deck[card]);
+        match score_mask(hole | board).cmp(&score_mask(evil_hole | board)) {
+            std::cmp::Ordering::Less => tally.0 += 1,
+            std::cmp::Ordering::Equal => tally.1 += 1,
+            std::cmp::Ordering::Greater => tally.2 += 1,
+        }
+    });
+    tally
+}
+
+/// A quick estimate of eval_preflop_exact, from n random boards against every evil pair on them
+pub fn eval_hand_monte_carlo(pair: &(Card, Card), n: usize) -> (usize, usize) {
+    let mut win_count: usize = 0;
+    let mut lose_count: usize = 0;
+
+    let mut deck: Vec<Card> = Card::get_deck();
+    deck.retain(|card| *card != pair.0 && *card != pair.1);
+
+    let mut rng = rng();
+
+
+    for community in deck.iter().copied().combinations(5).choose_multiple(&mut rng, n) {
+        
+        let score = get_best_score(pair, &community);
+        for evil_pair in deck.iter().copied().tuple_combinations::<(Card,Card)>() {
+            
+            if community.contains(&evil_pair.0) || community.contains(&evil_pair.1) {
+                continue;
+            }
+
+            if score < get_best_score(&evil_pair, &community) {
+                win_count += 1;
+            } else {
+                lose_count += 1;
+            }
+        }
+    }
+    (win_count, lose_count)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_preflop_heads_up() {
+        let pair = (Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::King, Suit::Hearts));
+        let evil_pair = (Card::new(Rank::Queen, Suit::Spades), Card::new(Rank::Queen, Suit::Diamonds));
+        // as eval_heads_up has it, over all 1,712,304 boards
+        assert_eq!(eval_preflop_heads_up(&pair, &evil_pair), (787966, 6732, 917606));
+    }
+
+    #[test]
+    fn test_eval_heads_up() {
+        let board = [Card::new(Rank::Ace, Suit::Hearts),
+                     Card::new(Rank::King, Suit::Hearts),
+                     Card::new(Rank::Four, Suit::Spades),
+                     Card::new(Rank::Nine, Suit::Clubs)];
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        let evil_pair = (Card::new(Rank::Ace, Suit::Spades), Card::new(Rank::Queen, Suit::Clubs));
+
+        // 9 hearts for the flush, 4 fives for the wheel, one of which is the 5h
+        assert_eq!(eval_heads_up(&pair, &evil_pair, &board), (12, 0, 32));
+        assert_eq!(eval_heads_up(&evil_pair, &pair, &board), (32, 0, 12));
+    }
+
+    #[test]
+    fn test_turn_and_river() {
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        let dead = [Card::new(Rank::Five, Suit::Hearts)];
+        let board = parse_cards("AhKh4s9c").unwrap();
+        for villain in [Villain::Random, Villain::Exposed(Card::new(Rank::Ace, Suit::Spades))] {
+            // every river and evil pair scored in full
+            let mut naive = (0, 0, 0);
+            let deck = deck_without(&[&board[..], &dead, &villain.known_cards(), &[pair.0, pair.1]].concat());
+            for &river in &deck {
+                let board = [&board[..], &[river]].concat();
+                for evil_pair in villain.evil_pairs(&deck).into_iter().filter(|evil_pair| evil_pair.0 != river && evil_pair.1 != river) {
+                    match get_best_score(&pair, &board).cmp(&get_best_score(&evil_pair, &board)) {
+                        std::cmp::Ordering::Less => naive.0 += 1,
+                        std::cmp::Ordering::Equal => naive.1 += 1,
+                        std::cmp::Ordering::Greater => naive.2 += 1,
+                    }
+                }
+            }
+            assert_eq!(tally_runout_range_vs(&board, &pair, &villain, &dead, 0..deck.len(), None), naive);
+
+            let river = [&board[..], &[deck[0]]].concat();
+            let split = tally_runout_range_vs(&board, &pair, &villain, &dead, 0..1, None);
+            assert_eq!(tally_runout_range_vs(&river, &pair, &villain, &dead, 0..1, None), split);
+            assert_eq!(tally_runout_range_vs(&river, &pair, &villain, &dead, 1..1, None), (0, 0, 0));
+        }
+    }
+
+    #[test]
+    fn test_symmetric_runouts_weighted() {
+        // spades and clubs are interchangeable here, so only half the rivers get scored
+        let board = [Card::new(Rank::Two, Suit::Hearts),
+                     Card::new(Rank::Seven, Suit::Diamonds),
+                     Card::new(Rank::Nine, Suit::Hearts),
+                     Card::new(Rank::Three, Suit::Diamonds)];
+        let pair = (Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::King, Suit::Hearts));
+        let evil_pair = (Card::new(Rank::Ace, Suit::Diamonds), Card::new(Rank::King, Suit::Diamonds));
+
+        let mut naive = (0, 0, 0);
+        for river in Card::DECK {
+            if board.contains(&river) || [pair.0, pair.1, evil_pair.0, evil_pair.1].contains(&river) {
+                continue;
+            }
+            let mut full = board.to_vec();
+            full.push(river);
+            match get_best_score(&pair, &full).cmp(&get_best_score(&evil_pair, &full)) {
+                std::cmp::Ordering::Less => naive.0 += 1,
+                std::cmp::Ordering::Equal => naive.1 += 1,
+                std::cmp::Ordering::Greater => naive.2 += 1,
+            }
+        }
+        assert_eq!(eval_heads_up(&pair, &evil_pair, &board), naive);
+    }
+
+    #[test]
+    fn test_runout_scores() {
+        let board = parse_cards("AhKh4s9c9h").unwrap();
+        let mut scores = RunoutScores::new(&board);
+        let deck = deck_without(&board);
+        for pair in deck.iter().copied().tuple_combinations() {
+            assert_eq!(scores.best(&pair), get_best_score(&pair, &board));
+        }
+    }
+
+    #[test]
+    fn test_exposed_villain() {
+        let board = [Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Seven, Suit::Diamonds),
+                     Card::new(Rank::Nine, Suit::Clubs), Card::new(Rank::Three, Suit::Spades)];
+        let pair = (Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::King, Suit::Hearts));
+        let villain = Villain::Exposed(Card::new(Rank::Nine, Suit::Hearts));
+        assert_eq!(villain.runouts(4, 0), 45);
+
+        // the same as adding up every possible hidden card heads up
+        let mut naive = (0, 0, 0);
+        for card in Card::DECK {
+            if board.contains(&card) || [pair.0, pair.1, Card::new(Rank::Nine, Suit::Hearts)].contains(&card) {
+                continue;
+            }
+            let (win, tie, lose) = eval_heads_up(&pair, &(Card::new(Rank::Nine, Suit::Hearts), card), &board);
+            naive = (naive.0 + win, naive.1 + tie, naive.2 + lose);
+        }
+        assert_eq!(tally_runout_range_vs(&board, &pair, &villain, &[], 0..45, None), naive);
+    }
+
+    #[test]
+    fn test_dead_cards() {
+        let board = [Card::new(Rank::Ace, Suit::Hearts),
+                     Card::new(Rank::King, Suit::Hearts),
+                     Card::new(Rank::Four, Suit::Spades),
+                     Card::new(Rank::Nine, Suit::Clubs)];
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        let evil_pair = (Card::new(Rank::Ace, Suit::Spades), Card::new(Rank::Queen, Suit::Clubs));
+
+        // two of the hearts and a five are mucked: 7 hearts and 3 fives (one a heart) left in 41 cards
+        let dead = [Card::new(Rank::Seven, Suit::Hearts), Card::new(Rank::Eight, Suit::Hearts), Card::new(Rank::Five, Suit::Clubs)];
+        assert_eq!(eval_heads_up_with_dead(&pair, &evil_pair, &board, &dead), (9, 0, 32));
+
+        let villain = Villain::Random;
+        let (win, tie, lose) = tally_runout_range_vs(&board, &pair, &villain, &dead, 0..villain.runouts(4, 3), None);
+        assert_eq!(win + tie + lose, villain.runouts(4, 3) * villain.evil_pairs_per_runout(3));
+        assert_eq!(villain.runouts(4, 3), 43);
+    }
+
+    #[test]
+    fn test_partial_boards() {
+        let pair = (Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::King, Suit::Hearts));
+        let evil_pair = (Card::new(Rank::Queen, Suit::Spades), Card::new(Rank::Queen, Suit::Clubs));
+        let board = [Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Seven, Suit::Diamonds)];
+
+        // a 2 card board (no flop) is completed with every 3 card runout
+        let (win, tie, lose) = eval_heads_up(&pair, &evil_pair, &board);
+        assert_eq!(win + tie + lose, runout_count(2, 46));
+        assert_eq!(runout_count(2, 46), 15180);
+
+        let turn = vec![Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Seven, Suit::Diamonds),
+                        Card::new(Rank::Nine, Suit::Clubs), Card::new(Rank::Three, Suit::Spades)];
+        let (win, lose) = eval_with_community(turn, &pair);
+        assert_eq!(win + lose, runout_count(4, 46) * choose(45, 2));
+    }
+
+    #[test]
+    fn test_game_rules() {
+        // hold'em where both hole cards have to play
+        struct BothCards;
+        impl GameRules for BothCards {
+            fn deck(&self) -> Deck { Deck::Full }
+            fn hole_cards(&self) -> usize { 2 }
+            fn hole_cards_used(&self) -> RangeInclusive<usize> { 2..=2 }
+            fn scores(&self) -> &'static HandMap<Score> { &SCORES }
+        }
+
+        let board = parse_cards("AsKsQsJs2d").unwrap();
+        let hole = parse_cards("Ts3c").unwrap();
+        assert_eq!(Game::Holdem.best_score(&hole, &board), SCORES[&Hand::from_straight_flush(Rank::Ace)]);
+        assert_eq!(BothCards.best_score(&hole, &board).category(), crate::category::Category::HighCard);
+        assert_eq!(Game::Omaha.hole_cards_used(), 2..=2);
+
+        let board = parse_cards("AsKsQs2d").unwrap();
+        let evil_hole = parse_cards("Ah9h").unwrap();
+        let (win, tie, lose) = eval_game_heads_up(&BothCards, &hole, &evil_hole, &board, &[]);
+        assert_eq!(win + tie + lose, 44);
+        assert!(lose > win);
+        let (win, _, _) = eval_game_heads_up(&Game::Holdem, &hole, &evil_hole, &board, &[]);
+        assert!(win > 0);
+    }
+}
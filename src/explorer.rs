@@ -0,0 +1,526 @@
+use crate::card::*;
+use crate::category::*;
+use crate::class::*;
+use crate::eval::*;
+use crate::library::*;
+use crate::monte_carlo::*;
+use crate::preflop::*;
+use crate::range::*;
+use ratatui::{
+    crossterm::{
+        event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton, MouseEvent, MouseEventKind},
+        execute,
+    },
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    DefaultTerminal, Frame,
+};
+use std::{cell::Cell, collections::BTreeSet, path::PathBuf};
+
+
+/// Which pane typing and toggling go to: the hole or board cards, or the range editor
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Field {
+    Hole,
+    Board,
+    Range,
+}
+
+/// What the explorer shows for the hand as it stands
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    /// Against a random hand
+    pub equity: f64,
+    /// 0 when it's exact
+    pub half_width: f64,
+    /// None preflop
+    pub category: Option<Category>,
+    pub draw: Option<Draw>,
+    /// Only on the flop and turn
+    pub outs: Vec<Card>,
+    /// Equity and its half width against the range being edited, if any of it is still possible
+    pub vs_range: Option<(f64, f64)>,
+}
+
+impl Analysis {
+    /// Equity from the table preflop, sampled on the flop and exact after it. Against the range,
+    /// sampled to ±1% preflop and exact from the flop on
+    pub fn of(pair: &(Card, Card), board: &[Card], range: &HandRange) -> Analysis {
+        let (equity, half_width) = match board.len() {
+            0 => (preflop_equity(HandClass::from_pair(pair)), 0.0),
+            3 => {
+                let estimate = eval_until(pair, board, &StopRule::default(), &mut rand::rng());
+                (estimate.equity, estimate.half_width())
+            }
+            _ => (eval_exhaustive(pair, &Villain::Random, board, &[], 1, Hooks::default()).equity, 0.0),
+        };
+        let postflop = board.len() >= 3;
+        Analysis {
+            equity,
+            half_width,
+            category: postflop.then(|| Category::of(pair, board)),
+            draw: (postflop && board.len() < 5).then(|| Draw::of(pair, board)),
+            outs: if postflop && board.len() < 5 { outs(pair, board, &[]) } else { Vec::new() },
+            vs_range: Analysis::vs_range(pair, board, range),
+        }
+    }
+
+    fn vs_range(pair: &(Card, Card), board: &[Card], range: &HandRange) -> Option<(f64, f64)> {
+        if range.without(&[board, &[pair.0, pair.1]].concat()).is_empty() {
+            return None;
+        }
+        if board.is_empty() {
+            let rule = StopRule { half_width: 0.01, ..StopRule::default() };
+            let estimate = eval_vs_ranges(pair, std::slice::from_ref(range), board, &[], &rule, &mut rand::rng());
+            estimate.ok().map(|estimate| (estimate.equity, estimate.half_width()))
+        } else {
+            Some((equity_vs_range(pair, range, board, &[]), 0.0))
+        }
+    }
+}
+
+/// Pick hole and board cards, by typing them (e.g. Ah) or moving round the deck and toggling
+/// them, and see how the hand does after every change. A villain range is edited on the 13x13
+/// grid alongside, by clicking and dragging over it or moving round it, and can be saved to and
+/// loaded from the range library
+#[derive(Debug, Clone)]
+pub struct Explorer {
+    pub hole: Vec<Card>,
+    pub board: Vec<Card>,
+    pub focus: Field,
+    /// Card under the cursor in the deck, as (suit, rank) indices
+    pub cursor: (usize, usize),
+    /// Rank typed, waiting for its suit
+    typed: Option<Rank>,
+    pub analysis: Option<Analysis>,
+    pub range: HandRange,
+    /// Classes (by index) number keys set the weight of
+    pub selected: BTreeSet<usize>,
+    /// Class under the cursor in the grid, as (row, col)
+    pub grid_cursor: (usize, usize),
+    /// Where the grid was last drawn, to find the class under the mouse
+    grid_area: Cell<Rect>,
+    dragging: bool,
+    pub library: RangeLibrary,
+    /// Where saving writes the library, if anywhere
+    library_path: Option<PathBuf>,
+    /// Name being typed to save the range under
+    naming: Option<String>,
+    /// Last thing done to the library, or what went wrong
+    pub status: String,
+}
+
+impl Default for Explorer {
+    fn default() -> Self {
+        Explorer {
+            hole: Vec::new(),
+            board: Vec::new(),
+            focus: Field::Hole,
+            cursor: (0, 12),
+            typed: None,
+            analysis: None,
+            range: HandRange::new(),
+            selected: BTreeSet::new(),
+            grid_cursor: (0, 0),
+            grid_area: Cell::new(Rect::default()),
+            dragging: false,
+            library: RangeLibrary::default(),
+            library_path: None,
+            naming: None,
+            status: String::new(),
+        }
+    }
+}
+
+impl Explorer {
+    /// An explorer saving ranges to the library at path
+    pub fn with_library(library: RangeLibrary, path: PathBuf) -> Explorer {
+        Explorer { library, library_path: Some(path), ..Explorer::default() }
+    }
+
+    /// Take the card out of whichever field has it, or put it in the focused one if there's room
+    pub fn toggle(&mut self, card: Card) {
+        if let Some(i) = self.hole.iter().position(|&c| c == card) {
+            self.hole.remove(i);
+        } else if let Some(i) = self.board.iter().position(|&c| c == card) {
+            self.board.remove(i);
+        } else {
+            let (field, room) = match self.focus {
+                Field::Hole | Field::Range => (&mut self.hole, 2),
+                Field::Board => (&mut self.board, 5),
+            };
+            if field.len() < room {
+                field.push(card);
+            }
+        }
+        self.update();
+    }
+
+    fn update(&mut self) {
+        self.analysis = match (&self.hole[..], self.board.len()) {
+            (&[a, b], 0 | 3 | 4 | 5) => Some(Analysis::of(&(a, b), &self.board, &self.range)),
+            _ => None,
+        };
+    }
+
+    /// How much of a class's combos are in the range, 0-1
+    pub fn class_weight(&self, class: HandClass) -> f64 {
+        let combos = class.combos();
+        combos.iter().map(|pair| self.range.weight(pair)).sum::<f64>() / combos.len() as f64
+    }
+
+    /// Put every selected class in the range at this weight (0 takes it out), or the class under
+    /// the cursor if none are selected
+    pub fn set_weight(&mut self, weight: f64) {
+        let classes: Vec<usize> = match self.selected.is_empty() {
+            true => vec![HandClass::from_grid(self.grid_cursor.0, self.grid_cursor.1).index()],
+            false => self.selected.iter().copied().collect(),
+        };
+        for class in classes {
+            for pair in HandClass::from_index(class).combos() {
+                self.range.insert(pair, weight);
+            }
+        }
+        self.update();
+    }
+
+    fn toggle_selected(&mut self, class: usize) {
+        if !self.selected.remove(&class) {
+            self.selected.insert(class);
+        }
+    }
+
+    /// Save the range under the name in the library, and to its file if it has one
+    pub fn save_range(&mut self, name: &str) {
+        self.library.ranges.insert(name.to_string(), self.range.clone());
+        self.status = match self.library_path.as_ref().map(|path| self.library.save(path)) {
+            Some(Err(e)) => format!("couldn't save the library: {}", e),
+            _ => format!("saved {}", name),
+        };
+    }
+
+    /// Load the saved range after the one last saved or loaded, in name order
+    pub fn load_next_range(&mut self) {
+        let current = self.status.strip_prefix("loaded ").or(self.status.strip_prefix("saved ")).unwrap_or("").to_string();
+        let next = self.library.ranges.iter()
+            .find(|(name, _)| **name > current)
+            .or(self.library.ranges.iter().next());
+        if let Some((name, range)) = next {
+            self.range = range.clone();
+            self.status = format!("loaded {}", name);
+            self.update();
+        }
+    }
+
+    /// Handle a key press in the range editor, where the number keys set weights in tenths (0
+    /// for all of it), x takes classes out, space selects, c clears the selection, s names and
+    /// saves the range and l loads the next saved one
+    fn range_key(&mut self, code: KeyCode) {
+        let (row, col) = &mut self.grid_cursor;
+        match code {
+            KeyCode::Left => *col = (*col + 12) % 13,
+            KeyCode::Right => *col = (*col + 1) % 13,
+            KeyCode::Up => *row = (*row + 12) % 13,
+            KeyCode::Down => *row = (*row + 1) % 13,
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let class = HandClass::from_grid(*row, *col).index();
+                self.toggle_selected(class);
+            }
+            KeyCode::Char('0') => self.set_weight(1.0),
+            KeyCode::Char(c @ '1'..='9') => self.set_weight(c.to_digit(10).unwrap() as f64 / 10.0),
+            KeyCode::Char('x') | KeyCode::Delete | KeyCode::Backspace => self.set_weight(0.0),
+            KeyCode::Char('c') => self.selected.clear(),
+            KeyCode::Char('s') => self.naming = Some(String::new()),
+            KeyCode::Char('l') => self.load_next_range(),
+            _ => {}
+        }
+    }
+
+    /// Handle a key press, true when it's time to quit
+    pub fn key(&mut self, code: KeyCode) -> bool {
+        if let Some(name) = &mut self.naming {
+            match code {
+                KeyCode::Esc => self.naming = None,
+                KeyCode::Enter if !name.trim().is_empty() => {
+                    let name = name.trim().to_string();
+                    self.naming = None;
+                    self.save_range(&name);
+                }
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                // a colon would end the name in the library file
+                KeyCode::Char(c) if c != ':' => name.push(c),
+                _ => {}
+            }
+            return false;
+        }
+        if code == KeyCode::Esc {
+            return true;
+        }
+        if code == KeyCode::Tab {
+            self.focus = match self.focus {
+                Field::Hole => Field::Board,
+                Field::Board => Field::Range,
+                Field::Range => Field::Hole,
+            };
+            return false;
+        }
+        if self.focus == Field::Range {
+            self.range_key(code);
+            return false;
+        }
+
+        let (suit, rank) = &mut self.cursor;
+        match code {
+            KeyCode::Left => *rank = (*rank + 12) % 13,
+            KeyCode::Right => *rank = (*rank + 1) % 13,
+            KeyCode::Up => *suit = (*suit + 3) % 4,
+            KeyCode::Down => *suit = (*suit + 1) % 4,
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                self.toggle(Card::new(Rank::ALL_RANKS[self.cursor.1], Suit::ALL_SUITS[self.cursor.0]))
+            }
+            KeyCode::Backspace => {
+                match self.focus {
+                    Field::Hole | Field::Range => self.hole.pop(),
+                    Field::Board => self.board.pop(),
+                };
+                self.update();
+            }
+            KeyCode::Char(c) => match (self.typed, Rank::try_from(c), Suit::try_from(c)) {
+                (Some(rank), _, Ok(suit)) => {
+                    self.typed = None;
+                    self.toggle(Card::new(rank, suit));
+                }
+                (_, Ok(rank), _) => self.typed = Some(rank),
+                _ => self.typed = None,
+            },
+            _ => {}
+        }
+        false
+    }
+
+    /// Handle the mouse: pressing on the grid selects just the class under it, and dragging
+    /// selects every class it passes over
+    pub fn mouse(&mut self, event: MouseEvent) {
+        let area = self.grid_area.get();
+        // inside the border, four columns a class
+        let cell = (event.column > area.x && event.row > area.y)
+            .then(|| ((event.row - area.y - 1) as usize, (event.column - area.x - 1) as usize / 4))
+            .filter(|&(row, col)| row < 13 && col < 13);
+        match (event.kind, cell) {
+            (MouseEventKind::Down(MouseButton::Left), Some((row, col))) => {
+                self.focus = Field::Range;
+                self.dragging = true;
+                self.grid_cursor = (row, col);
+                self.selected = BTreeSet::from([HandClass::from_grid(row, col).index()]);
+            }
+            (MouseEventKind::Drag(MouseButton::Left), Some((row, col))) if self.dragging => {
+                self.grid_cursor = (row, col);
+                self.selected.insert(HandClass::from_grid(row, col).index());
+            }
+            (MouseEventKind::Up(MouseButton::Left), _) => self.dragging = false,
+            _ => {}
+        }
+    }
+
+    fn cards_line(&self, field: Field) -> Line<'_> {
+        let (name, cards) = match field {
+            Field::Hole | Field::Range => ("hole  ", &self.hole),
+            Field::Board => ("board ", &self.board),
+        };
+        let text = format!("{}{}", name, cards.iter().map(|card| card.to_string()).collect::<Vec<_>>().join(" "));
+        if self.focus == field { Line::from(text).bold() } else { Line::from(text) }
+    }
+
+    fn grid_lines(&self) -> Vec<Line<'_>> {
+        (0..13)
+            .map(|row| {
+                Line::from((0..13).map(|col| {
+                    let class = HandClass::from_grid(row, col);
+                    // the more of the class is in the range the brighter
+                    let shade = (60.0 + self.class_weight(class) * 160.0) as u8;
+                    let mut style = match self.class_weight(class) {
+                        0.0 => Style::default(),
+                        _ => Style::default().bg(Color::Rgb(35, shade, 60)).fg(Color::Black),
+                    };
+                    if self.selected.contains(&class.index()) {
+                        style = style.add_modifier(Modifier::UNDERLINED);
+                    }
+                    if self.focus == Field::Range && (row, col) == self.grid_cursor {
+                        style = style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+                    }
+                    Span::styled(format!("{:<3} ", class.to_string()), style)
+                }).collect::<Vec<_>>())
+            })
+            .collect()
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let [cards, deck, middle, help] =
+            Layout::vertical([Constraint::Length(4), Constraint::Length(6), Constraint::Min(15), Constraint::Length(1)]).areas(frame.area());
+        let [analysis, grid] = Layout::horizontal([Constraint::Min(30), Constraint::Length(54)]).areas(middle);
+
+        frame.render_widget(Paragraph::new(vec![self.cards_line(Field::Hole), self.cards_line(Field::Board)])
+            .block(Block::bordered().title("cards")), cards);
+
+        let rows: Vec<Line> = Suit::ALL_SUITS
+            .iter()
+            .enumerate()
+            .map(|(s, &suit)| {
+                Line::from(Rank::ALL_RANKS.iter().enumerate().map(|(r, &rank)| {
+                    let card = Card::new(rank, suit);
+                    let mut style = Style::default();
+                    if self.hole.contains(&card) || self.board.contains(&card) {
+                        style = style.add_modifier(Modifier::REVERSED);
+                    }
+                    if (s, r) == self.cursor {
+                        style = style.add_modifier(Modifier::UNDERLINED | Modifier::BOLD);
+                    }
+                    Span::styled(format!("{:>3} ", card.to_string()), style)
+                }).collect::<Vec<_>>())
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(rows).block(Block::bordered().title("deck")), deck);
+
+        let lines: Vec<Line> = match &self.analysis {
+            None => vec![Line::from("pick two hole cards and a board of 0, 3, 4 or 5 cards")],
+            Some(analysis) => {
+                let mut lines = vec![Line::from(match analysis.half_width {
+                    0.0 => format!("equity {:.2}% against a random hand", analysis.equity * 100.0),
+                    half_width => format!("equity {:.2}% ± {:.2}% against a random hand", analysis.equity * 100.0, half_width * 100.0),
+                })];
+                if let Some(category) = analysis.category {
+                    lines.push(Line::from(format!("made {}", category)));
+                }
+                if let Some(draw) = analysis.draw {
+                    lines.push(Line::from(format!("{}, {} outs: {}", draw, analysis.outs.len(),
+                        analysis.outs.iter().map(|card| card.to_string()).collect::<Vec<_>>().join(" "))));
+                }
+                match analysis.vs_range {
+                    Some((equity, 0.0)) => lines.push(Line::from(format!("equity {:.2}% against the range", equity * 100.0))),
+                    Some((equity, half_width)) => lines.push(Line::from(format!("equity {:.2}% ± {:.2}% against the range",
+                        equity * 100.0, half_width * 100.0))),
+                    None => {}
+                }
+                lines
+            }
+        };
+        frame.render_widget(Paragraph::new(lines).block(Block::bordered().title("analysis")), analysis);
+
+        let title = match &self.naming {
+            Some(name) => format!("range, save as: {}_", name),
+            None => format!("range {:.0} combos {}", self.range.total_weight(), self.status),
+        };
+        let block = Block::bordered().title(title);
+        let block = if self.focus == Field::Range { block.bold() } else { block };
+        frame.render_widget(Paragraph::new(self.grid_lines()).block(block), grid);
+        self.grid_area.set(grid);
+
+        frame.render_widget(Line::from(match self.focus {
+            Field::Range => "drag or space to select, 1-9 tenths, 0 all, x out, c clears, s saves, l loads, tab to cards, esc quits",
+            _ => "type cards (Ah) or move with arrows and toggle with space, tab switches hole/board/range, backspace removes, esc quits",
+        }), help);
+    }
+
+    /// Run in the terminal until esc
+    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        execute!(std::io::stdout(), EnableMouseCapture)?;
+        let result = self.event_loop(terminal);
+        execute!(std::io::stdout(), DisableMouseCapture)?;
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press && self.key(key.code) => return Ok(()),
+                Event::Mouse(mouse) => self.mouse(mouse),
+                _ => {}
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explorer() {
+        let mut explorer = Explorer::default();
+        for c in "AhKh".chars() {
+            explorer.key(KeyCode::Char(c));
+        }
+        assert_eq!(explorer.hole, parse_cards("AhKh").unwrap());
+        assert!(explorer.analysis.as_ref().unwrap().equity > 0.6);
+
+        // a third hole card doesn't fit, and toggling the ace of hearts takes it out
+        explorer.key(KeyCode::Down);
+        explorer.key(KeyCode::Char(' '));
+        assert_eq!(explorer.hole.len(), 2);
+        explorer.key(KeyCode::Up);
+        explorer.key(KeyCode::Char(' '));
+        assert_eq!(explorer.hole, parse_cards("Kh").unwrap());
+
+        explorer.key(KeyCode::Tab);
+        for c in "Ah2h3c7h".chars() {
+            explorer.key(KeyCode::Char(c));
+        }
+        // past the range editor back to the hole
+        explorer.key(KeyCode::Tab);
+        explorer.key(KeyCode::Tab);
+        explorer.key(KeyCode::Char('Q'));
+        explorer.key(KeyCode::Char('h'));
+        assert_eq!(explorer.hole, parse_cards("KhQh").unwrap());
+        let analysis = explorer.analysis.clone().unwrap();
+        assert_eq!(analysis.category, Some(Category::Flush));
+        assert_eq!((analysis.draw, analysis.outs.len()), (Some(Draw::Nothing), 0));
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| explorer.render(frame)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("made flush") && screen.contains("AKs"));
+
+        // clicking AA, dragging across to AQs and putting them in at half weight
+        let grid = explorer.grid_area.get();
+        let mouse = |kind, col: u16| MouseEvent {
+            kind, column: grid.x + 1 + 4 * col, row: grid.y + 1, modifiers: ratatui::crossterm::event::KeyModifiers::NONE,
+        };
+        explorer.mouse(mouse(MouseEventKind::Down(MouseButton::Left), 0));
+        explorer.mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 2));
+        explorer.mouse(mouse(MouseEventKind::Up(MouseButton::Left), 2));
+        assert_eq!(explorer.focus, Field::Range);
+        assert_eq!(explorer.selected.len(), 2);
+        explorer.key(KeyCode::Char('5'));
+        assert_eq!(explorer.range.total_weight(), 0.5 * 6.0 + 0.5 * 4.0);
+        let (equity, half_width) = explorer.analysis.as_ref().unwrap().vs_range.unwrap();
+        // the flush only loses to the aces filling up, 10 rivers in 44
+        assert!((equity - (1.5 * 34.0 / 44.0 + 1.5) / 3.0).abs() < 1e-9 && half_width == 0.0);
+
+        // the cursor's class if nothing's selected, and saving and loading by name
+        explorer.key(KeyCode::Char('c'));
+        explorer.key(KeyCode::Down);
+        explorer.key(KeyCode::Left);
+        explorer.key(KeyCode::Char('0'));
+        assert_eq!(explorer.class_weight("KK".parse().unwrap()), 1.0);
+        for c in "s:top\n".chars() {
+            explorer.key(if c == '\n' { KeyCode::Enter } else { KeyCode::Char(c) });
+        }
+        assert_eq!(explorer.status, "saved top");
+        let saved = explorer.range.clone();
+        explorer.key(KeyCode::Char('x'));
+        assert_ne!(explorer.range, saved);
+        explorer.key(KeyCode::Char('l'));
+        assert_eq!(explorer.range, explorer.library.ranges["top"]);
+        assert_eq!(explorer.range, saved);
+        explorer.key(KeyCode::Tab);
+
+        assert!(!explorer.key(KeyCode::Backspace));
+        assert!(explorer.analysis.is_none());
+        assert!(explorer.key(KeyCode::Esc));
+    }
+}
@@ -0,0 +1,145 @@
+//! The 22,100 flops only make 1,755 strategically distinct ones once suits are relabelled,
+//! so batch flop analysis just needs to visit those with the right weights
+
+use crate::card::*;
+use crate::category::*;
+use crate::iso::*;
+use crate::json::*;
+use std::sync::LazyLock;
+use itertools::Itertools;
+
+/// Flops distinct up to suits, each with how many of the 22,100 flops it stands for
+pub static CANONICAL_FLOPS: LazyLock<Vec<([Card; 3], usize)>> = LazyLock::new(|| distinct_flops(&[]));
+
+pub fn canonical_flops() -> impl Iterator<Item = ([Card; 3], usize)> {
+    CANONICAL_FLOPS.iter().copied()
+}
+
+/// Flops distinct up to the suit relabellings that leave the holdings unchanged,
+/// with multiplicity weights. Flops that clash with every holding are left out.
+/// With no holdings this is the 1,755 canonical flops
+pub fn distinct_flops(holdings: &[(Card, Card)]) -> Vec<([Card; 3], usize)> {
+    let perms = if holdings.is_empty() { permutations() } else { holdings_stabilizer(holdings) };
+    Card::DECK
+        .into_iter()
+        .combinations(3)
+        .filter(|flop| holdings.is_empty() || holdings.iter().any(|pair| !clashes(pair, flop)))
+        .filter_map(|flop| orbit_weight(&flop, &perms).map(|weight| ([flop[0], flop[1], flop[2]], weight)))
+        .collect()
+}
+
+fn clashes(pair: &(Card, Card), board: &[Card]) -> bool {
+    board.contains(&pair.0) || board.contains(&pair.1)
+}
+
+/// Evaluate f for the holdings on every distinct flop, returning (flop, weight, result)
+/// f gets the flop and the holdings that don't clash with it
+pub fn eval_over_flops<T>(holdings: &[(Card, Card)], mut f: impl FnMut(&[Card], &[(Card, Card)]) -> T) -> Vec<([Card; 3], usize, T)> {
+    distinct_flops(holdings)
+        .into_iter()
+        .map(|(flop, weight)| {
+            let live: Vec<(Card, Card)> = holdings.iter().copied().filter(|pair| !clashes(pair, &flop)).collect();
+            let result = f(&flop, &live);
+            (flop, weight, result)
+        })
+        .collect()
+}
+
+/// How often holdings make each hand category and draw on the flop
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FlopHits {
+    /// indexed by Category
+    pub made: [f64; 9],
+    /// indexed by Draw
+    pub draws: [f64; 5],
+    /// Number of holdings the shares are out of
+    pub combos: usize,
+}
+
+impl FlopHits {
+    /// Share of the holdings making each category / draw on a single flop
+    pub fn on_flop(holdings: &[(Card, Card)], flop: &[Card]) -> FlopHits {
+        let mut hits = FlopHits::default();
+        for pair in holdings {
+            hits.made[usize::from(Category::of(pair, flop))] += 1.0;
+            hits.draws[Draw::of(pair, flop) as usize] += 1.0;
+        }
+        hits.combos = holdings.len();
+        let n = holdings.len().max(1) as f64;
+        hits.made.iter_mut().chain(hits.draws.iter_mut()).for_each(|x| *x /= n);
+        hits
+    }
+
+    pub fn made(&self, category: Category) -> f64 {
+        self.made[usize::from(category)]
+    }
+
+    pub fn draw(&self, draw: Draw) -> f64 {
+        self.draws[draw as usize]
+    }
+}
+
+impl ToJson for FlopHits {
+    /// Shares by the names categories and draws display as
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("combos", self.combos.into()),
+            ("made", Json::Object(Category::ALL_CATEGORIES.iter().map(|&category| (category.to_string(), self.made(category).into())).collect())),
+            ("draws", Json::Object(Draw::ALL_DRAWS.iter().map(|&draw| (draw.to_string(), self.draw(draw).into())).collect())),
+        ])
+    }
+}
+
+/// FlopHits on every distinct flop, plus the average over all flops
+pub fn flop_hits(holdings: &[(Card, Card)]) -> (Vec<([Card; 3], usize, FlopHits)>, FlopHits) {
+    let per_flop = eval_over_flops(holdings, |flop, live| FlopHits::on_flop(live, flop));
+
+    // every (holding, flop) deal counts the same
+    let mut total = FlopHits::default();
+    let total_weight: usize = per_flop.iter().map(|(_, weight, hits)| weight * hits.combos).sum();
+    for (_, weight, hits) in &per_flop {
+        let share = (weight * hits.combos) as f64 / total_weight as f64;
+        for (t, x) in total.made.iter_mut().zip(hits.made).chain(total.draws.iter_mut().zip(hits.draws)) {
+            *t += x * share;
+        }
+    }
+    total.combos = holdings.len();
+    (per_flop, total)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::*;
+
+    #[test]
+    fn test_canonical_flops() {
+        assert_eq!(canonical_flops().count(), 1755);
+        assert_eq!(canonical_flops().map(|(_, weight)| weight).sum::<usize>(), 22100);
+    }
+
+    #[test]
+    fn test_distinct_flops_for_hand() {
+        let pair = (Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::King, Suit::Hearts));
+        let flops = distinct_flops(&[pair]);
+        assert!(flops.len() < 19600);
+        assert_eq!(flops.iter().map(|(_, weight)| weight).sum::<usize>(), 19600);
+
+        // a whole hand class is symmetric in the suits, so only canonical flops are needed
+        let class: HandClass = "AKs".parse().unwrap();
+        assert_eq!(distinct_flops(&class.combos()).len(), 1755);
+    }
+
+    #[test]
+    fn test_flop_hits() {
+        let aces: HandClass = "AA".parse().unwrap();
+        let (per_flop, total) = flop_hits(&aces.combos());
+        // every canonical flop but the three aces
+        assert_eq!(per_flop.len(), 1754);
+        // aces are always at least a pair
+        assert_eq!(total.made(Category::HighCard), 0.0);
+        // quads when both other aces come: 48 / C(50,3)
+        assert!((total.made(Category::Quads) - 48.0 / 19600.0).abs() < 1e-9);
+    }
+}
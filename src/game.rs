@@ -0,0 +1,470 @@
+use crate::card::*;
+use crate::eval::*;
+use crate::pots::*;
+use crate::position::*;
+use crate::street::*;
+use std::fmt::{Display, Formatter};
+use rand::{seq::SliceRandom, Rng};
+
+
+/// Amounts of money, in the smallest unit there is
+pub type Chips = u64;
+
+/// What a player can do when it's their turn
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Action {
+    Fold,
+    Check,
+    Call,
+    /// Bet or raise to this much in total on the street
+    Raise(Chips),
+    /// Put the whole stack in, whether that's a call, a raise or not even a full raise
+    AllIn,
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Fold => write!(f, "fold"),
+            Action::Check => write!(f, "check"),
+            Action::Call => write!(f, "call"),
+            Action::Raise(to) => write!(f, "raise to {}", to),
+            Action::AllIn => write!(f, "all in"),
+        }
+    }
+}
+
+/// A player in the hand
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Seat {
+    /// Chips behind, not yet bet
+    pub stack: Chips,
+    pub hole: (Card, Card),
+    /// Put in on this street
+    pub bet: Chips,
+    /// Put in over the whole hand, including this street
+    pub contributed: Chips,
+    pub folded: bool,
+}
+
+impl Seat {
+    pub fn is_all_in(&self) -> bool {
+        !self.folded && self.stack == 0
+    }
+
+    /// Still able to bet: in the hand with chips behind
+    fn can_act(&self) -> bool {
+        !self.folded && self.stack > 0
+    }
+
+    /// Move chips from the stack into the pot, up to the whole stack
+    fn put_in(&mut self, amount: Chips) {
+        let amount = amount.min(self.stack);
+        self.stack -= amount;
+        self.bet += amount;
+        self.contributed += amount;
+    }
+}
+
+/// What one seat can see of a hand: everything but the other seats' hole cards and the deck
+#[derive(Debug, Clone)]
+pub struct GameView<'a> {
+    pub seat: usize,
+    pub hole: (Card, Card),
+    pub position: Position,
+    pub street: Street,
+    pub board: &'a [Card],
+    /// Chips behind for every seat
+    pub stacks: Vec<Chips>,
+    /// Put in on this street by every seat
+    pub bets: Vec<Chips>,
+    /// Put in over the whole hand by every seat
+    pub contributed: Vec<Chips>,
+    pub folded: Vec<bool>,
+    pub pot: Chips,
+    pub big_blind: Chips,
+    pub to_call: Chips,
+    pub min_raise_to: Option<Chips>,
+    pub max_raise_to: Chips,
+    pub history: &'a [(usize, Street, Action)],
+}
+
+impl GameView<'_> {
+    /// Seats other than this one still in the hand
+    pub fn opponents(&self) -> usize {
+        (0..self.folded.len()).filter(|&i| i != self.seat && !self.folded[i]).count()
+    }
+
+    /// Share of the pot after calling that the call is, the equity it needs to break even
+    pub fn pot_odds(&self) -> f64 {
+        self.to_call as f64 / (self.pot + self.to_call) as f64
+    }
+}
+
+/// The seats posting the small and big blinds, the button posting the small blind heads up
+pub fn blind_seats(seats: usize, button: usize) -> (usize, usize) {
+    let small = if seats == 2 { button } else { (button + 1) % seats };
+    (small, (small + 1) % seats)
+}
+
+/// One hand of no limit hold'em, from the blinds to the showdown. Seats are in order round the
+/// table, and chips only move through act, so stacks plus the pot always add up to the same
+#[derive(Debug, Clone)]
+pub struct HandState {
+    pub seats: Vec<Seat>,
+    pub button: usize,
+    pub small_blind: Chips,
+    pub big_blind: Chips,
+    pub street: Street,
+    pub board: Vec<Card>,
+    /// Cards left to deal, from the front
+    deck: Vec<Card>,
+    /// Highest bet on this street
+    pub current_bet: Chips,
+    /// Smallest raise allowed: the size of the last full bet or raise, at least the big blind
+    pub min_raise: Chips,
+    to_act: Option<usize>,
+    /// Seats yet to act since the last bet or raise
+    pending: Vec<bool>,
+    /// Seats allowed to raise when it gets to them, which an all in short of a full raise doesn't
+    /// give back to those who have already acted
+    may_raise: Vec<bool>,
+    pub history: Vec<(usize, Street, Action)>,
+    /// What each seat won, once the hand is over
+    winnings: Option<Vec<Chips>>,
+}
+
+impl HandState {
+    /// Shuffle up and deal: hole cards to every seat and the blinds posted by the two after the
+    /// button (the button posting the small blind heads up)
+    pub fn new<R: Rng + ?Sized>(stacks: &[Chips], button: usize, small_blind: Chips, big_blind: Chips, rng: &mut R) -> HandState {
+        let mut deck = Card::get_deck();
+        deck.shuffle(rng);
+        HandState::with_deck(stacks, button, small_blind, big_blind, deck)
+    }
+
+    /// HandState::new with the deck in a known order: two cards to each seat starting left of
+    /// the button, then the board, with no burn cards
+    pub fn with_deck(stacks: &[Chips], button: usize, small_blind: Chips, big_blind: Chips, deck: Vec<Card>) -> HandState {
+        let n = stacks.len();
+        assert!((2..=9).contains(&n) && button < n, "2-9 seats and a button among them");
+        assert!(stacks.iter().all(|&stack| stack > 0), "every seat needs chips");
+        let seats = stacks
+            .iter()
+            .enumerate()
+            .map(|(i, &stack)| {
+                let k = (i + n - button - 1) % n;
+                Seat { stack, hole: (deck[2 * k], deck[2 * k + 1]), bet: 0, contributed: 0, folded: false }
+            })
+            .collect();
+        let mut deck = deck;
+        deck.drain(..2 * n);
+        let mut state = HandState {
+            seats,
+            button,
+            small_blind,
+            big_blind,
+            street: Street::Preflop,
+            board: Vec::new(),
+            deck,
+            current_bet: big_blind,
+            min_raise: big_blind,
+            to_act: None,
+            pending: vec![true; n],
+            may_raise: vec![true; n],
+            history: Vec::new(),
+            winnings: None,
+        };
+
+        let (small, big) = blind_seats(n, button);
+        state.seats[small].put_in(small_blind);
+        state.seats[big].put_in(big_blind);
+        for (i, seat) in state.seats.iter().enumerate() {
+            state.pending[i] = seat.can_act();
+        }
+        state.to_act = state.next_to_act(big);
+        if state.to_act.is_none() {
+            state.end_street();
+        }
+        state
+    }
+
+    /// Seat whose turn it is, None once the hand is over
+    pub fn to_act(&self) -> Option<usize> {
+        self.to_act
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winnings.is_some()
+    }
+
+    /// What each seat won from the pot (their own chips back included), once the hand is over
+    pub fn winnings(&self) -> Option<&[Chips]> {
+        self.winnings.as_deref()
+    }
+
+    /// The hand as the seat sees it
+    pub fn view(&self, seat: usize) -> GameView<'_> {
+        GameView {
+            seat,
+            hole: self.seats[seat].hole,
+            position: self.position(seat),
+            street: self.street,
+            board: &self.board,
+            stacks: self.seats.iter().map(|seat| seat.stack).collect(),
+            bets: self.seats.iter().map(|seat| seat.bet).collect(),
+            contributed: self.seats.iter().map(|seat| seat.contributed).collect(),
+            folded: self.seats.iter().map(|seat| seat.folded).collect(),
+            pot: self.pot(),
+            big_blind: self.big_blind,
+            to_call: self.to_call(seat),
+            min_raise_to: self.min_raise_to(seat),
+            max_raise_to: self.max_raise_to(seat),
+            history: &self.history,
+        }
+    }
+
+    /// Where a seat sits relative to the button this hand
+    pub fn position(&self, seat: usize) -> Position {
+        let n = self.seats.len();
+        Position::left_of_button((seat + n - self.button) % n, n)
+    }
+
+    /// Everything put in so far, this street included
+    pub fn pot(&self) -> Chips {
+        self.seats.iter().map(|seat| seat.contributed).sum()
+    }
+
+    /// What the seat has to put in to call, capped at their stack
+    pub fn to_call(&self, seat: usize) -> Chips {
+        (self.current_bet - self.seats[seat].bet).min(self.seats[seat].stack)
+    }
+
+    /// Smallest total the seat could raise to, or None if they can't raise.
+    /// Going all in for less is still allowed when raising is
+    pub fn min_raise_to(&self, seat: usize) -> Option<Chips> {
+        let max = self.max_raise_to(seat);
+        (self.may_raise[seat] && max > self.current_bet).then_some((self.current_bet + self.min_raise).min(max))
+    }
+
+    /// Total the seat would have bet all in
+    pub fn max_raise_to(&self, seat: usize) -> Chips {
+        self.seats[seat].bet + self.seats[seat].stack
+    }
+
+    /// Take the action for the seat whose turn it is, moving on through the streets and settling
+    /// the pot when that ends the hand. Illegal actions are refused, leaving the hand as it was
+    pub fn act(&mut self, action: Action) -> Result<(), &'static str> {
+        let i = self.to_act.ok_or("the hand is over")?;
+        let to_call = self.to_call(i);
+        let action = match action {
+            Action::AllIn if self.max_raise_to(i) <= self.current_bet => Action::Call,
+            Action::AllIn => Action::Raise(self.max_raise_to(i)),
+            action => action,
+        };
+        match action {
+            Action::Fold => self.seats[i].folded = true,
+            Action::Check if to_call > 0 => return Err("can't check facing a bet"),
+            Action::Check => {}
+            Action::Call if to_call == 0 => return Err("nothing to call"),
+            Action::Call => self.seats[i].put_in(to_call),
+            Action::Raise(to) => {
+                let max = self.max_raise_to(i);
+                if !self.may_raise[i] {
+                    return Err("raising isn't open again after a short all in");
+                }
+                if to <= self.current_bet || to > max {
+                    return Err("raise has to be more than the current bet and no more than the stack");
+                }
+                let full = to - self.current_bet >= self.min_raise;
+                if !full && to < max {
+                    return Err("raise smaller than the minimum without being all in");
+                }
+                let bet = self.seats[i].bet;
+                self.seats[i].put_in(to - bet);
+                for j in 0..self.seats.len() {
+                    if j == i || !self.seats[j].can_act() {
+                        continue;
+                    }
+                    // a full raise reopens the betting for everyone, a short all in only for those yet to act
+                    self.may_raise[j] = full || self.pending[j];
+                    self.pending[j] = true;
+                }
+                if full {
+                    self.min_raise = to - self.current_bet;
+                }
+                self.current_bet = to;
+            }
+            Action::AllIn => unreachable!(),
+        }
+        self.pending[i] = false;
+        self.history.push((i, self.street, action));
+
+        if self.seats.iter().filter(|seat| !seat.folded).count() == 1 {
+            self.settle();
+        } else if let Some(next) = self.next_to_act(i) {
+            self.to_act = Some(next);
+        } else {
+            self.end_street();
+        }
+        Ok(())
+    }
+
+    /// Seats in the hand with chips left to bet
+    fn acting_seats(&self) -> usize {
+        self.seats.iter().filter(|seat| seat.can_act()).count()
+    }
+
+    /// First seat after this one still to act on this street. Someone with chips behind who's
+    /// the only one left able to bet doesn't need to, once they've matched the bet
+    fn next_to_act(&self, after: usize) -> Option<usize> {
+        let n = self.seats.len();
+        let next = (1..=n)
+            .map(|k| (after + k) % n)
+            .find(|&j| self.pending[j] && self.seats[j].can_act())?;
+        let alone = self.acting_seats() == 1 && self.seats[next].bet >= self.current_bet;
+        (!alone).then_some(next)
+    }
+
+    /// Deal the next street, or every street left with no more betting possible, until someone has to act or it's showdown
+    fn end_street(&mut self) {
+        loop {
+            for seat in self.seats.iter_mut() {
+                seat.bet = 0;
+            }
+            let Some(street) = self.street.next() else {
+                self.settle();
+                return;
+            };
+            self.street = street;
+            let cards: Vec<Card> = self.deck.drain(..street.cards_dealt()).collect();
+            self.board.extend(cards);
+            self.current_bet = 0;
+            self.min_raise = self.big_blind;
+            for (i, seat) in self.seats.iter().enumerate() {
+                self.pending[i] = seat.can_act();
+                self.may_raise[i] = true;
+            }
+            if self.acting_seats() >= 2 {
+                self.to_act = self.next_to_act(self.button);
+                if self.to_act.is_some() {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Main pot then side pots, from what everyone has put in so far
+    pub fn pots(&self) -> Vec<Pot> {
+        let contributed: Vec<Chips> = self.seats.iter().map(|seat| seat.contributed).collect();
+        let folded: Vec<bool> = self.seats.iter().map(|seat| seat.folded).collect();
+        side_pots(&contributed, &folded)
+    }
+
+    /// Award every pot: to the last seat standing, or the best hand among those eligible, split
+    /// evenly with any odd chips going to the first winner left of the button
+    fn settle(&mut self) {
+        self.to_act = None;
+        let scores: Vec<Option<_>> = self.seats
+            .iter()
+            .map(|seat| (!seat.folded && self.board.len() == 5).then(|| Game::Holdem.best_score(&[seat.hole.0, seat.hole.1], &self.board)))
+            .collect();
+
+        let winnings = award_pots(&self.pots(), &scores, OddChip::LeftOfButton(self.button));
+        for (seat, &won) in self.seats.iter_mut().zip(&winnings) {
+            seat.stack += won;
+        }
+        self.winnings = Some(winnings);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A deck dealing these hole cards in seat order starting left of the button, then this board
+    fn stacked(holes: &[&str], board: &str) -> Vec<Card> {
+        let mut deck = parse_cards(&format!("{}{}", holes.concat(), board)).unwrap();
+        let rest: Vec<Card> = Card::DECK.into_iter().filter(|card| !deck.contains(card)).collect();
+        deck.extend(rest);
+        deck
+    }
+
+    #[test]
+    fn test_blinds_and_folds() {
+        let mut state = HandState::new(&[100, 100, 100], 0, 1, 2, &mut rand::rng());
+        assert_eq!((state.seats[1].bet, state.seats[2].bet, state.pot()), (1, 2, 3));
+        assert_eq!((state.position(1), state.position(2)), (Position::SmallBlind, Position::BigBlind));
+        // first to act preflop is left of the big blind, the button three handed
+        assert_eq!(state.to_act(), Some(0));
+        assert_eq!(state.act(Action::Check), Err("can't check facing a bet"));
+        assert_eq!(state.act(Action::Raise(3)), Err("raise smaller than the minimum without being all in"));
+        state.act(Action::Raise(6)).unwrap();
+        assert_eq!(state.min_raise_to(1), Some(10));
+        state.act(Action::Fold).unwrap();
+        state.act(Action::Fold).unwrap();
+        assert!(state.is_over());
+        assert_eq!(state.winnings(), Some(&[9, 0, 0][..]));
+        assert_eq!(state.seats.iter().map(|seat| seat.stack).sum::<Chips>(), 300);
+    }
+
+    #[test]
+    fn test_streets() {
+        // heads up: the button posts the small blind and acts first preflop, last after
+        let deck = stacked(&["AhAd", "KsKc"], "2c7d9sTh3c");
+        let mut state = HandState::with_deck(&[100, 100], 0, 1, 2, deck);
+        assert_eq!(state.seats[0].hole, (Card::new(Rank::King, Suit::Spades), Card::new(Rank::King, Suit::Clubs)));
+        assert_eq!(state.to_act(), Some(0));
+        state.act(Action::Call).unwrap();
+        // the big blind gets the option
+        assert_eq!(state.to_act(), Some(1));
+        state.act(Action::Check).unwrap();
+        assert_eq!((state.street, state.board.len(), state.to_act()), (Street::Flop, 3, Some(1)));
+        state.act(Action::Raise(4)).unwrap();
+        state.act(Action::Call).unwrap();
+        state.act(Action::Check).unwrap();
+        state.act(Action::Check).unwrap();
+        state.act(Action::Check).unwrap();
+        state.act(Action::Check).unwrap();
+        assert_eq!(state.winnings(), Some(&[0, 12][..]));
+    }
+
+    #[test]
+    fn test_side_pots() {
+        // the short stack's aces win the main pot, the kings the side pot over the queens
+        let deck = stacked(&["AhAd", "KsKc", "QhQd", "5h4d"], "2c7d9sTh3c");
+        let mut state = HandState::with_deck(&[200, 50, 100, 200], 0, 1, 2, deck);
+        assert_eq!(state.to_act(), Some(3));
+        state.act(Action::Raise(100)).unwrap();
+        state.act(Action::Fold).unwrap();
+        // all in for less is a call
+        state.act(Action::AllIn).unwrap();
+        state.act(Action::Call).unwrap();
+        // only seat 3 has chips left to bet, so the board runs out
+        assert!(state.is_over());
+        assert_eq!(state.board.len(), 5);
+        assert_eq!(state.pots(), vec![Pot { amount: 150, eligible: vec![1, 2, 3] }, Pot { amount: 100, eligible: vec![2, 3] }]);
+        assert_eq!(state.winnings(), Some(&[0, 150, 100, 0][..]));
+        assert_eq!(state.seats.iter().map(|seat| seat.stack).sum::<Chips>(), 550);
+    }
+
+    #[test]
+    fn test_short_all_in() {
+        let mut state = HandState::new(&[100, 100, 11], 2, 1, 2, &mut rand::rng());
+        // seat 0 small blind, 1 big blind, 2 the button first to act
+        state.act(Action::Raise(10)).unwrap();
+        state.act(Action::Call).unwrap();
+        state.act(Action::Call).unwrap();
+        assert_eq!(state.street, Street::Flop);
+        state.act(Action::Check).unwrap();
+        state.act(Action::Check).unwrap();
+        // the button's last chip isn't a full bet, so neither can raise it
+        state.act(Action::AllIn).unwrap();
+        assert_eq!(state.min_raise_to(0), None);
+        assert_eq!(state.act(Action::Raise(20)), Err("raising isn't open again after a short all in"));
+        state.act(Action::Call).unwrap();
+        state.act(Action::Call).unwrap();
+        assert_eq!(state.street, Street::Turn);
+    }
+}
@@ -0,0 +1,198 @@
+//! Exhaustive enumeration on the GPU, through wgpu compute shaders (the `gpu` feature). Each
+//! runout gets its own invocation, which scores the hand against every villain pair on it, so
+//! even every runout preflop (about two billion showdowns against a random pair) is feasible.
+//!
+//! The shader doesn't use the score table: it keeps the rank counts of the hand encoding as a
+//! mask of ranks for each count, which is all shifts, ands and bit counts on u32s. See gpu.wgsl
+
+use crate::card::*;
+use crate::combinatorics::*;
+use crate::eval::*;
+use std::sync::mpsc;
+use wgpu::util::DeviceExt;
+
+/// Runouts in one dispatch, so the tallies stay small and no dispatch is too large
+const RUNOUTS_PER_DISPATCH: usize = 1 << 18;
+const WORKGROUP_SIZE: usize = 64;
+
+/// A GPU ready to enumerate on
+pub struct GpuEvaluator {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuEvaluator {
+    /// The first GPU wgpu finds, if there is one
+    pub fn new() -> Result<GpuEvaluator, String> {
+        pollster::block_on(async {
+            let instance = wgpu::Instance::default();
+            let adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions::default())
+                .await
+                .map_err(|e| format!("No GPU: {}", e))?;
+            let (device, queue) = adapter
+                .request_device(&wgpu::DeviceDescriptor {
+                    label: Some("poker"),
+                    required_limits: adapter.limits(),
+                    ..Default::default()
+                })
+                .await
+                .map_err(|e| format!("Couldn't open the GPU: {}", e))?;
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("enumerate"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("gpu.wgsl").into()),
+            });
+            let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("enumerate"),
+                layout: None,
+                module: &module,
+                entry_point: Some("main"),
+                compilation_options: Default::default(),
+                cache: None,
+            });
+            Ok(GpuEvaluator { device, queue, pipeline })
+        })
+    }
+
+    /// tally_runout_range_vs over every runout, on the GPU
+    /// returns (win_count, tie_count, lose_count)
+    pub fn tally(&self, community: &[Card], pair: &(Card, Card), villain: &Villain, dead: &[Card]) -> Result<(usize, usize, usize), String> {
+        let known = villain.known_cards();
+        if !is_valid_deal_with(community, &[*pair], &[&known, dead].concat()) {
+            return Err("Cards are repeated".to_string());
+        }
+        let deck = deck_without(&[&[pair.0, pair.1], community, &known, dead].concat());
+        let runouts = runout_count(community.len(), deck.len());
+        let index = |card: Card| usize::from(card) as u32;
+
+        let mut input = vec![0u32; 14];
+        input[2] = community.len() as u32;
+        input[3] = deck.len() as u32;
+        input[4] = match villain {
+            Villain::Random => 0,
+            Villain::Exposed(_) => 1,
+            Villain::Known(_, _) => 2,
+        };
+        input[5..7].copy_from_slice(&[index(pair.0), index(pair.1)]);
+        for (slot, &card) in input[7..9].iter_mut().zip(&known) {
+            *slot = index(card);
+        }
+        for (slot, &card) in input[9..14].iter_mut().zip(community) {
+            *slot = index(card);
+        }
+        input.extend(deck.iter().map(|&card| index(card)));
+
+        let mut tally = (0, 0, 0);
+        for offset in (0..runouts).step_by(RUNOUTS_PER_DISPATCH) {
+            let count = RUNOUTS_PER_DISPATCH.min(runouts - offset);
+            input[0] = offset as u32;
+            input[1] = count as u32;
+            let tallies = self.dispatch(&input, count)?;
+            for runout in tallies.chunks(3) {
+                tally.0 += runout[0] as usize;
+                tally.1 += runout[1] as usize;
+                tally.2 += runout[2] as usize;
+            }
+        }
+        Ok(tally)
+    }
+
+    /// Run the shader over count runouts, reading back their tallies
+    fn dispatch(&self, input: &[u32], count: usize) -> Result<Vec<u32>, String> {
+        let bytes: Vec<u8> = input.iter().flat_map(|x| x.to_le_bytes()).collect();
+        let input = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("input"),
+            contents: &bytes,
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let size = (count * 3 * size_of::<u32>()) as u64;
+        let tallies = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("tallies"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: input.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: tallies.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(count.div_ceil(WORKGROUP_SIZE) as u32, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&tallies, 0, &readback, 0, size);
+        self.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = mpsc::channel();
+        readback.map_async(wgpu::MapMode::Read, .., move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::PollType::wait_indefinitely()).map_err(|e| e.to_string())?;
+        receiver.recv().map_err(|e| e.to_string())?.map_err(|e| e.to_string())?;
+        let view = readback.get_mapped_range(..).map_err(|e| e.to_string())?;
+        Ok(view.chunks(4).map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]])).collect())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::direct::*;
+    use wgpu::naga::{front::wgsl, valid::{Capabilities, ValidationFlags, Validator}};
+
+    #[test]
+    fn test_strength_scheme() {
+        // the shader has to compile, adapter or not
+        let module = wgsl::parse_str(include_str!("gpu.wgsl")).unwrap();
+        Validator::new(ValidationFlags::all(), Capabilities::all()).validate(&module).unwrap();
+
+        // worked out by hand under the shader's category << 26 | primary << 13 | secondary, ranks
+        // being bits from the two up
+        let ranks = |ranks: &[u32]| ranks.iter().map(|rank| 1 << rank).sum::<u32>();
+        let expected = [
+            ("AhKhQhJhTh9c2d", 8 << 26 | 13),
+            ("5d4d3d2dAd9c9h", 8 << 26 | 4),
+            ("AsAdAcAh7d7c2s", 7 << 26 | ranks(&[12]) << 13 | ranks(&[5])),
+            ("KsKdKc7h7d2c2s", 6 << 26 | ranks(&[11]) << 13 | ranks(&[5])),
+            ("Ah9h7h4h2hKdQc", 5 << 26 | ranks(&[12, 7, 5, 2, 0]) << 13),
+            ("9c8d7h6s5cAdAh", 4 << 26 | 8),
+            ("QsQdQh9c5d3s2c", 3 << 26 | ranks(&[10]) << 13 | ranks(&[7, 3])),
+            ("JsJd4c4h8s3d2c", 2 << 26 | ranks(&[9, 2]) << 13 | ranks(&[6])),
+            ("TsTd8c6h4s3d2c", 1 << 26 | ranks(&[8]) << 13 | ranks(&[6, 4, 2])),
+            ("AsJd9c7h5s3d2c", ranks(&[12, 9, 7, 5, 3])),
+        ];
+        for (cards, bits) in expected {
+            assert_eq!(strength(&parse_cards(cards).unwrap()).bits(), bits, "{}", cards);
+        }
+    }
+
+    #[test]
+    #[ignore = "needs a GPU adapter"]
+    fn test_gpu_tally() {
+        let gpu = GpuEvaluator::new().unwrap();
+        let pair = ("Ah".parse().unwrap(), "Kh".parse().unwrap());
+        let board = parse_cards("Qh7h2c").unwrap();
+        let dead = parse_cards("Jd").unwrap();
+        for villain in [Villain::Random, Villain::Exposed("7c".parse().unwrap()), Villain::Known("7c".parse().unwrap(), "7d".parse().unwrap())] {
+            let runouts = villain.runouts(board.len(), dead.len());
+            let cpu = tally_runout_range_vs(&board, &pair, &villain, &dead, 0..runouts, None);
+            assert_eq!(gpu.tally(&board, &pair, &villain, &dead), Ok(cpu), "{:?}", villain);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+// One invocation per runout: deal it, score the pair, then tally against every villain pair
+// the rest of the deck allows. Cards are indices, rank * 4 + suit.
+//
+// Hands aren't looked up in the score table. Their rank counts are kept as masks of ranks
+// (ranks held once, twice, ...) and turned into a strength, higher being better:
+// category << 26 | primary << 13 | secondary, where primary and secondary are masks of ranks
+// (or one more than a straight's high rank). Masks with as many ranks compare the way kickers do
+
+// 0 offset of this dispatch's first runout, 1 runouts in the dispatch, 2 board length,
+// 3 deck length, 4 villain (0 random, 1 one card exposed, 2 both known), 5..7 pair,
+// 7..9 villain cards, 9..14 board, then the deck left to deal from
+@group(0) @binding(0) var<storage, read> input: array<u32>;
+// win, tie, lose for each runout of the dispatch
+@group(0) @binding(1) var<storage, read_write> tallies: array<u32>;
+
+const DECK: u32 = 14u;
+
+fn choose(n: u32, k: u32) -> u32 {
+    if (k > n) {
+        return 0u;
+    }
+    var c = 1u;
+    for (var i = 0u; i < k; i++) {
+        c = c * (n - i) / (i + 1u);
+    }
+    return c;
+}
+
+// the n highest ranks of a mask
+fn top(ranks: u32, n: u32) -> u32 {
+    var kept = ranks;
+    while (countOneBits(kept) > n) {
+        kept &= kept - 1u;
+    }
+    return kept;
+}
+
+// one more than the high rank of the best straight, or 0 without one
+fn straight(ranks: u32) -> u32 {
+    for (var low = 8i; low >= 0i; low--) {
+        let run = 0x1fu << u32(low);
+        if ((ranks & run) == run) {
+            return u32(low) + 5u;
+        }
+    }
+    let wheel = 0x100fu;
+    if ((ranks & wheel) == wheel) {
+        return 4u;
+    }
+    return 0u;
+}
+
+fn strength(cards: array<u32, 7>) -> u32 {
+    var suits = array<u32, 4>(0u, 0u, 0u, 0u);
+    for (var i = 0u; i < 7u; i++) {
+        suits[cards[i] & 3u] |= 1u << (cards[i] >> 2u);
+    }
+    // with seven cards a flush rules out quads and full houses
+    for (var s = 0u; s < 4u; s++) {
+        if (countOneBits(suits[s]) >= 5u) {
+            let high = straight(suits[s]);
+            if (high != 0u) {
+                return (8u << 26u) | high;
+            }
+            return (5u << 26u) | (top(suits[s], 5u) << 13u);
+        }
+    }
+
+    var held = array<u32, 5>(0u, 0u, 0u, 0u, 0u);
+    for (var r = 0u; r < 13u; r++) {
+        let n = ((suits[0] >> r) & 1u) + ((suits[1] >> r) & 1u) + ((suits[2] >> r) & 1u) + ((suits[3] >> r) & 1u);
+        held[n] |= 1u << r;
+    }
+    let present = held[1] | held[2] | held[3] | held[4];
+    if (held[4] != 0u) {
+        let quad = top(held[4], 1u);
+        return (7u << 26u) | (quad << 13u) | top(present & ~quad, 1u);
+    }
+    let trip = top(held[3], 1u);
+    let full = top((held[3] | held[2]) & ~trip, 1u);
+    if (trip != 0u && full != 0u) {
+        return (6u << 26u) | (trip << 13u) | full;
+    }
+    let high = straight(present);
+    if (high != 0u) {
+        return (4u << 26u) | high;
+    }
+    if (trip != 0u) {
+        return (3u << 26u) | (trip << 13u) | top(present & ~trip, 2u);
+    }
+    let paired = top(held[2], 2u);
+    let pairs = countOneBits(paired);
+    return (pairs << 26u) | (paired << 13u) | top(present & ~paired, 5u - 2u * pairs);
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= input[1]) {
+        return;
+    }
+    let board_len = input[2];
+    let deck_len = input[3];
+    let dealt = 5u - board_len;
+
+    // the runout's cards, as positions in the deck
+    var runout = array<u32, 5>(99u, 99u, 99u, 99u, 99u);
+    var board = array<u32, 5>(input[9], input[10], input[11], input[12], input[13]);
+    var rest = input[0] + i;
+    for (var k = dealt; k > 0u; k--) {
+        var c = k - 1u;
+        while (choose(c + 1u, k) <= rest) {
+            c++;
+        }
+        rest -= choose(c, k);
+        runout[k - 1u] = c;
+        board[board_len + k - 1u] = input[DECK + c];
+    }
+
+    let hero = strength(array<u32, 7>(input[5], input[6], board[0], board[1], board[2], board[3], board[4]));
+    var win = 0u;
+    var tie = 0u;
+    var lose = 0u;
+    let villain = input[4];
+    let firsts = select(1u, deck_len, villain == 0u);
+    for (var a = 0u; a < firsts; a++) {
+        if (villain == 0u && (a == runout[0] || a == runout[1] || a == runout[2] || a == runout[3] || a == runout[4])) {
+            continue;
+        }
+        let start = select(0u, a + 1u, villain == 0u);
+        let end = select(deck_len, 1u, villain == 2u);
+        for (var b = start; b < end; b++) {
+            if (villain != 2u && (b == runout[0] || b == runout[1] || b == runout[2] || b == runout[3] || b == runout[4])) {
+                continue;
+            }
+            var first = input[7];
+            var second = input[8];
+            if (villain == 0u) {
+                first = input[DECK + a];
+            }
+            if (villain != 2u) {
+                second = input[DECK + b];
+            }
+            let evil = strength(array<u32, 7>(first, second, board[0], board[1], board[2], board[3], board[4]));
+            if (hero > evil) {
+                win++;
+            } else if (hero == evil) {
+                tie++;
+            } else {
+                lose++;
+            }
+        }
+    }
+    tallies[3u * i] = win;
+    tallies[3u * i + 1u] = tie;
+    tallies[3u * i + 2u] = lose;
+}
@@ -0,0 +1,201 @@
+//! The 13x13 starting hand grid with a colour for each cell, for a range's weights or
+//! per-class equities, in the terminal or as HTML or SVG
+
+use crate::card::*;
+use crate::class::*;
+use crate::eval::*;
+use crate::eval_batch::*;
+use crate::json::*;
+use crate::range::*;
+use itertools::Itertools;
+
+
+/// A value for each of the 169 classes, by HandClass::index, meant to be 0-1 (or NaN for a class
+/// with nothing to show, drawn blank)
+#[derive(Debug, PartialEq, Clone)]
+pub struct Grid {
+    pub values: Vec<f64>,
+}
+
+impl Grid {
+    pub fn from_fn(mut value: impl FnMut(HandClass) -> f64) -> Grid {
+        Grid { values: (0..HandClass::COUNT).map(|index| value(HandClass::from_index(index))).collect() }
+    }
+
+    /// How much of each class the range holds, as the average weight over its combos
+    pub fn from_range(range: &HandRange) -> Grid {
+        Grid::from_fn(|class| {
+            let combos = class.combos();
+            combos.iter().map(|pair| range.weight(pair)).sum::<f64>() / combos.len() as f64
+        })
+    }
+
+    /// Each class's equity against the range on a board from the flop on, exact over every runout
+    /// and averaged over the class's combos. Classes the board blocks completely are NaN
+    pub fn from_equity_vs_range(range: &HandRange, board: &[Card]) -> Grid {
+        assert!((3..=5).contains(&board.len()), "the flop has to be out");
+        let board_mask = board.iter().fold(0, |mask, &card| mask | card_bit(card));
+        let combos: Vec<(Card, Card)> = Card::DECK
+            .into_iter()
+            .tuple_combinations()
+            .filter(|&(a, b)| (card_bit(a) | card_bit(b)) & board_mask == 0)
+            .collect();
+        let masks: Vec<u64> = combos.iter().map(|&(a, b)| card_bit(a) | card_bit(b)).collect();
+        let villains: Vec<(usize, f64)> = combos.iter().map(|pair| range.weight(pair)).enumerate().filter(|&(_, weight)| weight > 0.0).collect();
+
+        // every combo meets every villain combo on every runout, each combo scored once a runout
+        let mut points = vec![0.0; combos.len()];
+        let mut totals = vec![0.0; combos.len()];
+        let mut scores = vec![None; combos.len()];
+        for remainder in deck_without(board).into_iter().combinations(5 - board.len()) {
+            let runout_mask = remainder.iter().fold(board_mask, |mask, &card| mask | card_bit(card));
+            for (score, &mask) in scores.iter_mut().zip(&masks) {
+                *score = (mask & runout_mask == 0).then(|| score_mask(runout_mask | mask));
+            }
+            for (hero, hero_score) in scores.iter().enumerate() {
+                let Some(hero_score) = hero_score else { continue };
+                for &(villain, weight) in &villains {
+                    let Some(villain_score) = scores[villain] else { continue };
+                    if masks[hero] & masks[villain] != 0 {
+                        continue;
+                    }
+                    points[hero] += weight * match hero_score.cmp(&villain_score) {
+                        std::cmp::Ordering::Less => 2.0,
+                        std::cmp::Ordering::Equal => 1.0,
+                        std::cmp::Ordering::Greater => 0.0,
+                    };
+                    totals[hero] += 2.0 * weight;
+                }
+            }
+        }
+
+        let mut sums = vec![(0.0, 0); HandClass::COUNT];
+        for (i, pair) in combos.iter().enumerate() {
+            if totals[i] > 0.0 {
+                let sum = &mut sums[HandClass::from_pair(pair).index()];
+                *sum = (sum.0 + points[i] / totals[i], sum.1 + 1);
+            }
+        }
+        Grid { values: sums.into_iter().map(|(sum, n)| if n == 0 { f64::NAN } else { sum / n as f64 }).collect() }
+    }
+
+    pub fn value(&self, class: HandClass) -> f64 {
+        self.values[class.index()]
+    }
+
+    /// Rows of the grid, aces first
+    fn rows(&self) -> impl Iterator<Item = Vec<(HandClass, f64)>> + '_ {
+        (0..13).map(move |row| (0..13).map(|col| HandClass::from_grid(row, col)).map(|class| (class, self.value(class))).collect())
+    }
+
+    /// Each cell's class and value as a percentage, on a background for the value when coloured
+    pub fn render(&self, colour: bool) -> String {
+        let mut out = String::new();
+        for row in self.rows() {
+            for (class, value) in row {
+                let cell = format!("{:>4} {:>5} ", class.to_string(), percent(value));
+                if colour {
+                    let (r, g, b) = heat(value);
+                    out += &format!("\x1b[30;48;2;{};{};{}m{}\x1b[0m", r, g, b, cell);
+                } else {
+                    out += &cell;
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// A standalone page with the grid as a table
+    pub fn to_html(&self, title: &str) -> String {
+        let mut out = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n", escape(title));
+        out += "<style>table{border-collapse:collapse;font-family:monospace}td{width:3.5em;height:2.5em;text-align:center;border:1px solid #fff}</style>\n";
+        out += "</head>\n<body>\n<table>\n";
+        for row in self.rows() {
+            out += "<tr>";
+            for (class, value) in row {
+                let (r, g, b) = heat(value);
+                out += &format!("<td style=\"background:#{:02x}{:02x}{:02x}\">{}<br>{}</td>", r, g, b, class, percent(value));
+            }
+            out += "</tr>\n";
+        }
+        out + "</table>\n</body>\n</html>\n"
+    }
+
+    pub fn to_svg(&self) -> String {
+        const CELL: usize = 40;
+        let size = 13 * CELL;
+        let mut out = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" font-family=\"monospace\" font-size=\"11\" text-anchor=\"middle\">\n", size);
+        for (row, cells) in self.rows().enumerate() {
+            for (col, (class, value)) in cells.into_iter().enumerate() {
+                let (x, y) = (col * CELL, row * CELL);
+                let (r, g, b) = heat(value);
+                out += &format!("<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" fill=\"rgb({3},{4},{5})\" stroke=\"white\"/>", x, y, CELL, r, g, b);
+                out += &format!("<text x=\"{}\" y=\"{}\">{}</text>", x + CELL / 2, y + CELL / 2 - 2, class);
+                out += &format!("<text x=\"{}\" y=\"{}\">{}</text>\n", x + CELL / 2, y + CELL / 2 + 11, percent(value));
+            }
+        }
+        out + "</svg>\n"
+    }
+}
+
+impl ToJson for Grid {
+    /// Each class's value by name, in grid order
+    fn to_json(&self) -> Json {
+        Json::Object(self.rows().flatten().map(|(class, value)| (class.to_string(), value.into())).collect())
+    }
+}
+
+/// A value as a percentage, or a dash for NaN
+fn percent(value: f64) -> String {
+    match value.is_nan() {
+        true => "-".to_string(),
+        false => format!("{:.1}", value * 100.0),
+    }
+}
+
+/// Red at 0 through yellow to green at 1, grey for NaN
+fn heat(value: f64) -> (u8, u8, u8) {
+    if value.is_nan() {
+        return (160, 160, 160);
+    }
+    let value = value.clamp(0.0, 1.0);
+    let (r, g) = if value < 0.5 { (1.0, value * 2.0) } else { (2.0 - value * 2.0, 1.0) };
+    ((r * 220.0) as u8 + 35, (g * 180.0) as u8 + 35, 60)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let aces: HandClass = "AA".parse().unwrap();
+        let range = HandRange::from_combos(aces.combos().into_iter().take(3));
+        let grid = Grid::from_range(&range);
+        assert_eq!(grid.value(aces), 0.5);
+        assert_eq!(grid.value("AKs".parse().unwrap()), 0.0);
+
+        let plain = grid.render(false);
+        assert_eq!(plain.lines().count(), 13);
+        assert!(plain.starts_with("  AA  50.0  AKs   0.0 "));
+        assert!(grid.render(true).contains("\x1b[30;48;2;"));
+        assert_eq!(grid.to_html("<AA>").matches("<td").count(), 169);
+        assert!(grid.to_html("<AA>").contains("&lt;AA&gt;"));
+        assert_eq!(grid.to_svg().matches("<rect").count(), 169);
+        assert_eq!((heat(0.0), heat(1.0)), ((255, 35, 60), (35, 215, 60)));
+
+        // on the river kings fill up over queens and jacks under them, and the board has all but one ace
+        let board = parse_cards("AsAhAdKcQc").unwrap();
+        let grid = Grid::from_equity_vs_range(&"QQ".parse().unwrap(), &board);
+        assert_eq!(grid.value("KK".parse().unwrap()), 1.0);
+        assert_eq!(grid.value("JJ".parse().unwrap()), 0.0);
+        assert!(grid.value(aces).is_nan());
+        assert!(grid.render(false).starts_with("  AA     - "));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::card::*;
-use std::{collections::{hash_map::Entry, HashMap}, fmt::Display, hash::{Hash, Hasher}, ops::{BitOr, BitOrAssign}};
+use crate::category::*;
+use std::{collections::{hash_map::Entry, HashMap}, fmt::Display, hash::{BuildHasherDefault, Hash, Hasher}, ops::{BitAnd, BitOr, BitOrAssign, Sub}};
 use itertools::Itertools;
 
 
@@ -15,6 +16,8 @@ use itertools::Itertools;
 /// 
 /// Following 13 bits indicate which ranks have the flush suit:
 /// - i.e. bit 62 is set if the hand has a flush with an Ace (bit 63 is discriminant)
+///
+/// With the `serde` feature it serializes as the cards of Hand::cards, e.g. "AhKhQhJhTh2d"
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Hand(u64);
 
@@ -22,35 +25,32 @@ impl Hand {
     pub const EMPTY: Hand = Hand(0);
     const SUIT_OFFSET: u64 = 50;
 
-    pub fn new(cards: &Vec<Card>) -> Hand {
+    pub fn new(cards: &[Card]) -> Hand {
+        Hand::from_cards(cards.iter().copied())
+    }
+
+    /// The hand of any run of cards, in one pass over them
+    pub fn from_cards(cards: impl IntoIterator<Item = Card>) -> Hand {
         let mut val = 0;
         let mut suit_counts: [usize; 4] = [0; 4];
+        let mut suit_ranks: [u64; 4] = [0; 4];
         for card in cards {
-            val += 1 << (usize::from(card.rank) * 3);
-            suit_counts[usize::from(card.suit)] += 1;
+            val += 1 << (card.rank.index() * 3);
+            suit_counts[card.suit.index()] += 1;
+            suit_ranks[card.suit.index()] |= 1 << card.rank.index();
         }
         if let Some((flush_suit, _)) = suit_counts.iter().find_position(|&&x| x >= 5) {
             val |= 1 << 63; // Set flush bit
-
-            for card in cards {
-                if card.suit == Suit::try_from(flush_suit).unwrap() {
-                    val |= 1 << (usize::from(card.rank) as u64 + Hand::SUIT_OFFSET);
-                }
-            }
-        } 
+            val |= suit_ranks[flush_suit] << Hand::SUIT_OFFSET;
+        }
 
         Hand(val)
     }
 
     /// Get all combinations of n cards, best sorted first
     pub fn get_hand_combos(n: usize) -> Vec<Hand> {
-        Card::get_deck()
-            .into_iter()
-            .rev()
-            .combinations(n)
-            .map(|combo| Hand::new(&combo))
-            .collect()
-    } 
+        hand_combos_in(Deck::Full, n)
+    }
 
     pub fn from_straight_flush(high_rank: Rank) -> Hand {
         let mut hand = Hand::EMPTY;
@@ -66,8 +66,8 @@ impl Hand {
         
         let low_rank_offset = usize::from(high_rank) as u64 - 4;
         hand.0 |= 0b11111 << (low_rank_offset + Hand::SUIT_OFFSET);
-        hand.0 |= 0b001001001001001 << low_rank_offset * 3;
-        return hand;
+        hand.0 |= 0b001001001001001 << (low_rank_offset * 3);
+        hand
     }
 
     pub fn from_straight(high_rank: Rank) -> Hand {
@@ -79,8 +79,8 @@ impl Hand {
         }
         
         let low_rank_offset = usize::from(high_rank) as u64 - 4;
-        hand.0 |= 0b001001001001001 << low_rank_offset*3;
-        return hand;
+        hand.0 |= 0b001001001001001 << (low_rank_offset * 3);
+        hand
     }
 
     pub fn contains_rank(&self, rank: Rank) -> bool {
@@ -106,6 +106,116 @@ impl Hand {
         ((0b111 << (usize::from(rank) * 3)) & self.0) >> (usize::from(rank) * 3)
     }
 
+    /// Number of cards
+    pub fn len(&self) -> usize {
+        Rank::ALL_RANKS.iter().map(|&rank| self.count_rank(rank) as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranks_only() == Hand::EMPTY
+    }
+
+    /// Cards that make this hand, the suits being mostly lost: the flush ranks in hearts and the
+    /// other cards dealt round the rest of the suits in turn (all four without a flush), lowest
+    /// rank first
+    pub fn cards(self) -> Vec<Card> {
+        let others = if self.is_flush() { &Suit::ALL_SUITS[1..] } else { &Suit::ALL_SUITS[..] };
+        let mut cards = Vec::with_capacity(self.len());
+        let mut dealt = 0;
+        for rank in Rank::ALL_RANKS {
+            let mut count = self.count_rank(rank);
+            if self.is_in_flush(rank) {
+                cards.push(Card::new(rank, Suit::Hearts));
+                count -= 1;
+            }
+            for _ in 0..count {
+                cards.push(Card::new(rank, others[dealt % others.len()]));
+                dealt += 1;
+            }
+        }
+        cards
+    }
+
+    /// Rank of each card, lowest first
+    pub fn ranks(self) -> impl Iterator<Item = Rank> {
+        Rank::ALL_RANKS.into_iter().flat_map(move |rank| std::iter::repeat_n(rank, self.count_rank(rank) as usize))
+    }
+
+    /// Hand made rank by rank from the two hands' counts, with flush ranks made from theirs the
+    /// same way, still a flush only if five of them are left
+    fn combine(self, other: Hand, count: impl Fn(u64, u64) -> u64, flush: impl Fn(u64, u64) -> u64) -> Hand {
+        let counts = Rank::ALL_RANKS
+            .iter()
+            .fold(Hand::EMPTY, |acc, &rank| acc | Hand::from_n_rank(rank, count(self.count_rank(rank), other.count_rank(rank))));
+        let present = (0..13).filter(|&rank| counts.0 >> (3 * rank) & 0b111 != 0).fold(0, |acc, rank| acc | 1 << rank);
+        let suited = present & flush(self.0 >> Hand::SUIT_OFFSET & 0x1fff, other.0 >> Hand::SUIT_OFFSET & 0x1fff);
+        if suited.count_ones() < 5 {
+            return counts;
+        }
+        Hand(counts.0 | 1 << 63 | suited << Hand::SUIT_OFFSET)
+    }
+
+    /// Just the rank counts, dropping the flush bits, for games where suits don't matter
+    pub fn ranks_only(self) -> Hand {
+        Hand(self.0 & ((1 << 39) - 1))
+    }
+
+    /// The five card hand scoring best out of up to seven cards, found from the bits alone
+    pub fn best_five(self) -> Hand {
+        if self.is_flush() {
+            return Hand::best_flush((self.0 >> Hand::SUIT_OFFSET) & 0x1fff);
+        }
+        let mut held = [0u64; 5];
+        for rank in 0..13 {
+            held[((self.0 >> (3 * rank)) & 0b111) as usize] |= 1 << rank;
+        }
+        Hand::best_of_held(held)
+    }
+
+    /// best_five of up to seven cards given as the ranks each suit has, 13 bits a suit
+    pub fn best_of_suits(suits: [u64; 4]) -> Hand {
+        if let Some(&suited) = suits.iter().find(|suited| suited.count_ones() >= 5) {
+            return Hand::best_flush(suited);
+        }
+        // how many suits have each rank, added up a bit at a time across the masks
+        let [a, b, c, d] = suits;
+        let (low_ab, high_ab, low_cd, high_cd) = (a ^ b, a & b, c ^ d, c & d);
+        let ones = low_ab ^ low_cd;
+        let carry = low_ab & low_cd;
+        let twos = high_ab ^ high_cd ^ carry;
+        let fours = (high_ab & high_cd) | ((high_ab ^ high_cd) & carry);
+        Hand::best_of_held([0x1fff & !(ones | twos | fours), ones & !twos, twos & !ones, ones & twos, fours])
+    }
+
+    /// With seven cards a flush rules out quads and full houses, so the flush suit's ranks are all that matter
+    fn best_flush(suited: u64) -> Hand {
+        let kept = straight_ranks(suited).unwrap_or_else(|| top_ranks(suited, 5));
+        Hand(1 << 63 | kept << Hand::SUIT_OFFSET | spread_ranks(kept))
+    }
+
+    /// The best five from masks of the ranks held none, once, twice, three and four times
+    fn best_of_held(held: [u64; 5]) -> Hand {
+        let [_, _, pairs, trips, quads] = held;
+        let present = 0x1fff & !held[0];
+        if quads != 0 {
+            let quad = top_ranks(quads, 1);
+            return Hand(4 * spread_ranks(quad) + spread_ranks(top_ranks(present & !quad, 1)));
+        }
+        let trip = top_ranks(trips, 1);
+        let full = top_ranks((trips | pairs) & !trip, 1);
+        if trip != 0 && full != 0 {
+            return Hand(3 * spread_ranks(trip) + 2 * spread_ranks(full));
+        }
+        if let Some(straight) = straight_ranks(present) {
+            return Hand(spread_ranks(straight));
+        }
+        if trip != 0 {
+            return Hand(3 * spread_ranks(trip) + spread_ranks(top_ranks(present & !trip, 2)));
+        }
+        let paired = top_ranks(pairs, 2);
+        Hand(2 * spread_ranks(paired) + spread_ranks(top_ranks(present & !paired, 5 - 2 * paired.count_ones())))
+    }
+
     pub fn is_flush(&self) -> bool {
         self.0 & (1 << 63) != 0
     }
@@ -119,16 +229,16 @@ impl Hand {
         hand.0 |= 1 << 63; // Set flush bit
         hand.0 |= 1 << (usize::from(rank) as u64 + Hand::SUIT_OFFSET);
         hand.0 |= 1 << (usize::from(rank) * 3);
-        return hand;
+        hand
     }
 
     fn from_n_rank(rank: Rank, n: u64) -> Hand {
         debug_assert!(n <= 4);
         Hand(n << (usize::from(rank) * 3))
     }
-    /// Get all combinations of n ranks as flush
-    fn flush_combos() -> Vec<Hand> {
-        Rank::ALL_RANKS
+    /// Get all combinations of 5 of the deck's ranks as flush
+    fn flush_combos(deck: Deck) -> Vec<Hand> {
+        deck.ranks()
             .iter()
             .map(|&rank| Hand::from_rank_as_flush(rank))
             .rev()
@@ -141,12 +251,83 @@ impl Hand {
 
 }
 
+/// A count of one for each rank in a mask of ranks
+fn spread_ranks(mut ranks: u64) -> u64 {
+    let mut counts = 0;
+    while ranks != 0 {
+        counts |= 1 << (3 * ranks.trailing_zeros());
+        ranks &= ranks - 1;
+    }
+    counts
+}
+
+/// The n highest of a mask of ranks
+fn top_ranks(mut ranks: u64, n: u32) -> u64 {
+    while ranks.count_ones() > n {
+        ranks &= ranks - 1;
+    }
+    ranks
+}
+
+/// The five ranks of the highest straight in a mask of ranks, the wheel being Ace to Five
+fn straight_ranks(ranks: u64) -> Option<u64> {
+    const WHEEL: u64 = 1 << 12 | 0b1111;
+    for low in (0..=8).rev() {
+        let straight = 0b11111 << low;
+        if ranks & straight == straight {
+            return Some(straight);
+        }
+    }
+    (ranks & WHEEL == WHEEL).then_some(WHEEL)
+}
+
 impl Hash for Hand {
     fn hash<H: Hasher>(&self, state: &mut H) {
         state.write_u64(self.0);
     }
 }
 
+/// A map keyed by hands, hashed with HandHasher
+pub type HandMap<V> = HashMap<Hand, V, BuildHasherDefault<HandHasher>>;
+
+/// Hashes a hand's bits with one multiply instead of SipHash, which dominates looking scores up.
+/// The bits are unique to the hand but not spread out (the rank counts are low, the flush bits
+/// high and mostly zero), so the multiply's high and low halves are folded together to mix both
+/// ends, as the map uses both. Not resistant to chosen keys, which hands can't be
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HandHasher(u64);
+
+impl Hasher for HandHasher {
+    fn write_u64(&mut self, bits: u64) {
+        let product = (self.0 ^ bits) as u128 * 0x9e37_79b9_7f4a_7c15;
+        self.0 = product as u64 ^ (product >> 64) as u64;
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl From<&[Card]> for Hand {
+    fn from(cards: &[Card]) -> Hand {
+        Hand::new(cards)
+    }
+}
+
+impl FromIterator<Card> for Hand {
+    fn from_iter<I: IntoIterator<Item = Card>>(cards: I) -> Hand {
+        Hand::from_cards(cards)
+    }
+}
+
 impl BitOr for Hand {
     type Output = Hand;
 
@@ -155,6 +336,25 @@ impl BitOr for Hand {
     }
 }
 
+/// Ranks both hands have, as many of each as the one with fewer, and the flush ranks both have
+impl BitAnd for Hand {
+    type Output = Hand;
+
+    fn bitand(self, other: Hand) -> Hand {
+        self.combine(other, u64::min, |a, b| a & b)
+    }
+}
+
+/// The hand without the other's cards, taking off as many of each rank as it has (down to
+/// none) and its flush ranks
+impl Sub for Hand {
+    type Output = Hand;
+
+    fn sub(self, other: Hand) -> Hand {
+        self.combine(other, u64::saturating_sub, |a, b| a & !b)
+    }
+}
+
 impl BitOrAssign for Hand {
     fn bitor_assign(&mut self, rhs: Self) {
         *self = *self | rhs;
@@ -175,31 +375,59 @@ impl Display for Hand {
     }
 }   
 
+/// Straights in the deck as their ranks, best first. The ace also plays low, under the lowest
+/// rank in the deck: A-2-3-4-5 with a full deck, A-6-7-8-9 with a short one
+fn straights(deck: Deck) -> Vec<Vec<Rank>> {
+    let ranks = deck.ranks();
+    let mut straights: Vec<Vec<Rank>> = ranks.windows(5).rev().map(|window| window.to_vec()).collect();
+    straights.push([&[Rank::Ace], &ranks[..4]].concat());
+    straights
+}
+
+/// Five different ranks, all one suit or not
+fn from_ranks(ranks: &[Rank], flush: bool) -> Hand {
+    ranks
+        .iter()
+        .enumerate()
+        .map(|(i, &rank)| Card::new(rank, if flush || i > 0 { Suit::Hearts } else { Suit::Spades }))
+        .collect()
+}
+
+/// All combinations of n cards from the deck, best sorted first
+fn hand_combos_in(deck: Deck, n: usize) -> Vec<Hand> {
+    deck.cards()
+        .into_iter()
+        .rev()
+        .combinations(n)
+        .map(Hand::from_cards)
+        .collect()
+}
+
 /// Scores only 5 length
-fn score_straight_flush(scores: &mut HashMap<Hand, u64>, offset: u64) -> u64 {
-    let mut score: u64 = offset;
-    for high_rank in (usize::from(Rank::Five)..=usize::from(Rank::Ace)).rev() {
-        let hand = Hand::from_straight_flush(high_rank.try_into().unwrap());
+fn score_straight_flush(scores: &mut HandMap<u16>, offset: u16, deck: Deck) -> u16 {
+    let mut score: u16 = offset;
+    for ranks in straights(deck) {
+        let hand = from_ranks(&ranks, true);
         if let Entry::Vacant(v) = scores.entry(hand) {
                 v.insert(score);
                 score += 1;
             }
     }
 
-    return score;
+    score
 }
 
 /// Doesn't need to consider flushes because:
 /// - they are not possible with 4 of a kind (with at most 7 cards)
 /// - all others are worth less than a flush anyway
-fn score_n_of_a_kind(scores: &mut HashMap<Hand, u64>, offset: u64, n: u64) -> u64 {
-    let mut score: u64 = offset;
-    for set_rank in Rank::ALL_RANKS.iter().rev() {
-        for kickers in Hand::get_hand_combos(5 - n as usize) {
+fn score_n_of_a_kind(scores: &mut HandMap<u16>, offset: u16, n: u64, deck: Deck) -> u16 {
+    let mut score: u16 = offset;
+    for set_rank in deck.ranks().iter().rev() {
+        for kickers in hand_combos_in(deck, 5 - n as usize) {
             if kickers.contains_rank(*set_rank) {
                 continue;
             }
-            let mut hand = Hand::from_n_rank(*set_rank, n as u64);
+            let mut hand = Hand::from_n_rank(*set_rank, n);
             hand |= kickers;
 
             if let Entry::Vacant(v) = scores.entry(hand) {
@@ -208,14 +436,14 @@ fn score_n_of_a_kind(scores: &mut HashMap<Hand, u64>, offset: u64, n: u64) -> u6
             }
         }
     }
-    return score;
+    score
 }
 
 /// Also doesn't need to consider flushes it's impossible with 7 cards
-fn score_full_house(scores: &mut HashMap<Hand, u64>, offset: u64) -> u64 {
-    let mut score: u64 = offset;
-    for three_rank in Rank::ALL_RANKS.iter().rev() {
-        for pair_rank in Rank::ALL_RANKS.iter().rev() {
+fn score_full_house(scores: &mut HandMap<u16>, offset: u16, deck: Deck) -> u16 {
+    let mut score: u16 = offset;
+    for three_rank in deck.ranks().iter().rev() {
+        for pair_rank in deck.ranks().iter().rev() {
             if three_rank == pair_rank {
                 continue;
             }
@@ -227,41 +455,42 @@ fn score_full_house(scores: &mut HashMap<Hand, u64>, offset: u64) -> u64 {
             }
         }
     }
-    return score;
+    score
 }
 
 
-fn score_flush(scores: &mut HashMap<Hand, u64>, offset: u64) -> u64 {
-    let mut score: u64 = offset;
-    for hand in Hand::flush_combos() {
+fn score_flush(scores: &mut HandMap<u16>, offset: u16, deck: Deck) -> u16 {
+    let mut score: u16 = offset;
+    for hand in Hand::flush_combos(deck) {
         if let Entry::Vacant(v) = scores.entry(hand) {
             v.insert(score);
             score += 1;
         }
     }
-    return score;
+    score
 }
 
-fn score_straight(scores: &mut HashMap<Hand, u64>, offset: u64) -> u64 {
-    let mut score: u64 = offset;
-    for high_rank in (usize::from(Rank::Five)..=usize::from(Rank::Ace)).rev() {
-        let hand = Hand::from_straight(high_rank.try_into().unwrap());
+fn score_straight(scores: &mut HandMap<u16>, offset: u16, deck: Deck) -> u16 {
+    let mut score: u16 = offset;
+    for ranks in straights(deck) {
+        let hand = from_ranks(&ranks, false);
         if let Entry::Vacant(v) = scores.entry(hand) {
             v.insert(score);
             score += 1;
         }
     }
-    return score;
+    score
 }
 
-fn score_two_pair(scores: &mut HashMap<Hand, u64>, offset: u64) -> u64 {
-    let mut score: u64 = offset;
-    for high_pair in (usize::from(Rank::Three)..=usize::from(Rank::Ace)).rev() {
-        for low_pair in (usize::from(Rank::Two)..high_pair).rev() {
-            let mut hand = Hand::from_n_rank(high_pair.try_into().unwrap(), 2);
-            hand.add_n_rank(low_pair.try_into().unwrap(), 2);
+fn score_two_pair(scores: &mut HandMap<u16>, offset: u16, deck: Deck) -> u16 {
+    let mut score: u16 = offset;
+    let ranks = deck.ranks();
+    for (i, high_pair) in ranks.iter().enumerate().rev() {
+        for low_pair in ranks[..i].iter().rev() {
+            let mut hand = Hand::from_n_rank(*high_pair, 2);
+            hand.add_n_rank(*low_pair, 2);
             
-            for kicker in Rank::ALL_RANKS.iter().rev() {
+            for kicker in ranks.iter().rev() {
                 if hand.contains_rank(*kicker) {
                     continue;
                 }
@@ -275,50 +504,154 @@ fn score_two_pair(scores: &mut HashMap<Hand, u64>, offset: u64) -> u64 {
             }
         }
     }
-    return score;
+    score
 }
 
-fn score_high_card(scores: &mut HashMap<Hand, u64>, offset: u64) -> u64 {
-    let mut score: u64 = offset;
-    for hand in Hand::get_hand_combos(5) {
+fn score_high_card(scores: &mut HandMap<u16>, offset: u16, deck: Deck) -> u16 {
+    let mut score: u16 = offset;
+    for hand in hand_combos_in(deck, 5) {
         if let Entry::Vacant(v) = scores.entry(hand) {
             v.insert(score);
             score += 1;
         }
     }
-    return score;
+    score
+}
+
+/// Raw scores counting up from the best hand, with where each category starts.
+/// Only Score should see these values
+pub(crate) struct ScoreTable {
+    pub scores: HandMap<u16>,
+    /// First score of each category, best first
+    pub starts: Vec<(u16, Category)>,
+}
+
+impl ScoreTable {
+    pub fn category(&self, score: u16) -> Category {
+        self.starts.iter().rev().find(|(start, _)| score >= *start).unwrap().1
+    }
+}
+
+/// Scores every hand of a category from the given score on, returning the score after the last
+type ScoreCategory = fn(&mut HandMap<u16>, u16, Deck) -> u16;
+
+/// Score table for the deck. With a short deck there are fewer cards of each suit so flushes
+/// are harder to make, and beat full houses. Straights still beat three of a kind
+pub(crate) fn create_score_table(deck: Deck) -> ScoreTable {
+    let mut scores: HandMap<u16> = HandMap::default();
+    let mut starts = Vec::new();
+    let mut order: [(Category, ScoreCategory); 9] = [
+        (Category::StraightFlush, score_straight_flush),
+        (Category::Quads, |scores, score, deck| score_n_of_a_kind(scores, score, 4, deck)),
+        (Category::FullHouse, score_full_house),
+        (Category::Flush, score_flush),
+        (Category::Straight, score_straight),
+        (Category::Trips, |scores, score, deck| score_n_of_a_kind(scores, score, 3, deck)),
+        (Category::TwoPair, score_two_pair),
+        (Category::Pair, |scores, score, deck| score_n_of_a_kind(scores, score, 2, deck)),
+        (Category::HighCard, score_high_card),
+    ];
+    if deck == Deck::Short {
+        order.swap(2, 3);
+    }
+
+    let mut score: u16 = 0;
+    for (category, score_category) in order {
+        starts.push((score, category));
+        score = score_category(&mut scores, score, deck);
+    }
+
+    ScoreTable { scores, starts }
+}
+
+/// Ace-to-five low scores by rank counts (see Hand::ranks_only), counting up from the wheel.
+/// The ace is low and straights and flushes don't count, but pairs do: any five different ranks
+/// beat any pair, then two pair, trips, full houses and quads. Within each, the highest group
+/// decides first (the pair of a pair hand, the higher pair of two pair) then the rest highest first
+pub(crate) fn create_low_table() -> HandMap<u16> {
+    let value = |rank: Rank| match rank {
+        Rank::Ace => 1,
+        _ => usize::from(rank) + 2,
+    };
+    let mut lows: Vec<(Vec<usize>, Vec<usize>, Hand)> = Rank::ALL_RANKS
+        .iter()
+        .copied()
+        .combinations_with_replacement(5)
+        .filter_map(|ranks| {
+            let counts = ranks.iter().copied().counts();
+            if counts.values().any(|&count| count > 4) {
+                return None;
+            }
+            // biggest groups first, then highest
+            let groups: Vec<(usize, usize)> = counts
+                .iter()
+                .map(|(&rank, &count)| (count, value(rank)))
+                .sorted_unstable_by(|a, b| b.cmp(a))
+                .collect();
+            let hand = counts.iter().fold(Hand::EMPTY, |hand, (&rank, &count)| hand | Hand::from_n_rank(rank, count as u64));
+            Some((groups.iter().map(|group| group.0).collect(), groups.iter().map(|group| group.1).collect(), hand))
+        })
+        .collect();
+    lows.sort_unstable_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+    lows.into_iter().enumerate().map(|(score, (_, _, hand))| (hand, score as u16)).collect()
 }
 
-pub fn create_score_table() -> (HashMap<Hand, u64>, u64) {
-    let mut scores: HashMap<Hand, u64> = HashMap::new();
-    let mut score: u64 = 0;
-    score = score_straight_flush(&mut scores, score);
-    score = score_n_of_a_kind(&mut scores, score, 4);
-    score = score_full_house(&mut scores, score);
-    score = score_flush(&mut scores, score);
-    score = score_straight(&mut scores, score);
-    score = score_n_of_a_kind(&mut scores, score, 3);
-    score = score_two_pair(&mut scores, score);
-    score = score_n_of_a_kind(&mut scores, score, 2);
-    score = score_high_card(&mut scores, score);
-
-    return (scores, score);
+/// Deuce-to-seven low scores, counting up from 7-5-4-3-2. Hold'em upside down, straights, flushes
+/// and pairs all counting against you, except the ace is only ever high: A-2-3-4-5 is no straight
+/// but the lowest ace high, one better than A-6-4-3-2
+pub(crate) fn create_deuce_seven_table() -> HandMap<u16> {
+    let table = create_score_table(Deck::Full);
+    let wheel = [Rank::Ace, Rank::Five, Rank::Four, Rank::Three, Rank::Two];
+    let worst_ace_high = [Rank::Ace, Rank::Six, Rank::Four, Rank::Three, Rank::Two];
+    // as high hands the wheels fall just under A-6-4-3-2, so sort straight after it
+    let mut hands: Vec<((u16, bool), Hand)> = table.scores
+        .iter()
+        .map(|(&hand, &score)| match [false, true].into_iter().find(|&flush| hand == from_ranks(&wheel, flush)) {
+            Some(flush) => ((table.scores[&from_ranks(&worst_ace_high, flush)], true), hand),
+            None => ((score, false), hand),
+        })
+        .collect();
+    hands.sort_unstable_by_key(|&(key, _)| std::cmp::Reverse(key));
+    hands.into_iter().enumerate().map(|(score, (_, hand))| (hand, score as u16)).collect()
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
 
     #[test]
     fn test_new() {
-        let cards: Vec<Card> = vec![Rank::Two, Rank::Three, Rank::Four, Rank::Ace].iter().map(|rank| Card::new(*rank, Suit::Hearts)).collect();
+        let cards: Vec<Card> = [Rank::Two, Rank::Three, Rank::Four, Rank::Ace].iter().map(|rank| Card::new(*rank, Suit::Hearts)).collect();
         let hand = Hand::new(&cards);
         for card in cards {
             assert!(hand.contains_rank(card.rank));
         }
     }
 
+    #[test]
+    fn test_from_cards() {
+        let cards = parse_cards("AhKh9h5h2hAs9c").unwrap();
+        let hand = Hand::new(&cards);
+        assert!(hand.is_flush() && hand.count_rank(Rank::Ace) == 2);
+        assert_eq!(Hand::from_cards(cards.iter().copied()), hand);
+        assert_eq!(cards.iter().copied().collect::<Hand>(), hand);
+        assert_eq!(Hand::from(&cards[..]), hand);
+    }
+
+    #[test]
+    fn test_set_ops() {
+        let hand = Hand::new(&parse_cards("AhKh9h5h2hAs9c").unwrap());
+        let other = Hand::new(&parse_cards("AdAc9h9d3s").unwrap());
+        assert_eq!((hand.len(), other.len()), (7, 5));
+        assert_eq!(hand & other, Hand::new(&parse_cards("AsAc9s9c").unwrap()));
+        let rest = hand - other;
+        assert_eq!(rest.ranks().collect::<Vec<_>>(), [Rank::Two, Rank::Five, Rank::King]);
+        assert!(!rest.is_flush() && (hand - Hand::new(&parse_cards("As9c").unwrap())).is_flush());
+        assert!((hand - hand).is_empty() && !rest.is_empty());
+    }
+
     #[test]
     fn test_flush_makers() {
         for high_rank in (usize::from(Rank::Five)..=usize::from(Rank::Ace)).rev() {
@@ -359,7 +692,7 @@ mod tests {
     #[test]
     fn test_add_and_remove() {
         for hand in Hand::get_hand_combos(5) {
-            let mut hand2  = hand.clone();
+            let mut hand2  = hand;
             for rank in Rank::ALL_RANKS {
                 if !hand2.contains_rank(rank) {
                     hand2.add_rank(rank);
@@ -373,16 +706,79 @@ mod tests {
     #[test]
     fn test_score_table() {
         // confirm that the no. of distinct hands in each category matches
-        let mut scores: HashMap<Hand, u64> = HashMap::new();
-        assert_eq!(score_straight_flush(&mut scores, 0), 10);
-        assert_eq!(score_n_of_a_kind(&mut scores, 0, 4), 156);
-        assert_eq!(score_full_house(&mut scores, 0), 156);
-        assert_eq!(score_flush(&mut scores, 0), 1277);
-        assert_eq!(score_straight(&mut scores, 0), 10);
-        assert_eq!(score_n_of_a_kind(&mut scores, 0, 3), 858);
-        assert_eq!(score_two_pair(&mut scores, 0), 858);
-        assert_eq!(score_n_of_a_kind(&mut scores, 0,2), 2860);
-        assert_eq!(score_high_card(&mut scores, 0), 1277);
-
-    }
-}
\ No newline at end of file
+        let mut scores: HandMap<u16> = HandMap::default();
+        assert_eq!(score_straight_flush(&mut scores, 0, Deck::Full), 10);
+        assert_eq!(score_n_of_a_kind(&mut scores, 0, 4, Deck::Full), 156);
+        assert_eq!(score_full_house(&mut scores, 0, Deck::Full), 156);
+        assert_eq!(score_flush(&mut scores, 0, Deck::Full), 1277);
+        assert_eq!(score_straight(&mut scores, 0, Deck::Full), 10);
+        assert_eq!(score_n_of_a_kind(&mut scores, 0, 3, Deck::Full), 858);
+        assert_eq!(score_two_pair(&mut scores, 0, Deck::Full), 858);
+        assert_eq!(score_n_of_a_kind(&mut scores, 0,2, Deck::Full), 2860);
+        assert_eq!(score_high_card(&mut scores, 0, Deck::Full), 1277);
+
+    }
+
+    #[test]
+    fn test_short_deck_score_table() {
+        let table = create_score_table(Deck::Short);
+        assert_eq!(table.scores.values().max(), Some(&1403));
+        let score = |cards: &str| table.scores[&Hand::new(&parse_cards(cards).unwrap())];
+
+        assert!(score("AhKhQhJh9h") < score("AhAdAsKhKd"));
+        assert_eq!(table.category(score("AhKhQhJh9h")), Category::Flush);
+        // A-6-7-8-9 is the lowest straight (and straight flush)
+        assert_eq!(table.category(score("Ah6d7c8s9h")), Category::Straight);
+        assert!(score("Ah6d7c8s9h") > score("6h7d8c9sTh"));
+        assert!(score("Ah6h7h8h9h") > score("6h7h8h9hTh"));
+        assert_eq!(table.category(score("Ah6h7h8h9h")), Category::StraightFlush);
+        assert_eq!(table.starts.iter().map(|(_, category)| *category).collect::<Vec<_>>()[2..4], [Category::Flush, Category::FullHouse]);
+    }
+
+    #[test]
+    fn test_hand_hasher() {
+        // every scored hand hashes differently, and spread over the buckets of a map that size
+        let table = create_score_table(Deck::Full);
+        let hash = |hand: &Hand| {
+            let mut hasher = HandHasher::default();
+            hand.hash(&mut hasher);
+            hasher.finish()
+        };
+        let hashes: HashSet<u64> = table.scores.keys().map(hash).collect();
+        assert_eq!(hashes.len(), table.scores.len());
+        let buckets: HashSet<u64> = hashes.iter().map(|hash| hash & 0x1fff).collect();
+        assert!(buckets.len() > table.scores.len() / 2);
+    }
+
+    #[test]
+    fn test_low_table() {
+        let table = create_low_table();
+        // every multiset of 5 ranks but five of a kind
+        assert_eq!(table.len(), 6175);
+        let score = |cards: &str| table[&Hand::new(&parse_cards(cards).unwrap()).ranks_only()];
+
+        assert_eq!(score("5h4h3h2hAh"), 0);
+        assert_eq!(score("6c4d3s2hAc"), 1);
+        assert!(score("KcQdJsTh8c") < score("AcAd2s3h4c"));
+        assert!(score("AcAd4s3h2c") < score("2c2dAs3h4c"));
+        assert!(score("KcKdQsQhJc") < score("AcAdAs2h3c"));
+        assert_eq!(table.values().max(), Some(&6174));
+        assert_eq!(score("KcKdKsKhQc"), 6174);
+    }
+
+    #[test]
+    fn test_deuce_seven_table() {
+        let table = create_deuce_seven_table();
+        assert_eq!(table.len(), 7462);
+        let score = |cards: &str| table[&Hand::new(&parse_cards(cards).unwrap())];
+
+        assert_eq!(score("7h5d4c3s2h"), 0);
+        assert_eq!(score("AhKhQhJhTh"), 7461);
+        // no wheel: the lowest ace high, still worse than king high but better than any pair or straight
+        assert_eq!(score("Ah5d4c3s2h") + 1, score("Ah6d4c3s2h"));
+        assert!(score("KhQdJcTs8h") < score("Ah5d4c3s2h"));
+        assert!(score("Ah5d4c3s2h") < score("2c2d3s4h5c"));
+        assert!(score("Ah5h4h3h2h") < score("6h5h4h3h2h"));
+        assert!(score("8h6d4c3s2h") < score("6h5d4c3s2h"));
+    }
+}
@@ -0,0 +1,526 @@
+use crate::card::*;
+use crate::game::*;
+use crate::street::*;
+
+
+/// Blinds and antes posted before the cards are dealt
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Post {
+    SmallBlind,
+    BigBlind,
+    /// Both blinds at once, e.g. when coming back after missing them
+    Both,
+    Ante,
+}
+
+/// What a player did, with amounts as the site gives them
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Play {
+    Post(Post, Chips),
+    Fold,
+    Check,
+    Call(Chips),
+    Bet(Chips),
+    /// Raise by the first amount, to the second in total on the street
+    Raise(Chips, Chips),
+    /// Part of a bet nobody called, given back
+    Returned(Chips),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HistoryAction {
+    pub street: Street,
+    pub player: String,
+    pub play: Play,
+    pub all_in: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PlayerRecord {
+    pub seat: usize,
+    pub name: String,
+    pub stack: Chips,
+}
+
+/// One hand from a hand history. Amounts are in cents for real money games and in chips
+/// otherwise, so always whole numbers
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HandRecord {
+    /// Where the hand was played, e.g. "PokerStars"
+    pub site: String,
+    pub id: String,
+    /// e.g. "Hold'em No Limit"
+    pub game: String,
+    /// Amounts are in cents rather than chips
+    pub cents: bool,
+    pub small_blind: Chips,
+    pub big_blind: Chips,
+    pub table: String,
+    /// Seat number with the button
+    pub button: usize,
+    pub players: Vec<PlayerRecord>,
+    /// Whoever was dealt the hole cards we know about
+    pub hero: Option<(String, (Card, Card))>,
+    pub actions: Vec<HistoryAction>,
+    pub board: Vec<Card>,
+    /// Hole cards shown at showdown
+    pub shown: Vec<(String, (Card, Card))>,
+    /// Won from the pot (side pots included) by each player who won something
+    pub collected: Vec<(String, Chips)>,
+}
+
+impl HandRecord {
+    pub fn player(&self, name: &str) -> Option<&PlayerRecord> {
+        self.players.iter().find(|player| player.name == name)
+    }
+
+    /// Everything the player put in the pot, less anything returned
+    pub fn put_in(&self, name: &str) -> Chips {
+        let mut total = 0;
+        let mut street = Street::Preflop;
+        // put in on this street, which a raise sets rather than adds to
+        let mut bet = 0;
+        for action in self.actions.iter().filter(|action| action.player == name) {
+            if action.street != street {
+                street = action.street;
+                total += bet;
+                bet = 0;
+            }
+            match action.play {
+                Play::Post(Post::Ante, amount) => total += amount,
+                Play::Post(_, amount) | Play::Call(amount) | Play::Bet(amount) => bet += amount,
+                Play::Raise(_, to) => bet = to,
+                Play::Returned(amount) => bet -= amount,
+                Play::Fold | Play::Check => {}
+            }
+        }
+        total + bet
+    }
+
+    pub fn collected(&self, name: &str) -> Chips {
+        self.collected.iter().filter(|(player, _)| player == name).map(|(_, amount)| amount).sum()
+    }
+
+    /// What the player won or lost over the hand
+    pub fn net(&self, name: &str) -> i64 {
+        self.collected(name) as i64 - self.put_in(name) as i64
+    }
+}
+
+impl From<&HandState> for HandRecord {
+    /// A hand played with the engine, as if from a hand history. Players are named by seat, and
+    /// since every hand is known they all count as shown
+    fn from(state: &HandState) -> Self {
+        let n = state.seats.len();
+        let name = |seat: usize| format!("seat {}", seat);
+        let winnings: Vec<Chips> = state.winnings().map_or(vec![0; n], <[Chips]>::to_vec);
+        let mut stacks: Vec<Chips> = state.seats.iter().zip(&winnings).map(|(seat, won)| seat.stack + seat.contributed - won).collect();
+        let players = (0..n).map(|seat| PlayerRecord { seat, name: name(seat), stack: stacks[seat] }).collect();
+
+        // replay the bets to put amounts on the actions
+        let mut actions = Vec::new();
+        let mut bets = vec![0; n];
+        let mut current_bet = 0;
+        let mut street = Street::Preflop;
+        let small = if n == 2 { state.button } else { (state.button + 1) % n };
+        for (seat, post, blind) in [(small, Post::SmallBlind, state.small_blind), ((small + 1) % n, Post::BigBlind, state.big_blind)] {
+            let amount = blind.min(stacks[seat]);
+            stacks[seat] -= amount;
+            bets[seat] = amount;
+            current_bet = current_bet.max(amount);
+            actions.push(HistoryAction { street, player: name(seat), play: Play::Post(post, amount), all_in: stacks[seat] == 0 });
+        }
+        current_bet = current_bet.max(state.big_blind);
+        for &(seat, action_street, action) in &state.history {
+            if action_street != street {
+                street = action_street;
+                bets = vec![0; n];
+                current_bet = 0;
+            }
+            let play = match action {
+                Action::Fold => Play::Fold,
+                Action::Check => Play::Check,
+                Action::Call => Play::Call((current_bet - bets[seat]).min(stacks[seat])),
+                Action::Raise(to) => Play::Raise(to - current_bet, to),
+                Action::AllIn => Play::Raise(bets[seat] + stacks[seat] - current_bet, bets[seat] + stacks[seat]),
+            };
+            let put_in = match play {
+                Play::Call(amount) => amount,
+                Play::Raise(_, to) => {
+                    current_bet = to;
+                    to - bets[seat]
+                }
+                _ => 0,
+            };
+            stacks[seat] -= put_in;
+            bets[seat] += put_in;
+            let all_in = put_in > 0 && stacks[seat] == 0;
+            actions.push(HistoryAction { street, player: name(seat), play, all_in });
+        }
+
+        HandRecord {
+            site: "engine".to_string(),
+            id: String::new(),
+            game: "Hold'em No Limit".to_string(),
+            cents: false,
+            small_blind: state.small_blind,
+            big_blind: state.big_blind,
+            table: String::new(),
+            button: state.button,
+            players,
+            hero: None,
+            actions,
+            board: state.board.clone(),
+            shown: state.seats.iter().enumerate().map(|(seat, s)| (name(seat), s.hole)).collect(),
+            collected: (0..n).filter(|&seat| winnings[seat] > 0).map(|seat| (name(seat), winnings[seat])).collect(),
+        }
+    }
+}
+
+/// An amount like $1,234.56 or 1500, in cents or in chips
+fn parse_amount(s: &str, cents: bool) -> Result<Chips, &'static str> {
+    let s: String = s.chars().filter(|c| c.is_ascii_digit() || *c == '.').collect();
+    let (whole, fraction) = s.split_once('.').unwrap_or((&s, ""));
+    let whole: Chips = whole.parse().map_err(|_| "Invalid amount")?;
+    if !cents {
+        return Ok(whole);
+    }
+    let fraction: Chips = match fraction.len() {
+        0 => 0,
+        1 => fraction.parse::<Chips>().map_err(|_| "Invalid amount")? * 10,
+        2 => fraction.parse().map_err(|_| "Invalid amount")?,
+        _ => return Err("Invalid amount"),
+    };
+    Ok(whole * 100 + fraction)
+}
+
+/// Every card in square brackets on the line, in order
+fn bracketed_cards(line: &str) -> Result<Vec<Card>, &'static str> {
+    let mut cards = Vec::new();
+    for part in line.split('[').skip(1) {
+        let (inside, _) = part.split_once(']').ok_or("Unclosed [")?;
+        cards.extend(parse_cards(inside)?);
+    }
+    Ok(cards)
+}
+
+fn pair_of(cards: &[Card]) -> Result<(Card, Card), &'static str> {
+    match cards {
+        &[a, b] => Ok((a, b)),
+        _ => Err("Expected two hole cards"),
+    }
+}
+
+/// Something that reads one site's hand histories
+pub trait HistoryParser {
+    fn site(&self) -> &'static str;
+
+    /// Whether the line is the first of a hand in this format
+    fn starts_hand(&self, line: &str) -> bool;
+
+    fn parse_hand(&self, text: &str) -> Result<HandRecord, &'static str>;
+
+    /// Parse a file of hands, one after another
+    fn parse_file(&self, text: &str) -> Result<Vec<HandRecord>, &'static str> {
+        let mut hands: Vec<String> = Vec::new();
+        for line in text.lines() {
+            let line = line.trim_start_matches('\u{feff}');
+            if self.starts_hand(line) {
+                hands.push(String::new());
+            }
+            if let Some(hand) = hands.last_mut() {
+                hand.push_str(line);
+                hand.push('\n');
+            }
+        }
+        hands.iter().map(|hand| self.parse_hand(hand)).collect()
+    }
+}
+
+/// PokerStars hold'em hands, e.g.
+/// PokerStars Hand #123: Hold'em No Limit ($0.01/$0.02 USD) - 2020/01/01 12:00:00 ET
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PokerStars;
+
+impl HistoryParser for PokerStars {
+    fn site(&self) -> &'static str {
+        "PokerStars"
+    }
+
+    fn starts_hand(&self, line: &str) -> bool {
+        line.starts_with("PokerStars Hand #")
+    }
+
+    fn parse_hand(&self, text: &str) -> Result<HandRecord, &'static str> {
+        parse_stars_like(self.site(), "PokerStars Hand #", text)
+    }
+}
+
+/// GGPoker hold'em hands, which are written much like PokerStars ones, e.g.
+/// Poker Hand #HD123: Hold'em No Limit ($0.02/$0.05) - 2021/01/01 12:00:00
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GgPoker;
+
+impl HistoryParser for GgPoker {
+    fn site(&self) -> &'static str {
+        "GGPoker"
+    }
+
+    fn starts_hand(&self, line: &str) -> bool {
+        line.starts_with("Poker Hand #")
+    }
+
+    fn parse_hand(&self, text: &str) -> Result<HandRecord, &'static str> {
+        parse_stars_like(self.site(), "Poker Hand #", text)
+    }
+}
+
+/// The formats there are parsers for
+pub const PARSERS: [&dyn HistoryParser; 2] = [&PokerStars, &GgPoker];
+
+/// Parse a file of hands from whichever site its first line says it's from
+pub fn parse_history(text: &str) -> Result<Vec<HandRecord>, &'static str> {
+    let first = text.lines().map(|line| line.trim_start_matches('\u{feff}').trim()).find(|line| !line.is_empty()).unwrap_or("");
+    PARSERS
+        .iter()
+        .find(|parser| parser.starts_hand(first))
+        .ok_or("Unknown hand history format")?
+        .parse_file(text)
+}
+
+/// Parse one hold'em hand in the PokerStars layout, which starts with this prefix then the hand number
+fn parse_stars_like(site: &str, prefix: &str, text: &str) -> Result<HandRecord, &'static str> {
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+
+    // Hand #123: Hold'em No Limit ($0.01/$0.02 USD) - 2020/01/01 12:00:00 ET
+    let header = lines.next().ok_or("Empty hand history")?;
+    let rest = header.strip_prefix(prefix).ok_or("Hand from another site")?;
+    let (id, rest) = rest.split_once(':').ok_or("Missing hand number")?;
+    let rest = rest.split(" - ").next().unwrap();
+    let (blinds_start, blinds_end) = (rest.rfind('(').ok_or("Missing blinds")?, rest.rfind(')').ok_or("Missing blinds")?);
+    let blinds = &rest[blinds_start + 1..blinds_end];
+    let cents = blinds.contains(['$', '€', '£']);
+    let (small_blind, big_blind) = blinds.split_once('/').ok_or("Missing blinds")?;
+    let big_blind = big_blind.split_whitespace().next().ok_or("Missing blinds")?;
+    let mut hand = HandRecord {
+        site: site.to_string(),
+        id: id.to_string(),
+        game: rest[..blinds_start].trim().to_string(),
+        cents,
+        small_blind: parse_amount(small_blind, cents)?,
+        big_blind: parse_amount(big_blind, cents)?,
+        table: String::new(),
+        button: 0,
+        players: Vec::new(),
+        hero: None,
+        actions: Vec::new(),
+        board: Vec::new(),
+        shown: Vec::new(),
+        collected: Vec::new(),
+    };
+
+    // Table 'Alpha' 6-max Seat #3 is the button
+    let table = lines.next().ok_or("Missing table")?;
+    hand.table = table.split('\'').nth(1).ok_or("Missing table name")?.to_string();
+    let button = table.split("Seat #").nth(1).ok_or("Missing button")?;
+    hand.button = button.split_whitespace().next().unwrap().parse().map_err(|_| "Invalid button seat")?;
+
+    let mut street = Street::Preflop;
+    let mut seats_done = false;
+    for line in lines {
+        if line.starts_with("*** SUMMARY") {
+            break;
+        }
+        if let Some(section) = line.strip_prefix("*** ") {
+            seats_done = true;
+            street = match section.split(" ***").next().unwrap() {
+                "FLOP" => Street::Flop,
+                "TURN" => Street::Turn,
+                "RIVER" => Street::River,
+                _ => street,
+            };
+            if street != Street::Preflop && line.contains('[') {
+                hand.board = bracketed_cards(line)?;
+            }
+            continue;
+        }
+
+        // Seat 1: player1 ($2 in chips)
+        if !seats_done && let Some(seat) = line.strip_prefix("Seat ") {
+            let (number, rest) = seat.split_once(": ").ok_or("Invalid seat")?;
+            let (name, stack) = rest.rsplit_once(" (").ok_or("Invalid seat")?;
+            hand.players.push(PlayerRecord {
+                seat: number.parse().map_err(|_| "Invalid seat number")?,
+                name: name.to_string(),
+                stack: parse_amount(stack.split(" in chips").next().unwrap(), cents)?,
+            });
+            continue;
+        }
+        if let Some(dealt) = line.strip_prefix("Dealt to ") {
+            // GGPoker deals to everyone without saying what
+            if !dealt.contains('[') {
+                continue;
+            }
+            let name = dealt.split(" [").next().unwrap();
+            hand.hero = Some((name.to_string(), pair_of(&bracketed_cards(dealt)?)?));
+            continue;
+        }
+        if let Some(returned) = line.strip_prefix("Uncalled bet (") {
+            let (amount, name) = returned.split_once(") returned to ").ok_or("Invalid uncalled bet")?;
+            let play = Play::Returned(parse_amount(amount, cents)?);
+            hand.actions.push(HistoryAction { street, player: name.to_string(), play, all_in: false });
+            continue;
+        }
+
+        // lines about a player start with their name, the longest one matching if one name starts another
+        let Some(player) = hand.players
+            .iter()
+            .map(|player| player.name.as_str())
+            .filter(|&name| line.starts_with(name))
+            .max_by_key(|name| name.len())
+        else {
+            continue;
+        };
+        let player = player.to_string();
+        let rest = &line[player.len()..];
+        if let Some(collected) = rest.strip_prefix(" collected ") {
+            hand.collected.push((player, parse_amount(collected.split(" from").next().unwrap(), cents)?));
+            continue;
+        }
+        let Some(rest) = rest.strip_prefix(": ") else { continue };
+        let all_in = rest.ends_with("and is all-in");
+        let rest = rest.trim_end_matches(" and is all-in");
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        let play = match words[..] {
+            ["folds", ..] => Play::Fold,
+            ["checks"] => Play::Check,
+            ["calls", amount] => Play::Call(parse_amount(amount, cents)?),
+            ["bets", amount] => Play::Bet(parse_amount(amount, cents)?),
+            ["raises", by, "to", to] => Play::Raise(parse_amount(by, cents)?, parse_amount(to, cents)?),
+            ["posts", "small", "blind", amount] => Play::Post(Post::SmallBlind, parse_amount(amount, cents)?),
+            ["posts", "big", "blind", amount] => Play::Post(Post::BigBlind, parse_amount(amount, cents)?),
+            ["posts", "small", "&", "big", "blinds", amount] => Play::Post(Post::Both, parse_amount(amount, cents)?),
+            ["posts", "the", "ante", amount] => Play::Post(Post::Ante, parse_amount(amount, cents)?),
+            ["shows", ..] => {
+                hand.shown.push((player, pair_of(&bracketed_cards(rest)?)?));
+                continue;
+            }
+            // mucks, doesn't show, chat and so on
+            _ => continue,
+        };
+        hand.actions.push(HistoryAction { street, player, play, all_in });
+    }
+    Ok(hand)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND: &str = "\
+PokerStars Hand #123456789:  Hold'em No Limit ($0.01/$0.02 USD) - 2020/01/01 12:00:00 ET
+Table 'Alpha II' 6-max Seat #3 is the button
+Seat 1: player one ($2 in chips)
+Seat 2: player ($1.50 in chips)
+Seat 3: hero ($2.10 in chips)
+player one: posts small blind $0.01
+player: posts big blind $0.02
+*** HOLE CARDS ***
+Dealt to hero [Ah Kd]
+hero: raises $0.04 to $0.06
+player one: folds
+player: calls $0.04
+*** FLOP *** [2c 7d 9s]
+player: checks
+hero: bets $0.08
+player: calls $0.08
+*** TURN *** [2c 7d 9s] [Th]
+player: checks
+hero: checks
+*** RIVER *** [2c 7d 9s Th] [4c]
+player: bets $1.36 and is all-in
+hero: calls $1.36
+*** SHOW DOWN ***
+player: shows [9c 9d] (three of a kind, Nines)
+hero: mucks hand
+player collected $2.97 from pot
+*** SUMMARY ***
+Total pot $3.01 | Rake $0.04
+Board [2c 7d 9s Th 4c]
+Seat 1: player one (small blind) folded before Flop
+";
+
+    #[test]
+    fn test_parse_pokerstars() {
+        let hand = PokerStars.parse_hand(HAND).unwrap();
+        assert_eq!((hand.id.as_str(), hand.game.as_str(), hand.table.as_str()), ("123456789", "Hold'em No Limit", "Alpha II"));
+        assert_eq!((hand.cents, hand.small_blind, hand.big_blind, hand.button), (true, 1, 2, 3));
+        assert_eq!(hand.players.len(), 3);
+        assert_eq!(hand.player("player one").unwrap().stack, 200);
+        assert_eq!(hand.hero, Some(("hero".to_string(), pair_of(&parse_cards("AhKd").unwrap()).unwrap())));
+        assert_eq!(hand.board, parse_cards("2c7d9sTh4c").unwrap());
+        assert_eq!(hand.actions.len(), 12);
+        assert_eq!(hand.actions[3], HistoryAction { street: Street::Preflop, player: "player one".to_string(), play: Play::Fold, all_in: false });
+        assert_eq!(hand.actions[10].play, Play::Bet(136));
+        assert!(hand.actions[10].all_in);
+        assert_eq!(hand.shown, vec![("player".to_string(), pair_of(&parse_cards("9c9d").unwrap()).unwrap())]);
+
+        assert_eq!(hand.put_in("hero"), 6 + 8 + 136);
+        assert_eq!(hand.put_in("player"), 150);
+        assert_eq!(hand.net("player"), 297 - 150);
+        assert_eq!(hand.net("player one"), -1);
+
+        let hands = parse_history(&format!("{}\n\n\n{}", HAND, HAND.replace("#123456789", "#2"))).unwrap();
+        assert_eq!(hands.len(), 2);
+        assert_eq!(hands[1].id, "2");
+        assert_eq!(parse_amount("1,500", false), Ok(1500));
+        assert_eq!(parse_amount("€1.5", true), Ok(150));
+    }
+
+    #[test]
+    fn test_parse_ggpoker() {
+        let text = "\
+Poker Hand #HD12345: Hold'em No Limit ($0.02/$0.05) - 2021/06/01 20:00:00
+Table 'NLHWhite7' 6-max Seat #1 is the button
+Seat 1: 5f3a2b ($5.00 in chips)
+Seat 2: Hero ($4.90 in chips)
+Hero: posts small blind $0.02
+5f3a2b: posts big blind $0.05
+*** HOLE CARDS ***
+Dealt to 5f3a2b 
+Dealt to Hero [Qs Qd]
+Hero: raises $0.10 to $0.15
+5f3a2b: folds
+Uncalled bet ($0.10) returned to Hero
+Hero collected $0.10 from pot
+*** SHOWDOWN ***
+*** SUMMARY ***
+Total pot $0.10 | Rake $0 | Jackpot $0 | Bingo $0
+";
+        let hands = parse_history(text).unwrap();
+        assert_eq!(hands.len(), 1);
+        let hand = &hands[0];
+        assert_eq!((hand.site.as_str(), hand.id.as_str(), hand.big_blind), ("GGPoker", "HD12345", 5));
+        assert_eq!(hand.hero.as_ref().unwrap().0, "Hero");
+        assert_eq!(hand.put_in("Hero"), 5);
+        assert_eq!(hand.net("Hero"), 5);
+        assert!(PokerStars.parse_hand(text).is_err());
+
+        // and from the engine, where everything nets out
+        let mut state = HandState::new(&[100, 100, 50], 0, 1, 2, &mut rand::rng());
+        for action in [Action::Raise(6), Action::AllIn, Action::Call, Action::Call] {
+            state.act(action).unwrap();
+        }
+        assert!(state.is_over());
+        let hand = HandRecord::from(&state);
+        assert_eq!(hand.actions[0].play, Play::Post(Post::SmallBlind, 1));
+        assert_eq!(hand.actions[3].play, Play::Raise(100 - 6, 100));
+        assert!(hand.actions[3].all_in);
+        assert_eq!(hand.players.iter().map(|player| hand.net(&player.name)).sum::<i64>(), 0);
+        for (seat, player) in hand.players.iter().enumerate() {
+            assert_eq!(hand.put_in(&player.name), state.seats[seat].contributed);
+        }
+        assert_eq!(parse_history("Full Tilt Poker Game #1"), Err("Unknown hand history format"));
+    }
+}
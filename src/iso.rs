@@ -0,0 +1,142 @@
+//! Suit isomorphism: hands that only differ by relabelling suits are equivalent,
+//! so enumerations only need to evaluate one of each and weight it
+
+use crate::card::*;
+use itertools::Itertools;
+
+
+/// Image of each suit, indexed by usize::from(suit)
+pub type SuitPermutation = [usize; 4];
+
+/// All 24 ways to relabel the suits
+pub fn permutations() -> Vec<SuitPermutation> {
+    (0..4)
+        .permutations(4)
+        .map(|p| [p[0], p[1], p[2], p[3]])
+        .collect()
+}
+
+pub fn apply(perm: &SuitPermutation, card: Card) -> Card {
+    Card::new(card.rank, Suit::try_from(perm[usize::from(card.suit)]).unwrap())
+}
+
+fn image(perm: &SuitPermutation, cards: &[Card]) -> Vec<Card> {
+    let mut image: Vec<Card> = cards.iter().map(|&card| apply(perm, card)).collect();
+    image.sort();
+    image
+}
+
+/// Canonical form of a set of cards under suit relabelling:
+/// the smallest sorted image over every permutation
+pub fn canonicalize(cards: &[Card]) -> Vec<Card> {
+    canonicalize_groups(&[cards]).pop().unwrap()
+}
+
+/// Canonical form of several sets relabelled together (e.g. hole cards and board),
+/// so AhKh on Qh7s2s and AsKs on Qs7h2h come out the same
+pub fn canonicalize_groups(groups: &[&[Card]]) -> Vec<Vec<Card>> {
+    permutations()
+        .iter()
+        .map(|perm| groups.iter().map(|group| image(perm, group)).collect::<Vec<_>>())
+        .min()
+        .unwrap()
+}
+
+/// Relabellings that map every group onto itself
+pub fn stabilizer(groups: &[&[Card]]) -> Vec<SuitPermutation> {
+    let sorted: Vec<Vec<Card>> = groups.iter().map(|group| image(&[0, 1, 2, 3], group)).collect();
+    permutations()
+        .into_iter()
+        .filter(|perm| groups.iter().zip(&sorted).all(|(group, sorted)| image(perm, group) == *sorted))
+        .collect()
+}
+
+/// Relabellings that map a set of hole card combos (e.g. a range) onto itself
+pub fn holdings_stabilizer(holdings: &[(Card, Card)]) -> Vec<SuitPermutation> {
+    let normalize = |perm: &SuitPermutation| {
+        let mut image: Vec<Vec<Card>> = holdings.iter().map(|pair| image(perm, &[pair.0, pair.1])).collect();
+        image.sort();
+        image
+    };
+    let sorted = normalize(&[0, 1, 2, 3]);
+    permutations()
+        .into_iter()
+        .filter(|perm| normalize(perm) == sorted)
+        .collect()
+}
+
+/// Some(orbit size) if the sorted cards are the smallest of their images under perms
+/// (so exactly one member of each orbit is kept), None otherwise
+pub fn orbit_weight(cards: &[Card], perms: &[SuitPermutation]) -> Option<usize> {
+    debug_assert!(cards.is_sorted());
+    if perms.len() == 1 {
+        return Some(1);
+    }
+    let mut images: Vec<Vec<Card>> = Vec::with_capacity(perms.len());
+    for perm in perms {
+        let image = image(perm, cards);
+        if image.as_slice() < cards {
+            return None;
+        }
+        images.push(image);
+    }
+    images.sort();
+    images.dedup();
+    Some(images.len())
+}
+
+/// Distinct items up to suit relabelling, each with how many of the inputs it stands for
+pub fn dedupe<T>(items: impl IntoIterator<Item = T>, groups: impl Fn(&T) -> Vec<Vec<Card>>) -> Vec<(T, usize)> {
+    let mut seen: Vec<(Vec<Vec<Card>>, T, usize)> = Vec::new();
+    for item in items {
+        let groups = groups(&item);
+        let refs: Vec<&[Card]> = groups.iter().map(|group| group.as_slice()).collect();
+        let canonical = canonicalize_groups(&refs);
+        match seen.iter_mut().find(|(c, _, _)| *c == canonical) {
+            Some((_, _, count)) => *count += 1,
+            None => seen.push((canonical, item, 1)),
+        }
+    }
+    seen.into_iter().map(|(_, item, count)| (item, count)).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize() {
+        let hearts = [Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::King, Suit::Hearts)];
+        let spades = [Card::new(Rank::King, Suit::Spades), Card::new(Rank::Ace, Suit::Spades)];
+        let offsuit = [Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::King, Suit::Spades)];
+        assert_eq!(canonicalize(&hearts), canonicalize(&spades));
+        assert_ne!(canonicalize(&hearts), canonicalize(&offsuit));
+
+        // 1,326 hole card combos fall into the 169 starting hands
+        let deck = Card::get_deck();
+        let classes = deck.iter()
+            .tuple_combinations()
+            .map(|(&a, &b)| canonicalize(&[a, b]))
+            .unique()
+            .count();
+        assert_eq!(classes, 169);
+    }
+
+    #[test]
+    fn test_orbit_weights() {
+        let pair = [Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::King, Suit::Hearts)];
+        let perms = stabilizer(&[&pair]);
+        assert_eq!(perms.len(), 6);
+
+        // every flop is counted exactly once across the kept representatives
+        let mut deck = Card::get_deck();
+        deck.retain(|card| !pair.contains(card));
+        let total: usize = deck.iter()
+            .copied()
+            .combinations(3)
+            .filter_map(|flop| orbit_weight(&flop, &perms))
+            .sum();
+        assert_eq!(total, 50 * 49 * 48 / 6);
+    }
+}
@@ -0,0 +1,315 @@
+//! Just enough JSON for the files the crate reads and writes, without pulling in a serializer
+
+use std::fmt::{Display, Formatter};
+
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Keys in the order they came
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Json, &'static str> {
+        let mut parser = Parser { text: text.as_bytes(), at: 0, depth: 0 };
+        let value = parser.value()?;
+        parser.space();
+        if parser.at != parser.text.len() {
+            return Err("Trailing characters after JSON");
+        }
+        Ok(value)
+    }
+}
+
+/// Things with a JSON form, e.g. results for the CLI to print with --output json
+pub trait ToJson {
+    fn to_json(&self) -> Json;
+}
+
+impl Json {
+    pub fn object<'a>(fields: impl IntoIterator<Item = (&'a str, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+}
+
+impl From<f64> for Json {
+    fn from(x: f64) -> Self {
+        Json::Number(x)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(x: usize) -> Self {
+        Json::Number(x as f64)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(values: Vec<T>) -> Self {
+        Json::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+/// A string in quotes with JSON escapes
+pub fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl Display for Json {
+    /// Compact, on one line
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(x) if x.is_finite() => write!(f, "{}", x),
+            // JSON has no infinities or NaN
+            Json::Number(_) => write!(f, "null"),
+            Json::String(s) => write!(f, "{}", quote(s)),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    write!(f, "{}{}", if i > 0 { "," } else { "" }, value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    write!(f, "{}{}:{}", if i > 0 { "," } else { "" }, quote(key), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Deepest arrays and objects can nest, so a hostile document is an error rather than a
+/// stack overflow
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    text: &'a [u8],
+    at: usize,
+    /// Arrays and objects open around `at`
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn space(&mut self) {
+        while self.at < self.text.len() && self.text[self.at].is_ascii_whitespace() {
+            self.at += 1;
+        }
+    }
+
+    fn eat(&mut self, c: u8) -> Result<(), &'static str> {
+        self.space();
+        if self.text.get(self.at) == Some(&c) {
+            self.at += 1;
+            Ok(())
+        } else {
+            Err("Invalid JSON")
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, &'static str> {
+        if self.text[self.at..].starts_with(word.as_bytes()) {
+            self.at += word.len();
+            Ok(value)
+        } else {
+            Err("Invalid JSON")
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, &'static str> {
+        self.space();
+        match self.text.get(self.at).ok_or("Unexpected end of JSON")? {
+            b'n' => self.literal("null", Json::Null),
+            b't' => self.literal("true", Json::Bool(true)),
+            b'f' => self.literal("false", Json::Bool(false)),
+            b'"' => Ok(Json::String(self.string()?)),
+            b'[' | b'{' if self.depth == MAX_DEPTH => Err("JSON nested too deeply"),
+            b'[' => {
+                self.depth += 1;
+                self.at += 1;
+                let mut values = Vec::new();
+                self.space();
+                if self.text.get(self.at) == Some(&b']') {
+                    self.at += 1;
+                    self.depth -= 1;
+                    return Ok(Json::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    self.space();
+                    match self.text.get(self.at) {
+                        Some(b',') => self.at += 1,
+                        Some(b']') => {
+                            self.at += 1;
+                            self.depth -= 1;
+                            return Ok(Json::Array(values));
+                        }
+                        _ => return Err("Invalid JSON array"),
+                    }
+                }
+            }
+            b'{' => {
+                self.depth += 1;
+                self.at += 1;
+                let mut fields = Vec::new();
+                self.space();
+                if self.text.get(self.at) == Some(&b'}') {
+                    self.at += 1;
+                    self.depth -= 1;
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    self.space();
+                    let key = self.string()?;
+                    self.eat(b':')?;
+                    fields.push((key, self.value()?));
+                    self.space();
+                    match self.text.get(self.at) {
+                        Some(b',') => self.at += 1,
+                        Some(b'}') => {
+                            self.at += 1;
+                            self.depth -= 1;
+                            return Ok(Json::Object(fields));
+                        }
+                        _ => return Err("Invalid JSON object"),
+                    }
+                }
+            }
+            _ => {
+                let start = self.at;
+                while self.at < self.text.len() && matches!(self.text[self.at], b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') {
+                    self.at += 1;
+                }
+                std::str::from_utf8(&self.text[start..self.at])
+                    .ok()
+                    .and_then(|number| number.parse().ok())
+                    .map(Json::Number)
+                    .ok_or("Invalid JSON number")
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, &'static str> {
+        self.eat(b'"')?;
+        let mut out = Vec::new();
+        loop {
+            let c = *self.text.get(self.at).ok_or("Unterminated JSON string")?;
+            self.at += 1;
+            match c {
+                b'"' => return String::from_utf8(out).map_err(|_| "Invalid UTF-8 in JSON string"),
+                b'\\' => {
+                    let escape = *self.text.get(self.at).ok_or("Unterminated JSON string")?;
+                    self.at += 1;
+                    match escape {
+                        b'n' => out.push(b'\n'),
+                        b't' => out.push(b'\t'),
+                        b'r' => out.push(b'\r'),
+                        b'b' => out.push(8),
+                        b'f' => out.push(12),
+                        b'u' => {
+                            let hex = self.text.get(self.at..self.at + 4).ok_or("Invalid JSON escape")?;
+                            let code = u32::from_str_radix(std::str::from_utf8(hex).map_err(|_| "Invalid JSON escape")?, 16)
+                                .map_err(|_| "Invalid JSON escape")?;
+                            self.at += 4;
+                            let c = char::from_u32(code).unwrap_or('\u{fffd}');
+                            out.extend_from_slice(c.to_string().as_bytes());
+                        }
+                        c => out.push(c),
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json() {
+        let text = r#" {"name": "K♠ \"x\"", "values": [1, -2.5e1, true, null], "empty": {}} "#;
+        let json = Json::parse(text).unwrap();
+        assert_eq!(json.get("name").and_then(Json::as_str), Some("K♠ \"x\""));
+        assert_eq!(json.get("values"), Some(&Json::Array(vec![Json::Number(1.0), Json::Number(-25.0), Json::Bool(true), Json::Null])));
+        assert_eq!(Json::parse(&json.to_string()).unwrap(), json);
+        assert!(Json::parse("{\"a\": }").is_err());
+        assert!(Json::parse("[1] 2").is_err());
+        let built = Json::object([("n", 2usize.into()), ("x", Json::from(None::<f64>)), ("v", vec![0.5, 1.0].into())]);
+        assert_eq!(built.to_string(), r#"{"n":2,"x":null,"v":[0.5,1]}"#);
+        let nested = |depth| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(Json::parse(&nested(MAX_DEPTH)).is_ok());
+        assert!(Json::parse(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(Json::parse(&"[{\"a\":".repeat(100_000)).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+//! Two Plus Two style jump table (the `big-table` feature): a state for every hand of up to six
+//! cards that could still end differently, each with where to go on every next card, so seven
+//! cards score in seven lookups. The table is over a hundred megabytes, so it's generated once,
+//! written to a file and memory-mapped from then on.
+//!
+//! A state is the rank counts (as in Hand) and the ranks held in each suit that could still make
+//! a flush with the cards to come. Hands that can only end up the same share a state, and a suit
+//! that can't make a flush any more is forgotten
+//!
+//! The file starts with the format's version and a checksum of the entries, so a table from an
+//! older version is regenerated rather than misread, and verify catches one that's been corrupted
+
+use crate::card::*;
+use crate::eval::*;
+use crate::hand::*;
+use crate::score::*;
+use memmap2::Mmap;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, fs::{self, File}, io::{self, BufWriter, Write}, path::Path};
+
+/// Entries for each state: the score of the hand so far if it has five or six cards, then one
+/// for each card, going to the next state's first entry (or with six cards, the score)
+const STRIDE: usize = 53;
+const MAGIC: &[u8; 7] = b"pokerjt";
+/// Bumped whenever what the entries mean changes, e.g. how scores are packed. Follows the magic
+const VERSION: u8 = b'2';
+/// The magic and version, the number of entries, then the sha256 of the entries
+const HEADER: usize = 48;
+
+/// The jump table, mapped from its file
+pub struct JumpTable {
+    map: Mmap,
+}
+
+impl JumpTable {
+    /// The table in the file at path, generating it and writing it there first if there's no file
+    pub fn open(path: impl AsRef<Path>) -> io::Result<JumpTable> {
+        let path = path.as_ref();
+        if !path.exists() {
+            JumpTable::write(path)?;
+        }
+        let mut map = JumpTable::map(path)?;
+        if map.get(7) != Some(&VERSION) {
+            // a table from another version could be laid out differently, so it's made again
+            drop(map);
+            JumpTable::write(path)?;
+            map = JumpTable::map(path)?;
+        }
+        let invalid = |e: &str| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+        let len = u64::from_ne_bytes(map[8..16].try_into().unwrap()) as usize;
+        if map.len() != HEADER + 4 * len || !len.is_multiple_of(STRIDE) {
+            return Err(invalid("Jump table is the wrong size"));
+        }
+        Ok(JumpTable { map })
+    }
+
+    /// Generate the table into a file at path. It's written alongside and moved into place, so a
+    /// file at path is always whole
+    fn write(path: &Path) -> io::Result<()> {
+        let partial = path.with_extension("partial");
+        let entries = generate();
+        let bytes: Vec<u8> = entries.iter().flat_map(|entry| entry.to_ne_bytes()).collect();
+        let mut out = BufWriter::new(File::create(&partial)?);
+        out.write_all(MAGIC)?;
+        out.write_all(&[VERSION])?;
+        out.write_all(&(entries.len() as u64).to_ne_bytes())?;
+        out.write_all(&Sha256::digest(&bytes))?;
+        out.write_all(&bytes)?;
+        out.into_inner()?.sync_all()?;
+        fs::rename(&partial, path)
+    }
+
+    /// Map the file at path, checking it's a jump table of some version
+    fn map(path: &Path) -> io::Result<Mmap> {
+        // SAFETY: the file is only ever replaced whole, never written in place
+        let map = unsafe { Mmap::map(&File::open(path)?)? };
+        if map.len() < HEADER || &map[..7] != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a jump table"));
+        }
+        Ok(map)
+    }
+
+    /// Check the entries against the checksum, then score a sample of hands both through the
+    /// table and from scratch, with the error saying what's wrong if anything is
+    pub fn verify(&self) -> Result<(), String> {
+        if Sha256::digest(&self.map[HEADER..])[..] != self.map[16..HEADER] {
+            return Err("Jump table doesn't match its checksum".to_string());
+        }
+        let mut deck = Card::get_deck();
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10_000 {
+            deck.shuffle(&mut rng);
+            let cards = &deck[..7];
+            for n in 5..=7 {
+                let expected = get_best_score(&(cards[0], cards[1]), &cards[2..n]);
+                if self.score(&cards[..n]) != expected {
+                    return Err(format!("Jump table misscores {}", format_cards(&cards[..n])));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn entries(&self) -> &[u32] {
+        let bytes = &self.map[HEADER..];
+        // SAFETY: maps start on a page, so after the header the entries are aligned, and every
+        // bit pattern is a u32
+        unsafe { std::slice::from_raw_parts(bytes.as_ptr().cast::<u32>(), bytes.len() / 4) }
+    }
+
+    /// Score of seven cards, one lookup for each
+    pub fn score_seven(&self, cards: &[Card; 7]) -> Score {
+        let entries = self.entries();
+        let mut at = 0;
+        for &card in cards {
+            at = entries[at + 1 + usize::from(card)] as usize;
+        }
+        Score::from_bits(at as u16)
+    }
+
+    /// Score of five to seven cards
+    pub fn score(&self, cards: &[Card]) -> Score {
+        assert!((5..=7).contains(&cards.len()), "five to seven cards");
+        if let Ok(cards) = cards.try_into() {
+            return self.score_seven(cards);
+        }
+        let entries = self.entries();
+        let mut at = 0;
+        for &card in cards {
+            at = entries[at + 1 + usize::from(card)] as usize;
+        }
+        Score::from_bits(entries[at] as u16)
+    }
+}
+
+/// Every state's entries, starting with the state of no cards
+pub fn generate() -> Vec<u32> {
+    let mut levels: Vec<Vec<u128>> = vec![vec![0]];
+    let mut ids: HashMap<u128, usize> = HashMap::from([(0, 0)]);
+    for n in 0..6 {
+        let mut next = Vec::new();
+        for &state in &levels[n] {
+            for card in 0..52 {
+                if let Some(after) = deal(state, n, card) && !ids.contains_key(&after) {
+                    ids.insert(after, ids.len());
+                    next.push(after);
+                }
+            }
+        }
+        levels.push(next);
+    }
+
+    let mut entries = vec![0; ids.len() * STRIDE];
+    for (n, level) in levels.iter().enumerate() {
+        for &state in level {
+            let at = ids[&state] * STRIDE;
+            if n >= 5 {
+                entries[at] = u32::from(score_state(state).to_bits());
+            }
+            for card in 0..52 {
+                if let Some(after) = deal(state, n, card) {
+                    entries[at + 1 + card] = match n {
+                        6 => u32::from(score_state(after).to_bits()),
+                        _ => (ids[&after] * STRIDE) as u32,
+                    };
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// States are rank counts, 3 bits a rank as in Hand, then from this bit on 13 bits of ranks for
+/// each suit that could still make a flush
+const SUITS_FROM: usize = 64;
+
+fn suit_ranks(state: u128, suit: usize) -> u128 {
+    (state >> (SUITS_FROM + 13 * suit)) & 0x1fff
+}
+
+/// The state after dealing a card (as an index, see Card) to one with n cards, if it could be dealt
+fn deal(state: u128, n: usize, card: usize) -> Option<u128> {
+    let (rank, suit) = (card / 4, card % 4);
+    if (state >> (3 * rank)) & 0b111 == 4 || suit_ranks(state, suit) & (1 << rank) != 0 {
+        return None;
+    }
+    let mut after = state + (1 << (3 * rank));
+    // with two cards or fewer every suit could still make a flush, even one with no cards yet
+    if n <= 2 || suit_ranks(state, suit) != 0 {
+        after |= 1 << (SUITS_FROM + 13 * suit + rank);
+    }
+    let to_come = 7 - (n + 1);
+    for suit in 0..4 {
+        if suit_ranks(after, suit).count_ones() as usize + to_come < 5 {
+            after &= !(0x1fff << (SUITS_FROM + 13 * suit));
+        }
+    }
+    Some(after)
+}
+
+/// Score of a state with five to seven cards, from cards that make it: the suits still known
+/// as they are, and the rest spread over the forgotten suits so none of them has a flush
+fn score_state(state: u128) -> Score {
+    let mut cards = Vec::new();
+    let mut in_suit = [0; 4];
+    for (suit, count) in in_suit.iter_mut().enumerate() {
+        for rank in 0..13 {
+            if suit_ranks(state, suit) & (1 << rank) != 0 {
+                cards.push(Card::new(Rank::try_from(rank).unwrap(), Suit::try_from(suit).unwrap()));
+                *count += 1;
+            }
+        }
+    }
+    let forgotten: Vec<usize> = (0..4).filter(|&suit| suit_ranks(state, suit) == 0).collect();
+    for rank in 0..13 {
+        let known = (0..4).filter(|&suit| suit_ranks(state, suit) & (1 << rank) != 0).count();
+        let mut used = Vec::new();
+        for _ in known..((state >> (3 * rank)) & 0b111) as usize {
+            let &suit = forgotten
+                .iter()
+                .filter(|suit| !used.contains(*suit))
+                .min_by_key(|&&suit| in_suit[suit])
+                .expect("a forgotten suit for every card not in a known one");
+            used.push(suit);
+            in_suit[suit] += 1;
+            cards.push(Card::new(Rank::try_from(rank).unwrap(), Suit::try_from(suit).unwrap()));
+        }
+    }
+    SCORES[&Hand::new(&cards).best_five()]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jump_table() {
+        let path = std::env::temp_dir().join(format!("poker-jump-table-{}", std::process::id()));
+        let table = JumpTable::open(&path).unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut deck = Card::get_deck();
+        for _ in 0..20_000 {
+            deck.shuffle(&mut rng);
+            let pair = (deck[0], deck[1]);
+            for n in 5..=7 {
+                assert_eq!(table.score(&deck[..n]), get_best_score(&pair, &deck[2..n]), "{}", format_cards(&deck[..n]));
+            }
+        }
+        // opening again maps the file that's there
+        assert_eq!(JumpTable::open(&path).unwrap().entries().len(), table.entries().len());
+        assert_eq!(table.verify(), Ok(()));
+
+        // a flipped bit is caught
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        assert!(JumpTable::open(&path).unwrap().verify().is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
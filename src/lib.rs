@@ -0,0 +1,87 @@
+//! Poker hand evaluation and analysis. Everything but the card types and the table-free
+//! evaluator in direct needs the `std` feature, on by default
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+/// Modules that need std
+macro_rules! with_std {
+    ($($item:item)*) => { $(#[cfg(feature = "std")] $item)* };
+}
+
+pub mod card;
+pub mod direct;
+
+with_std! {
+    pub mod cancel;
+    pub mod hand;
+    pub mod eval;
+    pub mod score;
+    pub mod strength;
+    pub mod bundle;
+    pub mod class;
+    pub mod preflop;
+    pub mod matchup;
+    pub mod store;
+    pub mod json;
+    pub mod iso;
+    pub mod texture;
+    pub mod category;
+    pub mod transition;
+    pub mod flops;
+    pub mod cluster;
+    pub mod monte_carlo;
+    pub mod low;
+    pub mod omaha;
+    pub mod short_deck;
+    pub mod stud;
+    pub mod draw;
+    pub mod pineapple;
+    pub mod double_board;
+    pub mod wild;
+    pub mod game;
+    pub mod pots;
+    pub mod showdown;
+    pub mod profile;
+    pub mod street;
+    pub mod position;
+    pub mod table;
+    pub mod strategy;
+    pub mod chaos;
+    pub mod explorer;
+    pub mod history;
+    pub mod all_in;
+    pub mod stats;
+    pub mod odds;
+    pub mod decide;
+    pub mod lines;
+    pub mod realization;
+    pub mod replay;
+    pub mod push_fold;
+    pub mod solver;
+    pub mod tree;
+    pub mod bucket;
+    pub mod chart;
+    pub mod grid;
+    pub mod combos;
+    pub mod combinatorics;
+    pub mod distribution;
+    pub mod range;
+    pub mod library;
+    pub mod narrow;
+    pub mod scenario;
+    pub mod batch;
+    pub mod web;
+    pub mod serve;
+    pub mod acpc;
+    pub mod eval_batch;
+    pub mod reference;
+    #[cfg(feature = "serde")]
+    pub mod serialize;
+    #[cfg(feature = "gpu")]
+    pub mod gpu;
+    #[cfg(feature = "big-table")]
+    pub mod jump_table;
+    #[cfg(feature = "proptest")]
+    pub mod arbitrary;
+}
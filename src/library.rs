@@ -0,0 +1,77 @@
+//! Named ranges kept in a text file between sessions, one `name: range` a line, e.g.
+//! `btn open: 22+,A2s+,K9s+,ATo+`
+
+use crate::range::*;
+use crate::store::*;
+use std::{collections::BTreeMap, fs, io, path::{Path, PathBuf}};
+
+
+/// The saved ranges, by name
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct RangeLibrary {
+    pub ranges: BTreeMap<String, HandRange>,
+}
+
+impl RangeLibrary {
+    /// ranges in the store directory
+    pub fn default_path() -> PathBuf {
+        Store::default_dir().join("ranges")
+    }
+
+    pub fn parse(text: &str) -> Result<RangeLibrary, String> {
+        let mut library = RangeLibrary::default();
+        for (i, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let invalid = |e: &str| format!("line {}: {}", i + 1, e);
+            let (name, range) = line.split_once(':').ok_or_else(|| invalid("expected name: range"))?;
+            let range = if range.trim().is_empty() { HandRange::new() } else { range.trim().parse().map_err(invalid)? };
+            library.ranges.insert(name.trim().to_string(), range);
+        }
+        Ok(library)
+    }
+
+    /// An empty library if there's no file yet
+    pub fn load(path: impl AsRef<Path>) -> io::Result<RangeLibrary> {
+        match fs::read_to_string(path) {
+            Ok(text) => RangeLibrary::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(RangeLibrary::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        if let Some(dir) = path.as_ref().parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_string())
+    }
+}
+
+impl std::fmt::Display for RangeLibrary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, range) in &self.ranges {
+            writeln!(f, "{}: {}", name, range)?;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library() {
+        let library = RangeLibrary::parse("btn open: 22+,A2s+,ATo+\n\nempty:\n").unwrap();
+        assert_eq!(library.ranges.len(), 2);
+        assert!(library.ranges["empty"].is_empty());
+        assert_eq!(RangeLibrary::parse(&library.to_string()).unwrap(), library);
+        assert!(RangeLibrary::parse("no colon").is_err());
+
+        let path = std::env::temp_dir().join(format!("poker-library-test-{}", std::process::id())).join("ranges");
+        assert_eq!(RangeLibrary::load(&path).unwrap(), RangeLibrary::default());
+        library.save(&path).unwrap();
+        assert_eq!(RangeLibrary::load(&path).unwrap(), library);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,181 @@
+//! Which line is better, without a solver: the EV of checking it down, betting a fraction of
+//! the pot, or jamming, against a range that defends just enough not to be exploited by any
+//! two cards. Facing a bet the range continues with its best hands by equity against a random
+//! hand, up to the minimum defense frequency, and calls; nobody puts in more after that.
+//! Equities are exact over every runout, so spots from the flop on are the quick ones
+
+use crate::card::*;
+use crate::json::*;
+use crate::odds::*;
+use crate::range::*;
+use crate::strength::*;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+
+/// What the hero does, first to act or checked to
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Line {
+    /// Check and check down the rest
+    Check,
+    /// Bet this fraction of the pot, or everything behind if that's less
+    Bet(f64),
+    /// Everything behind
+    Jam,
+}
+
+impl Display for Line {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Line::Check => write!(f, "check"),
+            Line::Bet(fraction) => write!(f, "bet {}%", fraction * 100.0),
+            Line::Jam => write!(f, "jam"),
+        }
+    }
+}
+
+impl FromStr for Line {
+    type Err = &'static str;
+
+    /// check, jam, or bet then a percentage of the pot, e.g. bet75
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "check" => Ok(Line::Check),
+            "jam" => Ok(Line::Jam),
+            _ => {
+                let percent: f64 = s
+                    .strip_prefix("bet")
+                    .and_then(|percent| percent.trim_end_matches('%').parse().ok())
+                    .ok_or("Expected check, jam or bet then a percentage, e.g. bet75")?;
+                if percent > 0.0 { Ok(Line::Bet(percent / 100.0)) } else { Err("A bet has to be more than nothing") }
+            }
+        }
+    }
+}
+
+/// The hero's hand against a range on a board from the flop on, with the pot and what's behind
+#[derive(Debug, PartialEq, Clone)]
+pub struct Spot {
+    pub pair: (Card, Card),
+    pub range: HandRange,
+    pub board: Vec<Card>,
+    pub pot: f64,
+    /// Effective stack, the most either can still put in
+    pub stack: f64,
+}
+
+/// How a line works out
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LineEv {
+    pub line: Line,
+    /// Chips put in by the line
+    pub amount: f64,
+    /// How often the range folds to it
+    pub fold: f64,
+    /// Equity at showdown, against whatever of the range gets there
+    pub equity: f64,
+    /// Chips won on average from here, against checking and folding anything
+    pub ev: f64,
+}
+
+impl Display for LineEv {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:<10} {:>+9.2}  folds {:>5.1}%  equity {:>5.1}%", self.line.to_string(), self.ev, self.fold * 100.0, self.equity * 100.0)
+    }
+}
+
+impl ToJson for LineEv {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("line", self.line.to_string().into()),
+            ("amount", self.amount.into()),
+            ("fold", self.fold.into()),
+            ("equity", self.equity.into()),
+            ("ev", self.ev.into()),
+        ])
+    }
+}
+
+impl Spot {
+    /// The EV of each line, in the order given
+    pub fn compare(&self, lines: &[Line]) -> Vec<LineEv> {
+        debug_assert!((3..=5).contains(&self.board.len()));
+        let range = self.range.without(&[&self.board[..], &[self.pair.0, self.pair.1]].concat());
+        assert!(!range.is_empty(), "every combo in the range is blocked");
+
+        // the range best first, by equity against a random hand
+        let mut ranked: Vec<((Card, Card), f64, f64)> = equities_vs_random(&self.board, &[self.pair.0, self.pair.1])
+            .into_iter()
+            .filter(|(pair, _)| range.contains(pair))
+            .map(|(pair, equity)| (pair, range.weight(&pair), equity))
+            .collect();
+        ranked.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+        lines.iter().map(|&line| self.line_ev(line, &range, &ranked)).collect()
+    }
+
+    pub fn ev(&self, line: Line) -> LineEv {
+        self.compare(&[line])[0]
+    }
+
+    fn line_ev(&self, line: Line, range: &HandRange, ranked: &[((Card, Card), f64, f64)]) -> LineEv {
+        let amount = match line {
+            Line::Check => 0.0,
+            Line::Bet(fraction) => (fraction * self.pot).min(self.stack),
+            Line::Jam => self.stack,
+        };
+        if amount <= 0.0 {
+            let equity = equity_vs_range(&self.pair, range, &self.board, &[]);
+            return LineEv { line, amount: 0.0, fold: 0.0, equity, ev: equity * self.pot };
+        }
+
+        // the best of the range up to the minimum defense, the combo at the edge in part
+        let mut left = minimum_defense(self.pot, amount) * range.total_weight();
+        let mut calling = HandRange::new();
+        for &(pair, weight, _) in ranked {
+            if left <= 0.0 {
+                break;
+            }
+            calling.insert(pair, weight.min(left));
+            left -= weight;
+        }
+        let fold = 1.0 - calling.total_weight() / range.total_weight();
+        let equity = equity_vs_range(&self.pair, &calling, &self.board, &[]);
+        let ev = fold * self.pot + (1.0 - fold) * call_ev(self.pot, amount, equity);
+        LineEv { line, amount, fold, equity, ev }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        assert_eq!("bet75".parse(), Ok(Line::Bet(0.75)));
+        assert_eq!("jam".parse(), Ok(Line::Jam));
+        assert!("bet0".parse::<Line>().is_err() && "raise".parse::<Line>().is_err());
+
+        // the nuts on the river wants as much in as it can get, and the range always defends
+        // against it as often as the price says
+        let board = parse_cards("AsKsQs7h2d").unwrap();
+        let spot = Spot {
+            pair: (Card::new(Rank::Jack, Suit::Spades), Card::new(Rank::Ten, Suit::Spades)),
+            range: HandRange::full(),
+            board,
+            pot: 100.0,
+            stack: 300.0,
+        };
+        let evs = spot.compare(&[Line::Check, Line::Bet(0.5), Line::Jam]);
+        assert_eq!(evs[0].ev, 100.0);
+        assert!((evs[1].fold - 1.0 / 3.0).abs() < 1e-9 && (evs[2].fold - 0.75).abs() < 1e-9);
+        assert!(evs[0].ev < evs[1].ev && evs[1].ev < evs[2].ev);
+        assert!((evs[2].ev - (0.75 * 100.0 + 0.25 * 400.0)).abs() < 1e-9);
+
+        // a bluff catcher can't bet for value into a range that only calls with better
+        let spot = Spot { pair: (Card::new(Rank::Seven, Suit::Diamonds), Card::new(Rank::Six, Suit::Diamonds)), ..spot };
+        let evs = spot.compare(&[Line::Check, Line::Jam]);
+        assert!(evs[1].equity < evs[0].equity);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::card::*;
+use crate::hand::*;
+use std::{fmt::{Display, Formatter}, sync::LazyLock};
+use itertools::Itertools;
+
+
+/// An ace-to-five low: five cards of different ranks with aces low, straights and flushes not
+/// counting against it. Ordered so the lower (better) low compares less, by the highest card
+/// first, so 6-4-3-2-A beats 6-5-3-2-A and 5-4-3-2-A (the wheel) is the best there is
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Low([u8; 5]);
+
+impl Low {
+    /// Ace is 1, then 2-13 for two to king
+    fn value(rank: Rank) -> u8 {
+        match rank {
+            Rank::Ace => 1,
+            _ => usize::from(rank) as u8 + 2,
+        }
+    }
+
+    /// The low the five cards make, if their ranks are all different
+    pub fn of(cards: &[Card]) -> Option<Low> {
+        debug_assert_eq!(cards.len(), 5);
+        let mut values: [u8; 5] = std::array::from_fn(|i| Low::value(cards[i].rank));
+        values.sort_unstable_by(|a, b| b.cmp(a));
+        values.windows(2).all(|pair| pair[0] != pair[1]).then_some(Low(values))
+    }
+
+    /// The low the five cards make if it qualifies for half the pot in an eight-or-better game
+    pub fn eight_or_better(cards: &[Card]) -> Option<Low> {
+        Low::of(cards).filter(|low| low.0[0] <= 8)
+    }
+}
+
+impl Display for Low {
+    /// e.g. 8-6-4-3-A
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let symbols: Vec<String> = self.0
+            .iter()
+            .map(|&value| match value {
+                1 => Rank::Ace.symbol().to_string(),
+                _ => Rank::try_from(value as usize - 2).unwrap().symbol().to_string(),
+            })
+            .collect();
+        write!(f, "{}", symbols.join("-"))
+    }
+}
+
+static LOW_SCORES: LazyLock<HandMap<u16>> = LazyLock::new(create_low_table);
+static DEUCE_SEVEN_SCORES: LazyLock<HandMap<u16>> = LazyLock::new(create_deuce_seven_table);
+
+/// An ace-to-five low with pairs allowed to play, as razz is scored: lower is better, and any
+/// unpaired low beats any pair. Unpaired lows compare the same as Low
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct LowScore(u16);
+
+impl LowScore {
+    /// Distinct lows, from the wheel to quad kings with a queen
+    pub const COUNT: usize = 6175;
+
+    /// Score of exactly five cards
+    pub fn of(cards: &[Card]) -> LowScore {
+        debug_assert_eq!(cards.len(), 5);
+        LowScore(LOW_SCORES[&Hand::new(cards).ranks_only()])
+    }
+
+    /// Best low from any five of the cards
+    pub fn best(cards: &[Card]) -> LowScore {
+        cards.iter().copied().combinations(5).map(|cards| LowScore::of(&cards)).min().unwrap()
+    }
+
+    /// Position among the distinct lows, 0 for the wheel
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A deuce-to-seven low: lower is better, the ace is always high and straights, flushes and pairs
+/// count against you, so 7-5-4-3-2 not all one suit is the best and a royal flush the worst
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct DeuceSeven(u16);
+
+impl DeuceSeven {
+    /// Distinct hands, the same as for high
+    pub const COUNT: usize = 7462;
+
+    /// Score of exactly five cards
+    pub fn of(cards: &[Card]) -> DeuceSeven {
+        debug_assert_eq!(cards.len(), 5);
+        DeuceSeven(DEUCE_SEVEN_SCORES[&Hand::new(cards)])
+    }
+
+    /// Position among the distinct hands, 0 for 7-5-4-3-2
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_low() {
+        let low = |s: &str| Low::eight_or_better(&parse_cards(s).unwrap());
+        let wheel = low("5h4h3h2hAh").unwrap();
+        assert_eq!(wheel.to_string(), "5-4-3-2-A");
+        assert!(wheel < low("6c4d3s2hAc").unwrap());
+        // the highest card decides first
+        assert!(low("7c4d3s2hAc").unwrap() < low("8c3d2s4hAc").unwrap());
+        assert!(low("8c6d5s4h3c").unwrap() > low("8c6d5s4hAc").unwrap());
+        assert_eq!(low("9c4d3s2hAc"), None);
+        assert_eq!(low("5c5d3s2hAc"), None);
+    }
+
+    #[test]
+    fn test_low_score() {
+        let score = |s: &str| LowScore::of(&parse_cards(s).unwrap());
+        assert_eq!(score("5h4h3h2hAh").index(), 0);
+        assert!(score("8c6d5s4h3c") > score("8c6d5s4hAc"));
+        assert!(score("KcQdJsTh8c") < score("AcAd2s3h4c"));
+        assert_eq!(score("KcKdKsKhQc").index(), LowScore::COUNT - 1);
+        // seven cards play the best five, dodging the pair
+        assert_eq!(LowScore::best(&parse_cards("2c2d3s4h5cKc9d").unwrap()), score("2c3s4h5c9d"));
+
+        // in line with Low
+        let hands: Vec<Vec<Card>> = ["7c4d3s2hAc", "8c3d2s4hAc", "6c4d3s2hAc", "8c6d5s4h3c"].iter().map(|s| parse_cards(s).unwrap()).collect();
+        for (a, b) in hands.iter().tuple_combinations() {
+            assert_eq!(LowScore::of(a).cmp(&LowScore::of(b)), Low::of(a).cmp(&Low::of(b)));
+        }
+    }
+
+    #[test]
+    fn test_deuce_seven() {
+        let score = |s: &str| DeuceSeven::of(&parse_cards(s).unwrap());
+        assert_eq!(score("7h5d4c3s2h").index(), 0);
+        assert!(score("7h6d4c3s2h") < score("8h5d4c3s2h"));
+        // the flush and the straight cost it
+        assert!(score("8h6d4c3s2h") < score("7h5h4h3h2h"));
+        assert!(score("KhQdJcTs8h") < score("6h5d4c3s2h"));
+        assert_eq!(score("AhKhQhJhTh").index(), DeuceSeven::COUNT - 1);
+    }
+}
@@ -1,109 +1,1617 @@
-mod card;
-mod hand;
-use card::*;
+use poker::cancel::*;
+use poker::card::*;
+use poker::hand::*;
+use poker::eval::*;
+use poker::preflop::*;
+use poker::class::*;
+use poker::matchup::*;
+use poker::store::*;
+use poker::flops::*;
+use poker::category::*;
+use poker::cluster;
+use poker::monte_carlo::*;
+use poker::omaha::*;
+use poker::short_deck::*;
+use poker::stud::*;
+use poker::draw::*;
+use poker::pineapple::*;
+use poker::double_board::*;
+use poker::wild::*;
+use poker::game::*;
+use poker::street::*;
+use poker::table::*;
+use poker::strategy::*;
+use poker::explorer::*;
+use poker::library::*;
+use poker::history::*;
+use poker::all_in::*;
+use poker::showdown::*;
+use poker::stats::*;
+use poker::odds::*;
+use poker::decide::*;
+use poker::lines::*;
+use poker::realization::*;
+use poker::position::*;
+use poker::push_fold::*;
+use poker::solver::*;
+use poker::bucket::*;
+use poker::profile::*;
+use poker::grid::*;
+use poker::combos::*;
+use poker::distribution::*;
+use poker::chart::*;
+use poker::json::*;
+use poker::range::*;
+use poker::scenario::*;
+use poker::batch::*;
+use poker::serve::*;
+use poker::acpc::*;
+use poker::reference::*;
+#[cfg(feature = "gpu")]
+use poker::gpu::*;
+use std::{io::BufRead, net::TcpListener, time::Duration};
+use clap::{Parser, Subcommand};
 use itertools::Itertools;
-use hand::*;
+use indicatif::ProgressBar;
 
-use std::{collections::HashMap, sync::LazyLock, time::Instant};
-use rand::{seq::IteratorRandom, rng};
+#[derive(Parser)]
+#[command(about = "Poker assistance algorithm, calculate chance of winning with given hand")]
+struct Cli {
+    /// Execution profile: latency, throughput or low-power (overrides the config file's)
+    #[arg(long, global = true)]
+    profile: Option<Profile>,
+    /// Print results as text, or as json for scripts (for commands with a result to print)
+    #[arg(long, global = true, default_value = "text")]
+    output: Output,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
 
-static SCORES: LazyLock<HashMap<Hand, u64>> = LazyLock::new(|| hand::create_score_table().0);
+#[derive(Subcommand)]
+enum Command {
+    /// Print heads-up equity against a random hand for all 169 starting hands
+    Preflop {
+        /// Re-estimate each hand with this many monte carlo samples instead of using the table
+        #[arg(long)]
+        samples: Option<usize>,
+    },
+    /// Exact heads-up equity of one starting hand against another, e.g. `matchup AKs QQ`
+    Matchup {
+        hero: HandClass,
+        villain: HandClass,
+    },
+    /// Unexploitable push/fold charts for the small blind against the big blind, as how often
+    /// each hand pushes and calls
+    PushFold {
+        /// The small blind's stack, in big blinds
+        #[arg(long, default_value_t = 10.0)]
+        stack: f64,
+        /// The big blind's stack, by default the same
+        #[arg(long)]
+        villain_stack: Option<f64>,
+        #[arg(long, default_value_t = 0.5)]
+        small_blind: f64,
+        /// From each player
+        #[arg(long, default_value_t = 0.0)]
+        ante: f64,
+        /// Use ICM with these payouts from first place, e.g. 50,30,20, rather than chips
+        #[arg(long, value_delimiter = ',')]
+        payouts: Vec<f64>,
+        /// Stacks of the other players left, for ICM
+        #[arg(long, value_delimiter = ',')]
+        others: Vec<f64>,
+        /// Monte carlo samples behind each matchup, computed once and stored
+        #[arg(long, default_value_t = 2000)]
+        samples: usize,
+        /// Rounds of fictitious play
+        #[arg(long, default_value_t = 1000)]
+        rounds: usize,
+        /// Also save the charts as a strategy file, which `play --chart` can use
+        #[arg(long)]
+        save: Option<String>,
+    },
+    /// Solve a toy poker game with counterfactual regret minimization, printing the strategy
+    /// for every information set
+    Solve {
+        /// kuhn or leduc
+        #[arg(default_value = "kuhn")]
+        game: ToyGame,
+        #[arg(long, default_value_t = 10_000)]
+        iterations: usize,
+        /// Deal one random outcome each iteration rather than walking every deal
+        #[arg(long)]
+        sampled: bool,
+        /// Also save the strategy to this file
+        #[arg(long)]
+        save: Option<String>,
+    },
+    /// Bucket every hand on a board by how its equity against a random hand spreads over the
+    /// runouts, as the solver's hand abstraction would
+    Buckets {
+        board: String,
+        #[arg(long, default_value_t = 8)]
+        k: usize,
+        #[arg(long, default_value_t = 10)]
+        bins: usize,
+        /// Random runouts to the river, when there are more
+        #[arg(long, default_value_t = 50)]
+        runouts: usize,
+    },
+    /// Pot odds, equity needed to call, minimum defense and bluff break-even for a bet
+    Odds {
+        /// Pot before the bet
+        pot: f64,
+        bet: f64,
+        /// Equity of the hand facing the bet, 0-1, to work out what calling wins
+        #[arg(long)]
+        equity: Option<f64>,
+    },
+    /// EV of checking down, betting and jamming against a range that defends as often as the price says
+    Lines {
+        /// Hole cards, e.g. AhKh
+        hand: String,
+        /// Board cards from the flop on, e.g. 2c7d9s
+        board: String,
+        #[arg(long)]
+        pot: f64,
+        /// Effective stack behind
+        #[arg(long)]
+        stack: f64,
+        /// Lines to compare: check, jam, or bet then a percentage of the pot
+        #[arg(long, value_delimiter = ',', default_value = "check,bet33,bet75,jam")]
+        lines: Vec<Line>,
+        /// Villain's range, e.g. TT+,AKs,AQo:0.5, or any two cards
+        #[arg(long)]
+        range: Option<HandRange>,
+    },
+    /// How much of its equity a hand realizes playing out against simple betting, against checking down
+    Realization {
+        /// Hole cards, e.g. AhKh
+        hand: String,
+        /// Board cards, e.g. 2c7d9s, or none to deal the flop
+        #[arg(default_value = "")]
+        board: String,
+        #[arg(long, default_value_t = 10.0)]
+        pot: f64,
+        /// Effective stack behind
+        #[arg(long, default_value_t = 100.0)]
+        stack: f64,
+        /// Play the hand out of position
+        #[arg(long)]
+        oop: bool,
+        /// Villain's range, e.g. TT+,AKs,AQo:0.5, or any two cards
+        #[arg(long)]
+        range: Option<HandRange>,
+        #[arg(long, default_value_t = 1000)]
+        samples: usize,
+        /// A file of realization factors to compare with, by position and hand class
+        #[arg(long, requires = "position")]
+        factors: Option<String>,
+        /// The position to look the factor up for
+        #[arg(long)]
+        position: Option<Position>,
+    },
+    /// Draw a range, or preflop equities, as the 13x13 grid. With a board, each starting hand's
+    /// equity against the range on it instead
+    Grid {
+        /// The range, e.g. TT+,AKs,AQo:0.5
+        #[arg(required_unless_present_any = ["equity", "chart"])]
+        range: Option<HandRange>,
+        /// Show each class's preflop equity instead
+        #[arg(long, conflicts_with_all = ["range", "chart"])]
+        equity: bool,
+        /// Show each class's equity against the range on this board (from the flop on), e.g. Ah7d2c
+        #[arg(long, conflicts_with = "equity")]
+        board: Option<String>,
+        /// Read the range from a chart instead, CSV or JSON of hand to frequency
+        #[arg(long, conflicts_with = "range")]
+        chart: Option<String>,
+        /// The chart's column or key for the action to show, if it has more than one
+        #[arg(long, requires = "chart")]
+        action: Option<String>,
+        /// Without colours
+        #[arg(long)]
+        plain: bool,
+        /// Also write the grid as an HTML page
+        #[arg(long)]
+        html: Option<String>,
+        /// Also write the grid as an SVG image
+        #[arg(long)]
+        svg: Option<String>,
+    },
+    /// How many combos of a range make each hand and draw on a board
+    Combos {
+        /// Board cards from the flop on, e.g. Kh9h4c
+        board: String,
+        /// The range, e.g. TT+,AKs,AQo:0.5, or any two cards
+        #[arg(long)]
+        range: Option<HandRange>,
+        /// Cards held, to show how many of the range's combos they block, e.g. Ah
+        #[arg(long)]
+        holding: Option<String>,
+    },
+    /// Equity against each combo of a range as a histogram, not just the average
+    Distribution {
+        /// Hole cards, e.g. AhKh
+        hand: String,
+        /// Board cards from the flop on, e.g. 2c7d9s
+        board: String,
+        /// Villain's range, e.g. TT+,AKs,AQo:0.5, or any two cards
+        #[arg(long)]
+        range: Option<HandRange>,
+        #[arg(long, default_value_t = 10)]
+        bins: usize,
+    },
+    /// Equities for many spots read from a CSV or JSON lines file (hand, board, range, opponents),
+    /// written to another in the same format
+    Batch {
+        input: String,
+        /// Where the results go, as JSON lines if it ends in .jsonl and CSV otherwise
+        results: String,
+        /// Stop each spot once the interval is within ± this many percent
+        #[arg(long, default_value_t = 0.5)]
+        precision: f64,
+        #[arg(long, default_value_t = 1_000_000)]
+        max_samples: usize,
+        /// Deal exactly this many samples for each spot instead of stopping at the precision
+        #[arg(long)]
+        samples: Option<usize>,
+    },
+    /// Answer equity and range requests as JSON over HTTP (POST /equity, POST /range), streaming
+    /// equity as it converges over a WebSocket at /equity/stream
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: String,
+    },
+    /// Play a match with a bot against an ACPC dealer, e.g. localhost:18791
+    Acpc {
+        dealer: String,
+        #[arg(long, default_value_t = Bot::Ehs)]
+        bot: Bot,
+        /// The dealer's game, which the protocol doesn't say: players, stacks and blinds
+        #[arg(long, default_value_t = 2)]
+        players: usize,
+        #[arg(long, default_value_t = 20000)]
+        stack: Chips,
+        #[arg(long, default_value_t = 50)]
+        small_blind: Chips,
+        #[arg(long, default_value_t = 100)]
+        big_blind: Chips,
+    },
+    /// Check the score table against a plain rule-based evaluator on all 2,598,960 five card hands
+    VerifyTable,
+    /// EV of calling, folding and shoving facing a bet, from equities or from ranges
+    Decide {
+        /// Pot before the bet
+        pot: f64,
+        bet: f64,
+        /// Effective stack, counting the bet to call
+        #[arg(long)]
+        stack: f64,
+        /// Equity of the hand facing the bet, 0-1
+        #[arg(long, required_unless_present = "hand", conflicts_with = "hand")]
+        equity: Option<f64>,
+        /// How often a shove gets a fold, 0-1
+        #[arg(long, requires = "called_equity", conflicts_with = "hand")]
+        folds: Option<f64>,
+        /// Equity when a shove is called, 0-1
+        #[arg(long, requires = "folds")]
+        called_equity: Option<f64>,
+        /// Hole cards, e.g. AhKh, to work out the equities against the range instead
+        #[arg(long, requires = "range")]
+        hand: Option<String>,
+        /// Board cards, e.g. 2c7d9s, none for preflop
+        #[arg(long, requires = "hand")]
+        board: Option<String>,
+        /// The bettor's range, e.g. TT+,AKs,AQo:0.5
+        #[arg(long)]
+        range: Option<HandRange>,
+        /// The part of the range that calls a shove
+        #[arg(long, requires = "hand")]
+        calling: Option<HandRange>,
+    },
+    /// How often a starting hand makes each hand and draw across every flop
+    Flops {
+        hand: HandClass,
+    },
+    /// Equity against a random hand, exact or sampled until the estimate is precise enough (depending on the profile),
+    /// or sampled multi-way against villains' ranges
+    Equity {
+        /// Hole cards, e.g. AhKh
+        hand: String,
+        /// Board cards, e.g. 2c7d9s
+        #[arg(default_value = "")]
+        board: String,
+        /// Stop once the interval is within ± this many percent
+        #[arg(long, default_value_t = 0.5)]
+        precision: f64,
+        /// Confidence level of the interval, in percent
+        #[arg(long, default_value_t = 95.0)]
+        confidence: f64,
+        #[arg(long, default_value_t = 10_000_000)]
+        max_samples: usize,
+        /// Give up after this many seconds
+        #[arg(long)]
+        max_time: Option<f64>,
+        /// Deal exactly this many samples instead of stopping at the precision
+        #[arg(long)]
+        samples: Option<usize>,
+        /// Give the most accurate answer possible in this many seconds (exact if it fits)
+        #[arg(long, conflicts_with = "samples")]
+        budget: Option<f64>,
+        /// A card the villain is showing, e.g. As (the other is unknown)
+        #[arg(long)]
+        exposed: Option<Card>,
+        /// Cards out of play, e.g. mucked by players who folded
+        #[arg(long, default_value = "")]
+        dead: String,
+        /// Players who have folded, their hands dealt before the villain's and the board
+        #[arg(long, default_value_t = 0)]
+        folded: usize,
+        /// Percent of starting hands the folded players would have played, so they folded the rest
+        /// (any two cards if not given)
+        #[arg(long, requires = "folded")]
+        folder_range: Option<f64>,
+        /// For exact results, also give the counts and the equity as a fraction in lowest terms
+        #[arg(long)]
+        rational: bool,
+        /// Enumerate every runout on the GPU, even preflop (needs the gpu feature)
+        #[arg(long, conflicts_with_all = ["samples", "budget", "folded"])]
+        gpu: bool,
+        /// A villain's range, e.g. TT+,AQs+:0.5, given once for each villain to play them all at once
+        #[arg(long = "range", conflicts_with_all = ["budget", "exposed", "folded", "gpu"])]
+        ranges: Vec<HandRange>,
+    },
+    /// The top percent of combos on a board, shown as how much of each starting hand makes it
+    Top {
+        /// Percent of the combos still possible on the board
+        percent: f64,
+        /// Board cards, e.g. 2c7d9s (empty for preflop)
+        #[arg(default_value = "")]
+        board: String,
+        /// Rank combos by equity or made-hand
+        #[arg(long, default_value_t = Ranking::Equity)]
+        by: Ranking,
+        #[arg(long, default_value = "")]
+        dead: String,
+    },
+    /// Run the rest of the board several times and show how often each share of the pot is won
+    RunIt {
+        /// Hole cards, e.g. AhKh
+        hand: String,
+        /// Board cards, e.g. 2c7d9s
+        #[arg(default_value = "")]
+        board: String,
+        /// Times to run the board
+        #[arg(long, default_value_t = 2)]
+        runs: usize,
+        #[arg(long, default_value_t = 100_000)]
+        trials: usize,
+        /// The villain's hole cards (any two cards if not given)
+        #[arg(long)]
+        villain: Option<String>,
+        #[arg(long, default_value = "")]
+        dead: String,
+    },
+    /// Equity after each street of a runout, against a known hand or a random one
+    Streets {
+        /// Hole cards, e.g. AhAs
+        hand: String,
+        /// Board cards as they came, e.g. 9c6c2h5dKs
+        runout: String,
+        /// The villain's hole cards (any two cards if not given)
+        #[arg(long)]
+        villain: Option<String>,
+        /// Deals used to estimate preflop equity
+        #[arg(long, default_value_t = 10_000)]
+        samples: usize,
+    },
+    /// Omaha equity against a random hand (sampled) or a known one (exact)
+    Omaha {
+        /// Four hole cards, e.g. AhAsKhQs
+        hand: String,
+        /// Board cards, e.g. 2c7d9s
+        #[arg(default_value = "")]
+        board: String,
+        /// The villain's four hole cards
+        #[arg(long)]
+        villain: Option<String>,
+        /// Stop once the interval is within ± this many percent
+        #[arg(long, default_value_t = 0.5)]
+        precision: f64,
+        #[arg(long, default_value = "")]
+        dead: String,
+        /// Split the pot with the best eight-or-better low, dealing --samples times against a random hand
+        #[arg(long)]
+        hi_lo: bool,
+        #[arg(long, default_value_t = 100_000)]
+        samples: usize,
+    },
+    /// Exact short deck (6+) hold'em equity, where flushes beat full houses and A-6-7-8-9 is a straight
+    ShortDeck {
+        /// Hole cards, six or higher, e.g. AhKh
+        hand: String,
+        /// Board cards, e.g. 7c8d9s
+        #[arg(default_value = "")]
+        board: String,
+        /// The villain's hole cards, otherwise against a random hand
+        #[arg(long)]
+        villain: Option<String>,
+        #[arg(long, default_value = "")]
+        dead: String,
+    },
+    /// Seven-card stud (or razz) equity against opponents showing only their upcards
+    Stud {
+        /// All of your cards, down and up, e.g. AhAsKd
+        hand: String,
+        /// An opponent's upcards, once per opponent, e.g. --opponent Kc7d
+        #[arg(long, required = true)]
+        opponent: Vec<String>,
+        /// Upcards folded by other players
+        #[arg(long, default_value = "")]
+        dead: String,
+        /// Enumerate every unseen card rather than sampling, only quick late in the hand
+        #[arg(long)]
+        exact: bool,
+        /// Play razz, where the best ace-to-five low wins
+        #[arg(long)]
+        razz: bool,
+        /// Stop once the interval is within ± this many percent
+        #[arg(long, default_value_t = 0.5)]
+        precision: f64,
+    },
+    /// Deuce-to-seven single draw equity once both players have chosen what to keep
+    DeuceSeven {
+        /// The cards you keep, drawing the rest, e.g. 7c5d4s3h
+        hand: String,
+        /// The cards the villain keeps
+        #[arg(long)]
+        villain: String,
+        /// Cards out of play, such as your discards
+        #[arg(long, default_value = "")]
+        dead: String,
+        /// Enumerate every draw rather than sampling, only quick up to two cards drawn each
+        #[arg(long)]
+        exact: bool,
+        /// Stop once the interval is within ± this many percent
+        #[arg(long, default_value_t = 0.5)]
+        precision: f64,
+    },
+    /// Five card draw: the chance of ending with each category after discarding and drawing
+    Draw {
+        /// Your five cards, e.g. AhKhQh7h2c
+        hand: String,
+        /// The cards thrown away, e.g. 2c
+        #[arg(long, default_value = "")]
+        discard: String,
+        #[arg(long, default_value = "")]
+        dead: String,
+    },
+    /// Pineapple equity for each card you could throw away: before the flop, or after it in crazy pineapple
+    Pineapple {
+        /// Three hole cards, e.g. AhAd3c
+        hand: String,
+        /// Board cards, e.g. 2c7d9s
+        #[arg(default_value = "")]
+        board: String,
+        /// The villain's three hole cards, who discards whatever is worst for you
+        #[arg(long)]
+        villain: Option<String>,
+        #[arg(long, default_value = "")]
+        dead: String,
+        /// Stop once the interval is within ± this many percent, when sampling preflop
+        #[arg(long, default_value_t = 0.5)]
+        precision: f64,
+    },
+    /// Double board (bomb pot) odds of scooping, splitting or losing, half the pot going to each board
+    DoubleBoard {
+        /// Hole cards, e.g. AhKh
+        hand: String,
+        /// Cards on the first board
+        #[arg(default_value = "")]
+        first: String,
+        /// Cards on the second board
+        #[arg(default_value = "")]
+        second: String,
+        /// The villain's hole cards, otherwise against a random hand
+        #[arg(long)]
+        villain: Option<String>,
+        #[arg(long, default_value = "")]
+        dead: String,
+        /// Enumerate both runouts against the villain, only quick with the turn out on both
+        #[arg(long, requires = "villain")]
+        exact: bool,
+        #[arg(long, default_value_t = 100_000)]
+        samples: usize,
+    },
+    /// Hold'em equity against a random hand with jokers or wild ranks, five of a kind beating everything
+    Wild {
+        /// Hole cards, Jo for a joker, e.g. AhJo
+        hand: String,
+        #[arg(default_value = "")]
+        board: String,
+        /// Jokers in the deck
+        #[arg(long, default_value_t = 0)]
+        jokers: usize,
+        /// Ranks that are wild, e.g. 2 for deuces wild
+        #[arg(long, default_value = "")]
+        wild_ranks: String,
+        #[arg(long, default_value_t = 100_000)]
+        samples: usize,
+    },
+    /// Play no limit hold'em against bots in the terminal, sitting in seat 0, until you bust or quit
+    Play {
+        /// Players at the table, you included (2-9)
+        #[arg(long, default_value_t = 2)]
+        players: usize,
+        /// Starting stacks, in big blinds
+        #[arg(long, default_value_t = 100)]
+        stack: Chips,
+        /// How the bots play: call, push-fold or ehs
+        #[arg(long, default_value_t = Bot::Ehs)]
+        bot: Bot,
+        /// Have the bots play a push/fold strategy file (from `push-fold --save`) instead
+        #[arg(long)]
+        chart: Option<String>,
+    },
+    /// Pick cards interactively and watch equity, outs and the made hand change as you go, and edit
+    /// a villain range on the grid, saved by name in the range library in the store directory
+    Explore,
+    /// Summarise the hands in a PokerStars or GGPoker hand history file: the cards and what was won or lost
+    History {
+        file: String,
+        /// Whose results to show, by default whoever was dealt the known hole cards
+        #[arg(long)]
+        player: Option<String>,
+        /// Also show all in adjusted winnings: what each all in would have won on average
+        #[arg(long)]
+        ev: bool,
+        /// Random runouts for a preflop all in
+        #[arg(long, default_value_t = 100_000)]
+        samples: usize,
+        /// Replay each hand street by street, with equities wherever every hand still in is known
+        #[arg(long)]
+        replay: bool,
+    },
+    /// HUD statistics for every player in a hand history file: VPIP, PFR, 3-bet, aggression and WTSD
+    Stats {
+        file: String,
+        /// Only players with at least this many hands
+        #[arg(long, default_value_t = 1)]
+        min_hands: usize,
+        /// Also break VPIP down by position for this player
+        #[arg(long)]
+        player: Option<String>,
+    },
+    /// Check the regression scenarios in a directory of .scenarios files with the current profile
+    Scenarios {
+        #[arg(default_value = "scenarios")]
+        dir: String,
+    },
+    /// Split an exhaustive evaluation over workers connecting to this address
+    Coordinate {
+        #[arg(long, default_value = "0.0.0.0:7878")]
+        listen: String,
+        /// Hole cards, e.g. AhKh
+        #[arg(long)]
+        hand: String,
+        /// Board cards, e.g. 2c7d9s (may be empty)
+        #[arg(long, default_value = "")]
+        board: String,
+        /// Runouts per chunk handed to a worker
+        #[arg(long, default_value_t = 1000)]
+        chunk: usize,
+        /// Seconds to wait for a chunk before giving it to another worker
+        #[arg(long, default_value_t = 600)]
+        timeout: u64,
+    },
+    /// Evaluate chunks for a coordinator
+    Worker {
+        coordinator: String,
+    },
+}
 
-fn get_best_score(pair: &(Card, Card), community: &Vec<Card>) -> u64 {
-    community.clone()
-        .into_iter()
-        .chain(std::iter::once(pair.0))
-        .chain(std::iter::once(pair.1))
-        .combinations(5)
-        .map(|cards|  Hand::new(&cards))
-        .map(|hand| *SCORES.get(&hand).unwrap())
-        .min()
-        .unwrap()
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Output {
+    Text,
+    Json,
 }
 
+impl std::str::FromStr for Output {
+    type Err = &'static str;
 
-/// exhaustive search is manageable with at least the flop on the board
-/// returns (win_count, lose_count)
-fn eval_with_community(community: Vec<Card>, pair: &(Card, Card)) -> (usize, usize) {
-    let mut win_count: usize = 0;
-    let mut lose_count: usize = 0;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Output::Text),
+            "json" => Ok(Output::Json),
+            _ => Err("Expected text or json"),
+        }
+    }
+}
 
-    let mut deck: Vec<Card> = Card::get_deck();
-    deck.retain(|card| !community.contains(card) && *card != pair.0 && *card != pair.1);
+impl Command {
+    /// Commands that print a result with a json form
+    fn has_json(&self) -> bool {
+        matches!(self, Command::Preflop { .. } | Command::Matchup { .. } | Command::Odds { .. } | Command::Decide { .. } | Command::Lines { .. }
+            | Command::Realization { .. } | Command::Grid { .. } | Command::Combos { .. } | Command::Distribution { .. }
+            | Command::Flops { .. } | Command::Equity { .. } | Command::Top { .. } | Command::RunIt { .. } | Command::Streets { .. }
+            | Command::Omaha { .. } | Command::ShortDeck { .. } | Command::Stud { .. } | Command::DeuceSeven { .. } | Command::Draw { .. }
+            | Command::Pineapple { .. } | Command::DoubleBoard { .. } | Command::Wild { .. } | Command::PushFold { .. } | Command::Solve { .. }
+            | Command::Buckets { .. } | Command::History { .. } | Command::Stats { .. } | Command::Batch { .. } | Command::Coordinate { .. }
+            | Command::Worker { .. } | Command::Scenarios { .. } | Command::VerifyTable | Command::Acpc { .. })
+    }
+}
 
-    let evil_pairs: Vec<(Card, Card)> = deck
-        .iter()
-        .copied()
-        .tuple_combinations()
-        .collect();
+fn parse_pair(s: &str) -> Result<(Card, Card), &'static str> {
+    match parse_cards(s)?[..] {
+        [a, b] => Ok((a, b)),
+        _ => Err("Expected two hole cards"),
+    }
+}
 
-    let mut community = community;
-    let n = community.len();
+/// Hole cards, board and dead cards
+type Cards = ((Card, Card), Vec<Card>, Vec<Card>);
 
-    for remainder in deck.iter().copied().combinations(5-n) {
-        community.append(&mut remainder.clone());
+/// The hole cards, board and dead cards of a spot, with no card dealt twice (the exposed card
+/// included) and no more than five on the board
+fn parse_spot(hand: &str, board: &str, exposed: Option<Card>, dead: &str) -> Result<Cards, &'static str> {
+    let pair = parse_pair(hand)?;
+    let board = parse_cards(board)?;
+    let dead = parse_cards(dead)?;
+    if !is_valid_deal_with(&board, &[pair], &[exposed.as_slice(), &dead].concat()) {
+        return Err("A card is dealt twice or the board has more than five cards");
+    }
+    Ok((pair, board, dead))
+}
 
-        let my_score = get_best_score(pair, &community);
+/// Exact equity from every runout, enumerated on the GPU
+#[cfg(feature = "gpu")]
+fn gpu_equity(pair: &(Card, Card), villain: &Villain, board: &[Card], dead: &[Card]) -> Estimate {
+    let gpu = GpuEvaluator::new().expect("no GPU to enumerate on");
+    let (wins, ties, losses) = gpu.tally(board, pair, villain, dead).expect("couldn't enumerate on the GPU");
+    Estimate::exact(Tally { wins, ties, losses })
+}
 
-        for evil_pair in &evil_pairs {
-            // Skip if evil_pair contains turn or river
-            if remainder.contains(&evil_pair.0) || remainder.contains(&evil_pair.1) {
-                continue;
-            }
-            if my_score < get_best_score(evil_pair, &community) {
-                win_count += 1;
-            } else {
-                lose_count += 1;
-            }
-        }
-        community.truncate(n);
+#[cfg(not(feature = "gpu"))]
+fn gpu_equity(_: &(Card, Card), _: &Villain, _: &[Card], _: &[Card]) -> Estimate {
+    panic!("built without the gpu feature");
+}
+
+/// An exact equity from win, tie and lose counts over every way the cards come
+fn print_exact(win: usize, tie: usize, lose: usize, counted: &str, json: bool) {
+    let estimate = Estimate::exact(Tally { wins: win, ties: tie, losses: lose });
+    match json {
+        true => println!("{}", estimate.to_json()),
+        false => println!("{:.2}% (exact, {} {})", estimate.equity * 100.0, win + tie + lose, counted),
     }
-    (win_count, lose_count)
 }
 
-/// not currently feasible to do an exhaustive search with just the hand
-/// so a monte carlo random search is implemented
-fn eval_hand_monte_carlo(pair: &(Card, Card), n: usize) -> (usize, usize) {
-    let mut win_count: usize = 0;
-    let mut lose_count: usize = 0;
+fn print_estimate(estimate: &Estimate, json: bool) {
+    match json {
+        true => println!("{}", estimate.to_json()),
+        false => println!("{:.2}% ± {:.2}% ({} samples)", estimate.equity * 100.0, estimate.half_width() * 100.0, estimate.tally.samples()),
+    }
+}
 
-    let mut deck: Vec<Card> = Card::get_deck();
-    deck.retain(|card| *card != pair.0 && *card != pair.1);
+fn print_hi_lo(result: &HiLo, json: bool) {
+    if json {
+        println!("{}", result.to_json());
+        return;
+    }
+    println!("equity {:.2}%: high {:.2}%, low {:.2}%, scoop {:.2}% (someone makes a low {:.2}% of the time)",
+        result.equity() * 100.0, result.high() * 100.0, result.low() * 100.0, result.scoop() * 100.0,
+        result.lows as f64 / result.deals as f64 * 100.0);
+}
 
-    let mut rng = rng();
+/// Ask the player at the terminal what to do, None if they quit
+fn ask(view: &GameView, input: &mut impl BufRead) -> Option<Action> {
+    let board = view.board.iter().map(|card| card.to_string()).collect::<String>();
+    println!("you have {}{} ({}), board [{}], pot {}, {} behind", view.hole.0, view.hole.1, view.position, board, view.pot, view.stacks[view.seat]);
+    let raise = match view.min_raise_to {
+        Some(min) => format!(", (r)aise to {}-{}", min, view.max_raise_to),
+        None => String::new(),
+    };
+    loop {
+        match view.to_call {
+            0 => println!("chec(k){}, (a)ll in or (q)uit?", raise),
+            to_call => println!("(f)old, (c)all {}{}, (a)ll in or (q)uit?", to_call, raise),
+        }
+        let mut line = String::new();
+        if input.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            ["f" | "fold"] => return Some(Action::Fold),
+            ["k" | "check"] => return Some(Action::Check),
+            ["c" | "call"] => return Some(Action::Call),
+            ["a" | "all"] => return Some(Action::AllIn),
+            ["q" | "quit"] => return None,
+            ["r" | "raise", to] => match to.parse() {
+                Ok(to) => return Some(Action::Raise(to)),
+                Err(_) => println!("raise to how much?"),
+            },
+            _ => println!("didn't get that"),
+        }
+    }
+}
 
+/// Run CFR on the game and print the average strategy and what it's worth, as a strategy file
+/// with the value and exploitability added if there's a game name for it
+fn solve<G: ExtensiveGame>(game: G, iterations: usize, sampled: bool, json: Option<&str>) -> StrategyProfile {
+    let mut cfr = Cfr::new(game);
+    let mut rng = rand::rng();
+    for _ in 0..iterations {
+        if sampled { cfr.iterate_sampled(&mut rng) } else { cfr.iterate() }
+    }
+    let profile = cfr.average_strategy();
+    let (value, exploitability) = (expected_value(&cfr.game, &profile), exploitability(&cfr.game, &profile));
+    if let Some(name) = json {
+        let Ok(Json::Object(mut fields)) = Json::parse(&profile.to_json(name)) else { unreachable!() };
+        fields.extend([("value".to_string(), value.into()), ("exploitability".to_string(), exploitability.into())]);
+        println!("{}", Json::Object(fields));
+    } else {
+        print!("{}", profile);
+        println!("{} information sets, worth {:+.4} to the first player", profile.info_sets.len(), value);
+        println!("exploitable for {:.4} a game", exploitability);
+    }
+    profile
+}
 
-    for community in deck.iter().copied().combinations(5).choose_multiple(&mut rng, n) {
-        
-        let score = get_best_score(pair, &community);
-        for evil_pair in deck.iter().copied().tuple_combinations::<(Card,Card)>() {
-            
-            if community.contains(&evil_pair.0) || community.contains(&evil_pair.1) {
+/// Play one hand at the table with the person at the terminal in seat 0, printing what happens.
+/// False if they quit
+fn play_in_terminal(table: &Table, hand: &mut HandState, bots: &mut [Box<dyn Strategy>], input: &mut impl BufRead) -> bool {
+    let active = table.active();
+    let name = |k: usize| table.seats[active[k]].as_ref().unwrap().name.clone();
+    println!("--- new hand, {} on the button", name(hand.button));
+    let mut shown = 0;
+    let mut street = Street::Preflop;
+    // the money went in on the street of the last action, before any board was run out
+    let mut all_in_board = 0;
+    while !hand.is_over() {
+        let k = hand.to_act().unwrap();
+        let action = if active[k] == 0 {
+            match ask(&hand.view(k), input) {
+                Some(action) => action,
+                None => return false,
+            }
+        } else {
+            bots[active[k]].act(&hand.view(k))
+        };
+        if let Err(e) = hand.act(action) {
+            println!("{}", e);
+            if active[k] == 0 {
                 continue;
             }
+            // a bot that gets it wrong gives up the hand
+            hand.act(Action::Fold).unwrap();
+        }
+        for &(k, _, action) in &hand.history[shown..] {
+            println!("{} ({}): {}", name(k), hand.position(k), action);
+        }
+        shown = hand.history.len();
+        all_in_board = hand.history.last().unwrap().1.board_len();
+        if hand.street != street && !hand.is_over() {
+            street = hand.street;
+            println!("{}: {}", street, hand.board.iter().map(|card| card.to_string()).collect::<String>());
+        }
+    }
 
-            if score < get_best_score(&evil_pair, &community) {
-                win_count += 1;
-            } else {
-                lose_count += 1;
+    let showdown: Vec<usize> = (0..hand.seats.len()).filter(|&k| !hand.seats[k].folded).collect();
+    if showdown.len() > 1 {
+        println!("board: {}", hand.board.iter().map(|card| card.to_string()).collect::<String>());
+        let holes: Vec<(Card, Card)> = showdown.iter().map(|&k| hand.seats[k].hole).collect();
+        let equities = showdown_odds(&holes, &hand.board[..all_in_board]).shares;
+        for (&k, equity) in showdown.iter().zip(equities) {
+            let score = get_best_score(&hand.seats[k].hole, &hand.board);
+            let (a, b) = hand.seats[k].hole;
+            print!("{} {}{}: {}", name(k), a, b, score.category());
+            match Street::from_board_len(all_in_board).unwrap() {
+                Street::River => println!(),
+                street => println!(" ({:.1}% when the money went in, {})", equity * 100.0, street),
             }
         }
     }
-    return (win_count, lose_count)
+    for (k, &won) in hand.winnings().unwrap().iter().enumerate() {
+        if won > 0 {
+            println!("{} wins {}", name(k), won);
+        }
+    }
+    true
 }
 
-fn main() {
+fn demo() {
     (*SCORES).get(&Hand::EMPTY);
-    
-    let community = vec![Card::new(Rank::Ace, Suit::Hearts), 
-                                        Card::new(Rank::King, Suit::Hearts), 
+
+    let community = vec![Card::new(Rank::Ace, Suit::Hearts),
+                                        Card::new(Rank::King, Suit::Hearts),
                                         Card::new(Rank::Four, Suit::Spades)];
-                    
+
     let my_hand = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
 
 
     let (win, lose) = eval_with_community(community, &my_hand);
 
     println!("{}: {} {}", (win as f64)/((win+lose) as f64), win, lose)
-    
+
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let config = match ExecConfig::load(ExecConfig::default_path(), cli.profile) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("ignoring config file: {}", e);
+            cli.profile.unwrap_or_default().config()
+        }
+    };
+
+    let json = cli.output == Output::Json;
+    if json && !cli.command.as_ref().is_some_and(Command::has_json) {
+        eprintln!("--output json isn't supported for this command");
+        std::process::exit(2);
+    }
+
+    match cli.command {
+        Some(Command::Preflop { samples }) => {
+            let grid = match samples {
+                Some(n) => {
+                    let mut rng = rand::rng();
+                    Grid::from_fn(|class| simulate_preflop_equity(class, n, &mut rng))
+                }
+                None => Grid::from_fn(preflop_equity),
+            };
+            match json {
+                true => println!("{}", grid.to_json()),
+                false => print!("{}", format_grid(|class| grid.value(class))),
+            }
+        }
+        Some(Command::Matchup { hero, villain }) => {
+            // reuse (and add to) matchups computed in earlier runs
+            let store = Store::open(Store::default_dir()).ok();
+            let mut matrix: MatchupMatrix = store.as_ref()
+                .and_then(|store| store.get(MATCHUP_MATRIX_INPUTS))
+                .unwrap_or_default();
+            let equity = matrix.get(hero, villain);
+            if let Some(store) = &store
+                && let Err(e) = store.put(MATCHUP_MATRIX_INPUTS, "heads-up matchup matrix", &matrix) {
+                eprintln!("couldn't save matchup matrix: {}", e);
+            }
+            match json {
+                true => println!("{}", Json::object([("hero", hero.to_string().into()), ("villain", villain.to_string().into()), ("equity", equity.into())])),
+                false => println!("{} vs {}: {:.2}% / {:.2}%", hero, villain, equity * 100.0, (1.0 - equity) * 100.0),
+            }
+        }
+        Some(Command::PushFold { stack, villain_stack, small_blind, ante, payouts, others, samples, rounds, save }) => {
+            let simulate = || {
+                let mut matrix = MatchupMatrix::new();
+                matrix.simulate_all(samples, &mut rand::rng());
+                matrix
+            };
+            let inputs = simulated_matrix_inputs(samples);
+            let matrix = match Store::open(Store::default_dir()) {
+                Ok(store) => store.get_or_compute(&inputs, "simulated heads-up matchup matrix", simulate).expect("couldn't save matchup matrix"),
+                Err(_) => simulate(),
+            };
+            let game = PushFoldGame {
+                small_blind_stack: stack,
+                big_blind_stack: villain_stack.unwrap_or(stack),
+                small_blind,
+                ante,
+                others,
+                payoff: if payouts.is_empty() { Payoff::ChipEv } else { Payoff::Icm(payouts) },
+            };
+            let equity = |hero, villain| matrix.lookup(hero, villain).unwrap();
+            let solution = solve_push_fold(&game, equity, rounds);
+            let exploitability = push_fold_exploitability(&game, &solution, equity);
+            if json {
+                let Json::Object(mut fields) = solution.to_json() else { unreachable!() };
+                fields.push(("exploitability".to_string(), exploitability.into()));
+                println!("{}", Json::Object(fields));
+            } else {
+                println!("small blind pushes (%):");
+                print!("{}", format_grid(|class| solution.push(class)));
+                println!("big blind calls (%):");
+                print!("{}", format_grid(|class| solution.call(class)));
+                println!("exploitable for {:.4} a hand", exploitability);
+            }
+            if let Some(file) = save {
+                solution.to_profile().save(file, "push-fold").expect("couldn't save the strategy");
+            }
+        }
+        Some(Command::Solve { game, iterations, sampled, save }) => {
+            let name = json.then(|| game.to_string());
+            let profile = match game {
+                ToyGame::Kuhn => solve(Kuhn, iterations, sampled, name.as_deref()),
+                ToyGame::Leduc => solve(Leduc, iterations, sampled, name.as_deref()),
+            };
+            if let Some(file) = save {
+                profile.save(file, &game.to_string()).expect("couldn't save the strategy");
+            }
+        }
+        Some(Command::Buckets { board, k, bins, runouts }) => {
+            let board = parse_cards(&board).expect("invalid board");
+            let abstraction = Abstraction::new(&board, &HandRange::full(), k, bins, runouts, &mut rand::rng());
+            if json {
+                println!("{}", abstraction.to_json());
+                return;
+            }
+            let equities = abstraction.equities();
+            let mut order: Vec<usize> = (0..equities.len()).collect();
+            order.sort_by(|&a, &b| equities[b].total_cmp(&equities[a]));
+            for bucket in order {
+                let hands: Vec<String> = abstraction.buckets.iter().filter(|&(_, &b)| b == bucket).map(|(pair, _)| format!("{}{}", pair.0, pair.1)).collect();
+                println!("{:>5.1}% {:>4} hands: {}{}", equities[bucket] * 100.0, hands.len(), hands[..hands.len().min(8)].join(" "),
+                    if hands.len() > 8 { " ..." } else { "" });
+            }
+        }
+        Some(Command::Odds { pot, bet, equity }) => {
+            let odds = Odds::new(pot, bet, equity).expect("invalid bet");
+            match json {
+                true => println!("{}", odds.to_json()),
+                false => println!("{}", odds),
+            }
+        }
+        Some(Command::Lines { hand, board, pot, stack, lines, range }) => {
+            let pair = parse_pair(&hand).expect("invalid hand");
+            let board = parse_cards(&board).expect("invalid board");
+            assert!(is_valid_deal_with(&board, &[pair], &[]), "invalid deal");
+            assert!((3..=5).contains(&board.len()), "board must be at least the flop");
+            assert!(pot > 0.0 && stack >= 0.0, "the pot must be positive and the stack not negative");
+            let range = range.unwrap_or_else(HandRange::full);
+            let spot = Spot { pair, range, board, pot, stack };
+            let evs = spot.compare(&lines);
+            match json {
+                true => println!("{}", Json::Array(evs.iter().map(ToJson::to_json).collect())),
+                false => evs.iter().for_each(|ev| println!("{}", ev)),
+            }
+        }
+        Some(Command::Realization { hand, board, pot, stack, oop, range, samples, factors, position }) => {
+            let pair = parse_pair(&hand).expect("invalid hand");
+            let board = parse_cards(&board).expect("invalid board");
+            assert!(is_valid_deal_with(&board, &[pair], &[]), "invalid deal");
+            assert!(board.len() != 1 && board.len() != 2, "board must be empty or at least the flop");
+            assert!(pot > 0.0 && stack >= 0.0 && samples >= 1, "the pot and samples must be positive and the stack not negative");
+            let range = range.unwrap_or_else(HandRange::full);
+            let estimate = estimate_realization(&pair, &range, &board, pot, stack, !oop, [Betting::default(); 2], samples, &mut rand::rng());
+            let configured = factors.zip(position).map(|(path, position)| {
+                let text = std::fs::read_to_string(path).expect("can't read factors");
+                let realization = Realization::parse(&text).expect("invalid factors");
+                (realization.factor(position, HandClass::from_pair(&pair)), position)
+            });
+            if json {
+                let Json::Object(mut fields) = estimate.to_json() else { unreachable!() };
+                fields.push(("realization".to_string(), estimate.factor(pot).into()));
+                fields.push(("configured".to_string(), configured.map(|(factor, _)| factor).into()));
+                println!("{}", Json::Object(fields));
+                return;
+            }
+            println!("checking down {:.2}% of the pot, playing it out {:+.2}", estimate.equity * 100.0, estimate.ev);
+            println!("realization {:.2}", estimate.factor(pot));
+            if let Some((factor, position)) = configured {
+                println!("configured {:.2} for {} in {}", factor, HandClass::from_pair(&pair), position);
+            }
+        }
+        Some(Command::Grid { range, equity, board, chart, action, plain, html, svg }) => {
+            let range = match chart {
+                Some(path) => {
+                    let text = std::fs::read_to_string(&path).expect("can't read chart");
+                    let range = if path.ends_with(".json") { parse_chart_json(&text, action.as_deref()) } else { parse_chart_csv(&text, action.as_deref()) };
+                    Some(range.expect("invalid chart"))
+                }
+                None => range,
+            };
+            let board = board.map(|board| parse_cards(&board).expect("invalid board"));
+            let grid = match (&range, &board) {
+                (Some(range), Some(board)) => Grid::from_equity_vs_range(range, board),
+                (Some(range), None) if !equity => Grid::from_range(range),
+                _ => Grid::from_fn(preflop_equity),
+            };
+            match json {
+                true => println!("{}", Json::object([("grid", grid.to_json()), ("range", range.as_ref().map(HandRange::to_string).into())])),
+                false => {
+                    print!("{}", grid.render(!plain));
+                    match (&range, &board) {
+                        (Some(range), Some(board)) => println!("equity against {} on {}", range, format_cards(board)),
+                        (Some(range), None) => println!("{}", range),
+                        _ => {}
+                    }
+                }
+            }
+            if let Some(path) = html {
+                std::fs::write(path, grid.to_html(if equity { "Preflop equity" } else if board.is_some() { "Equity on the board" } else { "Range" })).expect("can't write html");
+            }
+            if let Some(path) = svg {
+                std::fs::write(path, grid.to_svg()).expect("can't write svg");
+            }
+        }
+        Some(Command::Combos { board, range, holding }) => {
+            let board = parse_cards(&board).expect("invalid board");
+            let held = parse_cards(holding.as_deref().unwrap_or("")).expect("invalid holding");
+            assert!(is_valid_deal_with(&board, &[], &held), "invalid deal");
+            assert!((3..=5).contains(&board.len()), "board must be at least the flop");
+            let range = range.unwrap_or_else(HandRange::full);
+            match (holding, json) {
+                (Some(_), true) => println!("{}", Blockers::new(&range, &board, &held).to_json()),
+                (Some(_), false) => println!("{}", Blockers::new(&range, &board, &held)),
+                (None, true) => println!("{}", ComboReport::new(&range, &board).to_json()),
+                (None, false) => println!("{}", ComboReport::new(&range, &board)),
+            }
+        }
+        Some(Command::Distribution { hand, board, range, bins }) => {
+            let pair = parse_pair(&hand).expect("invalid hand");
+            let board = parse_cards(&board).expect("invalid board");
+            assert!(is_valid_deal_with(&board, &[pair], &[]), "invalid deal");
+            assert!((3..=5).contains(&board.len()), "board must be at least the flop");
+            assert!(bins >= 1, "bins must be at least 1");
+            let range = range.unwrap_or_else(HandRange::full);
+            let distribution = EquityDistribution::new(&pair, &range, &board, &[]);
+            assert!(!distribution.equities.is_empty(), "every combo in the range is blocked");
+            if json {
+                let Json::Object(mut fields) = distribution.to_json() else { unreachable!() };
+                fields.push(("histogram".to_string(), distribution.histogram(bins).into()));
+                println!("{}", Json::Object(fields));
+                return;
+            }
+            print!("{}", distribution.render(bins, 40));
+            println!("mean {:.1}%, 10th percentile {:.1}%, median {:.1}%, 90th percentile {:.1}%", distribution.mean() * 100.0,
+                     distribution.percentile(0.1) * 100.0, distribution.median() * 100.0, distribution.percentile(0.9) * 100.0);
+        }
+        Some(Command::Batch { input, results: path, precision, max_samples, samples }) => {
+            let text = std::fs::read_to_string(&input).expect("can't read spots");
+            let spots = match input.ends_with(".jsonl") {
+                true => parse_batch_jsonl(&text),
+                false => parse_batch_csv(&text),
+            };
+            let spots = spots.expect("invalid spots");
+            let rule = match samples {
+                Some(n) => {
+                    assert!(n >= 1, "samples must be at least 1");
+                    StopRule { half_width: 0.0, max_samples: n, ..StopRule::default() }
+                }
+                None => StopRule { half_width: precision / 100.0, max_samples, ..StopRule::default() },
+            };
+            let results = run_batch(&spots, &rule, config.threads, &mut rand::rng()).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            });
+            let text = match path.ends_with(".jsonl") {
+                true => format_batch_jsonl(&spots, &results),
+                false => format_batch_csv(&spots, &results),
+            };
+            std::fs::write(&path, text).expect("can't write results");
+            match json {
+                true => println!("{}", Json::object([("spots", spots.len().into()), ("results", path.into())])),
+                false => println!("{} spots written to {}", spots.len(), path),
+            }
+        }
+        Some(Command::Serve { listen }) => {
+            let listener = TcpListener::bind(&listen).expect("couldn't listen");
+            println!("listening on {}", listener.local_addr().expect("couldn't listen"));
+            serve(listener).expect("server failed");
+        }
+        Some(Command::Acpc { dealer, bot, players, stack, small_blind, big_blind }) => {
+            assert!((2..=9).contains(&players), "players must be 2-9");
+            let game = AcpcGame { players, stack, small_blind, big_blind };
+            let result = play_match(&dealer, &game, bot.strategy().as_mut()).expect("match failed");
+            match json {
+                true => println!("{}", result.to_json()),
+                false => println!("{} hands, {:+} chips ({:+.1} big blinds a hundred hands)", result.hands, result.winnings,
+                                  result.winnings as f64 / big_blind as f64 / result.hands.max(1) as f64 * 100.0),
+            }
+        }
+        Some(Command::VerifyTable) => match (verify_score_table(), json) {
+            (Ok(checked), true) => println!("{}", Json::object([("checked", checked.into())])),
+            (Ok(checked), false) => println!("all {} five card hands agree with the reference evaluator", checked),
+            (Err(e), true) => {
+                println!("{}", Json::object([("error", e.as_str().into())]));
+                std::process::exit(1);
+            }
+            (Err(e), false) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        Some(Command::Decide { pot, bet, stack, equity, folds, called_equity, hand, board, range, calling }) => {
+            let decision = match (hand, range) {
+                (Some(hand), Some(range)) => {
+                    let pair = parse_pair(&hand).expect("invalid hand");
+                    let board = parse_cards(board.as_deref().unwrap_or("")).expect("invalid board");
+                    Decision::from_ranges(&pair, &board, &range, calling.as_ref(), pot, bet, stack, &mut rand::rng())
+                }
+                _ => {
+                    let shove = folds.zip(called_equity).map(|(folds, equity)| Shove { folds, equity });
+                    Decision::new(pot, bet, stack, equity.expect("no equity"), shove)
+                }
+            };
+            let decision = decision.expect("invalid spot");
+            match json {
+                true => println!("{}", decision.to_json()),
+                false => println!("{}", decision),
+            }
+        }
+        Some(Command::Flops { hand }) => {
+            let (_, hits) = flop_hits(&hand.combos());
+            if json {
+                println!("{}", hits.to_json());
+                return;
+            }
+            for category in Category::ALL_CATEGORIES.iter().rev() {
+                println!("{:<24} {:>5.1}%", category.to_string(), hits.made(*category) * 100.0);
+            }
+            for draw in &Draw::ALL_DRAWS[1..] {
+                println!("{:<24} {:>5.1}%", draw.to_string(), hits.draw(*draw) * 100.0);
+            }
+        }
+        Some(Command::Equity { hand, board, precision, confidence, max_samples, max_time, samples, budget, exposed, dead, folded, folder_range, rational, gpu, ranges }) => {
+            let (pair, board, dead) = parse_spot(&hand, &board, exposed, &dead).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            });
+            let villain = exposed.map_or(Villain::Random, Villain::Exposed);
+            let bar = ProgressBar::new(0);
+            let on_progress = |progress: Progress| {
+                bar.set_length(progress.total as u64);
+                bar.set_position(progress.done as u64);
+            };
+            // ctrl-c stops early and prints what's been worked out so far
+            let cancel = CancelToken::new();
+            let handler_cancel = cancel.clone();
+            let _ = ctrlc::set_handler(move || handler_cancel.cancel());
+            let hooks = Hooks { on_progress: Some(&on_progress), cancel: Some(&cancel) };
+            let rule = StopRule {
+                half_width: precision / 100.0,
+                confidence: confidence / 100.0,
+                max_samples,
+                max_time: max_time.map(Duration::from_secs_f64),
+                ..StopRule::default()
+            };
+            let estimate = match (samples, budget) {
+                _ if gpu => gpu_equity(&pair, &villain, &board, &dead),
+                _ if !ranges.is_empty() => {
+                    assert!(ranges.len() <= 8, "at most 8 villains");
+                    let rule = match samples {
+                        Some(n) => StopRule { half_width: 0.0, max_samples: n, ..rule },
+                        None => rule,
+                    };
+                    eval_vs_ranges(&pair, &ranges, &board, &dead, &rule, &mut rand::rng()).unwrap_or_else(|e| {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    })
+                }
+                _ if folded > 0 => {
+                    assert!(folded <= 8, "at most 8 players can have folded");
+                    let model = folder_range.map_or(FoldModel::AnyTwo, |percent| FoldModel::playing_top(percent / 100.0));
+                    let rule = match samples {
+                        Some(n) => StopRule { half_width: 0.0, max_samples: n, ..rule },
+                        None => rule,
+                    };
+                    eval_with_folds(&pair, &villain, &board, &dead, &vec![model; folded], &rule, hooks, &mut rand::rng())
+                }
+                (Some(n), _) => {
+                    let rule = StopRule { half_width: 0.0, max_samples: n, ..StopRule::default() };
+                    let estimate = eval_until_threaded(&pair, &villain, &board, &dead, &rule, config.threads, config.batch, hooks, &mut rand::rng());
+                    Estimate { cancelled: estimate.cancelled, ..Estimate::wilson(estimate.tally, confidence / 100.0) }
+                }
+                (None, Some(budget)) => eval_with_budget(&pair, &villain, &board, &dead, Duration::from_secs_f64(budget), hooks, &mut rand::rng()),
+                (None, None) => config.equity(&pair, &villain, &board, &dead, &rule, hooks, &mut rand::rng()),
+            };
+            bar.finish_and_clear();
+            if json {
+                println!("{}", estimate.to_json());
+                return;
+            }
+            if estimate.cancelled {
+                println!("cancelled, partial result:");
+            }
+            match estimate.method {
+                Method::Exhaustive => {
+                    println!("{:.2}% (exact, {} deals)", estimate.equity * 100.0, estimate.tally.samples());
+                    if rational {
+                        let tally = estimate.tally;
+                        println!("{} wins, {} ties, {} losses: {}", tally.wins, tally.ties, tally.losses, tally.exact_equity());
+                    }
+                }
+                Method::MonteCarlo => println!("{:.2}% ± {:.2}% ({:.0}% interval {:.2}%-{:.2}%, {} samples)",
+                    estimate.equity * 100.0, estimate.half_width() * 100.0, estimate.confidence * 100.0,
+                    estimate.low * 100.0, estimate.high * 100.0, estimate.tally.samples()),
+            }
+        }
+        Some(Command::Top { percent, board, by, dead }) => {
+            let board = parse_cards(&board).expect("invalid board");
+            let dead = parse_cards(&dead).expect("invalid dead cards");
+            assert!(is_valid_deal_with(&board, &[], &dead), "invalid board");
+            assert!(board.len() != 1 && board.len() != 2, "board must be empty or at least the flop");
+            assert!((0.0..=100.0).contains(&percent), "percent must be between 0 and 100");
+            let range = HandRange::top_on_board(&board, &dead, percent / 100.0, by);
+            let grid = Grid::from_fn(|class| {
+                let live = HandRange::from_classes([class]).without(&[&board[..], &dead].concat());
+                let kept = live.combos().filter(|(pair, _)| range.contains(pair)).count();
+                if live.is_empty() { 0.0 } else { kept as f64 / live.len() as f64 }
+            });
+            match json {
+                true => println!("{}", Json::object([("grid", grid.to_json()), ("range", range.to_string().into()), ("combos", range.len().into())])),
+                false => {
+                    print!("{}", format_grid(|class| grid.value(class)));
+                    println!("{} combos", range.len());
+                }
+            }
+        }
+        Some(Command::RunIt { hand, board, runs, trials, villain, dead }) => {
+            let pair = parse_pair(&hand).expect("invalid hand");
+            let board = parse_cards(&board).expect("invalid board");
+            let dead = parse_cards(&dead).expect("invalid dead cards");
+            let villain = match villain {
+                Some(villain) => {
+                    let (a, b) = parse_pair(&villain).expect("invalid villain hand");
+                    Villain::Known(a, b)
+                }
+                None => Villain::Random,
+            };
+            assert!(is_valid_deal_with(&board, &[pair], &[villain.known_cards(), dead.clone()].concat()), "invalid deal");
+            assert!(runs >= 1 && trials >= 1, "runs and trials must be at least 1");
+            let shares = eval_run_it(&pair, &villain, &board, &dead, runs, trials, &mut rand::rng());
+            if json {
+                println!("{}", shares.to_json());
+                return;
+            }
+            for k in 0..shares.counts.len() {
+                println!("{:>6.1}% of the pot {:>6.2}%", shares.share(k) * 100.0, shares.probability(k) * 100.0);
+            }
+            println!("equity {:.2}%, standard deviation {:.2}%", shares.equity() * 100.0, shares.std_dev() * 100.0);
+        }
+        Some(Command::Streets { hand, runout, villain, samples }) => {
+            let pair = parse_pair(&hand).expect("invalid hand");
+            let runout = parse_cards(&runout).expect("invalid runout");
+            let range = match villain {
+                Some(villain) => HandRange::from_combos([parse_pair(&villain).expect("invalid villain hand")]),
+                None => HandRange::full(),
+            };
+            let blocked: Vec<Card> = range.combos().flat_map(|(pair, _)| [pair.0, pair.1]).collect();
+            assert!(is_valid_deal_with(&runout, &[pair], if range.len() == 1 { &blocked } else { &[] }), "invalid deal");
+            assert!(runout.len() != 1 && runout.len() != 2, "runout must be empty or at least the flop");
+            let streets = equity_by_street(&pair, &range, &runout, samples, &mut rand::rng());
+            match json {
+                true => println!("{}", Json::Object(streets.iter().map(|(street, equity)| (street.to_string(), (*equity).into())).collect())),
+                false => streets.iter().for_each(|(street, equity)| println!("{:<8} {:.2}%", street.to_string(), equity * 100.0)),
+            }
+        }
+        Some(Command::ShortDeck { hand, board, villain, dead }) => {
+            let pair = parse_pair(&hand).expect("invalid hand");
+            let evil_pair = villain.as_deref().map(|s| parse_pair(s).expect("invalid villain hand"));
+            let board = parse_cards(&board).expect("invalid board");
+            let dead = parse_cards(&dead).expect("invalid dead cards");
+            let pairs: Vec<(Card, Card)> = [pair].into_iter().chain(evil_pair).collect();
+            assert!(is_valid_deal_with(&board, &pairs, &dead), "invalid deal");
+            let cards = [&board[..], &dead, &pairs.iter().flat_map(|pair| [pair.0, pair.1]).collect::<Vec<_>>()].concat();
+            assert!(cards.iter().all(|card| Deck::Short.ranks().contains(&card.rank)), "the short deck has nothing below a six");
+            match evil_pair {
+                Some(evil_pair) => {
+                    let (win, tie, lose) = eval_short_deck_heads_up(&pair, &evil_pair, &board, &dead);
+                    print_exact(win, tie, lose, "runouts", json);
+                }
+                None => {
+                    let estimate = eval_short_deck(&pair, &board, &dead);
+                    print_exact(estimate.tally.wins, estimate.tally.ties, estimate.tally.losses, "deals", json);
+                }
+            }
+        }
+        Some(Command::Stud { hand, opponent, dead, exact, razz, precision }) => {
+            let hero = parse_cards(&hand).expect("invalid hand");
+            let opponents: Vec<Vec<Card>> = opponent.iter().map(|up| parse_cards(up).expect("invalid upcards")).collect();
+            let dead = parse_cards(&dead).expect("invalid dead cards");
+            let cards = [&hero[..], &dead, &opponents.concat()].concat();
+            assert!(cards.iter().all_unique(), "invalid deal");
+            assert!(hero.len() <= STUD_CARDS && opponents.iter().all(|up| up.len() <= 4), "too many cards");
+            assert!(STUD_CARDS * (1 + opponents.len()) + dead.len() <= 52, "not enough cards left to deal");
+            if exact {
+                let estimate = if razz { eval_razz_exact(&hero, &opponents, &dead) } else { eval_stud_exact(&hero, &opponents, &dead) };
+                print_exact(estimate.tally.wins, estimate.tally.ties, estimate.tally.losses, "deals", json);
+            } else {
+                let rule = StopRule { half_width: precision / 100.0, ..StopRule::default() };
+                let estimate = if razz {
+                    eval_razz(&hero, &opponents, &dead, &rule, &mut rand::rng())
+                } else {
+                    eval_stud(&hero, &opponents, &dead, &rule, &mut rand::rng())
+                };
+                print_estimate(&estimate, json);
+            }
+        }
+        Some(Command::DeuceSeven { hand, villain, dead, exact, precision }) => {
+            let keep = parse_cards(&hand).expect("invalid hand");
+            let villain_keep = parse_cards(&villain).expect("invalid villain hand");
+            let dead = parse_cards(&dead).expect("invalid dead cards");
+            assert!(keep.len() <= DRAW_CARDS && villain_keep.len() <= DRAW_CARDS, "at most five cards kept");
+            assert!([&keep[..], &villain_keep, &dead].concat().iter().all_unique(), "invalid deal");
+            if exact {
+                let (win, tie, lose) = eval_deuce_seven_heads_up(&keep, &villain_keep, &dead);
+                print_exact(win, tie, lose, "draws", json);
+            } else {
+                let rule = StopRule { half_width: precision / 100.0, ..StopRule::default() };
+                print_estimate(&eval_deuce_seven(&keep, &villain_keep, &dead, &rule, &mut rand::rng()), json);
+            }
+        }
+        Some(Command::Draw { hand, discard, dead }) => {
+            let hand = parse_cards(&hand).expect("invalid hand");
+            let discard = parse_cards(&discard).expect("invalid discards");
+            let dead = parse_cards(&dead).expect("invalid dead cards");
+            assert_eq!(hand.len(), DRAW_CARDS, "expected five cards");
+            assert!([&hand[..], &dead].concat().iter().all_unique(), "invalid deal");
+            assert!(discard.iter().all(|card| hand.contains(card)), "can only discard cards in the hand");
+            let outcomes = draw_outcomes(&hand, &discard, &dead);
+            if json {
+                println!("{}", outcomes.to_json());
+                return;
+            }
+            println!("{} now, {} draws", outcomes.before, outcomes.total());
+            for category in Category::ALL_CATEGORIES.into_iter().rev() {
+                if outcomes.counts[usize::from(category)] > 0 {
+                    println!("{:<16} {:>6.2}%", category.to_string(), outcomes.probability(category) * 100.0);
+                }
+            }
+            println!("improves {:.2}%", outcomes.improve() * 100.0);
+        }
+        Some(Command::Pineapple { hand, board, villain, dead, precision }) => {
+            let parse_hand = |s: &str| -> PineappleHand {
+                parse_cards(s).expect("invalid hand").try_into().expect("expected three hole cards")
+            };
+            let hole = parse_hand(&hand);
+            let board = parse_cards(&board).expect("invalid board");
+            let dead = parse_cards(&dead).expect("invalid dead cards");
+            match villain.as_deref().map(parse_hand) {
+                Some(evil_hole) => {
+                    assert!(is_valid_deal_with(&board, &[], &[&hole[..], &evil_hole, &dead].concat()), "invalid deal");
+                    let matrix = discard_matrix(&hole, &evil_hole, &board, &dead);
+                    let worst: Vec<f64> = matrix.iter().map(|row| row.iter().copied().fold(1.0, f64::min)).collect();
+                    let (discard, equity) = best_discard_heads_up(&hole, &evil_hole, &board, &dead);
+                    if json {
+                        println!("{}", Json::object([
+                            ("worst", Json::Object(hole.iter().zip(worst).map(|(card, worst)| (card.to_string(), worst.into())).collect())),
+                            ("best", discard.to_string().into()),
+                            ("equity", equity.into()),
+                        ]));
+                        return;
+                    }
+                    for (card, worst) in hole.iter().zip(worst) {
+                        println!("discard {}: {:.2}% at worst", card, worst * 100.0);
+                    }
+                    println!("best: discard {} for {:.2}%", discard, equity * 100.0);
+                }
+                None => {
+                    assert!(is_valid_deal_with(&board, &[], &[&hole[..], &dead].concat()), "invalid deal");
+                    let rule = StopRule { half_width: precision / 100.0, ..StopRule::default() };
+                    let equities = discard_equities(&hole, &board, &dead, &rule, &mut rand::rng());
+                    match json {
+                        true => println!("{}", Json::Object(equities.iter().map(|(discard, estimate)| (discard.to_string(), estimate.to_json())).collect())),
+                        false => for (discard, estimate) in equities {
+                            println!("discard {}: {:.2}% ± {:.2}%", discard, estimate.equity * 100.0, estimate.half_width() * 100.0);
+                        },
+                    }
+                }
+            }
+        }
+        Some(Command::DoubleBoard { hand, first, second, villain, dead, exact, samples }) => {
+            let pair = parse_pair(&hand).expect("invalid hand");
+            let evil_pair = villain.as_deref().map(|s| parse_pair(s).expect("invalid villain hand"));
+            let first = parse_cards(&first).expect("invalid first board");
+            let second = parse_cards(&second).expect("invalid second board");
+            let dead = parse_cards(&dead).expect("invalid dead cards");
+            let pairs: Vec<(Card, Card)> = [pair].into_iter().chain(evil_pair).collect();
+            assert!(second.len() <= 5 && is_valid_deal_with(&first, &pairs, &[&second[..], &dead].concat()), "invalid deal");
+            let result = match evil_pair {
+                Some(evil_pair) if exact => eval_double_board_heads_up(&pair, &evil_pair, [&first, &second], &dead),
+                _ => {
+                    let villain = evil_pair.map_or(Villain::Random, |(a, b)| Villain::Known(a, b));
+                    eval_double_board(&pair, &villain, [&first, &second], &dead, samples, &mut rand::rng())
+                }
+            };
+            if json {
+                println!("{}", result.to_json());
+                return;
+            }
+            println!("scoop {:.2}%, split {:.2}%, lose {:.2}%", result.scoop() * 100.0, result.split() * 100.0, result.lose() * 100.0);
+            println!("equity {:.2}% ({} deals)", result.equity() * 100.0, result.deals);
+        }
+        Some(Command::Wild { hand, board, jokers, wild_ranks, samples }) => {
+            let hole: [WildCard; 2] = parse_wild_cards(&hand).expect("invalid hand").try_into().expect("expected two hole cards");
+            let board = parse_wild_cards(&board).expect("invalid board");
+            let ranks = wild_ranks.chars().map(|c| Rank::try_from(c).expect("invalid wild rank")).collect();
+            let wilds = Wilds { jokers, ranks };
+            let cards = [&hole[..], &board].concat();
+            assert!(board.len() <= 5, "invalid board");
+            assert!(cards.iter().filter(|&&card| card == WildCard::Joker).count() <= jokers, "more jokers than in the deck");
+            assert!(cards.iter().filter(|&&card| card != WildCard::Joker).all_unique(), "invalid deal");
+            print_estimate(&Estimate::wilson(eval_wild(&hole, &board, &wilds, samples, &mut rand::rng()), 0.95), json);
+        }
+        Some(Command::Play { players, stack, bot, chart }) => {
+            assert!((2..=9).contains(&players), "2-9 players");
+            let mut table = Table::new(players, 1, 2);
+            for seat in 0..players {
+                let name = if seat == 0 { "you".to_string() } else { format!("bot {}", seat) };
+                table.sit(seat, Player { name, stack: 2 * stack }).unwrap();
+            }
+            let chart = chart.map(|file| StrategyProfile::load(file).expect("couldn't read the strategy file").1);
+            let mut bots: Vec<Box<dyn Strategy>> = (0..players)
+                .map(|_| match &chart {
+                    Some(profile) => Box::new(ChartBot { profile: profile.clone() }) as Box<dyn Strategy>,
+                    None => bot.strategy(),
+                })
+                .collect();
+            let mut rng = rand::rng();
+            let mut input = std::io::stdin().lock();
+            while table.active().contains(&0) && table.active().len() > 1 {
+                let mut hand = table.deal(&mut rng).unwrap();
+                if !play_in_terminal(&table, &mut hand, &mut bots, &mut input) {
+                    return;
+                }
+                table.finish(&hand);
+            }
+            let you = table.seats[0].as_ref().unwrap().stack;
+            println!("{}", if you > 0 { "you won every chip" } else { "you're out of chips" });
+        }
+        Some(Command::Explore) => {
+            let path = RangeLibrary::default_path();
+            let library = RangeLibrary::load(&path).unwrap_or_else(|e| {
+                eprintln!("Couldn't load the range library: {}", e);
+                std::process::exit(1);
+            });
+            ratatui::run(|terminal| Explorer::with_library(library, path).run(terminal)).expect("terminal error");
+        }
+        Some(Command::History { file, player, ev, samples, replay }) => {
+            let text = std::fs::read_to_string(&file).expect("couldn't read the hand history");
+            let hands = parse_history(&text).expect("invalid hand history");
+            let mut total = 0;
+            let mut expected = 0.0;
+            let mut summaries = Vec::new();
+            for hand in &hands {
+                let replayed = replay.then(|| poker::replay::replay(hand, true));
+                if let (Some(replayed), false) = (&replayed, json) {
+                    println!("{}", replayed);
+                }
+                let Some(name) = player.clone().or(hand.hero.as_ref().map(|(name, _)| name.clone())) else {
+                    continue;
+                };
+                if hand.player(&name).is_none() {
+                    continue;
+                }
+                let hole = hand.hero.iter().chain(&hand.shown)
+                    .find(|(shown, _)| *shown == name)
+                    .map(|(_, (a, b))| format!("{}{}", a, b));
+                let board = hand.board.iter().map(|card| card.to_string()).collect::<String>();
+                let net = hand.net(&name);
+                total += net;
+                let point = ev.then(|| ev_line(std::slice::from_ref(hand), &name, samples, &mut rand::rng()).pop()).flatten();
+                expected += point.as_ref().map_or(0.0, |point| point.expected);
+                if json {
+                    summaries.push(Json::object([
+                        ("id", hand.id.clone().into()),
+                        ("player", name.into()),
+                        ("hole", hole.into()),
+                        ("board", board.into()),
+                        ("net", (net as f64).into()),
+                        ("all_in_adjusted", point.as_ref().map(|point| point.expected).into()),
+                        ("all_in", point.as_ref().map(|point| point.all_in).into()),
+                        ("replay", replayed.into()),
+                    ]));
+                    continue;
+                }
+                print!("#{}: {} {} [{}] {:+}", hand.id, name, hole.as_deref().unwrap_or("????"), board, net);
+                match point {
+                    Some(point) => println!(" (all in adjusted {:+.0}){}", point.expected, if point.all_in { " all in" } else { "" }),
+                    None => println!(),
+                }
+            }
+            if json {
+                println!("{}", Json::object([
+                    ("hands", Json::Array(summaries)),
+                    ("total", (total as f64).into()),
+                    ("all_in_adjusted", ev.then_some(expected).into()),
+                ]));
+                return;
+            }
+            println!("{} hands, {:+} in total", hands.len(), total);
+            if ev {
+                println!("{:+.0} all in adjusted, {:+.0} from luck", expected, total as f64 - expected);
+            }
+        }
+        Some(Command::Stats { file, min_hands, player }) => {
+            let text = std::fs::read_to_string(&file).expect("couldn't read the hand history");
+            let stats = player_stats(&parse_history(&text).expect("invalid hand history"));
+            if json {
+                let players = stats.iter().filter(|(_, stats)| stats.hands >= min_hands).sorted_by_key(|(_, stats)| std::cmp::Reverse(stats.hands));
+                println!("{}", Json::Object(players.map(|(name, stats)| (name.clone(), stats.to_json())).collect()));
+                return;
+            }
+            println!("{:<20} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6}", "player", "hands", "vpip", "pfr", "3bet", "af", "wtsd");
+            for (name, stats) in stats.iter().filter(|(_, stats)| stats.hands >= min_hands).sorted_by_key(|(_, stats)| std::cmp::Reverse(stats.hands)) {
+                // no bets, raises or calls after the flop at all
+                let af = match stats.aggression() {
+                    af if af.is_nan() => "-".to_string(),
+                    af => format!("{:.2}", af),
+                };
+                println!("{:<20} {:>6} {:>6.1} {:>6.1} {:>6.1} {:>6} {:>6.1}", name, stats.hands, stats.vpip(), stats.pfr(),
+                    stats.three_bet(), af, stats.wtsd());
+            }
+            if let Some(stats) = player.and_then(|name| stats.get(&name)) {
+                for (position, (hands, vpip)) in stats.by_position.iter().sorted() {
+                    println!("{:<6} {:>6} hands, vpip {:.1}%", position.to_string(), hands, *vpip as f64 / *hands as f64 * 100.0);
+                }
+            }
+        }
+        Some(Command::Omaha { hand, board, villain, precision, dead, hi_lo, samples }) => {
+            let parse_hand = |s: &str| -> OmahaHand {
+                parse_cards(s).expect("invalid hand").try_into().expect("expected four hole cards")
+            };
+            let hole = parse_hand(&hand);
+            let board = parse_cards(&board).expect("invalid board");
+            let dead = parse_cards(&dead).expect("invalid dead cards");
+            match villain.as_deref().map(parse_hand) {
+                Some(evil_hole) if hi_lo => {
+                    assert!(is_valid_deal_with(&board, &[], &[&hole[..], &evil_hole, &dead].concat()), "invalid deal");
+                    print_hi_lo(&eval_hi_lo_heads_up(&hole, &evil_hole, &board, &dead), json);
+                }
+                None if hi_lo => {
+                    assert!(is_valid_deal_with(&board, &[], &[&hole[..], &dead].concat()), "invalid deal");
+                    print_hi_lo(&eval_hi_lo(&hole, &board, &dead, samples, &mut rand::rng()), json);
+                }
+                Some(evil_hole) => {
+                    assert!(is_valid_deal_with(&board, &[], &[&hole[..], &evil_hole, &dead].concat()), "invalid deal");
+                    let (win, tie, lose) = eval_omaha_heads_up(&hole, &evil_hole, &board, &dead);
+                    print_exact(win, tie, lose, "runouts", json);
+                }
+                None => {
+                    assert!(is_valid_deal_with(&board, &[], &[&hole[..], &dead].concat()), "invalid deal");
+                    let rule = StopRule { half_width: precision / 100.0, ..StopRule::default() };
+                    print_estimate(&eval_omaha(&hole, &board, &dead, &rule, &mut rand::rng()), json);
+                }
+            }
+        }
+        Some(Command::Scenarios { dir }) => {
+            let corpus = load_corpus(&dir).expect("couldn't load scenarios");
+            let outcomes = run_corpus(&corpus, |scenario| {
+                config.equity(&scenario.pair, &scenario.villain, &scenario.board, &scenario.dead,
+                              &StopRule::default(), Hooks::default(), &mut rand::rng())
+            });
+            let failed = outcomes.iter().filter(|outcome| !outcome.passed()).count();
+            if json {
+                println!("{}", Json::object([
+                    ("scenarios", outcomes.iter().map(ToJson::to_json).collect::<Vec<_>>().into()),
+                    ("passed", (outcomes.len() - failed).into()),
+                    ("failed", failed.into()),
+                ]));
+            } else {
+                for outcome in &outcomes {
+                    println!("{} {}", if outcome.passed() { "ok  " } else { "FAIL" }, outcome.name);
+                    for failure in &outcome.failures {
+                        println!("     {}", failure);
+                    }
+                }
+                println!("{} passed, {} failed", outcomes.len() - failed, failed);
+            }
+            if failed > 0 {
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Coordinate { listen, hand, board, chunk, timeout }) => {
+            let job = cluster::Job {
+                pair: parse_pair(&hand).expect("invalid hand"),
+                board: parse_cards(&board).expect("invalid board"),
+            };
+            let listener = TcpListener::bind(&listen).expect("couldn't listen");
+            let (win, lose) = cluster::coordinate(listener, &job, chunk, Duration::from_secs(timeout))
+                .expect("coordinator failed");
+            match json {
+                true => println!("{}", Estimate::exact(Tally { wins: win, ties: 0, losses: lose }).to_json()),
+                false => println!("{}: {} {}", (win as f64)/((win+lose) as f64), win, lose),
+            }
+        }
+        Some(Command::Worker { coordinator }) => {
+            let chunks = cluster::work(&coordinator).expect("worker failed");
+            match json {
+                true => println!("{}", Json::object([("chunks", chunks.into())])),
+                false => println!("evaluated {} chunks", chunks),
+            }
+        }
+        None => demo(),
+    }
 }
@@ -0,0 +1,186 @@
+use crate::card::*;
+use crate::class::*;
+use crate::eval::*;
+use itertools::Itertools;
+use rand::{seq::{IndexedRandom, SliceRandom}, Rng};
+use crate::store::*;
+use crate::iso;
+
+
+/// Exact heads-up equity of one starting hand against another, ties count half.
+/// Averages every pairing of concrete combos that don't share a card,
+/// each enumerated over all 1,712,304 boards, so this is slow (seconds per distinct pairing)
+pub fn matchup_equity(hero: HandClass, villain: HandClass) -> f64 {
+    let pairings = hero.combos()
+        .into_iter()
+        .cartesian_product(villain.combos())
+        .filter(|(pair, evil_pair)| {
+            ![evil_pair.0, evil_pair.1].iter().any(|card| *card == pair.0 || *card == pair.1)
+        });
+    // e.g. AKs vs QQ has 24 pairings but only 2 distinct ones up to suits
+    let distinct = iso::dedupe(pairings, |(pair, evil_pair)| {
+        vec![vec![pair.0, pair.1], vec![evil_pair.0, evil_pair.1]]
+    });
+
+    let mut points: usize = 0;
+    let mut total: usize = 0;
+    for ((pair, evil_pair), weight) in distinct {
+        let (win, tie, lose) = eval_heads_up(&pair, &evil_pair, &[]);
+        points += weight * (2 * win + tie);
+        total += weight * 2 * (win + tie + lose);
+    }
+    points as f64 / total as f64
+}
+
+/// Monte carlo estimate of matchup_equity, dealing n random pairings of combos and boards
+pub fn simulate_matchup_equity<R: Rng + ?Sized>(hero: HandClass, villain: HandClass, n: usize, rng: &mut R) -> f64 {
+    let pairings: Vec<((Card, Card), (Card, Card))> = hero.combos()
+        .into_iter()
+        .cartesian_product(villain.combos())
+        .filter(|(pair, evil_pair)| ![evil_pair.0, evil_pair.1].iter().any(|card| *card == pair.0 || *card == pair.1))
+        .collect();
+    let mut points: usize = 0;
+    for _ in 0..n {
+        let &(pair, evil_pair) = pairings.choose(rng).expect("hands can't share every card");
+        let mut deck = deck_without(&[pair.0, pair.1, evil_pair.0, evil_pair.1]);
+        let (community, _) = deck.partial_shuffle(rng, 5);
+        // 2 points for a win, 1 for a tie
+        points += match get_best_score(&pair, community).cmp(&get_best_score(&evil_pair, community)) {
+            std::cmp::Ordering::Less => 2,
+            std::cmp::Ordering::Equal => 1,
+            std::cmp::Ordering::Greater => 0,
+        };
+    }
+    points as f64 / (2 * n) as f64
+}
+
+/// 169x169 heads-up equities between starting hands
+/// Entries are filled in as they're computed (see matchup_equity), since the whole
+/// matrix is far too expensive to build in one go
+#[derive(Debug, Clone)]
+pub struct MatchupMatrix {
+    equity: Vec<Option<f64>>,
+}
+
+impl Default for MatchupMatrix {
+    fn default() -> Self {
+        MatchupMatrix { equity: vec![None; HandClass::COUNT * HandClass::COUNT] }
+    }
+}
+
+impl MatchupMatrix {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn index(hero: HandClass, villain: HandClass) -> usize {
+        hero.index() * HandClass::COUNT + villain.index()
+    }
+
+    /// Equity of hero against villain if it has been computed
+    pub fn lookup(&self, hero: HandClass, villain: HandClass) -> Option<f64> {
+        self.equity[MatchupMatrix::index(hero, villain)]
+    }
+
+    /// Record hero's equity against villain (and villain's against hero)
+    pub fn insert(&mut self, hero: HandClass, villain: HandClass, equity: f64) {
+        self.equity[MatchupMatrix::index(hero, villain)] = Some(equity);
+        self.equity[MatchupMatrix::index(villain, hero)] = Some(1.0 - equity);
+    }
+
+    /// Equity of hero against villain, computing it if needed
+    pub fn get(&mut self, hero: HandClass, villain: HandClass) -> f64 {
+        if let Some(equity) = self.lookup(hero, villain) {
+            return equity;
+        }
+        let equity = matchup_equity(hero, villain);
+        self.insert(hero, villain, equity);
+        equity
+    }
+
+    /// Number of (hero, villain) entries filled in
+    pub fn computed(&self) -> usize {
+        self.equity.iter().filter(|x| x.is_some()).count()
+    }
+
+    /// Fill in every missing entry
+    pub fn compute_all(&mut self) {
+        for hero in HandClass::all() {
+            for villain in HandClass::all() {
+                self.get(hero, villain);
+            }
+        }
+    }
+
+    /// Fill in every missing entry with simulate_matchup_equity, which is quick enough to do
+    /// all at once with a few thousand samples each
+    pub fn simulate_all<R: Rng + ?Sized>(&mut self, n: usize, rng: &mut R) {
+        for hero in HandClass::all() {
+            for villain in HandClass::all().filter(|villain| *villain >= hero) {
+                if self.lookup(hero, villain).is_none() {
+                    let equity = if hero == villain { 0.5 } else { simulate_matchup_equity(hero, villain, n, rng) };
+                    self.insert(hero, villain, equity);
+                }
+            }
+        }
+    }
+}
+
+impl Artifact for MatchupMatrix {
+    const KIND: &'static str = "matchup-matrix";
+
+    /// f64 per entry, NaN where it hasn't been computed
+    fn to_bytes(&self) -> Vec<u8> {
+        self.equity
+            .iter()
+            .flat_map(|x| x.unwrap_or(f64::NAN).to_le_bytes())
+            .collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != HandClass::COUNT * HandClass::COUNT * 8 {
+            return None;
+        }
+        let equity = bytes
+            .chunks_exact(8)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+            .map(|x| if x.is_nan() { None } else { Some(x) })
+            .collect();
+        Some(MatchupMatrix { equity })
+    }
+}
+
+/// The matrix only depends on the rules and the evaluator, so one store entry covers every run
+pub const MATCHUP_MATRIX_INPUTS: &[u8] = b"holdem heads-up exact v1";
+
+/// Store inputs for a matrix filled by simulate_all with n samples per matchup
+pub fn simulated_matrix_inputs(n: usize) -> Vec<u8> {
+    format!("holdem heads-up simulated {} v1", n).into_bytes()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_symmetry() {
+        let aces: HandClass = "AA".parse().unwrap();
+        let kings: HandClass = "KK".parse().unwrap();
+        let mut matrix = MatchupMatrix::new();
+        assert_eq!(matrix.lookup(aces, kings), None);
+
+        matrix.insert(aces, kings, 0.82);
+        assert_eq!(matrix.get(aces, kings), 0.82);
+        assert!((matrix.get(kings, aces) - 0.18).abs() < 1e-9);
+        assert_eq!(matrix.computed(), 2);
+
+        let restored = MatchupMatrix::from_bytes(&matrix.to_bytes()).unwrap();
+        assert_eq!(restored.lookup(kings, aces), matrix.lookup(kings, aces));
+        assert_eq!(restored.computed(), 2);
+
+        // aces are about 82% against kings
+        let estimate = simulate_matchup_equity(aces, kings, 2000, &mut rand::rng());
+        assert!((estimate - 0.82).abs() < 0.05);
+    }
+}
@@ -0,0 +1,1118 @@
+use crate::cancel::*;
+use crate::card::*;
+use crate::eval::*;
+use crate::json::*;
+use crate::range::*;
+use std::{fmt::{Display, Formatter}, sync::{atomic::{AtomicUsize, Ordering}, LazyLock}, time::{Duration, Instant}};
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::{IndexedRandom, SliceRandom}, Rng, SeedableRng};
+
+
+/// Win/tie/loss counts from independent random deals
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tally {
+    pub wins: usize,
+    pub ties: usize,
+    pub losses: usize,
+}
+
+impl Tally {
+    pub fn samples(&self) -> usize {
+        self.wins + self.ties + self.losses
+    }
+
+    /// Ties count half
+    pub fn equity(&self) -> f64 {
+        (self.wins as f64 + self.ties as f64 / 2.0) / self.samples() as f64
+    }
+
+    /// Standard error of equity(), each sample scoring 1, 1/2 or 0
+    pub fn std_error(&self) -> f64 {
+        let n = self.samples() as f64;
+        let mean = self.equity();
+        let mean_square = (self.wins as f64 + self.ties as f64 / 4.0) / n;
+        ((mean_square - mean * mean).max(0.0) / n).sqrt()
+    }
+
+    pub fn add(&mut self, other: Tally) {
+        self.wins += other.wins;
+        self.ties += other.ties;
+        self.losses += other.losses;
+    }
+
+    /// equity() without rounding, for exhaustive counts that can be quoted exactly
+    pub fn exact_equity(&self) -> Ratio {
+        Ratio::new(2 * self.wins + self.ties, 2 * self.samples())
+    }
+}
+
+/// A fraction in lowest terms
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ratio {
+    pub numerator: usize,
+    pub denominator: usize,
+}
+
+impl Ratio {
+    pub fn new(numerator: usize, denominator: usize) -> Ratio {
+        debug_assert!(denominator > 0);
+        let gcd = gcd(numerator, denominator);
+        Ratio { numerator: numerator / gcd, denominator: denominator / gcd }
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+impl Display for Ratio {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+/// How an estimate was arrived at
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum Method {
+    /// Every runout and evil pair, so the equity is exact
+    Exhaustive,
+    MonteCarlo,
+}
+
+/// Equity estimate with a confidence interval around it
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Estimate {
+    pub method: Method,
+    pub tally: Tally,
+    pub equity: f64,
+    pub std_error: f64,
+    /// e.g. 0.95
+    pub confidence: f64,
+    pub low: f64,
+    pub high: f64,
+    /// Stopped early by a CancelToken, so only covers the runouts or samples done by then
+    pub cancelled: bool,
+}
+
+impl Estimate {
+    /// Normal approximation interval equity ± z * std_error, clamped to [0, 1]
+    pub fn new(tally: Tally, confidence: f64) -> Estimate {
+        Estimate::with_std_error(tally, tally.equity(), tally.std_error(), confidence)
+    }
+
+    /// Normal approximation interval for a sampling scheme with its own estimator and standard
+    /// error (e.g. stratified), rather than the mean of independent samples
+    pub fn with_std_error(tally: Tally, equity: f64, std_error: f64, confidence: f64) -> Estimate {
+        let z = z_score(confidence);
+        Estimate {
+            method: Method::MonteCarlo,
+            tally,
+            equity,
+            std_error,
+            confidence,
+            low: (equity - z * std_error).max(0.0),
+            high: (equity + z * std_error).min(1.0),
+            cancelled: false,
+        }
+    }
+
+    /// Wilson score interval, which stays sensible near 0% / 100% and for small samples
+    /// (ties count as half a win, as in the equity)
+    pub fn wilson(tally: Tally, confidence: f64) -> Estimate {
+        let equity = tally.equity();
+        let n = tally.samples() as f64;
+        let z = z_score(confidence);
+        let denominator = 1.0 + z * z / n;
+        let centre = (equity + z * z / (2.0 * n)) / denominator;
+        let spread = z / denominator * (equity * (1.0 - equity) / n + z * z / (4.0 * n * n)).sqrt();
+        Estimate {
+            method: Method::MonteCarlo,
+            tally,
+            equity,
+            std_error: tally.std_error(),
+            confidence,
+            low: (centre - spread).max(0.0),
+            high: (centre + spread).min(1.0),
+            cancelled: false,
+        }
+    }
+
+    /// Result of an exhaustive enumeration, so there's no uncertainty
+    pub fn exact(tally: Tally) -> Estimate {
+        let equity = tally.equity();
+        Estimate {
+            method: Method::Exhaustive,
+            tally,
+            equity,
+            std_error: 0.0,
+            confidence: 1.0,
+            low: equity,
+            high: equity,
+            cancelled: false,
+        }
+    }
+
+    pub fn half_width(&self) -> f64 {
+        (self.high - self.low) / 2.0
+    }
+}
+
+impl ToJson for Estimate {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("equity", self.equity.into()),
+            ("method", match self.method {
+                Method::Exhaustive => "exhaustive",
+                Method::MonteCarlo => "monte-carlo",
+            }.into()),
+            ("samples", self.tally.samples().into()),
+            ("wins", self.tally.wins.into()),
+            ("ties", self.tally.ties.into()),
+            ("losses", self.tally.losses.into()),
+            ("std_error", self.std_error.into()),
+            ("confidence", self.confidence.into()),
+            ("low", self.low.into()),
+            ("high", self.high.into()),
+            ("cancelled", self.cancelled.into()),
+        ])
+    }
+}
+
+/// Two sided z score for a confidence level, e.g. 0.95 -> 1.96
+pub fn z_score(confidence: f64) -> f64 {
+    debug_assert!(confidence > 0.0 && confidence < 1.0);
+    inverse_normal_cdf(0.5 + confidence / 2.0)
+}
+
+/// Acklam's rational approximation (relative error < 1.2e-9)
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [-3.969683028665376e1, 2.209460984245205e2, -2.759285104469687e2,
+                         1.38357751867269e2, -3.066479806614716e1, 2.506628277459239];
+    const B: [f64; 5] = [-5.447609879822406e1, 1.615858368580409e2, -1.556989798598866e2,
+                         6.680131188771972e1, -1.328068155288572e1];
+    const C: [f64; 6] = [-7.784894002430293e-3, -3.223964580411365e-1, -2.400758277161838,
+                         -2.549732539343734, 4.374664141464968, 2.938163982698783];
+    const D: [f64; 4] = [7.784695709041462e-3, 3.224671290700398e-1, 2.445134137142996, 3.754408661907416];
+    const P_LOW: f64 = 0.02425;
+
+    let tail = |q: f64| (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+        / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0);
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// When to stop sampling: once the interval is narrow enough, or a cap is hit
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StopRule {
+    /// Stop once the interval is within ± this, e.g. 0.005 for ±0.5%
+    pub half_width: f64,
+    pub confidence: f64,
+    /// Don't trust the interval before this many samples
+    pub min_samples: usize,
+    pub max_samples: usize,
+    pub max_time: Option<Duration>,
+}
+
+impl Default for StopRule {
+    /// ±0.5% at 95%
+    fn default() -> Self {
+        StopRule {
+            half_width: 0.005,
+            confidence: 0.95,
+            min_samples: 1000,
+            max_samples: 10_000_000,
+            max_time: None,
+        }
+    }
+}
+
+impl StopRule {
+    /// Whether an evaluation started at start can stop with this estimate
+    pub fn is_done(&self, estimate: &Estimate, start: Instant) -> bool {
+        let samples = estimate.tally.samples();
+        let converged = samples >= self.min_samples && estimate.half_width() <= self.half_width;
+        let out_of_samples = samples >= self.max_samples;
+        let out_of_time = self.max_time.is_some_and(|max_time| start.elapsed() >= max_time);
+        converged || out_of_samples || out_of_time
+    }
+}
+
+/// One random deal of the rest of the board and an evil pair from deck
+/// returns the outcome as a single sample tally
+pub fn sample_deal<R: Rng + ?Sized>(pair: &(Card, Card), board: &[Card], deck: &[Card], rng: &mut R) -> Tally {
+    sample_deal_vs(pair, &Villain::Random, board, deck, rng)
+}
+
+/// sample_deal where the evil pair may have known cards (not in deck),
+/// the rest of it being dealt at random
+pub fn sample_deal_vs<R: Rng + ?Sized>(pair: &(Card, Card), villain: &Villain, board: &[Card], deck: &[Card], rng: &mut R) -> Tally {
+    let (evil_pair, community) = deal_vs(villain, board, deck, rng);
+    showdown(pair, &evil_pair, &community)
+}
+
+/// Random evil pair (filling in any cards not known) and full board, without allocating: the
+/// few cards needed are picked from the deck by index, picking again any already picked
+fn deal_vs<R: Rng + ?Sized>(villain: &Villain, board: &[Card], deck: &[Card], rng: &mut R) -> ((Card, Card), [Card; 5]) {
+    let mut cards = [deck[0]; 7];
+    let known = match *villain {
+        Villain::Random => 0,
+        Villain::Exposed(card) => {
+            cards[0] = card;
+            1
+        }
+        Villain::Known(a, b) => {
+            cards[..2].copy_from_slice(&[a, b]);
+            2
+        }
+    };
+    let needed = 2 - known + 5 - board.len();
+    let mut picked = [0; 7];
+    for i in 0..needed {
+        picked[i] = loop {
+            let index = rng.random_range(0..deck.len());
+            if !picked[..i].contains(&index) {
+                break index;
+            }
+        };
+        cards[known + i] = deck[picked[i]];
+    }
+    let mut community = [deck[0]; 5];
+    community[..board.len()].copy_from_slice(board);
+    community[board.len()..].copy_from_slice(&cards[2..7 - board.len()]);
+    ((cards[0], cards[1]), community)
+}
+
+/// Single sample tally of pair against evil_pair on a full board
+fn showdown(pair: &(Card, Card), evil_pair: &(Card, Card), community: &[Card]) -> Tally {
+    let mut tally = Tally::default();
+    match get_best_score(pair, community).cmp(&get_best_score(evil_pair, community)) {
+        std::cmp::Ordering::Less => tally.wins += 1,
+        std::cmp::Ordering::Equal => tally.ties += 1,
+        std::cmp::Ordering::Greater => tally.losses += 1,
+    }
+    tally
+}
+
+/// Monte carlo equity against a random hand from any board length with a fixed
+/// number of independent deals, reporting the Wilson interval at the given confidence
+pub fn eval_monte_carlo<R: Rng + ?Sized>(pair: &(Card, Card), board: &[Card], n: usize, confidence: f64, rng: &mut R) -> Estimate {
+    debug_assert!(is_valid_deal(board, &[*pair]));
+    let mut deck: Vec<Card> = Card::get_deck();
+    deck.retain(|card| !board.contains(card) && *card != pair.0 && *card != pair.1);
+
+    let mut tally = Tally::default();
+    for _ in 0..n {
+        tally.add(sample_deal(pair, board, &deck, rng));
+    }
+    Estimate::wilson(tally, confidence)
+}
+
+/// How a pot was split when it was run several times:
+/// counts[k] is the number of trials that won share(k) of it
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PotShares {
+    pub runs: usize,
+    pub counts: Vec<usize>,
+}
+
+impl PotShares {
+    pub fn trials(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Each run is worth 1/runs of the pot and a tied run half that, so k halves of a run
+    pub fn share(&self, k: usize) -> f64 {
+        k as f64 / (2 * self.runs) as f64
+    }
+
+    pub fn probability(&self, k: usize) -> f64 {
+        self.counts[k] as f64 / self.trials() as f64
+    }
+
+    /// Average share of the pot, the same as the equity of running it once
+    pub fn equity(&self) -> f64 {
+        (0..self.counts.len()).map(|k| self.share(k) * self.probability(k)).sum()
+    }
+
+    /// Spread of the share won from one all-in to the next, which more runs shrink
+    pub fn std_dev(&self) -> f64 {
+        let mean = self.equity();
+        let variance: f64 = (0..self.counts.len())
+            .map(|k| (self.share(k) - mean).powi(2) * self.probability(k))
+            .sum();
+        variance.sqrt()
+    }
+}
+
+impl ToJson for PotShares {
+    fn to_json(&self) -> Json {
+        let shares = (0..self.counts.len())
+            .map(|k| Json::object([("share", self.share(k).into()), ("probability", self.probability(k).into())]))
+            .collect();
+        Json::object([
+            ("runs", self.runs.into()),
+            ("shares", Json::Array(shares)),
+            ("equity", self.equity().into()),
+            ("std_dev", self.std_dev().into()),
+        ])
+    }
+}
+
+/// Run it `runs` times: each trial deals the evil pair once, then deals the rest of the board
+/// `runs` times without putting cards back in between, as dealers do
+pub fn eval_run_it<R: Rng + ?Sized>(pair: &(Card, Card), villain: &Villain, board: &[Card], dead: &[Card],
+                                    runs: usize, trials: usize, rng: &mut R) -> PotShares {
+    let known = villain.known_cards();
+    debug_assert!(is_valid_deal_with(board, &[*pair], &[&known, dead].concat()));
+    debug_assert!(runs >= 1);
+    let mut deck = deck_without(&[board, dead, &known, &[pair.0, pair.1]].concat());
+    let per_run = 5 - board.len();
+    assert!(2 - known.len() + runs * per_run <= deck.len(), "not enough cards to run it {} times", runs);
+
+    let mut counts = vec![0; 2 * runs + 1];
+    let mut community = board.to_vec();
+    for _ in 0..trials {
+        let (dealt, _) = deck.partial_shuffle(rng, 2 - known.len() + runs * per_run);
+        let (hidden, runouts) = dealt.split_at(2 - known.len());
+        let evil = [&known[..], hidden].concat();
+        let evil_pair = (evil[0], evil[1]);
+
+        let mut halves = 0;
+        for run in 0..runs {
+            community.extend_from_slice(&runouts[run * per_run..(run + 1) * per_run]);
+            let tally = showdown(pair, &evil_pair, &community);
+            halves += 2 * tally.wins + tally.ties;
+            community.truncate(board.len());
+        }
+        counts[halves] += 1;
+    }
+    PotShares { runs, counts }
+}
+
+/// What a fold says about the two cards thrown away
+#[derive(Debug, PartialEq, Clone, Default)]
+pub enum FoldModel {
+    /// Nothing, they could be any two cards (the same as not dealing them at all)
+    #[default]
+    AnyTwo,
+    /// A combo from this range, as often as its weight says
+    Range(HandRange),
+}
+
+impl FoldModel {
+    /// Folds from a player who plays the top fraction of starting hands by preflop equity,
+    /// so what they fold is always from the rest
+    pub fn playing_top(fraction: f64) -> FoldModel {
+        let played = HandRange::top_on_board(&[], &[], fraction, Ranking::Equity);
+        FoldModel::Range(HandRange::from_combos(
+            HandRange::full().combos().map(|(pair, _)| pair).filter(|pair| !played.contains(pair)),
+        ))
+    }
+
+    /// A folded hand without any of the used cards, any two if the range has none left
+    fn deal<R: Rng + ?Sized>(&self, used: &[Card], rng: &mut R) -> (Card, Card) {
+        if let FoldModel::Range(range) = self
+            && let Some(pair) = range.sample(used, rng) {
+            return pair;
+        }
+        let cards: Vec<Card> = deck_without(used).choose_multiple(rng, 2).copied().collect();
+        (cards[0], cards[1])
+    }
+}
+
+/// Monte carlo against the villain after other players have folded: each folded hand is dealt
+/// from its fold model before the villain's cards and the board, so when folds are weak the
+/// low cards they took are less likely to come
+#[allow(clippy::too_many_arguments)]
+pub fn eval_with_folds<R: Rng + ?Sized>(pair: &(Card, Card), villain: &Villain, board: &[Card], dead: &[Card], folds: &[FoldModel],
+                                        rule: &StopRule, hooks: Hooks, rng: &mut R) -> Estimate {
+    let known = villain.known_cards();
+    debug_assert!(is_valid_deal_with(board, &[*pair], &[&known, dead].concat()));
+    let fixed = [board, &known, dead, &[pair.0, pair.1]].concat();
+
+    let start = Instant::now();
+    let mut tally = Tally::default();
+    loop {
+        for _ in 0..BATCH.min(rule.max_samples - tally.samples()) {
+            if hooks.is_cancelled() {
+                break;
+            }
+            let mut used = fixed.clone();
+            for fold in folds {
+                let (a, b) = fold.deal(&used, rng);
+                used.extend([a, b]);
+            }
+            tally.add(sample_deal_vs(pair, villain, board, &deck_without(&used), rng));
+        }
+        hooks.progress(tally.samples(), rule.max_samples);
+        let estimate = Estimate::wilson(tally, rule.confidence);
+        if hooks.is_cancelled() {
+            return Estimate { cancelled: true, ..estimate };
+        }
+        if rule.is_done(&estimate, start) {
+            return estimate;
+        }
+    }
+}
+
+/// Monte carlo equity against a villain for each range, every villain's hand dealt from their
+/// range around the cards already out (see deal_ranges). The pot is split between everyone tied for best, so a
+/// tie is worth less than half once more than two get there. An error if there are no ranges or
+/// they can't all be dealt around the cards out, e.g. two villains on aces with an ace in the
+/// hero's hand
+pub fn eval_vs_ranges<R: Rng + ?Sized>(pair: &(Card, Card), ranges: &[HandRange], board: &[Card], dead: &[Card],
+                                       rule: &StopRule, rng: &mut R) -> Result<Estimate, &'static str> {
+    stream_vs_ranges(pair, ranges, board, dead, rule, rng, |_| true)
+}
+
+/// eval_vs_ranges, handing on_estimate the estimate so far after every batch of samples so it
+/// can be shown converging (e.g. sent down a channel). Returning false stops early, the estimate
+/// returned then being marked cancelled
+pub fn stream_vs_ranges<R: Rng + ?Sized>(pair: &(Card, Card), ranges: &[HandRange], board: &[Card], dead: &[Card],
+                                         rule: &StopRule, rng: &mut R, mut on_estimate: impl FnMut(&Estimate) -> bool) -> Result<Estimate, &'static str> {
+    debug_assert!(is_valid_deal_with(board, &[*pair], dead));
+    if ranges.is_empty() {
+        return Err("Expected a range for at least one villain");
+    }
+    let fixed = [board, dead, &[pair.0, pair.1]].concat();
+    let samplers: Vec<RangeSampler> = ranges.iter().map(RangeSampler::new).collect();
+
+    let start = Instant::now();
+    let mut tally = Tally::default();
+    let (mut sum, mut sum_squares) = (0.0, 0.0);
+    loop {
+        for _ in 0..BATCH.min(rule.max_samples - tally.samples()) {
+            let share = deal_vs_ranges(pair, &samplers, board, &fixed, rng).ok_or("The ranges can't all be dealt around the cards out")?;
+            match share {
+                1.0 => tally.wins += 1,
+                0.0 => tally.losses += 1,
+                _ => tally.ties += 1,
+            }
+            sum += share;
+            sum_squares += share * share;
+        }
+        let n = tally.samples() as f64;
+        let equity = sum / n;
+        let std_error = ((sum_squares / n - equity * equity).max(0.0) / n).sqrt();
+        let mut estimate = Estimate::with_std_error(tally, equity, std_error, rule.confidence);
+        if rule.is_done(&estimate, start) {
+            return Ok(estimate);
+        }
+        if !on_estimate(&estimate) {
+            estimate.cancelled = true;
+            return Ok(estimate);
+        }
+    }
+}
+
+/// Deals of the villains' ranges thrown away before giving up, as they may clash every time
+const MAX_REJECTIONS: usize = 10_000;
+
+/// A hand for each villain from their range, all together as often as the weights say: each is
+/// picked on its own and the whole deal is thrown away if any cards clash, so no villain's range
+/// is cut down by the hands dealt before theirs. None if nothing fits after MAX_REJECTIONS deals
+pub fn deal_ranges<R: Rng + ?Sized>(samplers: &[RangeSampler], used: &[Card], rng: &mut R) -> Option<Vec<(Card, Card)>> {
+    let bit = |card: Card| 1u64 << usize::from(card);
+    let used_mask = used.iter().fold(0, |mask, &card| mask | bit(card));
+    'deal: for _ in 0..MAX_REJECTIONS {
+        let mut mask = used_mask;
+        let mut dealt = Vec::with_capacity(samplers.len());
+        for sampler in samplers {
+            let pair = sampler.sample(rng)?;
+            let cards = bit(pair.0) | bit(pair.1);
+            if mask & cards != 0 {
+                continue 'deal;
+            }
+            mask |= cards;
+            dealt.push(pair);
+        }
+        return Some(dealt);
+    }
+    None
+}
+
+/// One deal for eval_vs_ranges, the villains dealt by deal_ranges and then the rest of the board
+/// around the fixed cards (board, dead and the pair), returning the share of the pot won. None if
+/// the ranges can't be dealt. There has to be at least one sampler
+pub(crate) fn deal_vs_ranges<R: Rng + ?Sized>(pair: &(Card, Card), samplers: &[RangeSampler], board: &[Card], fixed: &[Card], rng: &mut R) -> Option<f64> {
+    let evil_pairs = deal_ranges(samplers, fixed, rng)?;
+    let used = [fixed, &evil_pairs.iter().flat_map(|&(a, b)| [a, b]).collect::<Vec<_>>()].concat();
+    let mut deck = deck_without(&used);
+    let (remainder, _) = deck.partial_shuffle(rng, 5 - board.len());
+    let community = [board, remainder].concat();
+
+    let score = get_best_score(pair, &community);
+    let scores: Vec<_> = evil_pairs.iter().map(|evil_pair| get_best_score(evil_pair, &community)).collect();
+    let best = *scores.iter().min().expect("no villains");
+    Some(match score.cmp(&best) {
+        std::cmp::Ordering::Less => 1.0,
+        std::cmp::Ordering::Equal => 1.0 / (scores.iter().filter(|&&s| s == best).count() + 1) as f64,
+        std::cmp::Ordering::Greater => 0.0,
+    })
+}
+
+/// Monte carlo equity against a random hand under any game's rules, dealing the evil hole cards
+/// and the rest of the board until the stop rule is met
+pub fn eval_game<R: Rng + ?Sized>(game: &impl GameRules, hole: &[Card], community: &[Card], dead: &[Card], rule: &StopRule, rng: &mut R) -> Estimate {
+    debug_assert!(is_valid_deal_with(community, &[], &[hole, dead].concat()));
+    let mut deck = game_deck_without(game, &[community, dead, hole].concat());
+    let needed = game.hole_cards() + 5 - community.len();
+
+    let start = Instant::now();
+    let mut tally = Tally::default();
+    let mut board = community.to_vec();
+    loop {
+        for _ in 0..BATCH.min(rule.max_samples - tally.samples()) {
+            let (cards, _) = deck.partial_shuffle(rng, needed);
+            let (evil_hole, runout) = cards.split_at(game.hole_cards());
+            board.extend_from_slice(runout);
+            match game.best_score(hole, &board).cmp(&game.best_score(evil_hole, &board)) {
+                std::cmp::Ordering::Less => tally.wins += 1,
+                std::cmp::Ordering::Equal => tally.ties += 1,
+                std::cmp::Ordering::Greater => tally.losses += 1,
+            }
+            board.truncate(community.len());
+        }
+        let estimate = Estimate::wilson(tally, rule.confidence);
+        if rule.is_done(&estimate, start) {
+            return estimate;
+        }
+    }
+}
+
+/// Exact equity against a random hand under any game's rules, enumerating every runout and every
+/// evil hole the rest of the deck could make. Only practical when that's not many
+pub fn eval_game_exhaustive(game: &impl GameRules, hole: &[Card], community: &[Card], dead: &[Card]) -> Estimate {
+    debug_assert!(is_valid_deal_with(community, &[], &[hole, dead].concat()));
+    let deck = game_deck_without(game, &[community, dead, hole].concat());
+    let evil_holes: Vec<Vec<Card>> = deck.iter().copied().combinations(game.hole_cards()).collect();
+
+    let mut tally = Tally::default();
+    for_each_runout(community, &deck, &[hole, community, dead], |board, remainder, weight| {
+        let my_score = game.best_score(hole, board);
+        for evil_hole in &evil_holes {
+            if evil_hole.iter().any(|card| remainder.contains(card)) {
+                continue;
+            }
+            match my_score.cmp(&game.best_score(evil_hole, board)) {
+                std::cmp::Ordering::Less => tally.wins += weight,
+                std::cmp::Ordering::Equal => tally.ties += weight,
+                std::cmp::Ordering::Greater => tally.losses += weight,
+            }
+        }
+    });
+    Estimate::exact(tally)
+}
+
+/// Samples checked between looks at the stopping rule
+const BATCH: usize = 256;
+
+/// How far through an evaluation is: runouts enumerated or samples dealt,
+/// out of every runout or the stop rule's max_samples
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Ways for a caller to watch or stop a long evaluation, all optional
+#[derive(Clone, Copy, Default)]
+pub struct Hooks<'a> {
+    /// Called from whichever thread finished some work, so it has to be Sync
+    pub on_progress: Option<&'a (dyn Fn(Progress) + Sync)>,
+    /// Checked between runouts and samples, the evaluation returning what it has so far once cancelled
+    pub cancel: Option<&'a CancelToken>,
+}
+
+impl Hooks<'_> {
+    fn progress(&self, done: usize, total: usize) {
+        if let Some(on_progress) = self.on_progress {
+            on_progress(Progress { done, total });
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        is_cancelled(self.cancel)
+    }
+}
+
+/// Monte carlo equity against a random hand from any board length,
+/// sampling until the stop rule is satisfied
+pub fn eval_until<R: Rng + ?Sized>(pair: &(Card, Card), board: &[Card], rule: &StopRule, rng: &mut R) -> Estimate {
+    eval_until_threaded(pair, &Villain::Random, board, &[], rule, 1, BATCH, Hooks::default(), rng)
+}
+
+/// eval_until against what's known of the villain with dead cards out of the deck, sampling on several
+/// threads (each seeded from rng) with each thread dealing up to batch samples between looks at the
+/// stop rule (and reports of progress)
+#[allow(clippy::too_many_arguments)]
+pub fn eval_until_threaded<R: Rng + ?Sized>(pair: &(Card, Card), villain: &Villain, board: &[Card], dead: &[Card], rule: &StopRule,
+                                            threads: usize, batch: usize, hooks: Hooks, rng: &mut R) -> Estimate {
+    let known = villain.known_cards();
+    debug_assert!(is_valid_deal_with(board, &[*pair], &[&known, dead].concat()));
+    let deck = deck_without(&[board, &known, dead, &[pair.0, pair.1]].concat());
+
+    let threads = threads.max(1);
+    let mut rngs: Vec<StdRng> = (0..threads).map(|_| StdRng::seed_from_u64(rng.random())).collect();
+
+    let start = Instant::now();
+    let mut tally = Tally::default();
+    loop {
+        // share out this round's samples as evenly as possible, never going over max_samples
+        let round = (batch.max(1) * threads).min(rule.max_samples - tally.samples());
+        let deal = |n: usize, rng: &mut StdRng| {
+            let mut tally = Tally::default();
+            for _ in 0..n {
+                if hooks.is_cancelled() {
+                    break;
+                }
+                tally.add(sample_deal_vs(pair, villain, board, &deck, rng));
+            }
+            tally
+        };
+        if threads == 1 {
+            tally.add(deal(round, &mut rngs[0]));
+        } else {
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = rngs.iter_mut()
+                    .enumerate()
+                    .map(|(i, rng)| {
+                        let n = round / threads + usize::from(i < round % threads);
+                        scope.spawn(move || deal(n, rng))
+                    })
+                    .collect();
+                for handle in handles {
+                    tally.add(handle.join().unwrap());
+                }
+            });
+        }
+        hooks.progress(tally.samples(), rule.max_samples);
+        let estimate = Estimate::new(tally, rule.confidence);
+        if hooks.is_cancelled() {
+            return Estimate { cancelled: true, ..estimate };
+        }
+        if rule.is_done(&estimate, start) {
+            return estimate;
+        }
+    }
+}
+
+/// Exact equity (ties count half) over every runout and evil pair the villain could have,
+/// with threads taking chunks of the runout index space until there are none left
+pub fn eval_exhaustive(pair: &(Card, Card), villain: &Villain, board: &[Card], dead: &[Card], threads: usize, hooks: Hooks) -> Estimate {
+    let total = villain.runouts(board.len(), dead.len());
+    let threads = threads.clamp(1, total.max(1));
+    // small enough chunks for the threads to finish together and progress to move smoothly
+    let chunk = total.div_ceil(threads * 100).max(1);
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+
+    let work = || {
+        let mut tally = Tally::default();
+        loop {
+            let start = next.fetch_add(chunk, Ordering::Relaxed);
+            if start >= total || hooks.is_cancelled() {
+                return tally;
+            }
+            let end = (start + chunk).min(total);
+            let (wins, ties, losses) = tally_runout_range_vs(board, pair, villain, dead, start..end, hooks.cancel);
+            tally.add(Tally { wins, ties, losses });
+
+            let done = done.fetch_add(end - start, Ordering::Relaxed) + end - start;
+            hooks.progress(done, total);
+        }
+    };
+
+    let mut tally = Tally::default();
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads).map(|_| scope.spawn(work)).collect();
+        for handle in handles {
+            tally.add(handle.join().unwrap());
+        }
+    });
+    // the tally is only short of every deal if a runout got skipped
+    let cancelled = tally.samples() < total * villain.evil_pairs_per_runout(dead.len());
+    Estimate { cancelled, ..Estimate::exact(tally) }
+}
+
+/// Rounds of eval_stratified before their spread is trusted for the standard error
+const MIN_ROUNDS: usize = 20;
+
+/// Monte carlo stratified over the evil pairs: each round deals one random runout to every
+/// evil pair the villain could have, taking out the variance from an unlucky spread of villain
+/// hands. Against a random hand most of the variance is in the board so the gain is modest,
+/// it's bigger when villain's holding decides more (e.g. an exposed card).
+/// Only whole rounds are dealt, and the standard error comes from how much rounds vary
+pub fn eval_stratified<R: Rng + ?Sized>(pair: &(Card, Card), villain: &Villain, board: &[Card], dead: &[Card],
+                                        rule: &StopRule, hooks: Hooks, rng: &mut R) -> Estimate {
+    let known = villain.known_cards();
+    debug_assert!(is_valid_deal_with(board, &[*pair], &[&known, dead].concat()));
+    let deck = deck_without(&[board, &known, dead, &[pair.0, pair.1]].concat());
+    let evil_pairs = villain.evil_pairs(&deck);
+    let needed = 5 - board.len();
+
+    let start = Instant::now();
+    let mut tally = Tally::default();
+    let mut community = board.to_vec();
+    // sums of each round's equity and its square
+    let (mut sum, mut sum_squares, mut rounds) = (0.0, 0.0, 0);
+    loop {
+        let mut round = Tally::default();
+        for evil_pair in &evil_pairs {
+            if hooks.is_cancelled() {
+                break;
+            }
+            let runout = loop {
+                let runout: Vec<Card> = deck.choose_multiple(rng, needed).copied().collect();
+                if !runout.contains(&evil_pair.0) && !runout.contains(&evil_pair.1) {
+                    break runout;
+                }
+            };
+            community.extend_from_slice(&runout);
+            round.add(showdown(pair, evil_pair, &community));
+            community.truncate(board.len());
+        }
+        if hooks.is_cancelled() {
+            tally.add(round);
+            return Estimate { cancelled: true, ..Estimate::new(tally, rule.confidence) };
+        }
+        tally.add(round);
+        sum += round.equity();
+        sum_squares += round.equity() * round.equity();
+        rounds += 1;
+        hooks.progress(tally.samples(), rule.max_samples);
+
+        // with proportional strata the independent samples' standard error is an upper bound,
+        // which is safer than the spread of a handful of rounds
+        let std_error = if rounds < MIN_ROUNDS {
+            tally.std_error()
+        } else {
+            let mean = sum / rounds as f64;
+            ((sum_squares - rounds as f64 * mean * mean).max(0.0) / (rounds - 1) as f64 / rounds as f64).sqrt()
+        };
+        let estimate = Estimate::with_std_error(tally, tally.equity(), std_error, rule.confidence);
+
+        let converged = tally.samples() >= rule.min_samples && estimate.half_width() <= rule.half_width;
+        let out_of_samples = tally.samples() + evil_pairs.len() > rule.max_samples;
+        let out_of_time = rule.max_time.is_some_and(|max_time| start.elapsed() >= max_time);
+        if converged || out_of_samples || out_of_time {
+            return estimate;
+        }
+    }
+}
+
+/// Best equity estimate against the villain available within the time budget:
+/// exact if enumerating every runout looks like it will fit (judging by how long the first
+/// runouts take), otherwise Monte Carlo for the rest of the budget with a 95% interval
+pub fn eval_with_budget<R: Rng + ?Sized>(pair: &(Card, Card), villain: &Villain, board: &[Card], dead: &[Card],
+                                         budget: Duration, hooks: Hooks, rng: &mut R) -> Estimate {
+    // building the score table shouldn't count towards how long enumerating takes
+    LazyLock::force(&SCORES);
+    let start = Instant::now();
+    let total = villain.runouts(board.len(), dead.len());
+
+    // enumerate in doubling chunks, giving up as soon as the whole lot looks too slow
+    let mut tally = Tally::default();
+    let mut done = 0;
+    let mut chunk = 1;
+    while done < total {
+        let end = (done + chunk).min(total);
+        let (wins, ties, losses) = tally_runout_range_vs(board, pair, villain, dead, done..end, hooks.cancel);
+        tally.add(Tally { wins, ties, losses });
+        done = end;
+        chunk *= 2;
+        hooks.progress(done, total);
+        if hooks.is_cancelled() {
+            return Estimate { cancelled: true, ..Estimate::exact(tally) };
+        }
+
+        let projected = start.elapsed().mul_f64(total as f64 / done as f64);
+        if done < total && projected > budget {
+            let rule = StopRule {
+                half_width: 0.0,
+                max_samples: usize::MAX,
+                max_time: Some(budget.saturating_sub(start.elapsed())),
+                ..StopRule::default()
+            };
+            return eval_until_threaded(pair, villain, board, dead, &rule, 1, BATCH, hooks, rng);
+        }
+    }
+    Estimate::exact(tally)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combinatorics::*;
+
+    #[test]
+    fn test_z_score() {
+        assert!((z_score(0.95) - 1.959964).abs() < 1e-5);
+        assert!((z_score(0.99) - 2.575829).abs() < 1e-5);
+        assert!((z_score(0.6827) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_wilson() {
+        let tally = Tally { wins: 50, ties: 0, losses: 50 };
+        let estimate = Estimate::wilson(tally, 0.95);
+        assert!((estimate.low - 0.4038).abs() < 1e-4);
+        assert!((estimate.high - 0.5962).abs() < 1e-4);
+        assert!((estimate.std_error - 0.05).abs() < 1e-9);
+
+        // never wins: the interval still has some width above 0
+        let estimate = Estimate::wilson(Tally { wins: 0, ties: 0, losses: 20 }, 0.95);
+        assert_eq!(estimate.low, 0.0);
+        assert!(estimate.high > 0.1);
+    }
+
+    #[test]
+    fn test_eval_monte_carlo() {
+        let pair = (Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::Ace, Suit::Spades));
+        let mut rng = StdRng::seed_from_u64(7);
+        let estimate = eval_monte_carlo(&pair, &[], 400, 0.99, &mut rng);
+        assert_eq!(estimate.tally.samples(), 400);
+        assert!(estimate.low < 0.852 && 0.852 < estimate.high, "{:?}", estimate);
+    }
+
+    #[test]
+    fn test_eval_until() {
+        let board = parse_cards("AhKh4s9c").unwrap();
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        let rule = StopRule { half_width: 0.02, ..StopRule::default() };
+        let estimate = eval_until(&pair, &board, &rule, &mut rand::rng());
+
+        assert!(estimate.half_width() <= 0.02);
+        assert!(estimate.low <= estimate.equity && estimate.equity <= estimate.high);
+        // exact answer is 14002 / 45540
+        assert!((estimate.equity - 0.3075).abs() < 0.06);
+
+        let capped = StopRule { half_width: 0.0, max_samples: 500, ..StopRule::default() };
+        assert_eq!(eval_until(&pair, &board, &capped, &mut rand::rng()).tally.samples(), 500);
+    }
+
+    #[test]
+    fn test_threaded() {
+        let board = parse_cards("AhKh4s9c").unwrap();
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+
+        let exact = eval_exhaustive(&pair, &Villain::Random, &board, &[], 3, Hooks::default());
+        assert_eq!(exact, eval_exhaustive(&pair, &Villain::Random, &board, &[], 1, Hooks::default()));
+        assert_eq!(exact.tally.samples(), 46 * choose(45, 2));
+        let (win, lose) = eval_with_community(board.clone(), &pair);
+        assert_eq!((exact.tally.wins, exact.tally.ties + exact.tally.losses), (win, lose));
+
+        let capped = StopRule { half_width: 0.0, max_samples: 1001, ..StopRule::default() };
+        let estimate = eval_until_threaded(&pair, &Villain::Random, &board, &[], &capped, 3, 64, Hooks::default(), &mut StdRng::seed_from_u64(1));
+        assert_eq!(estimate.tally.samples(), 1001);
+        assert!(estimate.low < exact.equity && exact.equity < estimate.high, "{:?}", estimate);
+    }
+
+    #[test]
+    fn test_progress() {
+        let board = parse_cards("AhKh4s9c").unwrap();
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        let reports = std::sync::Mutex::new(Vec::new());
+        let record = |progress: Progress| reports.lock().unwrap().push(progress);
+
+        eval_exhaustive(&pair, &Villain::Random, &board, &[], 2, Hooks { on_progress: Some(&record), ..Hooks::default() });
+        let mut reports = std::mem::take(&mut *reports.lock().unwrap());
+        reports.sort_by_key(|progress| progress.done);
+        assert_eq!(reports.len(), 46);
+        assert_eq!(reports.last(), Some(&Progress { done: 46, total: 46 }));
+
+        let rule = StopRule { half_width: 0.0, max_samples: 1000, ..StopRule::default() };
+        let last = std::sync::Mutex::new(None);
+        let on_progress = |progress: Progress| *last.lock().unwrap() = Some(progress);
+        let hooks = Hooks { on_progress: Some(&on_progress), ..Hooks::default() };
+        eval_until_threaded(&pair, &Villain::Random, &board, &[], &rule, 1, 100, hooks, &mut rand::rng());
+        assert_eq!(*last.lock().unwrap(), Some(Progress { done: 1000, total: 1000 }));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let board = parse_cards("AhKh4s9c").unwrap();
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        let cancel = CancelToken::new();
+
+        // cancel partway through, from the progress callback
+        let on_progress = |progress: Progress| if progress.done >= 10 { cancel.cancel() };
+        let hooks = Hooks { on_progress: Some(&on_progress), cancel: Some(&cancel) };
+        let estimate = eval_exhaustive(&pair, &Villain::Random, &board, &[], 1, hooks);
+        assert!(estimate.cancelled);
+        assert!(estimate.tally.samples() > 0 && estimate.tally.samples() < 46 * 990);
+        assert!(!eval_exhaustive(&pair, &Villain::Random, &board, &[], 1, Hooks::default()).cancelled);
+
+        // already cancelled: nothing is dealt
+        let rule = StopRule::default();
+        let hooks = Hooks { cancel: Some(&cancel), ..Hooks::default() };
+        let estimate = eval_until_threaded(&pair, &Villain::Random, &board, &[], &rule, 2, BATCH, hooks, &mut rand::rng());
+        assert!(estimate.cancelled);
+        assert_eq!(estimate.tally.samples(), 0);
+    }
+
+    #[test]
+    fn test_stratified() {
+        let pair = (Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::Ace, Suit::Spades));
+        let rule = StopRule { half_width: 0.0, max_samples: 10 * 1225, ..StopRule::default() };
+        let mut rng = StdRng::seed_from_u64(3);
+        let stratified = eval_stratified(&pair, &Villain::Random, &[], &[], &rule, Hooks::default(), &mut rng);
+        assert_eq!(stratified.tally.samples(), 10 * 1225);
+        assert!(stratified.low < 0.852 && 0.852 < stratified.high, "{:?}", stratified);
+
+        // on the river every round is the same, so there's nothing left to estimate
+        let board = parse_cards("AhKh4s9c5d").unwrap();
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        let rule = StopRule { half_width: 0.0, max_samples: MIN_ROUNDS * 990, ..StopRule::default() };
+        let estimate = eval_stratified(&pair, &Villain::Random, &board, &[], &rule, Hooks::default(), &mut rng);
+        assert_eq!(estimate.std_error, 0.0);
+        assert_eq!(estimate.equity, eval_exhaustive(&pair, &Villain::Random, &board, &[], 1, Hooks::default()).equity);
+    }
+
+    #[test]
+    fn test_dead_cards() {
+        let board = parse_cards("AhKh4s9c").unwrap();
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        // most of the hearts are mucked, so the flush draw is worth a lot less
+        let dead = parse_cards("5h6h7h8hTh").unwrap();
+
+        let exact = eval_exhaustive(&pair, &Villain::Random, &board, &dead, 2, Hooks::default());
+        assert_eq!(exact.tally.samples(), 41 * choose(40, 2));
+        assert!(exact.equity < eval_exhaustive(&pair, &Villain::Random, &board, &[], 1, Hooks::default()).equity);
+
+        let rule = StopRule { half_width: 0.0, max_samples: 2000, ..StopRule::default() };
+        let estimate = eval_until_threaded(&pair, &Villain::Random, &board, &dead, &rule, 1, BATCH, Hooks::default(), &mut StdRng::seed_from_u64(5));
+        assert!(estimate.low < exact.equity && exact.equity < estimate.high, "{:?}", estimate);
+    }
+
+    #[test]
+    fn test_exposed_villain() {
+        // villain shows the Ah, taking away a heart and most of our two pair outs
+        let board = parse_cards("Kh9h4s2c").unwrap();
+        let pair = (Card::new(Rank::Queen, Suit::Hearts), Card::new(Rank::Jack, Suit::Hearts));
+        let villain = Villain::Exposed(Card::new(Rank::Ace, Suit::Hearts));
+
+        let exact = eval_exhaustive(&pair, &villain, &board, &[], 2, Hooks::default());
+        assert_eq!(exact.tally.samples(), 45 * 44);
+        assert!(exact.equity < eval_exhaustive(&pair, &Villain::Random, &board, &[], 1, Hooks::default()).equity);
+
+        let rule = StopRule { half_width: 0.0, max_samples: 2000, ..StopRule::default() };
+        let estimate = eval_until_threaded(&pair, &villain, &board, &[], &rule, 1, BATCH, Hooks::default(), &mut StdRng::seed_from_u64(3));
+        assert!(estimate.low < exact.equity && exact.equity < estimate.high, "{:?}", estimate);
+    }
+
+    #[test]
+    fn test_run_it() {
+        let board = parse_cards("Kh9h4s2c").unwrap();
+        let pair = (Card::new(Rank::Queen, Suit::Hearts), Card::new(Rank::Jack, Suit::Hearts));
+        let villain = Villain::Known(Card::new(Rank::King, Suit::Spades), Card::new(Rank::Ten, Suit::Clubs));
+        let exact = eval_exhaustive(&pair, &villain, &board, &[], 1, Hooks::default());
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let once = eval_run_it(&pair, &villain, &board, &[], 1, 4000, &mut rng);
+        let twice = eval_run_it(&pair, &villain, &board, &[], 2, 4000, &mut rng);
+        assert_eq!(twice.counts.len(), 5);
+        assert_eq!(twice.trials(), 4000);
+        // the same equity with less variance
+        for shares in [&once, &twice] {
+            assert!((shares.equity() - exact.equity).abs() < 0.03, "{:?}", shares);
+        }
+        assert!(twice.std_dev() < once.std_dev());
+
+        // 44 cards left can't be dealt 45 times
+        assert!(std::panic::catch_unwind(|| eval_run_it(&pair, &villain, &board, &[], 45, 1, &mut rand::rng())).is_err());
+    }
+
+    #[test]
+    fn test_eval_with_folds() {
+        let board = parse_cards("Kh9h4s2c").unwrap();
+        let pair = (Card::new(Rank::Queen, Suit::Hearts), Card::new(Rank::Jack, Suit::Hearts));
+        let rule = StopRule { half_width: 0.0, max_samples: 3000, ..StopRule::default() };
+        let mut rng = StdRng::seed_from_u64(6);
+
+        // a fold that can only be one hand is the same as those cards being dead
+        let aces = FoldModel::Range(HandRange::from_classes(["AA".parse().unwrap()]).without(&[Card::new(Rank::Ace, Suit::Clubs), Card::new(Rank::Ace, Suit::Diamonds)]));
+        let dead = [Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::Ace, Suit::Spades)];
+        let exact = eval_exhaustive(&pair, &Villain::Random, &board, &dead, 1, Hooks::default());
+        let estimate = eval_with_folds(&pair, &Villain::Random, &board, &[], &[aces], &rule, Hooks::default(), &mut rng);
+        assert!(estimate.low < exact.equity && exact.equity < estimate.high, "{:?} {:?}", estimate, exact);
+
+        let exact = eval_exhaustive(&pair, &Villain::Random, &board, &[], 1, Hooks::default());
+        let estimate = eval_with_folds(&pair, &Villain::Random, &board, &[], &vec![FoldModel::AnyTwo; 3], &rule, Hooks::default(), &mut rng);
+        assert!(estimate.low < exact.equity && exact.equity < estimate.high, "{:?} {:?}", estimate, exact);
+
+        let FoldModel::Range(folds) = FoldModel::playing_top(0.2) else { unreachable!() };
+        assert_eq!(folds.len(), 1326 - 265);
+        assert!(!folds.contains(&dead.into()));
+    }
+
+    #[test]
+    fn test_deal_ranges() {
+        let sampler = |range: &str| RangeSampler::new(&range.parse().unwrap());
+        let used = [Card::new(Rank::Ace, Suit::Hearts)];
+        let mut rng = StdRng::seed_from_u64(2);
+        // with three aces left, both can't have aces
+        for _ in 0..200 {
+            let dealt = deal_ranges(&[sampler("AA,KK"), sampler("AA")], &used, &mut rng).unwrap();
+            assert_eq!((dealt[0].0.rank, dealt[1].0.rank), (Rank::King, Rank::Ace));
+            assert!(!used.contains(&dealt[1].0) && !used.contains(&dealt[1].1));
+        }
+        assert_eq!(deal_ranges(&[sampler("AA"), sampler("AA")], &used, &mut rng), None);
+
+        let pair = (used[0], Card::new(Rank::King, Suit::Hearts));
+        let rule = StopRule { half_width: 0.0, max_samples: 1000, ..StopRule::default() };
+        let aces: HandRange = "AA".parse().unwrap();
+        assert!(eval_vs_ranges(&pair, &[aces.clone(), aces.clone()], &[], &[], &rule, &mut rng).is_err());
+        assert_eq!(eval_vs_ranges(&pair, &[], &[], &[], &rule, &mut rng).unwrap_err(), "Expected a range for at least one villain");
+        assert_eq!(eval_vs_ranges(&pair, &[aces], &[], &[], &rule, &mut rng).unwrap().tally.samples(), 1000);
+    }
+
+    #[test]
+    fn test_exact_equity() {
+        let tally = Tally { wins: 3, ties: 2, losses: 5 };
+        assert_eq!(tally.exact_equity(), Ratio { numerator: 2, denominator: 5 });
+        assert_eq!(tally.exact_equity().to_f64(), tally.equity());
+        assert_eq!(tally.exact_equity().to_string(), "2/5");
+        assert_eq!(Tally { wins: 0, ties: 0, losses: 4 }.exact_equity(), Ratio { numerator: 0, denominator: 1 });
+
+        let board = parse_cards("AhKh4s9c").unwrap();
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        let exact = eval_exhaustive(&pair, &Villain::Random, &board, &[], 1, Hooks::default());
+        assert_eq!(exact.tally.exact_equity().to_f64(), exact.equity);
+    }
+
+    #[test]
+    fn test_eval_with_budget() {
+        // a single runout on the river is quick to enumerate
+        let board = parse_cards("AhKh4s9c5d").unwrap();
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        let estimate = eval_with_budget(&pair, &Villain::Random, &board, &[], Duration::from_secs(10), Hooks::default(), &mut rand::rng());
+        assert_eq!(estimate.method, Method::Exhaustive);
+        assert_eq!(estimate.tally.samples(), choose(45, 2));
+
+        // every preflop runout is not
+        let start = Instant::now();
+        let estimate = eval_with_budget(&pair, &Villain::Random, &[], &[], Duration::from_millis(200), Hooks::default(), &mut rand::rng());
+        assert_eq!(estimate.method, Method::MonteCarlo);
+        assert!(estimate.tally.samples() > 0);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,119 @@
+//! Narrowing a player's range by what they do: each action they take is modelled by how likely
+//! it is with each combo, and the range is weighted by that (Bayes' rule) to give the range
+//! they're likely to hold now
+
+use crate::card::*;
+use crate::range::*;
+
+
+/// Which hands a player takes an action with
+#[derive(Debug, PartialEq, Clone)]
+pub enum ActionModel {
+    /// The best fraction of their range by weight, e.g. "raises the top 15%"
+    Top(f64, Ranking),
+    /// All but the best fraction of their range, e.g. calling with what they don't raise
+    AllBut(f64, Ranking),
+    /// Each combo as often as its weight in this range
+    Range(HandRange),
+}
+
+impl ActionModel {
+    /// How likely the action is with each combo of the range still possible on the board.
+    /// The combo the fraction ends in is only partly in it
+    pub fn likelihoods(&self, range: &HandRange, board: &[Card], dead: &[Card]) -> Vec<((Card, Card), f64)> {
+        let (fraction, ranking, inverted) = match self {
+            ActionModel::Top(fraction, ranking) => (*fraction, *ranking, false),
+            ActionModel::AllBut(fraction, ranking) => (*fraction, *ranking, true),
+            ActionModel::Range(model) => {
+                let live = range.without(&[board, dead].concat());
+                return live.combos().map(|(pair, _)| (pair, model.weight(&pair))).collect();
+            }
+        };
+        debug_assert!((0.0..=1.0).contains(&fraction));
+
+        let ranked: Vec<((Card, Card), f64)> = ranked_combos(board, dead, ranking)
+            .into_iter()
+            .map(|pair| (pair, range.weight(&pair)))
+            .filter(|&(_, weight)| weight > 0.0)
+            .collect();
+        let mut remaining = fraction * ranked.iter().map(|(_, weight)| weight).sum::<f64>();
+        ranked
+            .into_iter()
+            .map(|(pair, weight)| {
+                let taken = remaining.min(weight);
+                remaining -= taken;
+                let top = taken / weight;
+                (pair, if inverted { 1.0 - top } else { top })
+            })
+            .collect()
+    }
+}
+
+/// What a player did on a board
+#[derive(Debug, PartialEq, Clone)]
+pub struct Observation {
+    pub board: Vec<Card>,
+    pub action: ActionModel,
+}
+
+/// The range after seeing the action on the board: each live combo's weight times how likely the
+/// action is with it, scaled so the likeliest combo is at full weight. Empty if no combo in the
+/// range would take it
+pub fn narrow(range: &HandRange, board: &[Card], dead: &[Card], action: &ActionModel) -> HandRange {
+    let posterior: Vec<((Card, Card), f64)> = action
+        .likelihoods(range, board, dead)
+        .into_iter()
+        .map(|(pair, likelihood)| (pair, range.weight(&pair) * likelihood))
+        .collect();
+    let most = posterior.iter().map(|&(_, weight)| weight).fold(0.0, f64::max);
+
+    let mut narrowed = HandRange::new();
+    if most > 0.0 {
+        for (pair, weight) in posterior {
+            narrowed.insert(pair, (weight / most).min(1.0));
+        }
+    }
+    narrowed
+}
+
+/// The range after each observation in turn, e.g. a preflop raise then a flop check
+pub fn narrow_by(range: &HandRange, observations: &[Observation], dead: &[Card]) -> HandRange {
+    observations.iter().fold(range.clone(), |range, observation| {
+        narrow(&range, &observation.board, dead, &observation.action)
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::*;
+
+    #[test]
+    fn test_narrow() {
+        let class = |s: &str| s.parse::<HandClass>().unwrap();
+        let raised = narrow(&HandRange::full(), &[], &[], &ActionModel::Top(0.1, Ranking::Equity));
+        assert!((raised.total_weight() - 132.6).abs() < 1e-9);
+        assert!(class("AA").combos().into_iter().all(|pair| raised.weight(&pair) == 1.0));
+        assert!(!class("72o").combos().into_iter().any(|pair| raised.contains(&pair)));
+
+        // checking the flop with the sets and overpairs taken out
+        let board = parse_cards("Kh7d2c").unwrap();
+        let checked = narrow(&raised, &board, &[], &ActionModel::AllBut(0.3, Ranking::MadeHand));
+        assert!(checked.total_weight() < raised.without(&board).total_weight());
+        assert!(!class("KK").combos().into_iter().any(|pair| checked.contains(&pair)));
+        assert!(class("AQs").combos().into_iter().all(|pair| checked.weight(&pair) == 1.0));
+
+        let observations = [
+            Observation { board: vec![], action: ActionModel::Top(0.1, Ranking::Equity) },
+            Observation { board: board.clone(), action: ActionModel::AllBut(0.3, Ranking::MadeHand) },
+        ];
+        assert_eq!(narrow_by(&HandRange::full(), &observations, &[]), checked);
+
+        let model = ActionModel::Range("AA:0.5,KK".parse().unwrap());
+        let shoved = narrow(&raised, &[], &[], &model);
+        assert_eq!(shoved.len(), 12);
+        assert_eq!(shoved.total_weight(), 9.0);
+        assert!(narrow(&shoved, &[], &[], &ActionModel::Range("QQ".parse().unwrap())).is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+//! The arithmetic of facing a bet: what a call needs to be worth it, how often to defend, and
+//! how often a bluff has to work. Amounts can be in chips or big blinds, the pot being what was
+//! there before the bet
+
+use crate::json::*;
+use std::fmt::{Display, Formatter};
+
+
+/// What the pot lays a call, as x to 1
+pub fn pot_odds(pot: f64, bet: f64) -> f64 {
+    (pot + bet) / bet
+}
+
+/// Share of the pot after calling that the call is, the equity it needs to break even
+pub fn required_equity(pot: f64, bet: f64) -> f64 {
+    bet / (pot + 2.0 * bet)
+}
+
+/// Minimum defense frequency: how much of their range the player facing the bet has to continue
+/// with so that betting any two cards doesn't profit
+pub fn minimum_defense(pot: f64, bet: f64) -> f64 {
+    pot / (pot + bet)
+}
+
+/// How often a bluff needs to get a fold to break even
+pub fn bluff_break_even(pot: f64, bet: f64) -> f64 {
+    bet / (pot + bet)
+}
+
+/// What calling wins on average with this much equity, against folding, if nothing more goes in
+pub fn call_ev(pot: f64, bet: f64, equity: f64) -> f64 {
+    equity * (pot + 2.0 * bet) - bet
+}
+
+/// All of the above for one bet
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Odds {
+    pub pot: f64,
+    pub bet: f64,
+    pub pot_odds: f64,
+    pub required_equity: f64,
+    pub minimum_defense: f64,
+    pub bluff_break_even: f64,
+    /// With the equity, if one was given
+    pub call_ev: Option<f64>,
+}
+
+impl Odds {
+    pub fn new(pot: f64, bet: f64, equity: Option<f64>) -> Result<Odds, &'static str> {
+        if !(pot >= 0.0 && bet > 0.0) {
+            return Err("The bet has to be positive and the pot can't be negative");
+        }
+        if equity.is_some_and(|equity| !(0.0..=1.0).contains(&equity)) {
+            return Err("Equity has to be between 0 and 1");
+        }
+        Ok(Odds {
+            pot,
+            bet,
+            pot_odds: pot_odds(pot, bet),
+            required_equity: required_equity(pot, bet),
+            minimum_defense: minimum_defense(pot, bet),
+            bluff_break_even: bluff_break_even(pot, bet),
+            call_ev: equity.map(|equity| call_ev(pot, bet, equity)),
+        })
+    }
+}
+
+impl Display for Odds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} into {}", self.bet, self.pot)?;
+        if self.pot > 0.0 {
+            write!(f, " ({:.0}% pot)", self.bet / self.pot * 100.0)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "pot odds          {:.2} to 1", self.pot_odds)?;
+        writeln!(f, "equity to call    {:.1}%", self.required_equity * 100.0)?;
+        writeln!(f, "defend at least   {:.1}%", self.minimum_defense * 100.0)?;
+        write!(f, "bluff needs folds {:.1}%", self.bluff_break_even * 100.0)?;
+        if let Some(ev) = self.call_ev {
+            write!(f, "\ncalling wins      {:+.2}", ev)?;
+        }
+        Ok(())
+    }
+}
+
+impl ToJson for Odds {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("pot", self.pot.into()),
+            ("bet", self.bet.into()),
+            ("pot_odds", self.pot_odds.into()),
+            ("required_equity", self.required_equity.into()),
+            ("minimum_defense", self.minimum_defense.into()),
+            ("bluff_break_even", self.bluff_break_even.into()),
+            ("call_ev", self.call_ev.into()),
+        ])
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_odds() {
+        // a pot sized bet lays 2 to 1, so calling needs a third and bluffing half
+        let odds = Odds::new(100.0, 100.0, Some(0.5)).unwrap();
+        assert_eq!(odds.pot_odds, 2.0);
+        assert!((odds.required_equity - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!((odds.minimum_defense, odds.bluff_break_even), (0.5, 0.5));
+        assert_eq!(odds.call_ev, Some(50.0));
+        // calling with exactly the equity needed breaks even
+        assert!(call_ev(60.0, 20.0, required_equity(60.0, 20.0)).abs() < 1e-12);
+        assert!(Odds::new(100.0, 0.0, None).is_err());
+        assert!(Odds::new(100.0, 50.0, Some(1.5)).is_err());
+    }
+}
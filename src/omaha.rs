@@ -0,0 +1,223 @@
+use crate::card::*;
+use crate::eval::*;
+use crate::json::*;
+use crate::low::*;
+use crate::monte_carlo::*;
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
+
+
+/// Four hole cards
+pub type OmahaHand = [Card; 4];
+
+/// Exact result of an Omaha hand against a known evil hand over every runout of the community cards.
+/// From the flop on this is quick, but preflop is about a million runouts
+/// returns (win_count, tie_count, lose_count)
+pub fn eval_omaha_heads_up(hole: &OmahaHand, evil_hole: &OmahaHand, community: &[Card], dead: &[Card]) -> (usize, usize, usize) {
+    eval_game_heads_up(&Game::Omaha, hole, evil_hole, community, dead)
+}
+
+/// Monte carlo equity of an Omaha hand against a random one, dealing the evil hand and the rest
+/// of the board until the stop rule is met. There are far too many evil hands to enumerate
+pub fn eval_omaha<R: Rng + ?Sized>(hole: &OmahaHand, community: &[Card], dead: &[Card], rule: &StopRule, rng: &mut R) -> Estimate {
+    eval_game(&Game::Omaha, hole, community, dead, rule, rng)
+}
+
+/// Best eight-or-better low from exactly two hole cards and exactly three from the board
+pub fn best_low(hole: &OmahaHand, community: &[Card]) -> Option<Low> {
+    hole.iter()
+        .copied()
+        .tuple_combinations()
+        .cartesian_product(community.iter().copied().tuple_combinations())
+        .filter_map(|((a, b), (c, d, e))| Low::eight_or_better(&[a, b, c, d, e]))
+        .min()
+}
+
+/// How hi-lo pots went, counted per deal (or per runout, weighted, when enumerating).
+/// The pot is split between the best high and the best qualifying low, or all goes high without one
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct HiLo {
+    pub deals: usize,
+    pub high_wins: usize,
+    pub high_ties: usize,
+    /// Deals where anyone had a qualifying low
+    pub lows: usize,
+    pub low_wins: usize,
+    pub low_ties: usize,
+    /// Deals where the whole pot was won
+    pub scoops: usize,
+    /// Pot won over every deal, in quarter pots (a split half is a quarter)
+    pub quarters: usize,
+}
+
+impl HiLo {
+    /// Share of the pot won on average
+    pub fn equity(&self) -> f64 {
+        self.quarters as f64 / (4 * self.deals) as f64
+    }
+
+    /// How often the high half is won, ties counting half
+    pub fn high(&self) -> f64 {
+        (self.high_wins as f64 + self.high_ties as f64 / 2.0) / self.deals as f64
+    }
+
+    /// How often the low half is won, ties counting half, out of every deal (with a low or not)
+    pub fn low(&self) -> f64 {
+        (self.low_wins as f64 + self.low_ties as f64 / 2.0) / self.deals as f64
+    }
+
+    pub fn scoop(&self) -> f64 {
+        self.scoops as f64 / self.deals as f64
+    }
+
+    pub fn add(&mut self, other: HiLo) {
+        self.deals += other.deals;
+        self.high_wins += other.high_wins;
+        self.high_ties += other.high_ties;
+        self.lows += other.lows;
+        self.low_wins += other.low_wins;
+        self.low_ties += other.low_ties;
+        self.scoops += other.scoops;
+        self.quarters += other.quarters;
+    }
+
+    /// One hi-lo showdown on a full board, counted weight times
+    fn showdown(hole: &OmahaHand, evil_hole: &OmahaHand, board: &[Card], weight: usize) -> HiLo {
+        let mut result = HiLo { deals: weight, ..HiLo::default() };
+        let high = Game::Omaha.best_score(hole, board).cmp(&Game::Omaha.best_score(evil_hole, board));
+        // halves of the high (or whole) pot won: 2 a win, 1 a tie
+        let high_halves = match high {
+            std::cmp::Ordering::Less => 2,
+            std::cmp::Ordering::Equal => 1,
+            std::cmp::Ordering::Greater => 0,
+        };
+        result.high_wins = weight * (high_halves / 2);
+        result.high_ties = weight * (high_halves % 2);
+
+        let quarters = match (best_low(hole, board), best_low(evil_hole, board)) {
+            (None, None) => 2 * high_halves,
+            (mine, theirs) => {
+                // no low at all loses to any low
+                let low_halves = match (mine, theirs) {
+                    (Some(mine), Some(theirs)) => match mine.cmp(&theirs) {
+                        std::cmp::Ordering::Less => 2,
+                        std::cmp::Ordering::Equal => 1,
+                        std::cmp::Ordering::Greater => 0,
+                    },
+                    (Some(_), None) => 2,
+                    _ => 0,
+                };
+                result.lows = weight;
+                result.low_wins = weight * (low_halves / 2);
+                result.low_ties = weight * (low_halves % 2);
+                high_halves + low_halves
+            }
+        };
+        result.quarters = weight * quarters;
+        result.scoops = if quarters == 4 { weight } else { 0 };
+        result
+    }
+}
+
+impl ToJson for HiLo {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("deals", self.deals.into()),
+            ("equity", self.equity().into()),
+            ("high", self.high().into()),
+            ("low", self.low().into()),
+            ("scoop", self.scoop().into()),
+            ("lows", (self.lows as f64 / self.deals as f64).into()),
+        ])
+    }
+}
+
+/// eval_omaha_heads_up for hi-lo (eight or better), enumerating every runout
+pub fn eval_hi_lo_heads_up(hole: &OmahaHand, evil_hole: &OmahaHand, community: &[Card], dead: &[Card]) -> HiLo {
+    debug_assert!(is_valid_deal_with(community, &[], &[hole, evil_hole, dead].concat()));
+    let deck = deck_without(&[community, dead, hole, evil_hole].concat());
+    let mut result = HiLo::default();
+    let fixed: [&[Card]; 4] = [hole, evil_hole, community, dead];
+    for_each_runout(community, &deck, &fixed, |board, _, weight| {
+        result.add(HiLo::showdown(hole, evil_hole, board, weight));
+    });
+    result
+}
+
+/// Hi-lo (eight or better) against a random Omaha hand from n random deals
+pub fn eval_hi_lo<R: Rng + ?Sized>(hole: &OmahaHand, community: &[Card], dead: &[Card], n: usize, rng: &mut R) -> HiLo {
+    debug_assert!(is_valid_deal_with(community, &[], &[hole, dead].concat()));
+    let mut deck = deck_without(&[community, dead, hole].concat());
+    let mut result = HiLo::default();
+    let mut board = community.to_vec();
+    for _ in 0..n {
+        let (cards, _) = deck.partial_shuffle(rng, 4 + 5 - community.len());
+        let (evil_hole, runout) = cards.split_at(4);
+        board.extend_from_slice(runout);
+        result.add(HiLo::showdown(hole, evil_hole.try_into().unwrap(), &board, 1));
+        board.truncate(community.len());
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(s: &str) -> OmahaHand {
+        parse_cards(s).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_exactly_two_hole_cards() {
+        // four to a royal on the board is no flush with one spade in hand
+        let board = parse_cards("AsKsQsJs2d").unwrap();
+        let hole = hand("Ts3c4d5h");
+        assert!(Game::Omaha.best_score(&hole, &board) > Game::Holdem.best_score(&hole[..2], &board));
+        // and with two spades the best is a king high straight flush, since the ace would be a fourth board card
+        let hole = hand("Ts9s3c4d");
+        assert_eq!(Game::Omaha.best_score(&hole, &board), SCORES[&crate::hand::Hand::from_straight_flush(Rank::King)]);
+    }
+
+    #[test]
+    fn test_eval_omaha() {
+        let board = parse_cards("AsKsQs2d").unwrap();
+        let nuts = hand("JsTs3c4d");
+        let (win, tie, lose) = eval_omaha_heads_up(&nuts, &hand("AhAdKhKd"), &board, &[]);
+        assert_eq!((win, tie, lose), (40, 0, 0));
+
+        let rule = StopRule { half_width: 0.0, max_samples: 500, ..StopRule::default() };
+        let estimate = eval_omaha(&nuts, &board, &[], &rule, &mut rand::rng());
+        assert_eq!(estimate.tally.samples(), 500);
+        assert_eq!(estimate.equity, 1.0);
+    }
+
+    #[test]
+    fn test_hi_lo() {
+        // the nut low and a wheel straight for the high too
+        let board = parse_cards("3c4d5hKsQs").unwrap();
+        let wheel = hand("Ah2d9c9h");
+        assert_eq!(best_low(&wheel, &board).unwrap().to_string(), "5-4-3-2-A");
+        assert_eq!(best_low(&hand("KcKdQcQd"), &board), None);
+
+        // scoops a high hand with no low
+        let result = eval_hi_lo_heads_up(&wheel, &hand("KcKdJcJd"), &board, &[]);
+        assert_eq!((result.deals, result.scoops, result.lows), (1, 1, 1));
+        assert_eq!(result.equity(), 1.0);
+
+        // splits the low with another wheel and wins the high with a six high straight
+        let result = eval_hi_lo_heads_up(&hand("Ah2d6c9h"), &hand("As2c7h7d"), &board, &[]);
+        assert_eq!((result.high_wins, result.low_ties, result.quarters), (1, 1, 3));
+
+        // no low on the board: the high takes it all
+        let board = parse_cards("KhKdQc9s").unwrap();
+        let result = eval_hi_lo_heads_up(&hand("AcAd2c3c"), &hand("JcTd8h7h"), &board, &[]);
+        assert_eq!(result.lows, 0);
+        assert!((result.equity() - result.high()).abs() < 1e-9);
+
+        let result = eval_hi_lo(&wheel, &parse_cards("3c4d5h").unwrap(), &[], 500, &mut rand::rng());
+        assert_eq!(result.deals, 500);
+        assert!(result.low() > 0.5 && result.equity() > 0.5);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::card::*;
+use crate::eval::*;
+use crate::monte_carlo::*;
+use rand::Rng;
+
+
+/// Three hole cards, one of which has to go
+pub type PineappleHand = [Card; 3];
+
+/// The pair left after throwing away hole[discard]
+pub fn keep(hole: &PineappleHand, discard: usize) -> (Card, Card) {
+    let kept: Vec<Card> = (0..3).filter(|&i| i != discard).map(|i| hole[i]).collect();
+    (kept[0], kept[1])
+}
+
+/// Equity against a random hand keeping each pair, best first, with the discard dead.
+/// In pineapple the discard comes before the flop, so this samples until the stop rule is met;
+/// in crazy pineapple it comes after, where it's exact over every runout and evil pair.
+/// A random villain's own discard is just one more unknown card, so they play a random pair
+pub fn discard_equities<R: Rng + ?Sized>(hole: &PineappleHand, board: &[Card], dead: &[Card], rule: &StopRule, rng: &mut R) -> Vec<(Card, Estimate)> {
+    debug_assert!(is_valid_deal_with(board, &[], &[hole, dead].concat()));
+    let mut equities: Vec<(Card, Estimate)> = (0..3)
+        .map(|discard| {
+            let dead = [dead, &[hole[discard]]].concat();
+            let estimate = if board.len() >= 3 {
+                eval_exhaustive(&keep(hole, discard), &Villain::Random, board, &dead, 1, Hooks::default())
+            } else {
+                eval_until_threaded(&keep(hole, discard), &Villain::Random, board, &dead, rule, 1, 256, Hooks::default(), rng)
+            };
+            (hole[discard], estimate)
+        })
+        .collect();
+    equities.sort_by(|a, b| b.1.equity.total_cmp(&a.1.equity));
+    equities
+}
+
+/// Exact equity of each pair the hero could keep (rows, by the discard) against each pair the
+/// villain could (columns), with both discards dead. Quick from the flop on
+pub fn discard_matrix(hole: &PineappleHand, evil_hole: &PineappleHand, board: &[Card], dead: &[Card]) -> [[f64; 3]; 3] {
+    debug_assert!(is_valid_deal_with(board, &[], &[hole, evil_hole, dead].concat()));
+    std::array::from_fn(|discard| std::array::from_fn(|evil_discard| {
+        let dead = [dead, &[hole[discard], evil_hole[evil_discard]]].concat();
+        let (win, tie, lose) = eval_heads_up_with_dead(&keep(hole, discard), &keep(evil_hole, evil_discard), board, &dead);
+        (win as f64 + tie as f64 / 2.0) / (win + tie + lose) as f64
+    }))
+}
+
+/// The hero's discard doing best once the villain makes the discard that's best against it,
+/// with the equity it guarantees
+pub fn best_discard_heads_up(hole: &PineappleHand, evil_hole: &PineappleHand, board: &[Card], dead: &[Card]) -> (Card, f64) {
+    let matrix = discard_matrix(hole, evil_hole, board, dead);
+    (0..3)
+        .map(|discard| (hole[discard], matrix[discard].iter().copied().fold(1.0, f64::min)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(s: &str) -> PineappleHand {
+        parse_cards(s).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_discard() {
+        let board = parse_cards("AsKd7h2c").unwrap();
+        let hole = hand("AhAd3c");
+        assert_eq!(keep(&hole, 2), (hole[0], hole[1]));
+
+        // throwing an ace away is never right
+        let equities = discard_equities(&hole, &board, &[], &StopRule::default(), &mut rand::rng());
+        assert_eq!(equities[0].0, hole[2]);
+        assert_eq!(equities[0].1.method, Method::Exhaustive);
+
+        // the villain keeps the kings whatever, and the hero's set of aces beats them
+        let (discard, equity) = best_discard_heads_up(&hole, &hand("KhKc7d"), &board, &[]);
+        assert_eq!(discard, hole[2]);
+        assert!(equity > 0.9);
+        let matrix = discard_matrix(&hole, &hand("KhKc7d"), &board, &[]);
+        assert!(matrix[2][2] < matrix[2][0]);
+    }
+}
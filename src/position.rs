@@ -0,0 +1,115 @@
+use std::{fmt::{Display, Formatter}, str::FromStr};
+
+
+/// Seat relative to the button at a full (9 handed) table, in preflop action order
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum Position {
+    Utg,
+    Utg1,
+    Utg2,
+    Lojack,
+    Hijack,
+    Cutoff,
+    Button,
+    SmallBlind,
+    BigBlind,
+}
+
+impl Position {
+    pub const ALL_POSITIONS: [Position; 9] = [
+        Position::Utg,
+        Position::Utg1,
+        Position::Utg2,
+        Position::Lojack,
+        Position::Hijack,
+        Position::Cutoff,
+        Position::Button,
+        Position::SmallBlind,
+        Position::BigBlind,
+    ];
+
+    /// Positions in use with this many players (2-9), in preflop action order.
+    /// Early seats go first as the table gets shorter, e.g. 6 handed is LJ HJ CO BTN SB BB,
+    /// and heads up the button posts the small blind
+    pub fn for_players(players: usize) -> &'static [Position] {
+        debug_assert!((2..=9).contains(&players));
+        match players {
+            2 => &Position::ALL_POSITIONS[7..],
+            _ => &Position::ALL_POSITIONS[9 - players..],
+        }
+    }
+
+    /// Position of the player `seats` to the left of the button, with this many players
+    /// (2-9) round the table. 0 is the button itself
+    pub fn left_of_button(seats: usize, players: usize) -> Position {
+        let positions = Position::for_players(players);
+        match players {
+            // the button is the small blind
+            2 => positions[seats % 2],
+            _ => positions[(seats + players - 3) % players],
+        }
+    }
+
+    /// The same positions in the order they act after the flop (blinds first)
+    pub fn postflop_order(positions: &[Position]) -> Vec<Position> {
+        let mut order = positions.to_vec();
+        order.sort_by_key(|position| !position.is_blind());
+        order
+    }
+
+    pub fn is_blind(self) -> bool {
+        matches!(self, Position::SmallBlind | Position::BigBlind)
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Position::Utg => "UTG",
+            Position::Utg1 => "UTG+1",
+            Position::Utg2 => "UTG+2",
+            Position::Lojack => "LJ",
+            Position::Hijack => "HJ",
+            Position::Cutoff => "CO",
+            Position::Button => "BTN",
+            Position::SmallBlind => "SB",
+            Position::BigBlind => "BB",
+        })
+    }
+}
+
+impl FromStr for Position {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Position::ALL_POSITIONS
+            .into_iter()
+            .find(|position| position.to_string().eq_ignore_ascii_case(s))
+            .ok_or("Expected UTG, UTG+1, UTG+2, LJ, HJ, CO, BTN, SB or BB")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        assert_eq!(Position::for_players(9), &Position::ALL_POSITIONS);
+        assert_eq!(Position::for_players(6)[0], Position::Lojack);
+        assert_eq!(Position::for_players(2), &[Position::SmallBlind, Position::BigBlind]);
+        assert_eq!(Position::postflop_order(Position::for_players(3)),
+                   vec![Position::SmallBlind, Position::BigBlind, Position::Button]);
+        assert!(Position::Cutoff < Position::Button);
+        assert_eq!(Position::left_of_button(0, 6), Position::Button);
+        assert_eq!(Position::left_of_button(1, 6), Position::SmallBlind);
+        assert_eq!(Position::left_of_button(3, 6), Position::Lojack);
+        assert_eq!(Position::left_of_button(5, 6), Position::Cutoff);
+        assert_eq!(Position::left_of_button(0, 2), Position::SmallBlind);
+        assert_eq!(Position::left_of_button(1, 2), Position::BigBlind);
+        for position in Position::ALL_POSITIONS {
+            assert_eq!(position.to_string().parse(), Ok(position));
+        }
+    }
+}
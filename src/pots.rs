@@ -0,0 +1,129 @@
+use crate::card::*;
+use crate::game::Chips;
+
+
+/// Part of the pot and the seats that can win it. Everything still in the hand is eligible for
+/// the main pot, while side pots are only for those who put in enough to match them
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Pot {
+    pub amount: Chips,
+    pub eligible: Vec<usize>,
+}
+
+/// Main pot then side pots, from what each seat put in over the hand (an all in seat having put
+/// in its whole stack). Chips put in by folded seats stay in whichever pot they were in, and a
+/// bet nobody else could match makes a pot of its own that goes back to whoever bet it
+pub fn side_pots(contributed: &[Chips], folded: &[bool]) -> Vec<Pot> {
+    debug_assert_eq!(contributed.len(), folded.len());
+    let mut levels: Vec<Chips> = (0..contributed.len())
+        .filter(|&i| !folded[i])
+        .map(|i| contributed[i])
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+
+    let mut pots: Vec<Pot> = Vec::new();
+    let mut below = 0;
+    for level in levels {
+        let amount = contributed.iter().map(|&put_in| put_in.min(level) - put_in.min(below)).sum();
+        let eligible = (0..contributed.len())
+            .filter(|&i| !folded[i] && contributed[i] >= level)
+            .collect();
+        pots.push(Pot { amount, eligible });
+        below = level;
+    }
+    // chips folded above everyone still in go to the last pot
+    let above: Chips = contributed.iter().map(|&put_in| put_in.saturating_sub(below)).sum();
+    if let Some(pot) = pots.last_mut() {
+        pot.amount += above;
+    }
+    pots
+}
+
+/// Who gets the chips left over when a pot won't split evenly, one each until they run out
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OddChip<'a> {
+    /// The first winners round the table from the seat left of this button
+    LeftOfButton(usize),
+    /// The winners showing the highest card, suits breaking ties in the order spades, hearts,
+    /// diamonds, clubs, as in stud. Holds the cards each seat shows
+    HighCard(&'a [Vec<Card>]),
+}
+
+impl OddChip<'_> {
+    /// Put the winners in the order they get odd chips
+    fn order(&self, winners: &mut [usize], seats: usize) {
+        match *self {
+            OddChip::LeftOfButton(button) => winners.sort_by_key(|&i| (i + seats - button - 1) % seats),
+            OddChip::HighCard(cards) => {
+                let suit = |suit: Suit| match suit {
+                    Suit::Spades => 3,
+                    Suit::Hearts => 2,
+                    Suit::Diamonds => 1,
+                    Suit::Clubs => 0,
+                };
+                let high = |i: usize| cards[i].iter().map(|card| (card.rank, suit(card.suit))).max();
+                winners.sort_by_key(|&i| std::cmp::Reverse(high(i)));
+            }
+        }
+    }
+}
+
+/// Chips each seat wins from the pots at showdown. Strengths are lower for better hands (like
+/// Score), None for seats with no hand to show; each pot goes to the best hands eligible for it,
+/// split evenly, with the odd chips given out by the odd chip rule
+pub fn award_pots<S: Ord + Copy>(pots: &[Pot], strengths: &[Option<S>], odd_chip: OddChip) -> Vec<Chips> {
+    let seats = strengths.len();
+    let mut winnings = vec![0; seats];
+    for pot in pots {
+        // a pot with one seat eligible goes back to it, shown or not
+        let best = pot.eligible.iter().filter_map(|&i| strengths[i]).min();
+        let mut winners: Vec<usize> = pot.eligible.iter().copied().filter(|&i| strengths[i] == best).collect();
+        odd_chip.order(&mut winners, seats);
+        let share = pot.amount / winners.len() as Chips;
+        let odd = pot.amount % winners.len() as Chips;
+        for (k, &i) in winners.iter().enumerate() {
+            winnings[i] += share + if (k as Chips) < odd { 1 } else { 0 };
+        }
+    }
+    winnings
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_side_pots() {
+        // seat 0 folds after putting in 20, seat 1 is all in for 50, seats 2 and 3 go on to 120
+        let pots = side_pots(&[20, 50, 120, 120], &[true, false, false, false]);
+        assert_eq!(pots, vec![Pot { amount: 170, eligible: vec![1, 2, 3] }, Pot { amount: 140, eligible: vec![2, 3] }]);
+
+        // an uncalled bet makes a pot of its own
+        let pots = side_pots(&[30, 100, 60], &[false, false, false]);
+        assert_eq!(pots.last(), Some(&Pot { amount: 40, eligible: vec![1] }));
+        assert_eq!(pots.iter().map(|pot| pot.amount).sum::<Chips>(), 190);
+
+        // the short stack wins the main pot, the side pot goes to the better of the others
+        let winnings = award_pots(&side_pots(&[50, 100, 100], &[false; 3]), &[Some(1), Some(3), Some(2)], OddChip::LeftOfButton(0));
+        assert_eq!(winnings, vec![150, 0, 100]);
+    }
+
+    #[test]
+    fn test_odd_chips() {
+        let pots = [Pot { amount: 101, eligible: vec![0, 1, 2] }];
+        let strengths = [Some(0), None, Some(0)];
+        // seat 2 is first left of the button at seat 1
+        assert_eq!(award_pots(&pots, &strengths, OddChip::LeftOfButton(1)), vec![50, 0, 51]);
+        assert_eq!(award_pots(&pots, &strengths, OddChip::LeftOfButton(2)), vec![51, 0, 50]);
+
+        // the ace of spades beats the ace of hearts
+        let cards = [parse_cards("AhKd").unwrap(), vec![], parse_cards("AsQc").unwrap()];
+        assert_eq!(award_pots(&pots, &strengths, OddChip::HighCard(&cards)), vec![50, 0, 51]);
+
+        // three ways, two odd chips
+        let pots = [Pot { amount: 11, eligible: vec![0, 1, 2] }];
+        assert_eq!(award_pots(&pots, &[Some(0); 3], OddChip::LeftOfButton(0)), vec![3, 4, 4]);
+    }
+}
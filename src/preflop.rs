@@ -0,0 +1,95 @@
+use crate::card::*;
+use crate::class::*;
+use crate::eval::*;
+use rand::{seq::IndexedRandom, Rng};
+
+
+/// Heads-up all-in equity of each starting hand against a random hand, ties count half.
+/// Indexed by HandClass::index (13x13 grid, row major).
+/// Generated once with simulate_preflop_equity at 1,000,000 samples per class
+/// (standard error under 0.05%), since the exact values never change
+#[allow(clippy::approx_constant)] // 0.5236 is an equity, not pi / 6
+const PREFLOP_EQUITY: [f64; HandClass::COUNT] = [
+    0.8517, 0.6713, 0.6620, 0.6541, 0.6458, 0.6280, 0.6193, 0.6102, 0.5982, 0.5996, 0.5898, 0.5828, 0.5743,
+    0.6533, 0.8240, 0.6340, 0.6257, 0.6181, 0.6003, 0.5833, 0.5751, 0.5665, 0.5577, 0.5484, 0.5408, 0.5324,
+    0.6441, 0.6145, 0.7992, 0.6022, 0.5945, 0.5770, 0.5599, 0.5429, 0.5361, 0.5275, 0.5188, 0.5098, 0.5017,
+    0.6346, 0.6051, 0.5817, 0.7750, 0.5756, 0.5568, 0.5402, 0.5236, 0.5059, 0.4997, 0.4901, 0.4816, 0.4739,
+    0.6277, 0.5972, 0.5731, 0.5524, 0.7502, 0.5397, 0.5236, 0.5067, 0.4887, 0.4721, 0.4660, 0.4572, 0.4483,
+    0.6077, 0.5778, 0.5539, 0.5327, 0.5151, 0.7208, 0.5073, 0.4920, 0.4739, 0.4567, 0.4395, 0.4327, 0.4241,
+    0.5985, 0.5608, 0.5355, 0.5148, 0.4967, 0.4804, 0.6918, 0.4788, 0.4622, 0.4456, 0.4270, 0.4087, 0.4025,
+    0.5878, 0.5520, 0.5181, 0.4968, 0.4792, 0.4628, 0.4516, 0.6624, 0.4536, 0.4372, 0.4185, 0.4001, 0.3813,
+    0.5764, 0.5427, 0.5098, 0.4791, 0.4611, 0.4445, 0.4317, 0.4232, 0.6323, 0.4319, 0.4132, 0.3952, 0.3761,
+    0.5767, 0.5333, 0.5014, 0.4717, 0.4425, 0.4261, 0.4142, 0.4056, 0.3996, 0.6031, 0.4143, 0.3960, 0.3779,
+    0.5672, 0.5234, 0.4915, 0.4623, 0.4352, 0.4065, 0.3939, 0.3855, 0.3809, 0.3814, 0.5706, 0.3864, 0.3676,
+    0.5570, 0.5137, 0.4825, 0.4520, 0.4258, 0.4009, 0.3751, 0.3659, 0.3595, 0.3621, 0.3515, 0.5368, 0.3603,
+    0.5495, 0.5057, 0.4735, 0.4430, 0.4166, 0.3910, 0.3687, 0.3463, 0.3409, 0.3421, 0.3322, 0.3226, 0.5033,
+];
+
+/// Heads-up all-in equity against a random hand
+pub fn preflop_equity(class: HandClass) -> f64 {
+    PREFLOP_EQUITY[class.index()]
+}
+
+/// Monte carlo estimate of preflop_equity, dealing n random villain hands and boards
+pub fn simulate_preflop_equity<R: Rng + ?Sized>(class: HandClass, n: usize, rng: &mut R) -> f64 {
+    let pair = class.representative();
+    let mut deck: Vec<Card> = Card::get_deck();
+    deck.retain(|card| *card != pair.0 && *card != pair.1);
+
+    let mut points: usize = 0;
+    for _ in 0..n {
+        let cards: Vec<Card> = deck.choose_multiple(rng, 7).copied().collect();
+        let evil_pair = (cards[0], cards[1]);
+        let community = &cards[2..];
+
+        let my_score = get_best_score(&pair, community);
+        let evil_score = get_best_score(&evil_pair, community);
+        // 2 points for a win, 1 for a tie
+        points += match my_score.cmp(&evil_score) {
+            std::cmp::Ordering::Less => 2,
+            std::cmp::Ordering::Equal => 1,
+            std::cmp::Ordering::Greater => 0,
+        };
+    }
+    points as f64 / (2 * n) as f64
+}
+
+/// 13x13 grid of equities in the usual chart layout
+pub fn format_grid(mut equity: impl FnMut(HandClass) -> f64) -> String {
+    let mut out = String::new();
+    for row in 0..13 {
+        for col in 0..13 {
+            let class = HandClass::from_grid(row, col);
+            out += &format!("{:>4} {:>4.1} ", class.to_string(), equity(class) * 100.0);
+        }
+        out.pop();
+        out.push('\n');
+    }
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preflop_table() {
+        let aces: HandClass = "AA".parse().unwrap();
+        let deuces: HandClass = "22".parse().unwrap();
+        let worst: HandClass = "32o".parse().unwrap();
+
+        assert!((preflop_equity(aces) - 0.852).abs() < 0.005);
+        assert!(HandClass::all().all(|class| preflop_equity(class) <= preflop_equity(aces)));
+        assert!(HandClass::all().all(|class| preflop_equity(class) >= preflop_equity(worst)));
+        assert!(preflop_equity(deuces) > 0.5);
+    }
+
+    #[test]
+    fn test_simulate_matches_table() {
+        let class: HandClass = "T9s".parse().unwrap();
+        let estimate = simulate_preflop_equity(class, 4000, &mut rand::rng());
+        // ~5 standard errors
+        assert!((estimate - preflop_equity(class)).abs() < 0.04);
+    }
+}
@@ -0,0 +1,203 @@
+//! Execution profiles: one switch setting how many threads to use, how much work to do
+//! between looks at a stopping rule, and whether to enumerate or sample.
+//! Individual settings can be overridden in a config file of `key = value` lines, e.g.
+//!
+//! ```text
+//! # poker.conf
+//! profile = low-power
+//! threads = 2
+//! ```
+
+use crate::card::*;
+use crate::eval::*;
+use crate::monte_carlo::*;
+use std::{fmt::{Display, Formatter}, fs, io, path::{Path, PathBuf}, str::FromStr};
+use rand::Rng;
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Profile {
+    /// Interactive use: every core, checking the stopping rule often so answers come back quickly
+    #[default]
+    Latency,
+    /// Batch jobs: every core, big batches and exact answers where enumeration is feasible
+    Throughput,
+    /// Laptops on battery: a single thread
+    LowPower,
+}
+
+impl Profile {
+    pub const ALL_PROFILES: [Profile; 3] = [Profile::Latency, Profile::Throughput, Profile::LowPower];
+
+    pub fn config(self) -> ExecConfig {
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let (threads, batch, backend) = match self {
+            Profile::Latency => (cores, 64, Backend::MonteCarlo),
+            Profile::Throughput => (cores, 4096, Backend::Exhaustive),
+            Profile::LowPower => (1, 1024, Backend::MonteCarlo),
+        };
+        ExecConfig { profile: self, threads, batch, backend }
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Profile::Latency => "latency",
+            Profile::Throughput => "throughput",
+            Profile::LowPower => "low-power",
+        })
+    }
+}
+
+impl FromStr for Profile {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Profile::ALL_PROFILES
+            .into_iter()
+            .find(|profile| profile.to_string() == s)
+            .ok_or("Expected latency, throughput or low-power")
+    }
+}
+
+/// How equity against a random hand is worked out
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Backend {
+    /// Every runout and evil pair, once the flop is out or heads up preflop against any two
+    /// cards with nothing dead (Monte Carlo otherwise)
+    Exhaustive,
+    MonteCarlo,
+}
+
+impl Display for Backend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Backend::Exhaustive => "exhaustive",
+            Backend::MonteCarlo => "monte-carlo",
+        })
+    }
+}
+
+impl FromStr for Backend {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exhaustive" => Ok(Backend::Exhaustive),
+            "monte-carlo" => Ok(Backend::MonteCarlo),
+            _ => Err("Expected exhaustive or monte-carlo"),
+        }
+    }
+}
+
+/// Settings a profile picks, after any overrides
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ExecConfig {
+    pub profile: Profile,
+    pub threads: usize,
+    /// Samples each thread deals between looks at a stopping rule
+    pub batch: usize,
+    pub backend: Backend,
+}
+
+impl Default for ExecConfig {
+    fn default() -> Self {
+        Profile::default().config()
+    }
+}
+
+impl ExecConfig {
+    /// $POKER_CONFIG if set, otherwise poker.conf in the working directory
+    pub fn default_path() -> PathBuf {
+        std::env::var_os("POKER_CONFIG")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("poker.conf"))
+    }
+
+    /// Settings from a config file, with profile (if given) replacing the file's.
+    /// A missing file just means the profile's defaults
+    pub fn load(path: impl AsRef<Path>, profile: Option<Profile>) -> io::Result<ExecConfig> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        ExecConfig::parse(&text, profile).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Parse `key = value` lines (profile, threads, batch, backend), ignoring blanks and # comments.
+    /// The profile's settings are applied first so the other keys override them
+    pub fn parse(text: &str, profile: Option<Profile>) -> Result<ExecConfig, String> {
+        let mut settings: Vec<(&str, &str)> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {}: expected key = value", number + 1));
+            };
+            settings.push((key.trim(), value.trim()));
+        }
+
+        let file_profile = match settings.iter().find(|(key, _)| *key == "profile") {
+            Some((_, value)) => Some(value.parse::<Profile>()?),
+            None => None,
+        };
+        let mut config = profile.or(file_profile).unwrap_or_default().config();
+
+        for (key, value) in settings {
+            let invalid = |e: &dyn Display| format!("{}: {}", key, e);
+            match key {
+                "profile" => {}
+                "threads" => config.threads = value.parse().map_err(|e| invalid(&e))?,
+                "batch" => config.batch = value.parse().map_err(|e| invalid(&e))?,
+                "backend" => config.backend = value.parse().map_err(|e| invalid(&e))?,
+                _ => return Err(format!("unknown setting {}", key)),
+            }
+        }
+        if config.threads == 0 || config.batch == 0 {
+            return Err("threads and batch must be at least 1".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Equity against the villain using the configured backend, threads and batch size
+    #[allow(clippy::too_many_arguments)]
+    pub fn equity<R: Rng + ?Sized>(&self, pair: &(Card, Card), villain: &Villain, board: &[Card], dead: &[Card],
+                                   rule: &StopRule, hooks: Hooks, rng: &mut R) -> Estimate {
+        match self.backend {
+            Backend::Exhaustive if board.len() >= 3 => eval_exhaustive(pair, villain, board, dead, self.threads, hooks),
+            Backend::Exhaustive if board.is_empty() && *villain == Villain::Random && dead.is_empty() => {
+                let (wins, ties, losses) = eval_preflop_exact(pair);
+                Estimate::exact(Tally { wins, ties, losses })
+            }
+            _ => eval_until_threaded(pair, villain, board, dead, rule, self.threads, self.batch, hooks, rng),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        assert_eq!(ExecConfig::parse("", None), Ok(Profile::Latency.config()));
+
+        let text = "# laptop\nprofile = low-power\n\nbatch = 100  # smaller\n";
+        let config = ExecConfig::parse(text, None).unwrap();
+        assert_eq!(config, ExecConfig { batch: 100, ..Profile::LowPower.config() });
+
+        // a profile from the command line replaces the file's, but not its overrides
+        let config = ExecConfig::parse(text, Some(Profile::Throughput)).unwrap();
+        assert_eq!(config, ExecConfig { batch: 100, ..Profile::Throughput.config() });
+
+        assert!(ExecConfig::parse("threads = 0", None).is_err());
+        assert!(ExecConfig::parse("backend = gpu", None).is_err());
+        assert!(ExecConfig::parse("speed = 11", None).is_err());
+        assert!(ExecConfig::parse("profile", None).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use crate::class::*;
+use crate::json::*;
+use crate::solver::*;
+use std::collections::HashMap;
+
+
+/// What a result is worth to a player
+#[derive(Debug, PartialEq, Clone)]
+pub enum Payoff {
+    /// Chips, as in a cash game
+    ChipEv,
+    /// Share of the prize pool by the Independent Chip Model, with these payouts from first place
+    Icm(Vec<f64>),
+}
+
+/// Each player's expected prize with these stacks, by the Independent Chip Model: the chance of
+/// finishing first is the share of the chips, and each later place goes the same way among the
+/// players left
+pub fn icm(stacks: &[f64], payouts: &[f64]) -> Vec<f64> {
+    fn expected(mask: usize, stacks: &[f64], payouts: &[f64], memo: &mut HashMap<usize, Vec<f64>>) -> Vec<f64> {
+        if let Some(prizes) = memo.get(&mask) {
+            return prizes.clone();
+        }
+        let left: Vec<usize> = (0..stacks.len()).filter(|i| mask & (1 << i) != 0).collect();
+        let place = stacks.len() - left.len();
+        let mut prizes = vec![0.0; stacks.len()];
+        let total: f64 = left.iter().map(|&i| stacks[i]).sum();
+        for &i in &left {
+            // busted players split whatever places are left
+            let chance = if total > 0.0 { stacks[i] / total } else { 1.0 / left.len() as f64 };
+            if chance == 0.0 {
+                continue;
+            }
+            prizes[i] += chance * payouts.get(place).copied().unwrap_or(0.0);
+            for (prize, rest) in prizes.iter_mut().zip(expected(mask & !(1 << i), stacks, payouts, memo)) {
+                *prize += chance * rest;
+            }
+        }
+        memo.insert(mask, prizes.clone());
+        prizes
+    }
+    debug_assert!(stacks.len() < usize::BITS as usize);
+    expected((1 << stacks.len()) - 1, stacks, payouts, &mut HashMap::new())
+}
+
+/// How much more a player loses in prizes by losing a pot against the villain than they gain by
+/// winning it, all in for as much as the shorter of them has, by icm. 1 for chips; above it the
+/// player needs more than the chip odds to risk their stack
+pub fn bubble_factor(stacks: &[f64], payouts: &[f64], hero: usize, villain: usize) -> f64 {
+    let risk = stacks[hero].min(stacks[villain]);
+    let after = |won: f64| {
+        let mut stacks = stacks.to_vec();
+        stacks[hero] += won;
+        stacks[villain] -= won;
+        icm(&stacks, payouts)[hero]
+    };
+    let now = icm(stacks, payouts)[hero];
+    let (gain, loss) = (after(risk) - now, now - after(-risk));
+    if gain > 0.0 { loss / gain } else { 1.0 }
+}
+
+/// The equity a bot needs to risk its stack once a chip threshold is scaled by a bubble factor,
+/// the same risk for the same reward with losing counting bubble times as much
+pub fn icm_threshold(threshold: f64, bubble: f64) -> f64 {
+    threshold * bubble / (threshold * bubble + 1.0 - threshold)
+}
+
+/// One push/fold spot: the small blind moves all in or folds, and the big blind calls or folds.
+/// Amounts are in big blinds
+#[derive(Debug, PartialEq, Clone)]
+pub struct PushFoldGame {
+    pub small_blind_stack: f64,
+    pub big_blind_stack: f64,
+    pub small_blind: f64,
+    /// From each of the two players
+    pub ante: f64,
+    /// Stacks of anyone else still in the tournament, which only matter to ICM
+    pub others: Vec<f64>,
+    pub payoff: Payoff,
+}
+
+impl PushFoldGame {
+    /// Heads up, for chips, both players with this many big blinds
+    pub fn heads_up(stack: f64) -> Self {
+        PushFoldGame { small_blind_stack: stack, big_blind_stack: stack, small_blind: 0.5, ante: 0.0, others: Vec::new(), payoff: Payoff::ChipEv }
+    }
+
+    /// What the small and big blinds' results are worth with these stacks
+    fn value(&self, small_blind_stack: f64, big_blind_stack: f64) -> (f64, f64) {
+        match &self.payoff {
+            Payoff::ChipEv => (small_blind_stack, big_blind_stack),
+            Payoff::Icm(payouts) => {
+                let stacks = [&[small_blind_stack, big_blind_stack], &self.others[..]].concat();
+                let prizes = icm(&stacks, payouts);
+                (prizes[0], prizes[1])
+            }
+        }
+    }
+
+    /// Values of the ways the hand can go: the small blind folds, pushes and isn't called, and
+    /// pushes, is called and wins or loses
+    fn outcomes(&self) -> Outcomes {
+        let (small, big) = (self.small_blind_stack, self.big_blind_stack);
+        let at_risk = small.min(big);
+        let blinds_lost = self.small_blind + self.ante;
+        Outcomes {
+            fold: self.value(small - blinds_lost, big + blinds_lost),
+            steal: self.value(small + 1.0 + self.ante, big - 1.0 - self.ante),
+            win: self.value(small + at_risk, big - at_risk),
+            lose: self.value(small - at_risk, big + at_risk),
+        }
+    }
+}
+
+struct Outcomes {
+    fold: (f64, f64),
+    steal: (f64, f64),
+    win: (f64, f64),
+    lose: (f64, f64),
+}
+
+/// How often each starting hand pushes and calls, indexed by HandClass::index
+#[derive(Debug, PartialEq, Clone)]
+pub struct PushFoldSolution {
+    pub push: Vec<f64>,
+    pub call: Vec<f64>,
+}
+
+impl PushFoldSolution {
+    pub fn push(&self, class: HandClass) -> f64 {
+        self.push[class.index()]
+    }
+
+    pub fn call(&self, class: HandClass) -> f64 {
+        self.call[class.index()]
+    }
+
+    /// As a strategy profile, with information sets like push:AKs (fold or push) and call:AKs
+    /// (fold or call), which ChartBot plays from
+    pub fn to_profile(&self) -> StrategyProfile {
+        let mut profile = StrategyProfile::default();
+        for class in HandClass::all() {
+            for (decision, action, p) in [("push", "push", self.push(class)), ("call", "call", self.call(class))] {
+                profile.info_sets.insert(format!("{}:{}", decision, class), vec![("fold".to_string(), 1.0 - p), (action.to_string(), p)]);
+            }
+        }
+        profile
+    }
+
+    /// Back from a strategy profile, e.g. a chart someone's written by hand. Hands it doesn't
+    /// mention fold
+    pub fn from_profile(profile: &StrategyProfile) -> PushFoldSolution {
+        let frequency = |key: String| profile.info_sets.get(&key).and_then(|strategy| strategy.get(1)).map_or(0.0, |&(_, p)| p);
+        PushFoldSolution {
+            push: HandClass::all().map(|class| frequency(format!("push:{}", class))).collect(),
+            call: HandClass::all().map(|class| frequency(format!("call:{}", class))).collect(),
+        }
+    }
+}
+
+impl ToJson for PushFoldSolution {
+    /// How often each class pushes and calls, by name in class order
+    fn to_json(&self) -> Json {
+        let by_class = |frequency: &[f64]| Json::Object(HandClass::all().map(|class| (class.to_string(), frequency[class.index()].into())).collect());
+        Json::object([("push", by_class(&self.push)), ("call", by_class(&self.call))])
+    }
+}
+
+/// Number of ways to deal each pair of starting hands without sharing a card, so the chance of
+/// facing a hand given your own allows for the cards you hold
+fn pairings() -> Vec<Vec<f64>> {
+    let combos: Vec<_> = HandClass::all().map(|class| class.combos()).collect();
+    combos
+        .iter()
+        .map(|hero| {
+            combos
+                .iter()
+                .map(|villain| {
+                    let apart = |a: &(_, _), b: &(_, _)| a.0 != b.0 && a.0 != b.1 && a.1 != b.0 && a.1 != b.1;
+                    hero.iter().flat_map(|a| villain.iter().map(move |b| apart(a, b) as usize)).sum::<usize>() as f64
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// A game with everything the players' choices are weighed by worked out
+struct Spot {
+    pairings: Vec<Vec<f64>>,
+    equities: Vec<Vec<f64>>,
+    outcomes: Outcomes,
+}
+
+impl Spot {
+    fn new(game: &PushFoldGame, equity: impl Fn(HandClass, HandClass) -> f64) -> Spot {
+        let classes: Vec<HandClass> = HandClass::all().collect();
+        let equities = classes.iter().map(|&hero| classes.iter().map(|&villain| equity(hero, villain)).collect()).collect();
+        Spot { pairings: pairings(), equities, outcomes: game.outcomes() }
+    }
+
+    /// What the big blind's hand gets from calling and from folding against the pushing
+    /// range, weighing each pusher's hand by how often it's there and pushed
+    fn call_values(&self, hero: usize, push: &[f64]) -> (f64, f64) {
+        let (mut called, mut folded) = (0.0, 0.0);
+        for (villain, &pushed) in push.iter().enumerate() {
+            let w = self.pairings[hero][villain] * pushed;
+            let eq = self.equities[hero][villain];
+            called += w * (eq * self.outcomes.lose.1 + (1.0 - eq) * self.outcomes.win.1);
+            folded += w * self.outcomes.steal.1;
+        }
+        (called, folded)
+    }
+
+    /// What the small blind's hand gets from pushing and from folding against the calling range
+    fn push_values(&self, hero: usize, call: &[f64]) -> (f64, f64) {
+        let (mut pushed, mut folded) = (0.0, 0.0);
+        for (villain, &called) in call.iter().enumerate() {
+            let w = self.pairings[hero][villain];
+            let eq = self.equities[hero][villain];
+            let showdown = eq * self.outcomes.win.0 + (1.0 - eq) * self.outcomes.lose.0;
+            pushed += w * (called * showdown + (1.0 - called) * self.outcomes.steal.0);
+            folded += w * self.outcomes.fold.0;
+        }
+        (pushed, folded)
+    }
+}
+
+/// Unexploitable push/fold ranges, found by fictitious play: each round both players best
+/// respond to the other's average strategy so far, which converges to an equilibrium.
+/// Equity is heads up between starting hands, e.g. from a MatchupMatrix
+pub fn solve_push_fold(game: &PushFoldGame, equity: impl Fn(HandClass, HandClass) -> f64, rounds: usize) -> PushFoldSolution {
+    let spot = Spot::new(game, equity);
+    let n = HandClass::COUNT;
+    let mut push = vec![1.0; n];
+    let mut call = vec![1.0; n];
+    for round in 1..=rounds {
+        // the first round replaces the starting guess
+        let step = 1.0 / round as f64;
+        let calls: Vec<f64> = (0..n)
+            .map(|hero| {
+                let (called, folded) = spot.call_values(hero, &push);
+                (called > folded) as u8 as f64
+            })
+            .collect();
+        let pushes: Vec<f64> = (0..n)
+            .map(|hero| {
+                let (pushed, folded) = spot.push_values(hero, &call);
+                (pushed > folded) as u8 as f64
+            })
+            .collect();
+        for i in 0..n {
+            call[i] += step * (calls[i] - call[i]);
+            push[i] += step * (pushes[i] - push[i]);
+        }
+    }
+    PushFoldSolution { push, call }
+}
+
+/// How much the small and big blind could each gain on average, in the game's units, by
+/// switching to a best response against the other's chart, halved. 0 exactly at an equilibrium,
+/// so a measure of how far a chart (solved or not) is from one
+pub fn push_fold_exploitability(game: &PushFoldGame, solution: &PushFoldSolution, equity: impl Fn(HandClass, HandClass) -> f64) -> f64 {
+    let spot = Spot::new(game, equity);
+    let deals: f64 = spot.pairings.iter().flatten().sum();
+    let (mut small_blind, mut big_blind) = (0.0, 0.0);
+    for hero in 0..HandClass::COUNT {
+        let (pushed, folded) = spot.push_values(hero, &solution.call);
+        small_blind += pushed.max(folded) - (solution.push[hero] * pushed + (1.0 - solution.push[hero]) * folded);
+        let (called, folded) = spot.call_values(hero, &solution.push);
+        big_blind += called.max(folded) - (solution.call[hero] * called + (1.0 - solution.call[hero]) * folded);
+    }
+    (small_blind + big_blind) / deals / 2.0
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preflop::*;
+
+    #[test]
+    fn test_icm() {
+        let prizes = icm(&[50.0, 30.0, 20.0], &[0.5, 0.3, 0.2]);
+        assert!((prizes.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(prizes[0] > prizes[1] && prizes[1] > prizes[2]);
+        // the chip leader's share of prizes is less than their share of chips
+        assert!(prizes[0] < 0.5);
+        assert_eq!(icm(&[10.0, 0.0], &[0.7, 0.3]), vec![0.7, 0.3]);
+        // four left and three paid, so busting costs more than doubling up gains
+        let bubble = bubble_factor(&[25.0; 4], &[0.5, 0.3, 0.2], 0, 1);
+        assert!(bubble > 1.2);
+        assert_eq!(bubble_factor(&[50.0, 50.0], &[1.0], 0, 1), 1.0);
+        assert!(icm_threshold(0.5, bubble) > 0.5 && icm_threshold(0.5, 1.0) == 0.5);
+    }
+
+    #[test]
+    fn test_push_fold() {
+        // a rough stand in for the matchup matrix, good enough to order the hands
+        let equity = |hero: HandClass, villain: HandClass| (0.5 + preflop_equity(hero) - preflop_equity(villain)).clamp(0.0, 1.0);
+        let short = solve_push_fold(&PushFoldGame::heads_up(2.0), equity, 200);
+        let deep = solve_push_fold(&PushFoldGame::heads_up(20.0), equity, 200);
+        let aces = "AA".parse().unwrap();
+        let trash = "72o".parse().unwrap();
+        assert!(short.push(aces) > 0.99 && deep.push(aces) > 0.99 && deep.call(aces) > 0.99);
+        assert!(deep.push(trash) < 0.01);
+        // shorter stacks push and call wider
+        let width = |strategy: &[f64]| HandClass::all().map(|class| strategy[class.index()] * class.combos().len() as f64).sum::<f64>();
+        assert!(width(&short.push) > width(&deep.push) && width(&short.call) > width(&deep.call));
+        // close to an equilibrium, unlike pushing everything
+        let everything = PushFoldSolution { push: vec![1.0; HandClass::COUNT], call: deep.call.clone() };
+        let game = PushFoldGame::heads_up(20.0);
+        assert!(push_fold_exploitability(&game, &deep, equity) < 0.05);
+        assert!(push_fold_exploitability(&game, &everything, equity) > 0.5);
+        assert_eq!(PushFoldSolution::from_profile(&deep.to_profile()), deep);
+
+        // near the bubble ICM tightens the caller up
+        let bubble = PushFoldGame { others: vec![2.0], payoff: Payoff::Icm(vec![0.5, 0.5]), ..PushFoldGame::heads_up(20.0) };
+        assert!(width(&solve_push_fold(&bubble, equity, 200).call) < width(&deep.call));
+    }
+}
@@ -0,0 +1,211 @@
+//! Equity realization: raw all-in equity overstates what a hand wins when there's betting to
+//! come, by more for hands that get bet off their equity or play out of position. Factors can be
+//! set by position and by hand class (or shape) and multiplied in, or estimated by playing a
+//! hand out against simple betting strategies and comparing with checking it down
+
+use crate::card::*;
+use crate::class::*;
+use crate::eval::*;
+use crate::json::*;
+use crate::position::*;
+use crate::range::*;
+use crate::strength::*;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use rand::{seq::SliceRandom, Rng};
+
+
+/// Multipliers on raw equity; anything left out realizes all of it
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Realization {
+    pub positions: BTreeMap<Position, f64>,
+    pub classes: BTreeMap<HandClass, f64>,
+    /// For classes without their own factor
+    pub pairs: f64,
+    pub suited: f64,
+    pub offsuit: f64,
+}
+
+impl Realization {
+    pub fn new() -> Realization {
+        Realization { pairs: 1.0, suited: 1.0, offsuit: 1.0, ..Default::default() }
+    }
+
+    /// Parse `key = value` lines, the key a position (BTN), a hand class (AKs) or a shape
+    /// (pairs, suited, offsuit), ignoring blanks and # comments
+    pub fn parse(text: &str) -> Result<Realization, String> {
+        let mut realization = Realization::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {}: expected key = value", number + 1));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = |e: &dyn Display| format!("{}: {}", key, e);
+            let factor: f64 = value.parse().map_err(|e| invalid(&e))?;
+            if factor < 0.0 {
+                return Err(invalid(&"factors can't be negative"));
+            }
+            match key {
+                "pairs" => realization.pairs = factor,
+                "suited" => realization.suited = factor,
+                "offsuit" => realization.offsuit = factor,
+                _ => if let Ok(position) = key.parse::<Position>() {
+                    realization.positions.insert(position, factor);
+                } else if let Ok(class) = key.parse::<HandClass>() {
+                    realization.classes.insert(class, factor);
+                } else {
+                    return Err(format!("unknown setting {}", key));
+                },
+            }
+        }
+        Ok(realization)
+    }
+
+    /// The position's factor times the class's
+    pub fn factor(&self, position: Position, class: HandClass) -> f64 {
+        let shape = if class.is_pair() { self.pairs } else if class.suited { self.suited } else { self.offsuit };
+        self.positions.get(&position).unwrap_or(&1.0) * self.classes.get(&class).unwrap_or(&shape)
+    }
+
+    /// Raw equity scaled by the factor, kept within 0-1
+    pub fn realized(&self, equity: f64, position: Position, class: HandClass) -> f64 {
+        (equity * self.factor(position, class)).clamp(0.0, 1.0)
+    }
+}
+
+/// A simple postflop strategy played the same every street, going by hand strength on the
+/// board so far: bet this fraction of the pot with at least `value`, call a bet with at least
+/// `call`. Nobody raises
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Betting {
+    pub bet: f64,
+    pub value: f64,
+    pub call: f64,
+}
+
+impl Default for Betting {
+    fn default() -> Self {
+        Betting { bet: 0.66, value: 0.8, call: 0.6 }
+    }
+}
+
+/// What playing the hand out was worth, against checking it down
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RealizationEstimate {
+    pub samples: usize,
+    /// Average share of the starting pot checking down wins
+    pub equity: f64,
+    /// Average chips won from the starting pot, less what went in after it
+    pub ev: f64,
+}
+
+impl RealizationEstimate {
+    /// How much of its equity the hand realized: EV over what checking down wins
+    pub fn factor(&self, pot: f64) -> f64 {
+        self.ev / (self.equity * pot)
+    }
+}
+
+impl ToJson for RealizationEstimate {
+    fn to_json(&self) -> Json {
+        Json::object([("samples", self.samples.into()), ("equity", self.equity.into()), ("ev", self.ev.into())])
+    }
+}
+
+/// Play the pair out from the board (dealing the flop if there isn't one) against hands from
+/// the range n times, both sides betting by their strategy, the first to act out of position.
+/// Each deal is also checked down, so the two are compared over the same cards
+#[allow(clippy::too_many_arguments)]
+pub fn estimate_realization<R: Rng + ?Sized>(pair: &(Card, Card), range: &HandRange, board: &[Card], pot: f64, stack: f64,
+                                             in_position: bool, strategies: [Betting; 2], n: usize, rng: &mut R) -> RealizationEstimate {
+    debug_assert!(board.len() <= 5 && board.len() != 1 && board.len() != 2);
+    let hero = 0;
+    let used = [board, &[pair.0, pair.1]].concat();
+    let (mut equity, mut ev) = (0.0, 0.0);
+    for _ in 0..n {
+        let evil_pair = range.sample(&used, rng).expect("every combo in the range is blocked");
+        let mut deck = deck_without(&[&used[..], &[evil_pair.0, evil_pair.1]].concat());
+        let (runout, _) = deck.partial_shuffle(rng, 5 - board.len());
+        let community = [board, runout].concat();
+        let pairs = [*pair, evil_pair];
+        // out of position acts first
+        let order = if in_position { [1, 0] } else { [0, 1] };
+
+        let showdown = match get_best_score(pair, &community).cmp(&get_best_score(&evil_pair, &community)) {
+            std::cmp::Ordering::Less => 1.0,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Greater => 0.0,
+        };
+        equity += showdown;
+
+        let (mut pot, mut put_in, mut folded) = (pot, [0.0; 2], None);
+        for board_len in 3.max(board.len())..=5 {
+            let strengths = pairs.map(|pair| hand_strength(&pair, &community[..board_len]));
+            for (i, &player) in order.iter().enumerate() {
+                let other = order[1 - i];
+                let behind = stack - put_in[player];
+                let bet = (strategies[player].bet * pot).min(behind);
+                if bet <= 0.0 || strengths[player] < strategies[player].value {
+                    continue;
+                }
+                put_in[player] += bet;
+                if strengths[other] >= strategies[other].call {
+                    put_in[other] += bet;
+                    pot += 2.0 * bet;
+                } else {
+                    folded = Some(other);
+                    pot += bet;
+                }
+                break;
+            }
+            if folded.is_some() {
+                break;
+            }
+        }
+        let share = match folded {
+            Some(player) if player == hero => 0.0,
+            Some(_) => 1.0,
+            None => showdown,
+        };
+        ev += share * pot - put_in[hero];
+    }
+    RealizationEstimate { samples: n, equity: equity / n as f64, ev: ev / n as f64 }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_realization() {
+        let realization = Realization::parse("# rough\nBTN = 1.1\nBB = 0.8\nsuited = 1.05\nAKo = 1.0\n").unwrap();
+        let class = |s: &str| s.parse::<HandClass>().unwrap();
+        assert!((realization.factor(Position::Button, class("T9s")) - 1.1 * 1.05).abs() < 1e-12);
+        assert_eq!(realization.factor(Position::BigBlind, class("AKo")), 0.8);
+        assert_eq!(realization.factor(Position::Cutoff, class("72o")), 1.0);
+        assert_eq!(realization.realized(0.95, Position::Button, class("AKs")), 1.0);
+        assert!(Realization::parse("UTG+3 = 1").is_err() && Realization::parse("BTN = -1").is_err());
+
+        // the nuts gets paid with a bet on the river, where nothing is left to come
+        let board = parse_cards("AsKsQs7h2d").unwrap();
+        let nuts = (Card::new(Rank::Jack, Suit::Spades), Card::new(Rank::Ten, Suit::Spades));
+        let calling = Betting { call: 0.0, ..Betting::default() };
+        let estimate = estimate_realization(&nuts, &HandRange::full(), &board, 10.0, 100.0, true,
+                                            [Betting::default(), calling], 20, &mut rand::rng());
+        assert_eq!(estimate.equity, 1.0);
+        assert!((estimate.ev - (10.0 + 6.6)).abs() < 1e-9 && estimate.factor(10.0) > 1.0);
+
+        // and a hand that never bets or calls gets folded out of its share
+        let air = (Card::new(Rank::Four, Suit::Clubs), Card::new(Rank::Three, Suit::Diamonds));
+        let passive = Betting { value: 2.0, call: 2.0, ..Betting::default() };
+        let betting = Betting { value: 0.0, ..Betting::default() };
+        let estimate = estimate_realization(&air, &HandRange::full(), &board, 10.0, 100.0, false,
+                                            [passive, betting], 20, &mut rand::rng());
+        assert_eq!(estimate.ev, 0.0);
+    }
+}
@@ -0,0 +1,87 @@
+//! A slow evaluator written the obvious way, looking for flushes, straights and pairs in five
+//! cards, to check the score table (and so the hand encoding) against
+
+use crate::card::*;
+use crate::category::*;
+use crate::eval::*;
+use crate::hand::*;
+use itertools::Itertools;
+
+/// The category of five cards and the ranks that decide between hands of that category, most
+/// important first, so better hands compare greater
+pub fn reference_rank(cards: &[Card; 5]) -> (Category, Vec<Rank>) {
+    let flush = cards.iter().all(|card| card.suit == cards[0].suit);
+
+    // ranks by how many of them there are, then how high they are
+    let mut groups: Vec<(usize, Rank)> = cards.iter().map(|card| card.rank).counts().into_iter().map(|(rank, n)| (n, rank)).collect();
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+    let counts: Vec<usize> = groups.iter().map(|&(n, _)| n).collect();
+    let ranks: Vec<Rank> = groups.iter().map(|&(_, rank)| rank).collect();
+
+    let straight_high = match ranks[..] {
+        [Rank::Ace, Rank::Five, Rank::Four, Rank::Three, Rank::Two] => Some(Rank::Five),
+        [high, .., low] if ranks.len() == 5 && usize::from(high) - usize::from(low) == 4 => Some(high),
+        _ => None,
+    };
+
+    match (straight_high, flush, &counts[..]) {
+        (Some(high), true, _) => (Category::StraightFlush, vec![high]),
+        (_, _, [4, 1]) => (Category::Quads, ranks),
+        (_, _, [3, 2]) => (Category::FullHouse, ranks),
+        (_, true, _) => (Category::Flush, ranks),
+        (Some(high), false, _) => (Category::Straight, vec![high]),
+        (_, _, [3, 1, 1]) => (Category::Trips, ranks),
+        (_, _, [2, 2, 1]) => (Category::TwoPair, ranks),
+        (_, _, [2, 1, 1, 1]) => (Category::Pair, ranks),
+        _ => (Category::HighCard, ranks),
+    }
+}
+
+/// Check the score table against reference_rank on every five card hand
+/// returns how many hands were checked
+pub fn verify_score_table() -> Result<usize, String> {
+    verify_hands(Card::DECK.into_iter().combinations(5).map(|cards| [cards[0], cards[1], cards[2], cards[3], cards[4]]))
+}
+
+/// Check the score table against reference_rank on some five card hands: the scores have to order
+/// the hands just as the reference does, with the same category
+/// returns how many hands were checked
+pub fn verify_hands(hands: impl Iterator<Item = [Card; 5]>) -> Result<usize, String> {
+    let mut scored: Vec<_> = hands
+        .map(|cards| (SCORES[&Hand::new(&cards)], reference_rank(&cards), cards))
+        .collect();
+    scored.sort_unstable_by_key(|&(score, _, _)| score);
+
+    for (score, (category, _), cards) in &scored {
+        if Category::from_score(*score) != *category {
+            return Err(format!("{} scores as {} but is {}", format_cards(cards), Category::from_score(*score), category));
+        }
+    }
+    for ((score, rank, cards), (next_score, next_rank, next_cards)) in scored.iter().tuple_windows() {
+        // lower scores are better
+        let agrees = if score == next_score { rank == next_rank } else { rank > next_rank };
+        if !agrees {
+            return Err(format!("{} and {} are scored out of order", format_cards(cards), format_cards(next_cards)));
+        }
+    }
+    Ok(scored.len())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_hands() {
+        let rank = |s: &str| reference_rank(&parse_cards(s).unwrap().try_into().unwrap());
+        assert_eq!(rank("5h4h3h2hAh"), (Category::StraightFlush, vec![Rank::Five]));
+        assert_eq!(rank("7c7d2s2h7h"), (Category::FullHouse, vec![Rank::Seven, Rank::Two]));
+        assert!(rank("AsKd9c5h2h") < rank("2s2dAc9h5h"));
+
+        // every hand from the nine up, which has every category
+        let cards: Vec<Card> = Card::DECK.into_iter().filter(|card| card.rank >= Rank::Nine).collect();
+        let hands = cards.into_iter().combinations(5).map(|cards| cards.try_into().unwrap());
+        assert_eq!(verify_hands(hands), Ok(42504));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::card::*;
+use crate::game::*;
+use crate::history::*;
+use crate::showdown::*;
+use crate::stats::*;
+use crate::street::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// An amount as the hand's site shows it, in dollars or in chips
+fn amount(hand: &HandRecord, chips: Chips) -> String {
+    if hand.cents { format!("${}.{:02}", chips / 100, chips % 100) } else { chips.to_string() }
+}
+
+fn cards(cards: &[Card]) -> String {
+    cards.iter().map(|card| card.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+fn describe(hand: &HandRecord, play: Play) -> String {
+    match play {
+        Play::Post(post, chips) => format!("posts {} {}", match post {
+            Post::SmallBlind => "small blind",
+            Post::BigBlind => "big blind",
+            Post::Both => "small and big blinds",
+            Post::Ante => "the ante",
+        }, amount(hand, chips)),
+        Play::Fold => "folds".to_string(),
+        Play::Check => "checks".to_string(),
+        Play::Call(chips) => format!("calls {}", amount(hand, chips)),
+        Play::Bet(chips) => format!("bets {}", amount(hand, chips)),
+        Play::Raise(by, to) => format!("raises {} to {}", amount(hand, by), amount(hand, to)),
+        Play::Returned(chips) => format!("gets {} back", amount(hand, chips)),
+    }
+}
+
+/// A hand street by street, with the pot after every action. With equities, each decision also
+/// shows the share of the pot the player's hand would win on average against the others still
+/// in, when all of them are known (from the hero's cards or a showdown), exact over every runout
+pub fn replay(hand: &HandRecord, equities: bool) -> String {
+    let mut out = String::new();
+    let title = if hand.id.is_empty() { hand.site.clone() } else { format!("{} hand #{}", hand.site, hand.id) };
+    writeln!(out, "{}: {} ({}/{})", title, hand.game, amount(hand, hand.small_blind), amount(hand, hand.big_blind)).unwrap();
+
+    let known = |name: &str| hand.hero.iter().chain(&hand.shown).find(|(player, _)| player == name).map(|&(_, hole)| hole);
+    let positions = positions(hand).unwrap_or_default();
+    for player in &hand.players {
+        write!(out, "seat {}: {} ({})", player.seat, player.name, amount(hand, player.stack)).unwrap();
+        if let Some((_, position)) = positions.iter().find(|(name, _)| *name == player.name) {
+            write!(out, " {}", position).unwrap();
+        }
+        if let Some((a, b)) = known(&player.name) {
+            write!(out, " [{} {}]", a, b).unwrap();
+        }
+        out.push('\n');
+    }
+
+    let mut pot = 0;
+    let mut bets: HashMap<&str, Chips> = HashMap::new();
+    let mut folded: Vec<&str> = Vec::new();
+    let mut cached: HashMap<(Street, Vec<&str>), Option<Vec<f64>>> = HashMap::new();
+    let mut street = None;
+    for action in &hand.actions {
+        if street != Some(action.street) {
+            street = Some(action.street);
+            bets.clear();
+            let board = &hand.board[..action.street.board_len().min(hand.board.len())];
+            writeln!(out, "*** {} *** {}pot {}", action.street, if board.is_empty() { String::new() } else { cards(board) + ", " }, amount(hand, pot)).unwrap();
+        }
+
+        let mut line = format!("{} {}", action.player, describe(hand, action.play));
+        let decision = !matches!(action.play, Play::Post(..) | Play::Returned(_));
+        if equities && decision {
+            // everyone dealt in who hasn't folded, in seat order
+            let live: Vec<&str> = hand.players
+                .iter()
+                .map(|player| player.name.as_str())
+                .filter(|name| !folded.contains(name) && hand.actions.iter().any(|action| action.player == *name))
+                .collect();
+            let shares = cached.entry((action.street, live.clone())).or_insert_with(|| {
+                let holes: Option<Vec<(Card, Card)>> = live.iter().map(|name| known(name)).collect();
+                let board = &hand.board[..action.street.board_len()];
+                holes.filter(|holes| holes.len() > 1 && board.len() <= hand.board.len())
+                    .map(|holes| showdown_odds(&holes, board).shares)
+            });
+            if let Some(shares) = shares
+                && let Some(i) = live.iter().position(|name| *name == action.player)
+            {
+                write!(line, " with {:.1}% equity", shares[i] * 100.0).unwrap();
+            }
+        }
+
+        let bet = bets.entry(&action.player).or_default();
+        match action.play {
+            Play::Post(Post::Ante, chips) => pot += chips,
+            Play::Post(_, chips) | Play::Call(chips) | Play::Bet(chips) => {
+                *bet += chips;
+                pot += chips;
+            }
+            Play::Raise(_, to) => {
+                pot += to - *bet;
+                *bet = to;
+            }
+            Play::Returned(chips) => {
+                *bet -= chips;
+                pot -= chips;
+            }
+            Play::Fold => folded.push(&action.player),
+            Play::Check => {}
+        }
+        writeln!(out, "{}{}, pot {}", line, if action.all_in { " and is all in" } else { "" }, amount(hand, pot)).unwrap();
+    }
+
+    // streets dealt after the betting was over
+    let last = street.map_or(0, |street| street.board_len());
+    for street in Street::ALL_STREETS.into_iter().filter(|street| street.board_len() > last && street.board_len() <= hand.board.len()) {
+        writeln!(out, "*** {} *** {}, pot {}", street, cards(&hand.board[..street.board_len()]), amount(hand, pot)).unwrap();
+    }
+
+    let shown: Vec<_> = hand.shown.iter().filter(|(name, _)| !folded.contains(&name.as_str())).collect();
+    if !shown.is_empty() {
+        writeln!(out, "*** showdown ***").unwrap();
+        for (name, (a, b)) in shown {
+            writeln!(out, "{} shows {} {}", name, a, b).unwrap();
+        }
+    }
+    for (name, chips) in &hand.collected {
+        writeln!(out, "{} collects {}", name, amount(hand, *chips)).unwrap();
+    }
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND: &str = "\
+PokerStars Hand #9: Hold'em No Limit ($0.50/$1.00 USD) - 2020/01/01 12:00:00 ET
+Table 'Delta' 6-max Seat #1 is the button
+Seat 1: alice ($100 in chips)
+Seat 2: bob ($100 in chips)
+alice: posts small blind $0.50
+bob: posts big blind $1
+*** HOLE CARDS ***
+Dealt to alice [Ah Ad]
+alice: raises $2 to $3
+bob: calls $2
+*** FLOP *** [Ks 7c 2d]
+bob: checks
+alice: bets $4
+bob: folds
+Uncalled bet ($4) returned to alice
+alice collected $5.70 from pot
+*** SUMMARY ***
+";
+
+    #[test]
+    fn test_replay() {
+        let hand = PokerStars.parse_hand(HAND).unwrap();
+        let text = replay(&hand, true);
+        // heads up the button is the small blind
+        assert!(text.contains("seat 1: alice ($100.00) SB ["));
+        assert!(text.contains("alice raises $2.00 to $3.00, pot $4.00"));
+        assert!(text.contains(&format!("*** flop *** {}, pot $6.00", cards(&hand.board))));
+        assert!(text.contains("alice gets $4.00 back, pot $6.00"));
+        // bob's cards are never seen
+        assert!(!text.contains("equity"));
+
+        // every hand from the engine is known
+        let state = HandState::new(&[100, 100], 0, 1, 2, &mut rand::rng());
+        let text = replay(&HandRecord::from(&state), true);
+        assert!(text.contains("seat 0 posts small blind 1, pot 1"));
+        let mut state = state;
+        state.act(Action::Call).unwrap();
+        let text = replay(&HandRecord::from(&state), true);
+        assert!(text.contains("seat 0 calls 1 with") && text.contains("% equity, pot 4"));
+    }
+}
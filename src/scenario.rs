@@ -0,0 +1,277 @@
+//! Regression scenarios kept as data: `.scenarios` files of INI-like blocks, each a spot
+//! and what should come out of it, e.g.
+//!
+//! ```text
+//! # ties count half in equities, given in percent
+//! [flush and wheel draw vs top pair]
+//! hand = 2h3h
+//! board = AhKh4s9c
+//! villain = AsQc      # random (the default), one exposed card or both cards
+//! equity = 27.27 ± 0.01
+//!
+//! [board plays]
+//! hand = 2h3c
+//! board = AsKsQsJsTs
+//! villain = KdKc
+//! category = straight flush
+//! winner = tie
+//! ```
+//!
+//! Equities are checked against whichever evaluator the corpus is run with;
+//! categories and winners only need the board, so they're checked directly.
+
+use crate::card::*;
+use crate::category::*;
+use crate::eval::*;
+use crate::json::*;
+use crate::monte_carlo::*;
+use std::{fs, io, path::Path, str::FromStr};
+
+
+/// Who takes the pot on a complete board
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Winner {
+    Hero,
+    Villain,
+    Tie,
+}
+
+impl FromStr for Winner {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hero" => Ok(Winner::Hero),
+            "villain" => Ok(Winner::Villain),
+            "tie" => Ok(Winner::Tie),
+            _ => Err("Expected hero, villain or tie"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Expected {
+    /// Equity and how far off it may be, both as fractions (not percent)
+    Equity { equity: f64, tolerance: f64 },
+    /// The hero's made hand on the board
+    Category(Category),
+    Winner(Winner),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Scenario {
+    pub name: String,
+    pub pair: (Card, Card),
+    pub board: Vec<Card>,
+    pub villain: Villain,
+    pub dead: Vec<Card>,
+    pub expected: Vec<Expected>,
+}
+
+/// Equity tolerance when none is given, in percent
+const DEFAULT_TOLERANCE: f64 = 0.1;
+
+impl Scenario {
+    fn new(name: &str, fields: &[(&str, &str)]) -> Result<Scenario, String> {
+        let field = |key: &str| fields.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+        let cards = |key: &str| parse_cards(field(key).unwrap_or("")).map_err(|e| format!("{}: {}", key, e));
+
+        let pair = match cards("hand")?[..] {
+            [a, b] => (a, b),
+            _ => return Err("hand: expected two hole cards".to_string()),
+        };
+        let board = cards("board")?;
+        let villain = match field("villain") {
+            None | Some("random") => Villain::Random,
+            Some(_) => match cards("villain")?[..] {
+                [card] => Villain::Exposed(card),
+                [a, b] => Villain::Known(a, b),
+                _ => return Err("villain: expected random, one card or two".to_string()),
+            },
+        };
+        let dead = cards("dead")?;
+        if !is_valid_deal_with(&board, &[pair], &[villain.known_cards(), dead.clone()].concat()) {
+            return Err("a card is dealt twice".to_string());
+        }
+
+        let mut expected = Vec::new();
+        for &(key, value) in fields {
+            match key {
+                "hand" | "board" | "villain" | "dead" => {}
+                "equity" => expected.push(parse_equity(value)?),
+                "category" if board.len() >= 3 => expected.push(Expected::Category(value.parse()?)),
+                "winner" if board.len() == 5 && matches!(villain, Villain::Known(_, _)) => {
+                    expected.push(Expected::Winner(value.parse()?))
+                }
+                "category" => return Err("category needs at least the flop".to_string()),
+                "winner" => return Err("winner needs a full board and both villain cards".to_string()),
+                _ => return Err(format!("unknown field {}", key)),
+            }
+        }
+        Ok(Scenario { name: name.to_string(), pair, board, villain, dead, expected })
+    }
+}
+
+/// `27.27 ± 0.01` or `27.27 +- 0.01` (percent)
+fn parse_equity(value: &str) -> Result<Expected, String> {
+    let (equity, tolerance) = match value.split_once('±').or_else(|| value.split_once("+-")) {
+        Some((equity, tolerance)) => (equity, tolerance.trim()),
+        None => (value, ""),
+    };
+    let percent = |s: &str| s.trim().parse::<f64>().map(|x| x / 100.0).map_err(|e| format!("equity: {}", e));
+    Ok(Expected::Equity {
+        equity: percent(equity)?,
+        tolerance: if tolerance.is_empty() { DEFAULT_TOLERANCE / 100.0 } else { percent(tolerance)? },
+    })
+}
+
+/// Every scenario in a file's text, in order
+pub fn parse_scenarios(text: &str) -> Result<Vec<Scenario>, String> {
+    let mut scenarios = Vec::new();
+    let mut current: Option<(&str, Vec<(&str, &str)>)> = None;
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |e: String| format!("line {}: {}", number + 1, e);
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            if let Some((name, fields)) = current.replace((name.trim(), Vec::new())) {
+                scenarios.push(Scenario::new(name, &fields).map_err(|e| error(format!("{}: {}", name, e)))?);
+            }
+            continue;
+        }
+        let Some((_, fields)) = current.as_mut() else {
+            return Err(error("expected a [scenario name] first".to_string()));
+        };
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(error("expected key = value".to_string()));
+        };
+        fields.push((key.trim(), value.trim()));
+    }
+    if let Some((name, fields)) = current {
+        scenarios.push(Scenario::new(name, &fields).map_err(|e| format!("{}: {}", name, e))?);
+    }
+    Ok(scenarios)
+}
+
+/// Every scenario in the .scenarios files in dir, files taken in name order
+pub fn load_corpus(dir: impl AsRef<Path>) -> io::Result<Vec<Scenario>> {
+    let mut paths: Vec<_> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    paths.retain(|path| path.extension().is_some_and(|extension| extension == "scenarios"));
+    paths.sort();
+
+    let mut scenarios = Vec::new();
+    for path in paths {
+        let text = fs::read_to_string(&path)?;
+        let parsed = parse_scenarios(&text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?;
+        scenarios.extend(parsed);
+    }
+    Ok(scenarios)
+}
+
+/// How one scenario went
+#[derive(Debug, PartialEq, Clone)]
+pub struct Outcome {
+    pub name: String,
+    pub failures: Vec<String>,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl ToJson for Outcome {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("name", self.name.as_str().into()),
+            ("passed", self.passed().into()),
+            ("failures", self.failures.iter().map(String::as_str).collect::<Vec<_>>().into()),
+        ])
+    }
+}
+
+/// Check every scenario, getting equities from the evaluator under test (only called for
+/// scenarios that expect an equity). An estimate passes if it's within the tolerance plus
+/// its own half width of the expected equity
+pub fn run_corpus(scenarios: &[Scenario], mut equity: impl FnMut(&Scenario) -> Estimate) -> Vec<Outcome> {
+    scenarios
+        .iter()
+        .map(|scenario| {
+            let mut failures = Vec::new();
+            for expected in &scenario.expected {
+                match *expected {
+                    Expected::Equity { equity: want, tolerance } => {
+                        let estimate = equity(scenario);
+                        if (estimate.equity - want).abs() > tolerance + estimate.half_width() {
+                            failures.push(format!("equity {:.2}% ± {:.2}%, expected {:.2}% ± {:.2}%",
+                                estimate.equity * 100.0, estimate.half_width() * 100.0, want * 100.0, tolerance * 100.0));
+                        }
+                    }
+                    Expected::Category(want) => {
+                        let got = Category::of(&scenario.pair, &scenario.board);
+                        if got != want {
+                            failures.push(format!("category {}, expected {}", got, want));
+                        }
+                    }
+                    Expected::Winner(want) => {
+                        let Villain::Known(a, b) = scenario.villain else {
+                            unreachable!("checked when parsed");
+                        };
+                        let got = match get_best_score(&scenario.pair, &scenario.board).cmp(&get_best_score(&(a, b), &scenario.board)) {
+                            std::cmp::Ordering::Less => Winner::Hero,
+                            std::cmp::Ordering::Equal => Winner::Tie,
+                            std::cmp::Ordering::Greater => Winner::Villain,
+                        };
+                        if got != want {
+                            failures.push(format!("winner {:?}, expected {:?}", got, want));
+                        }
+                    }
+                }
+            }
+            Outcome { name: scenario.name.clone(), failures }
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scenarios() {
+        let text = "[a]\nhand = AhAs\nboard = 2c7d9sJhQd\nvillain = KhKs\nwinner = hero\nequity = 100 +- 0\n\n[b]\nhand = 2h3h\n";
+        let scenarios = parse_scenarios(text).unwrap();
+        assert_eq!(scenarios.len(), 2);
+        assert_eq!(scenarios[0].villain, Villain::Known(Card::new(Rank::King, Suit::Hearts), Card::new(Rank::King, Suit::Spades)));
+        assert_eq!(scenarios[0].expected, vec![Expected::Winner(Winner::Hero), Expected::Equity { equity: 1.0, tolerance: 0.0 }]);
+        assert_eq!(scenarios[1].villain, Villain::Random);
+
+        assert!(parse_scenarios("hand = AhAs").is_err());
+        assert!(parse_scenarios("[a]\nhand = AhAs\nwinner = hero").is_err());
+        assert!(parse_scenarios("[a]\nhand = AhAs\nboard = Ah2c3d").is_err());
+
+        // the wrong answer fails
+        let scenarios = parse_scenarios("[a]\nhand = AhAs\nboard = 2c7d9sJhQd\nvillain = KhKs\nwinner = villain\ncategory = pair").unwrap();
+        let outcomes = run_corpus(&scenarios, |_| unreachable!());
+        assert_eq!(outcomes[0].failures.len(), 1);
+    }
+
+    #[test]
+    fn test_corpus() {
+        let corpus = load_corpus(Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios")).unwrap();
+        assert!(!corpus.is_empty());
+        let outcomes = run_corpus(&corpus, |scenario| {
+            eval_exhaustive(&scenario.pair, &scenario.villain, &scenario.board, &scenario.dead, 1, Hooks::default())
+        });
+        for outcome in outcomes {
+            assert!(outcome.passed(), "{:?}", outcome);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::card::*;
+use crate::category::*;
+use crate::eval::*;
+use std::sync::LazyLock;
+
+
+/// How strong a five card hand is, as compared at showdown.
+///
+/// Scores are opaque so the table behind them can be re-encoded; what's guaranteed is:
+/// - a total order where lower is stronger: a < b means a beats b, and equal scores split the pot.
+///   Only scores from the same game compare meaningfully
+/// - every score handed out within a process stays valid and comparable for its lifetime,
+///   but the representation may change between versions, so persist index() rather than the score
+/// - category() gives the made hand
+///
+/// Scores are 16 bits, keeping the tables and anything holding scores for many hands small
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Score(u16);
+
+/// The index above a bit for the deck whose table it came from, so the index decides the order
+const DECK_BITS: u32 = 1;
+
+/// Hold'em scores by index, for from_index
+static BY_INDEX: LazyLock<Vec<Score>> = LazyLock::new(|| {
+    let mut scores: Vec<Score> = SCORES.values().copied().collect();
+    scores.sort();
+    scores.dedup();
+    scores
+});
+
+impl Score {
+    /// Distinct five card hand strengths in hold'em, from the royal flush to 7-5-4-3-2 offsuit
+    pub const COUNT: usize = 7462;
+
+    /// Only the score tables make scores
+    pub(crate) fn new(raw: u16, deck: Deck) -> Score {
+        debug_assert!((raw as usize) < Score::COUNT);
+        Score((raw << DECK_BITS) | u16::from(deck == Deck::Short))
+    }
+
+    /// The bits as they are, for tables of scores that are written out (see jump_table).
+    /// Only valid with the same version of this crate
+    #[cfg(feature = "big-table")]
+    pub(crate) fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    #[cfg(feature = "big-table")]
+    pub(crate) fn from_bits(bits: u16) -> Score {
+        Score(bits)
+    }
+
+    /// Position among the game's distinct hand strengths, 0 for the best.
+    /// This is fixed by the rules of poker, not the encoding, so it's safe to store
+    pub fn index(self) -> usize {
+        (self.0 >> DECK_BITS) as usize
+    }
+
+    /// The hold'em score at an index
+    pub fn from_index(index: usize) -> Option<Score> {
+        BY_INDEX.get(index).copied()
+    }
+
+    pub fn beats(self, other: Score) -> bool {
+        self < other
+    }
+
+    pub fn category(self) -> Category {
+        SCORE_TABLES[(self.0 & ((1 << DECK_BITS) - 1)) as usize].category(self.index() as u16)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::*;
+
+    #[test]
+    fn test_score() {
+        assert_eq!(create_score_table(Deck::Full).scores.values().max(), Some(&(Score::COUNT as u16 - 1)));
+        assert_eq!(SCORES.values().map(|score| score.index()).max(), Some(Score::COUNT - 1));
+
+        let best = Score::from_index(0).unwrap();
+        let worst = Score::from_index(Score::COUNT - 1).unwrap();
+        assert!(best.beats(worst) && !worst.beats(best) && !best.beats(best));
+        assert_eq!(Score::from_index(Score::COUNT), None);
+        assert_eq!(worst.index(), Score::COUNT - 1);
+        assert_eq!(size_of::<Score>(), 2);
+
+        // categories come in order
+        let categories: Vec<Category> = (0..Score::COUNT).rev().map(|i| Score::from_index(i).unwrap().category()).collect();
+        assert!(categories.is_sorted());
+        assert_eq!(categories.first(), Some(&Category::HighCard));
+    }
+}
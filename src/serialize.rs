@@ -0,0 +1,88 @@
+//! Serde support, with the `serde` feature: cards, hands, classes and ranges go to and from the
+//! short strings used everywhere else ("A", "h", "Ah", "AhKh2c", "AKs", "TT+,AQo:0.5") rather
+//! than variant names or bits
+
+use crate::card::*;
+use crate::class::*;
+use crate::hand::*;
+use crate::range::*;
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+
+/// Serialize with a function to the string and deserialize with one from it
+macro_rules! as_string {
+    ($type:ty, $to_string:expr, $from_str:expr) => {
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&$to_string(self))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                $from_str(&s).map_err(D::Error::custom)
+            }
+        }
+    };
+}
+
+as_string!(Rank, |rank: &Rank| rank.symbol().to_string(), |s: &str| Rank::try_from(single(s)?));
+as_string!(Suit, |suit: &Suit| suit.symbol().to_string(), |s: &str| Suit::try_from(single(s)?));
+as_string!(Card, Card::symbol, str::parse::<Card>);
+as_string!(Hand, hand_symbols, |s: &str| parse_cards(s).map(|cards| Hand::new(&cards)));
+as_string!(HandClass, HandClass::to_string, str::parse::<HandClass>);
+as_string!(HandRange, HandRange::to_string, str::parse::<HandRange>);
+
+/// A hand as the symbols of its cards run together
+fn hand_symbols(hand: &Hand) -> String {
+    hand.cards().iter().map(Card::symbol).collect()
+}
+
+fn single(s: &str) -> Result<char, &'static str> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err("Expected a single character"),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monte_carlo::*;
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+    use serde::de::{value::{Error, StrDeserializer}, IntoDeserializer};
+
+    fn from_str<'de, T: Deserialize<'de>>(s: &'de str) -> Result<T, Error> {
+        T::deserialize::<StrDeserializer<Error>>(s.into_deserializer())
+    }
+
+    #[test]
+    fn test_deserialize() {
+        assert_eq!(from_str::<Rank>("T"), Ok(Rank::Ten));
+        assert_eq!(from_str::<Suit>("h"), Ok(Suit::Hearts));
+        assert_eq!(from_str::<Card>("Ah"), Ok(Card::new(Rank::Ace, Suit::Hearts)));
+        assert_eq!(from_str::<HandClass>("AKs"), Ok("AKs".parse().unwrap()));
+        assert_eq!(from_str::<HandRange>("TT+,AQo:0.5"), Ok("TT+,AQo:0.5".parse().unwrap()));
+        assert!(from_str::<Card>("Ahh").is_err() && from_str::<Rank>("10").is_err());
+        assert_eq!(from_str::<Method>("monte_carlo"), Ok(Method::MonteCarlo));
+        assert_eq!(from_str::<Hand>(""), Ok(Hand::EMPTY));
+    }
+
+    #[test]
+    fn test_hand_round_trip() {
+        let flush = Hand::new(&parse_cards("AsKs9s5s2sAd9c").unwrap());
+        assert_eq!(hand_symbols(&flush), "2h5h9h9dKhAhAc");
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut deck = Card::DECK;
+        for n in [2, 5, 6, 7, 7, 7] {
+            for _ in 0..500 {
+                let (cards, _) = deck.partial_shuffle(&mut rng, n);
+                let hand = Hand::new(cards);
+                assert_eq!(from_str::<Hand>(&hand_symbols(&hand)), Ok(hand));
+            }
+        }
+    }
+}
@@ -0,0 +1,462 @@
+//! The evaluator as a JSON service over HTTP, each connection handled on its own thread and
+//! closed after one response:
+//! - `POST /equity {"hero": "AhKh", "board": "2c7d9s", "range": "TT+,AQs+", "opponents": 1, "samples": 100000}`
+//!   equity against opponents dealt from the range (any two cards if left out), sampled until
+//!   the default precision or for exactly the samples if given
+//! - `POST /range {"range": "TT+,AQs+", "board": "2c7d9s"}` the range in the compact syntax,
+//!   with how many combos and how much weight it has left around the board
+//! - `GET /health`
+//! - a WebSocket at `/equity/stream`, sent one text message with the same JSON as `POST /equity`,
+//!   sends back the estimate as it converges, every tenth of a second or so, with "done" false,
+//!   then the final one with "done" true and closes
+//!
+//! Errors come back as {"error": ...} with a 4xx status, or a 500 if answering went wrong
+
+use crate::card::*;
+use crate::eval::*;
+use crate::json::*;
+use crate::monte_carlo::*;
+use crate::range::*;
+use std::{io::{self, BufRead, BufReader, Read, Write}, net::{TcpListener, TcpStream}, panic::{self, AssertUnwindSafe}, time::{Duration, Instant}};
+
+
+/// Most samples a request can ask for, so no one request ties up a thread for long
+pub const MAX_SAMPLES: usize = 10_000_000;
+
+/// Biggest request body or WebSocket message read
+const MAX_BODY: usize = 1 << 16;
+
+/// Longest request or header line read
+const MAX_LINE: usize = 8192;
+
+/// Most headers read from a request
+const MAX_HEADERS: usize = 100;
+
+/// How long a read or write on a connection can block before it's dropped
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Least time between estimates streamed over a WebSocket
+const STREAM_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Appended to a WebSocket key before hashing it for the handshake (RFC 6455)
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// What's needed of an HTTP request
+struct Request {
+    method: String,
+    path: String,
+    /// Names lower case
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Accept connections forever, answering each on a new thread
+pub fn serve(listener: TcpListener) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        std::thread::spawn(move || {
+            let _ = handle_connection(stream);
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let request = read_request(&mut reader)?;
+    if let Some(request) = &request
+        && request.header("upgrade").is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+        && let Some(key) = request.header("sec-websocket-key")
+    {
+        if request.path != "/equity/stream" {
+            return write_response(&stream, 404, &error("no such endpoint"));
+        }
+        write!(&stream, "HTTP/1.1 101 {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+               reason(101), websocket_accept(key))?;
+        return match answer(|| stream_equity(&stream, &mut reader)) {
+            Some(streamed) => streamed,
+            None => {
+                write_frame(&stream, TEXT, error("internal error").to_string().as_bytes())?;
+                write_frame(&stream, CLOSE, &[])
+            }
+        };
+    }
+    let (status, json) = match request {
+        Some(request) => answer(|| respond(&request.method, &request.path, &request.body)).unwrap_or((500, error("internal error"))),
+        None => (400, error("malformed request")),
+    };
+    write_response(&stream, status, &json)
+}
+
+/// What the handler returns, None if it panics, so the client can be told with a 500 rather
+/// than the connection dropping
+fn answer<T>(handler: impl FnOnce() -> T) -> Option<T> {
+    panic::catch_unwind(AssertUnwindSafe(handler)).ok()
+}
+
+/// The reason phrase for each status the server sends
+fn reason(status: u16) -> &'static str {
+    match status {
+        101 => "Switching Protocols",
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        500 => "Internal Server Error",
+        _ => "",
+    }
+}
+
+fn write_response(mut stream: &TcpStream, status: u16, json: &Json) -> io::Result<()> {
+    let body = json.to_string();
+    write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+           status, reason(status), body.len(), body)
+}
+
+/// The request, or None if it can't be made sense of
+fn read_request(reader: &mut impl BufRead) -> io::Result<Option<Request>> {
+    let Some(line) = read_line(reader)? else {
+        return Ok(None);
+    };
+    let [method, path, _] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Ok(None);
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = Vec::new();
+    loop {
+        let Some(header) = read_line(reader)? else {
+            return Ok(None);
+        };
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Ok(None);
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    let length = match headers.iter().find(|(name, _)| name == "content-length") {
+        Some((_, value)) => match value.parse() {
+            Ok(n) if n <= MAX_BODY => n,
+            _ => return Ok(None),
+        },
+        None => 0,
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(String::from_utf8(body).ok().map(|body| Request { method, path, headers, body }))
+}
+
+/// The next line, None at the end of the stream or if it's longer than `MAX_LINE`
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    let read = reader.take(MAX_LINE as u64 + 1).read_line(&mut line)?;
+    Ok((read > 0 && read <= MAX_LINE).then_some(line))
+}
+
+/// The status and JSON for a request
+pub fn respond(method: &str, path: &str, body: &str) -> (u16, Json) {
+    let handler: fn(&Json) -> Result<Json, &'static str> = match path {
+        "/equity" => equity,
+        "/range" => range,
+        "/health" if method == "GET" => return (200, Json::object([("ok", true.into())])),
+        "/health" => return (405, error("expected GET")),
+        _ => return (404, error("no such endpoint")),
+    };
+    if method != "POST" {
+        return (405, error("expected POST"));
+    }
+    match Json::parse(body).and_then(|request| handler(&request)) {
+        Ok(json) => (200, json),
+        Err(e) => (400, error(e)),
+    }
+}
+
+/// An equity request as the pair, board, a range for each opponent and when to stop
+type EquityRequest = ((Card, Card), Vec<Card>, Vec<HandRange>, StopRule);
+
+fn equity(request: &Json) -> Result<Json, &'static str> {
+    let (pair, board, ranges, rule) = parse_equity(request)?;
+    Ok(eval_vs_ranges(&pair, &ranges, &board, &[], &rule, &mut rand::rng())?.to_json())
+}
+
+fn parse_equity(request: &Json) -> Result<EquityRequest, &'static str> {
+    let pair = match parse_cards(string(request, "hero")?.ok_or("missing hero")?)?[..] {
+        [a, b] => (a, b),
+        _ => return Err("Expected two hole cards"),
+    };
+    let board = parse_cards(string(request, "board")?.unwrap_or(""))?;
+    let range = parse_range(request)?;
+    let opponents = number(request, "opponents")?.unwrap_or(1);
+    if !is_valid_deal(&board, &[pair]) || !matches!(board.len(), 0 | 3 | 4 | 5) {
+        return Err("Invalid deal");
+    }
+    if !(1..=9).contains(&opponents) {
+        return Err("Opponents has to be 1-9");
+    }
+    if range.without(&[&board[..], &[pair.0, pair.1]].concat()).is_empty() {
+        return Err("Every combo in the range is blocked");
+    }
+    let rule = match number(request, "samples")? {
+        Some(n) if (1..=MAX_SAMPLES).contains(&n) => StopRule { half_width: 0.0, max_samples: n, ..StopRule::default() },
+        Some(_) => return Err("Samples has to be between 1 and 10,000,000"),
+        None => StopRule::default(),
+    };
+    Ok((pair, board, vec![range; opponents], rule))
+}
+
+/// Read the request from the WebSocket and stream estimates back until done or the client goes
+fn stream_equity(mut stream: &TcpStream, reader: &mut impl Read) -> io::Result<()> {
+    let message = read_frame(reader)?;
+    let request = message.as_deref().ok_or("expected a text message").and_then(Json::parse).and_then(|request| parse_equity(&request));
+    let (pair, board, ranges, rule) = match request {
+        Ok(request) => request,
+        Err(e) => {
+            write_frame(stream, TEXT, error(e).to_string().as_bytes())?;
+            return write_frame(stream, CLOSE, &[]);
+        }
+    };
+    let with_done = |estimate: &Estimate, done: bool| {
+        let Json::Object(mut fields) = estimate.to_json() else { unreachable!() };
+        fields.push(("done".to_string(), done.into()));
+        Json::Object(fields).to_string()
+    };
+
+    let mut last = Instant::now();
+    let estimate = stream_vs_ranges(&pair, &ranges, &board, &[], &rule, &mut rand::rng(), |estimate| {
+        if last.elapsed() < STREAM_INTERVAL {
+            return true;
+        }
+        last = Instant::now();
+        write_frame(stream, TEXT, with_done(estimate, false).as_bytes()).is_ok()
+    });
+    let estimate = match estimate {
+        Ok(estimate) => estimate,
+        Err(e) => {
+            write_frame(stream, TEXT, error(e).to_string().as_bytes())?;
+            return write_frame(stream, CLOSE, &[]);
+        }
+    };
+    if !estimate.cancelled {
+        write_frame(stream, TEXT, with_done(&estimate, true).as_bytes())?;
+        write_frame(stream, CLOSE, &[])?;
+    }
+    stream.flush()
+}
+
+fn range(request: &Json) -> Result<Json, &'static str> {
+    let range = parse_range(request)?;
+    let board = parse_cards(string(request, "board")?.unwrap_or(""))?;
+    let left = range.without(&board);
+    Ok(Json::object([
+        ("range", range.to_string().into()),
+        ("combos", left.len().into()),
+        ("weight", left.total_weight().into()),
+    ]))
+}
+
+fn parse_range(request: &Json) -> Result<HandRange, &'static str> {
+    string(request, "range")?.map_or(Ok(HandRange::full()), str::parse)
+}
+
+fn string<'a>(request: &'a Json, key: &str) -> Result<Option<&'a str>, &'static str> {
+    request.get(key).map(|value| value.as_str().ok_or("expected a string")).transpose()
+}
+
+fn number(request: &Json, key: &str) -> Result<Option<usize>, &'static str> {
+    request
+        .get(key)
+        .map(|value| value.as_f64().filter(|x| *x >= 0.0 && x.fract() == 0.0).map(|x| x as usize).ok_or("expected a whole number"))
+        .transpose()
+}
+
+fn error(e: &str) -> Json {
+    Json::object([("error", e.into())])
+}
+
+const TEXT: u8 = 0x1;
+const CLOSE: u8 = 0x8;
+
+/// The payload of the next text message from a client, or None if it sent something else.
+/// Messages in more than one frame aren't expected, requests being small
+fn read_frame(reader: &mut impl Read) -> io::Result<Option<String>> {
+    let mut head = [0; 2];
+    reader.read_exact(&mut head)?;
+    let length = match head[1] & 0x7f {
+        126 => {
+            let mut length = [0; 2];
+            reader.read_exact(&mut length)?;
+            u16::from_be_bytes(length) as usize
+        }
+        127 => {
+            let mut length = [0; 8];
+            reader.read_exact(&mut length)?;
+            u64::from_be_bytes(length) as usize
+        }
+        length => length as usize,
+    };
+    if length > MAX_BODY {
+        return Ok(None);
+    }
+    // frames from clients are always masked
+    let mut mask = [0; 4];
+    if head[1] & 0x80 != 0 {
+        reader.read_exact(&mut mask)?;
+    }
+    let mut payload = vec![0; length];
+    reader.read_exact(&mut payload)?;
+    payload.iter_mut().enumerate().for_each(|(i, byte)| *byte ^= mask[i % 4]);
+    let complete_text = head[0] == 0x80 | TEXT;
+    Ok(String::from_utf8(payload).ok().filter(|_| complete_text))
+}
+
+/// A whole message in one unmasked frame, as servers send them
+fn write_frame(mut stream: &TcpStream, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        n if n < 126 => frame.push(n as u8),
+        n if n <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend((n as u16).to_be_bytes());
+        }
+        n => {
+            frame.push(127);
+            frame.extend((n as u64).to_be_bytes());
+        }
+    }
+    frame.extend(payload);
+    stream.write_all(&frame)
+}
+
+/// Sec-WebSocket-Accept for a Sec-WebSocket-Key: base64 of the SHA-1 of it with the GUID
+fn websocket_accept(key: &str) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let digest = sha1(format!("{}{}", key.trim(), WEBSOCKET_GUID).as_bytes());
+    let mut accept = String::new();
+    for chunk in digest.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            accept.push(match i <= chunk.len() {
+                true => ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char,
+                false => '=',
+            });
+        }
+    }
+    accept
+}
+
+/// SHA-1, needed only for the WebSocket handshake
+fn sha1(message: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend((message.len() as u64 * 8).to_be_bytes());
+
+    for block in padded.chunks(64) {
+        let mut w = [0u32; 80];
+        for i in 0..80 {
+            w[i] = match i {
+                0..16 => u32::from_be_bytes(block[4 * i..4 * i + 4].try_into().unwrap()),
+                _ => (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1),
+            };
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..20 => ((b & c) | (!b & d), 0x5A827999),
+                20..40 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..60 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, temp);
+        }
+        for (h, x) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(x);
+        }
+    }
+    let mut digest = [0; 20];
+    for (i, word) in h.iter().enumerate() {
+        digest[4 * i..4 * i + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serve() {
+        let (status, json) = respond("POST", "/range", r#"{"range": "TT+,AKs", "board": "AhKh2c"}"#);
+        assert_eq!((status, json.get("combos").and_then(Json::as_f64)), (200, Some(24.0 + 3.0)));
+        assert_eq!(respond("POST", "/equity", r#"{"hero": "AhAh"}"#).0, 400);
+        // three villains can't all have aces with one in the hero's hand
+        let (status, json) = respond("POST", "/equity", r#"{"hero": "AhKd", "range": "AA", "opponents": 3}"#);
+        assert_eq!((status, json.get("error").and_then(Json::as_str)), (400, Some("The ranges can't all be dealt around the cards out")));
+        assert_eq!(respond("POST", "/equity", r#"{"hero": "AhAd", "samples": 0.5}"#).0, 400);
+        assert_eq!(respond("GET", "/equity", "").0, 405);
+        assert_eq!(respond("GET", "/nowhere", "").0, 404);
+        assert_eq!(reason(405), "Method Not Allowed");
+        assert_eq!(answer(|| -> u16 { panic!("handler failed") }), None);
+        assert_eq!(respond("POST", "/range", &"[".repeat(100_000)).0, 400);
+        let request = |text: String| read_request(&mut io::Cursor::new(text)).unwrap().map(|request| request.path);
+        assert_eq!(request("GET /health HTTP/1.1\r\nHost: x\r\n\r\n".into()), Some("/health".into()));
+        assert_eq!(request(format!("GET /health HTTP/1.1\r\nHost: {}\r\n\r\n", "x".repeat(MAX_LINE))), None);
+        assert_eq!(request(format!("GET /health HTTP/1.1\r\n{}\r\n", "Host: x\r\n".repeat(MAX_HEADERS + 1))), None);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || serve(listener));
+        let body = r#"{"hero": "AhAd", "range": "KK", "samples": 2000}"#;
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "POST /equity HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let json = Json::parse(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        // aces against kings win about 82% of the time
+        assert!((json.get("equity").and_then(Json::as_f64).unwrap() - 0.82).abs() < 0.04);
+
+        // the example handshake from RFC 6455
+        assert_eq!(websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET /equity/stream HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+                        Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n").unwrap();
+        let mask = [1, 2, 3, 4];
+        let mut frame = vec![0x81, 0x80 | body.len() as u8];
+        frame.extend(mask);
+        frame.extend(body.bytes().enumerate().map(|(i, byte)| byte ^ mask[i % 4]));
+        stream.write_all(&frame).unwrap();
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "HTTP/1.1 101 Switching Protocols\r\n");
+        while line != "\r\n" {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+        }
+        let mut last = None;
+        while let Some(message) = read_frame(&mut reader).unwrap() {
+            last = Some(Json::parse(&message).unwrap());
+        }
+        let last = last.unwrap();
+        assert_eq!((last.get("done"), last.get("samples").and_then(Json::as_f64)), (Some(&Json::Bool(true)), Some(2000.0)));
+    }
+}
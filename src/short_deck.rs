@@ -0,0 +1,50 @@
+use crate::card::*;
+use crate::eval::*;
+use crate::monte_carlo::*;
+
+
+fn is_short_deck(cards: &[Card]) -> bool {
+    cards.iter().all(|card| Deck::Short.ranks().contains(&card.rank))
+}
+
+/// Exact short deck result of pair against a known evil_pair over every runout.
+/// There are few enough cards that this is quick even preflop
+/// returns (win_count, tie_count, lose_count)
+pub fn eval_short_deck_heads_up(pair: &(Card, Card), evil_pair: &(Card, Card), community: &[Card], dead: &[Card]) -> (usize, usize, usize) {
+    debug_assert!(is_short_deck(&[community, dead, &[pair.0, pair.1, evil_pair.0, evil_pair.1]].concat()));
+    eval_game_heads_up(&Game::ShortDeck, &[pair.0, pair.1], &[evil_pair.0, evil_pair.1], community, dead)
+}
+
+/// Exact short deck equity against a random hand, enumerating every runout and evil pair.
+/// Preflop this is over a hundred million showdowns, but from the flop on it is instant
+pub fn eval_short_deck(pair: &(Card, Card), community: &[Card], dead: &[Card]) -> Estimate {
+    debug_assert!(is_short_deck(&[community, dead, &[pair.0, pair.1]].concat()));
+    eval_game_exhaustive(&Game::ShortDeck, &[pair.0, pair.1], community, dead)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flush_beats_full_house() {
+        let board = parse_cards("AhKhQdQc7h").unwrap();
+        let flush = (Card::new(Rank::Nine, Suit::Hearts), Card::new(Rank::Eight, Suit::Hearts));
+        let full_house = (Card::new(Rank::Queen, Suit::Spades), Card::new(Rank::King, Suit::Diamonds));
+        assert_eq!(eval_short_deck_heads_up(&flush, &full_house, &board, &[]), (1, 0, 0));
+        assert_eq!(eval_heads_up(&flush, &full_house, &board), (0, 0, 1));
+    }
+
+    #[test]
+    fn test_eval_short_deck() {
+        let board = parse_cards("AhKh9s6c").unwrap();
+        let pair = (Card::new(Rank::Seven, Suit::Diamonds), Card::new(Rank::Eight, Suit::Diamonds));
+        let estimate = eval_short_deck(&pair, &board, &[]);
+        assert_eq!(estimate.tally.samples(), 30 * 406);
+
+        // A-6-7-8-9 is already a straight, so trip aces need to fill up on a king, nine or six, or make quads
+        let aces = (Card::new(Rank::Ace, Suit::Spades), Card::new(Rank::Ace, Suit::Clubs));
+        assert_eq!(eval_short_deck_heads_up(&pair, &aces, &board, &[]), (18, 0, 10));
+    }
+}
@@ -0,0 +1,108 @@
+//! Showdowns between hands that are all known, multi-way all ins being the usual case: who wins
+//! on a whole board, and how the pot goes over every way an unfinished board could come
+
+use crate::card::*;
+use crate::combinatorics::*;
+use crate::eval::*;
+use crate::eval_batch::*;
+
+
+/// Who won a showdown and what share of the pot each hand gets, split evenly between the
+/// winners. Both in the order of the hands
+#[derive(Debug, PartialEq, Clone)]
+pub struct Showdown {
+    pub winners: Vec<usize>,
+    pub shares: Vec<f64>,
+}
+
+/// The showdown of the hands on a whole board
+pub fn showdown(hands: &[(Card, Card)], board: &[Card]) -> Showdown {
+    assert_eq!(board.len(), 5, "the whole board has to be out");
+    debug_assert!(is_valid_deal(board, hands));
+    let board_mask = board.iter().fold(0, |mask, &card| mask | card_bit(card));
+    showdown_of_masks(hands.iter().map(|&(a, b)| board_mask | card_bit(a) | card_bit(b)))
+}
+
+fn showdown_of_masks(masks: impl Iterator<Item = u64>) -> Showdown {
+    let scores: Vec<_> = masks.map(score_mask).collect();
+    let best = *scores.iter().min().expect("no hands");
+    let winners: Vec<usize> = (0..scores.len()).filter(|&i| scores[i] == best).collect();
+    let mut shares = vec![0.0; scores.len()];
+    for &winner in &winners {
+        shares[winner] = 1.0 / winners.len() as f64;
+    }
+    Showdown { winners, shares }
+}
+
+/// How the hands do over every runout of a board, in the order of the hands
+#[derive(Debug, PartialEq, Clone)]
+pub struct ShowdownOdds {
+    pub runouts: usize,
+    /// Runouts each hand wins alone
+    pub wins: Vec<usize>,
+    /// Runouts each hand splits
+    pub ties: Vec<usize>,
+    /// Share of the pot each hand wins on average, its equity
+    pub shares: Vec<f64>,
+}
+
+/// Exact showdown odds of the hands over every runout of the board, even preflop (where it's
+/// about a million and a half runouts)
+pub fn showdown_odds(hands: &[(Card, Card)], board: &[Card]) -> ShowdownOdds {
+    debug_assert!(is_valid_deal(board, hands));
+    let cards: Vec<Card> = hands.iter().flat_map(|&(a, b)| [a, b]).collect();
+    let deck = deck_without(&[&cards, board].concat());
+    let board_mask = board.iter().fold(0, |mask, &card| mask | card_bit(card));
+    let hand_masks: Vec<u64> = hands.iter().map(|&(a, b)| card_bit(a) | card_bit(b)).collect();
+
+    let mut odds = ShowdownOdds { runouts: 0, wins: vec![0; hands.len()], ties: vec![0; hands.len()], shares: vec![0.0; hands.len()] };
+    for_each_combination(deck.len(), 5 - board.len(), |runout| {
+        let mask = runout.iter().fold(board_mask, |mask, &i| mask | card_bit(deck[i]));
+        let result = showdown_of_masks(hand_masks.iter().map(|hand| mask | hand));
+        match result.winners[..] {
+            [winner] => odds.wins[winner] += 1,
+            _ => result.winners.iter().for_each(|&winner| odds.ties[winner] += 1),
+        }
+        for (total, share) in odds.shares.iter_mut().zip(result.shares) {
+            *total += share;
+        }
+        odds.runouts += 1;
+    });
+    for share in &mut odds.shares {
+        *share /= odds.runouts as f64;
+    }
+    odds
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_showdown() {
+        let hands = [parse_pair("AhKd"), parse_pair("AsKc"), parse_pair("7c7d")];
+        let result = showdown(&hands, &parse_cards("Kh9s4c2dJh").unwrap());
+        assert_eq!(result, Showdown { winners: vec![0, 1], shares: vec![0.5, 0.5, 0.0] });
+
+        let flop = parse_cards("Kh9s4c").unwrap();
+        let odds = showdown_odds(&hands, &flop);
+        assert_eq!(odds.runouts, 903);
+        // the same as adding up the showdowns on each runout
+        let deck = deck_without(&[&flop[..], &hands.iter().flat_map(|&(a, b)| [a, b]).collect::<Vec<_>>()].concat());
+        let mut shares = vec![0.0; hands.len()];
+        for runout in deck.into_iter().combinations(2) {
+            let result = showdown(&hands, &[&flop[..], &runout].concat());
+            shares.iter_mut().zip(result.shares).for_each(|(total, share)| *total += share / 903.0);
+        }
+        assert!(odds.shares.iter().zip(shares).all(|(a, b)| (a - b).abs() < 1e-12));
+        assert!((odds.shares.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(odds.ties[0] == odds.ties[1] && odds.wins[2] > 0);
+    }
+
+    fn parse_pair(s: &str) -> (Card, Card) {
+        let cards = parse_cards(s).unwrap();
+        (cards[0], cards[1])
+    }
+}
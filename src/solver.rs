@@ -0,0 +1,586 @@
+use crate::json::*;
+use crate::store::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::{fs, io, path::Path, str::FromStr};
+use rand::Rng;
+
+
+/// A point in a two player zero-sum game tree
+#[derive(Debug, Clone)]
+pub enum Node<S> {
+    /// The game is over, with this payoff to player 0 (and minus it to player 1)
+    Terminal(f64),
+    /// Cards are dealt: each next state with its probability
+    Chance(Vec<(f64, S)>),
+    /// A player picks an action, knowing only the information set, e.g. their cards and the
+    /// betting so far
+    Decision { player: usize, info_set: String, actions: Vec<(String, S)> },
+}
+
+/// A game the solver can work on, described by its states
+pub trait ExtensiveGame {
+    type State: Clone;
+
+    fn root(&self) -> Self::State;
+    fn node(&self, state: &Self::State) -> Node<Self::State>;
+}
+
+/// A strategy for every information set: each action with the probability of taking it
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct StrategyProfile {
+    pub info_sets: BTreeMap<String, Vec<(String, f64)>>,
+}
+
+impl StrategyProfile {
+    /// Action probabilities at the information set, uniform if the profile doesn't cover it
+    pub fn probabilities(&self, info_set: &str, actions: usize) -> Vec<f64> {
+        match self.info_sets.get(info_set) {
+            Some(strategy) if strategy.len() == actions => strategy.iter().map(|&(_, p)| p).collect(),
+            _ => vec![1.0 / actions as f64; actions],
+        }
+    }
+}
+
+/// Written into strategy files, and bumped whenever their layout changes
+pub const STRATEGY_FORMAT_VERSION: u32 = 1;
+
+impl StrategyProfile {
+    /// Strategy file JSON, with one information set per line so solves diff cleanly:
+    ///
+    /// ```text
+    /// {"format": "poker-strategy", "version": 1, "game": "kuhn", "info_sets": {
+    /// "J:": {"p": 0.79, "b": 0.21},
+    /// ...
+    /// }}
+    /// ```
+    pub fn to_json(&self, game: &str) -> String {
+        let mut out = format!("{{\"format\": \"poker-strategy\", \"version\": {}, \"game\": {}, \"info_sets\": {{\n",
+            STRATEGY_FORMAT_VERSION, quote(game));
+        for (i, (info_set, strategy)) in self.info_sets.iter().enumerate() {
+            let actions = Json::Object(strategy.iter().map(|(action, p)| (action.clone(), Json::Number(*p))).collect());
+            out += &format!("{}: {}{}\n", quote(info_set), actions, if i + 1 < self.info_sets.len() { "," } else { "" });
+        }
+        out + "}}\n"
+    }
+
+    /// The game the strategy is for and the strategy, from strategy file JSON
+    pub fn from_json(text: &str) -> Result<(String, StrategyProfile), &'static str> {
+        let json = Json::parse(text)?;
+        if json.get("format").and_then(Json::as_str) != Some("poker-strategy") {
+            return Err("Not a strategy file");
+        }
+        if json.get("version").and_then(Json::as_f64) != Some(STRATEGY_FORMAT_VERSION as f64) {
+            return Err("Unsupported strategy file version");
+        }
+        let game = json.get("game").and_then(Json::as_str).ok_or("Strategy file has no game")?;
+        let Some(Json::Object(info_sets)) = json.get("info_sets") else {
+            return Err("Strategy file has no information sets");
+        };
+        let mut profile = StrategyProfile::default();
+        for (info_set, actions) in info_sets {
+            let Json::Object(actions) = actions else {
+                return Err("Expected actions with their probabilities");
+            };
+            let strategy = actions
+                .iter()
+                .map(|(action, p)| Ok((action.clone(), p.as_f64().ok_or("Expected a probability")?)))
+                .collect::<Result<Vec<_>, &'static str>>()?;
+            profile.info_sets.insert(info_set.clone(), strategy);
+        }
+        Ok((game.to_string(), profile))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>, game: &str) -> io::Result<()> {
+        fs::write(path, self.to_json(game))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<(String, StrategyProfile)> {
+        StrategyProfile::from_json(&fs::read_to_string(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Artifact for StrategyProfile {
+    const KIND: &'static str = "strategy-profile";
+
+    /// The strategy file, without a game since the store's inputs say what it's for
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_json("").into_bytes()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        StrategyProfile::from_json(std::str::from_utf8(bytes).ok()?).ok().map(|(_, profile)| profile)
+    }
+}
+
+impl Display for StrategyProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (info_set, strategy) in &self.info_sets {
+            write!(f, "{:<12}", info_set)?;
+            for (action, p) in strategy {
+                write!(f, " {} {:.3}", action, p)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Payoff to player 0 when both play the profile
+pub fn expected_value<G: ExtensiveGame>(game: &G, profile: &StrategyProfile) -> f64 {
+    fn value<G: ExtensiveGame>(game: &G, profile: &StrategyProfile, state: &G::State) -> f64 {
+        match game.node(state) {
+            Node::Terminal(payoff) => payoff,
+            Node::Chance(outcomes) => outcomes.iter().map(|(p, next)| p * value(game, profile, next)).sum(),
+            Node::Decision { info_set, actions, .. } => profile
+                .probabilities(&info_set, actions.len())
+                .iter()
+                .zip(&actions)
+                .map(|(p, (_, next))| p * value(game, profile, next))
+                .sum(),
+        }
+    }
+    value(game, profile, &game.root())
+}
+
+#[derive(Debug, Clone)]
+struct InfoSet {
+    actions: Vec<String>,
+    regret: Vec<f64>,
+    strategy_sum: Vec<f64>,
+}
+
+impl InfoSet {
+    /// Regret matching: play each action in proportion to its positive regret
+    fn strategy(&self) -> Vec<f64> {
+        let positive: Vec<f64> = self.regret.iter().map(|r| r.max(0.0)).collect();
+        let total: f64 = positive.iter().sum();
+        if total > 0.0 {
+            positive.iter().map(|r| r / total).collect()
+        } else {
+            vec![1.0 / self.actions.len() as f64; self.actions.len()]
+        }
+    }
+}
+
+/// Counterfactual regret minimization: the average strategy over the iterations converges to
+/// an equilibrium
+#[derive(Debug, Clone)]
+pub struct Cfr<G: ExtensiveGame> {
+    pub game: G,
+    info_sets: HashMap<String, InfoSet>,
+    pub iterations: usize,
+}
+
+impl<G: ExtensiveGame> Cfr<G> {
+    pub fn new(game: G) -> Self {
+        Cfr { game, info_sets: HashMap::new(), iterations: 0 }
+    }
+
+    /// One pass over the whole tree
+    pub fn iterate(&mut self) {
+        let root = self.game.root();
+        self.walk(&root, [1.0, 1.0], 1.0, &mut None::<&mut rand::rngs::ThreadRng>);
+        self.iterations += 1;
+    }
+
+    /// One pass dealing a single random outcome at each chance node (chance sampled Monte Carlo
+    /// CFR), which is far cheaper per iteration when there are many deals
+    pub fn iterate_sampled<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let root = self.game.root();
+        self.walk(&root, [1.0, 1.0], 1.0, &mut Some(rng));
+        self.iterations += 1;
+    }
+
+    /// Update regrets below the state, returning its value to player 0. Reach is how likely
+    /// each player's own actions make it, and chance how likely the deals
+    fn walk<R: Rng + ?Sized>(&mut self, state: &G::State, reach: [f64; 2], chance: f64, rng: &mut Option<&mut R>) -> f64 {
+        match self.game.node(state) {
+            Node::Terminal(payoff) => payoff,
+            Node::Chance(outcomes) => match rng {
+                Some(rng) => {
+                    let mut pick = rng.random::<f64>();
+                    let (_, next) = outcomes
+                        .iter()
+                        .find(|(p, _)| {
+                            pick -= p;
+                            pick < 0.0
+                        })
+                        .unwrap_or(outcomes.last().expect("chance nodes need outcomes"));
+                    self.walk(next, reach, chance, &mut Some(&mut **rng))
+                }
+                None => outcomes.iter().map(|(p, next)| p * self.walk(next, reach, chance * p, rng)).sum(),
+            },
+            Node::Decision { player, info_set, actions } => {
+                let strategy = self.info_sets
+                    .entry(info_set.clone())
+                    .or_insert_with(|| InfoSet {
+                        actions: actions.iter().map(|(action, _)| action.clone()).collect(),
+                        regret: vec![0.0; actions.len()],
+                        strategy_sum: vec![0.0; actions.len()],
+                    })
+                    .strategy();
+                let values: Vec<f64> = actions
+                    .iter()
+                    .zip(&strategy)
+                    .map(|((_, next), p)| {
+                        let mut reach = reach;
+                        reach[player] *= p;
+                        self.walk(next, reach, chance, rng)
+                    })
+                    .collect();
+                let value: f64 = values.iter().zip(&strategy).map(|(v, p)| v * p).sum();
+
+                // regrets are from the acting player's side, weighted by how likely everyone
+                // else makes it here
+                let sign = if player == 0 { 1.0 } else { -1.0 };
+                let others = reach[1 - player] * chance;
+                let info = self.info_sets.get_mut(&info_set).unwrap();
+                for (a, v) in values.iter().enumerate() {
+                    info.regret[a] += others * sign * (v - value);
+                    info.strategy_sum[a] += reach[player] * strategy[a];
+                }
+                value
+            }
+        }
+    }
+
+    /// The average strategy over every iteration so far
+    pub fn average_strategy(&self) -> StrategyProfile {
+        let info_sets = self.info_sets
+            .iter()
+            .map(|(key, info)| {
+                let total: f64 = info.strategy_sum.iter().sum();
+                let strategy = info.actions
+                    .iter()
+                    .zip(&info.strategy_sum)
+                    .map(|(action, sum)| (action.clone(), if total > 0.0 { sum / total } else { 1.0 / info.actions.len() as f64 }))
+                    .collect();
+                (key.clone(), strategy)
+            })
+            .collect();
+        StrategyProfile { info_sets }
+    }
+}
+
+/// The most one player can get against the other's strategy in a profile, and the strategy
+/// that gets it
+#[derive(Debug, PartialEq, Clone)]
+pub struct BestResponse {
+    /// To the responding player
+    pub value: f64,
+    /// Always a single action, at every information set of the responding player
+    pub profile: StrategyProfile,
+}
+
+/// The best response for the player against the other's strategy in the profile. An action is
+/// picked for a whole information set at once, by its value summed over every state in the set
+/// weighted by how likely the opponent and the deal make each one
+pub fn best_response<G: ExtensiveGame>(game: &G, profile: &StrategyProfile, player: usize) -> BestResponse {
+    // every state in each of the player's information sets, with the chance of the others
+    // getting there
+    fn collect<G: ExtensiveGame>(game: &G, profile: &StrategyProfile, player: usize, state: &G::State, reach: f64,
+                                 states: &mut HashMap<String, Vec<(G::State, f64)>>) {
+        match game.node(state) {
+            Node::Terminal(_) => {}
+            Node::Chance(outcomes) => outcomes.iter().for_each(|(p, next)| collect(game, profile, player, next, reach * p, states)),
+            Node::Decision { player: acting, info_set, actions } => {
+                let probabilities = profile.probabilities(&info_set, actions.len());
+                if acting == player {
+                    states.entry(info_set).or_default().push((state.clone(), reach));
+                }
+                for (p, (_, next)) in probabilities.iter().zip(&actions) {
+                    collect(game, profile, player, next, if acting == player { reach } else { reach * p }, states);
+                }
+            }
+        }
+    }
+
+    struct Responder<'a, G: ExtensiveGame> {
+        game: &'a G,
+        profile: &'a StrategyProfile,
+        player: usize,
+        states: HashMap<String, Vec<(G::State, f64)>>,
+        /// Picked as they're needed: by perfect recall the sets below never lead back up
+        chosen: BTreeMap<String, (Vec<String>, usize)>,
+    }
+
+    impl<G: ExtensiveGame> Responder<'_, G> {
+        /// Value of the state to the responding player
+        fn value(&mut self, state: &G::State) -> f64 {
+            match self.game.node(state) {
+                Node::Terminal(payoff) => if self.player == 0 { payoff } else { -payoff },
+                Node::Chance(outcomes) => outcomes.iter().map(|(p, next)| p * self.value(next)).sum(),
+                Node::Decision { player, info_set, actions } if player == self.player => {
+                    let choice = self.choose(&info_set, &actions);
+                    self.value(&actions[choice].1)
+                }
+                Node::Decision { info_set, actions, .. } => self.profile
+                    .probabilities(&info_set, actions.len())
+                    .iter()
+                    .zip(&actions)
+                    .map(|(p, (_, next))| p * self.value(next))
+                    .sum(),
+            }
+        }
+
+        fn choose(&mut self, info_set: &str, actions: &[(String, G::State)]) -> usize {
+            if let Some((_, choice)) = self.chosen.get(info_set) {
+                return *choice;
+            }
+            let states = self.states.get(info_set).cloned().unwrap_or_default();
+            let mut values = vec![0.0; actions.len()];
+            for (state, reach) in states {
+                let Node::Decision { actions, .. } = self.game.node(&state) else { unreachable!() };
+                for (value, (_, next)) in values.iter_mut().zip(&actions) {
+                    *value += reach * self.value(next);
+                }
+            }
+            let choice = (0..values.len()).max_by(|&a, &b| values[a].total_cmp(&values[b])).unwrap_or(0);
+            self.chosen.insert(info_set.to_string(), (actions.iter().map(|(action, _)| action.clone()).collect(), choice));
+            choice
+        }
+    }
+
+    let mut states = HashMap::new();
+    collect(game, profile, player, &game.root(), 1.0, &mut states);
+    let mut responder = Responder { game, profile, player, states, chosen: BTreeMap::new() };
+    let value = responder.value(&game.root());
+    // sets the response never reaches still get an action
+    let unreached: Vec<String> = responder.states.keys().filter(|key| !responder.chosen.contains_key(*key)).cloned().collect();
+    for info_set in unreached {
+        let (state, _) = responder.states[&info_set][0].clone();
+        if let Node::Decision { actions, .. } = game.node(&state) {
+            responder.choose(&info_set, &actions);
+        }
+    }
+    let info_sets = responder.chosen
+        .into_iter()
+        .map(|(info_set, (actions, choice))| {
+            let strategy = actions.into_iter().enumerate().map(|(i, action)| (action, if i == choice { 1.0 } else { 0.0 })).collect();
+            (info_set, strategy)
+        })
+        .collect();
+    BestResponse { value, profile: StrategyProfile { info_sets } }
+}
+
+/// How much a player could gain on average by switching to a best response against the
+/// profile, which is 0 exactly at an equilibrium
+pub fn exploitability<G: ExtensiveGame>(game: &G, profile: &StrategyProfile) -> f64 {
+    (best_response(game, profile, 0).value + best_response(game, profile, 1).value) / 2.0
+}
+
+/// Kuhn poker: three cards (J, Q, K), one each, an ante of 1 and one bet of 1
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Kuhn;
+
+const KUHN_CARDS: [char; 3] = ['J', 'Q', 'K'];
+
+impl ExtensiveGame for Kuhn {
+    /// The two cards once dealt, and the actions so far as p (pass) and b (bet)
+    type State = (Option<[usize; 2]>, String);
+
+    fn root(&self) -> Self::State {
+        (None, String::new())
+    }
+
+    fn node(&self, (cards, history): &Self::State) -> Node<Self::State> {
+        let Some(cards) = *cards else {
+            let deals: Vec<[usize; 2]> = (0..3).flat_map(|a| (0..3).filter(move |&b| b != a).map(move |b| [a, b])).collect();
+            return Node::Chance(deals.into_iter().map(|deal| (1.0 / 6.0, (Some(deal), String::new()))).collect());
+        };
+        let showdown = if cards[0] > cards[1] { 1.0 } else { -1.0 };
+        match history.as_str() {
+            "pp" => Node::Terminal(showdown),
+            "bb" | "pbb" => Node::Terminal(2.0 * showdown),
+            "bp" => Node::Terminal(1.0),
+            "pbp" => Node::Terminal(-1.0),
+            _ => {
+                let player = history.len() % 2;
+                Node::Decision {
+                    player,
+                    info_set: format!("{}:{}", KUHN_CARDS[cards[player]], history),
+                    actions: ["p", "b"].iter().map(|&action| (action.to_string(), (Some(cards), format!("{}{}", history, action)))).collect(),
+                }
+            }
+        }
+    }
+}
+
+/// Leduc hold'em: six cards (two each of J, Q, K), one each and then one on the board, an
+/// ante of 1, bets of 2 before the board and 4 after it, and at most a bet and a raise a round.
+/// A pair with the board wins, then the higher card
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Leduc;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LeducState {
+    /// Indices into the six cards, whose rank is the index / 2
+    pub hole: Option<[usize; 2]>,
+    pub board: Option<usize>,
+    /// Actions each round as f (fold), c (check or call) and r (bet or raise)
+    pub rounds: Vec<String>,
+}
+
+/// What each player has put in over a round, with the bet size for the round
+fn round_bets(actions: &str, size: f64) -> [f64; 2] {
+    let mut bets = [0.0; 2];
+    for (i, action) in actions.chars().enumerate() {
+        let player = i % 2;
+        match action {
+            'c' => bets[player] = bets[1 - player],
+            'r' => bets[player] = bets[1 - player] + size,
+            _ => {}
+        }
+    }
+    bets
+}
+
+impl ExtensiveGame for Leduc {
+    type State = LeducState;
+
+    fn root(&self) -> Self::State {
+        LeducState { hole: None, board: None, rounds: vec![String::new()] }
+    }
+
+    fn node(&self, state: &Self::State) -> Node<Self::State> {
+        let Some(hole) = state.hole else {
+            let deals: Vec<[usize; 2]> = (0..6).flat_map(|a| (0..6).filter(move |&b| b != a).map(move |b| [a, b])).collect();
+            let p = 1.0 / deals.len() as f64;
+            return Node::Chance(deals.into_iter().map(|deal| (p, LeducState { hole: Some(deal), ..state.clone() })).collect());
+        };
+        let round = state.rounds.len() - 1;
+        let actions = &state.rounds[round];
+        let mut put_in = [1.0; 2];
+        for (i, actions) in state.rounds.iter().enumerate() {
+            let bets = round_bets(actions, if i == 0 { 2.0 } else { 4.0 });
+            put_in = [put_in[0] + bets[0], put_in[1] + bets[1]];
+        }
+
+        if actions.ends_with('f') {
+            let folder = (actions.len() - 1) % 2;
+            return Node::Terminal(if folder == 0 { -put_in[0] } else { put_in[1] });
+        }
+        let round_over = actions.len() >= 2 && actions.ends_with('c');
+        if round_over && round == 0 {
+            let left: Vec<usize> = (0..6).filter(|card| !hole.contains(card)).collect();
+            return Node::Chance(left
+                .into_iter()
+                .map(|card| {
+                    let mut rounds = state.rounds.clone();
+                    rounds.push(String::new());
+                    (0.25, LeducState { board: Some(card), rounds, ..state.clone() })
+                })
+                .collect());
+        }
+        if round_over {
+            let board = state.board.unwrap() / 2;
+            let strength = |card: usize| if card / 2 == board { 3 } else { card / 2 };
+            return Node::Terminal(match strength(hole[0]).cmp(&strength(hole[1])) {
+                std::cmp::Ordering::Greater => put_in[1],
+                std::cmp::Ordering::Less => -put_in[0],
+                std::cmp::Ordering::Equal => 0.0,
+            });
+        }
+
+        let player = actions.len() % 2;
+        let facing = actions.ends_with('r');
+        let raises = actions.matches('r').count();
+        let mut choices = Vec::new();
+        if facing {
+            choices.push(("fold", 'f'));
+        }
+        choices.push((if facing { "call" } else { "check" }, 'c'));
+        if raises < 2 {
+            choices.push((if facing { "raise" } else { "bet" }, 'r'));
+        }
+        let board = state.board.map_or(String::new(), |card| KUHN_CARDS[card / 2].to_string());
+        Node::Decision {
+            player,
+            info_set: format!("{}{}:{}", KUHN_CARDS[hole[player] / 2], board, state.rounds.join("/")),
+            actions: choices
+                .into_iter()
+                .map(|(name, action)| {
+                    let mut rounds = state.rounds.clone();
+                    rounds[round].push(action);
+                    (name.to_string(), LeducState { rounds, ..state.clone() })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The toy games that come with the solver, to pick from by name
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ToyGame {
+    Kuhn,
+    Leduc,
+}
+
+impl Display for ToyGame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            ToyGame::Kuhn => "kuhn",
+            ToyGame::Leduc => "leduc",
+        })
+    }
+}
+
+impl FromStr for ToyGame {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kuhn" => Ok(ToyGame::Kuhn),
+            "leduc" => Ok(ToyGame::Leduc),
+            _ => Err("Expected kuhn or leduc"),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cfr() {
+        // Kuhn poker is worth -1/18 to the first player
+        let mut cfr = Cfr::new(Kuhn);
+        for _ in 0..2000 {
+            cfr.iterate();
+        }
+        let profile = cfr.average_strategy();
+        assert_eq!(profile.info_sets.len(), 12);
+        assert!((expected_value(&Kuhn, &profile) + 1.0 / 18.0).abs() < 0.005);
+        // the king always calls a bet and the jack never does
+        assert!(profile.probabilities("K:pb", 2)[1] > 0.99);
+        assert!(profile.probabilities("J:b", 2)[0] > 0.99);
+        assert!(exploitability(&Kuhn, &profile) < 0.005);
+
+        // always betting loses to a player who calls with anything but the jack
+        let mut always_bet = StrategyProfile::default();
+        for info_set in profile.info_sets.keys() {
+            always_bet.info_sets.insert(info_set.clone(), vec![("p".to_string(), 0.0), ("b".to_string(), 1.0)]);
+        }
+        let response = best_response(&Kuhn, &always_bet, 1);
+        assert_eq!(response.profile.probabilities("J:b", 2), [1.0, 0.0]);
+        assert_eq!(response.profile.probabilities("Q:b", 2), [0.0, 1.0]);
+        assert!(exploitability(&Kuhn, &always_bet) > 0.1);
+
+        let text = profile.to_json("kuhn");
+        assert_eq!(text.lines().count(), 14);
+        assert_eq!(StrategyProfile::from_json(&text).unwrap(), ("kuhn".to_string(), profile.clone()));
+        assert_eq!(StrategyProfile::from_bytes(&profile.to_bytes()), Some(profile.clone()));
+        assert!(StrategyProfile::from_json(&text.replace("\"version\": 1", "\"version\": 9")).is_err());
+
+        let mut cfr = Cfr::new(Leduc);
+        let mut rng = rand::rng();
+        for _ in 0..200 {
+            cfr.iterate_sampled(&mut rng);
+        }
+        let profile = cfr.average_strategy();
+        assert!(profile.info_sets.len() <= 288);
+        assert!(expected_value(&Leduc, &profile).abs() < 1.0);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::history::*;
+use crate::json::*;
+use crate::position::*;
+use crate::street::*;
+use std::collections::HashMap;
+use itertools::Itertools;
+
+
+/// Counts behind the usual HUD statistics for one player, over the hands they were dealt into
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct PlayerStats {
+    pub hands: usize,
+    /// Voluntarily put money in preflop, by calling or raising
+    pub vpip: usize,
+    /// Raised preflop
+    pub pfr: usize,
+    /// Faced exactly one raise preflop with the chance to act
+    pub three_bet_chances: usize,
+    pub three_bets: usize,
+    /// Bets and raises after the flop
+    pub aggressive: usize,
+    /// Calls after the flop
+    pub calls: usize,
+    pub saw_flop: usize,
+    pub showdowns: usize,
+    /// Hands and vpip by position
+    pub by_position: HashMap<Position, (usize, usize)>,
+}
+
+fn percent(count: usize, out_of: usize) -> f64 {
+    if out_of == 0 { 0.0 } else { count as f64 / out_of as f64 * 100.0 }
+}
+
+impl PlayerStats {
+    pub fn vpip(&self) -> f64 {
+        percent(self.vpip, self.hands)
+    }
+
+    pub fn pfr(&self) -> f64 {
+        percent(self.pfr, self.hands)
+    }
+
+    pub fn three_bet(&self) -> f64 {
+        percent(self.three_bets, self.three_bet_chances)
+    }
+
+    /// Bets and raises for every call after the flop, infinite if they never call
+    pub fn aggression(&self) -> f64 {
+        self.aggressive as f64 / self.calls as f64
+    }
+
+    /// Went to showdown, of the hands they saw a flop in
+    pub fn wtsd(&self) -> f64 {
+        percent(self.showdowns, self.saw_flop)
+    }
+
+    /// Add one hand, which the player was dealt into
+    fn add(&mut self, hand: &HandRecord, name: &str, position: Option<Position>) {
+        let preflop: Vec<&HistoryAction> = hand.actions.iter().filter(|action| action.street == Street::Preflop).collect();
+        let my_preflop: Vec<&HistoryAction> = preflop.iter().copied().filter(|action| action.player == name).collect();
+        let vpip = my_preflop.iter().any(|action| matches!(action.play, Play::Call(_) | Play::Bet(_) | Play::Raise(..)));
+        self.hands += 1;
+        self.vpip += vpip as usize;
+        self.pfr += my_preflop.iter().any(|action| matches!(action.play, Play::Raise(..))) as usize;
+
+        // the first time they act facing a single raise
+        let mut raises = 0;
+        for action in &preflop {
+            if action.player == name && !matches!(action.play, Play::Post(..)) && raises == 1 {
+                self.three_bet_chances += 1;
+                self.three_bets += matches!(action.play, Play::Raise(..)) as usize;
+                break;
+            }
+            raises += matches!(action.play, Play::Raise(..) | Play::Bet(_)) as usize;
+        }
+
+        for action in hand.actions.iter().filter(|action| action.player == name && action.street > Street::Preflop) {
+            match action.play {
+                Play::Bet(_) | Play::Raise(..) => self.aggressive += 1,
+                Play::Call(_) => self.calls += 1,
+                _ => {}
+            }
+        }
+
+        let folded = |player: &str| hand.actions.iter().any(|action| action.player == player && action.play == Play::Fold);
+        let folded_preflop = my_preflop.iter().any(|action| action.play == Play::Fold);
+        let left = hand.players.iter().filter(|player| dealt_in(hand, &player.name) && !folded(&player.name)).count();
+        // a flop counts as seen if one came and they were still in for it
+        if hand.board.len() >= 3 && !folded_preflop {
+            self.saw_flop += 1;
+            self.showdowns += (!folded(name) && left > 1) as usize;
+        }
+
+        if let Some(position) = position {
+            let (hands, vpips) = self.by_position.entry(position).or_default();
+            *hands += 1;
+            *vpips += vpip as usize;
+        }
+    }
+}
+
+impl ToJson for PlayerStats {
+    /// The statistics as percentages, as the table shows them, with hands and vpip by position
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("hands", self.hands.into()),
+            ("vpip", self.vpip().into()),
+            ("pfr", self.pfr().into()),
+            ("three_bet", self.three_bet().into()),
+            ("aggression", self.aggression().into()),
+            ("wtsd", self.wtsd().into()),
+            ("by_position", Json::Object(self.by_position.iter().sorted().map(|(position, &(hands, vpip))| {
+                (position.to_string(), Json::object([("hands", hands.into()), ("vpip", percent(vpip, hands).into())]))
+            }).collect())),
+        ])
+    }
+}
+
+/// Whether the player took part in the hand, posting or acting
+fn dealt_in(hand: &HandRecord, name: &str) -> bool {
+    hand.actions.iter().any(|action| action.player == name)
+}
+
+/// Where each player dealt in sat relative to the button, None if the button's seat wasn't dealt in
+pub fn positions(hand: &HandRecord) -> Option<Vec<(String, Position)>> {
+    let mut seats: Vec<&PlayerRecord> = hand.players.iter().filter(|player| dealt_in(hand, &player.name)).collect();
+    seats.sort_by_key(|player| player.seat);
+    let button = seats.iter().position(|player| player.seat == hand.button)?;
+    let n = seats.len();
+    if !(2..=9).contains(&n) {
+        return None;
+    }
+    Some(seats
+        .iter()
+        .enumerate()
+        .map(|(i, player)| (player.name.clone(), Position::left_of_button((i + n - button) % n, n)))
+        .collect())
+}
+
+/// Statistics for everyone in the hands, by name
+pub fn player_stats(hands: &[HandRecord]) -> HashMap<String, PlayerStats> {
+    let mut stats: HashMap<String, PlayerStats> = HashMap::new();
+    for hand in hands {
+        let positions = positions(hand).unwrap_or_default();
+        for player in hand.players.iter().filter(|player| dealt_in(hand, &player.name)) {
+            let position = positions.iter().find(|(name, _)| *name == player.name).map(|&(_, position)| position);
+            stats.entry(player.name.clone()).or_default().add(hand, &player.name, position);
+        }
+    }
+    stats
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HANDS: &str = "\
+PokerStars Hand #1: Hold'em No Limit (1/2) - 2020/01/01 12:00:00 ET
+Table 'Gamma' 6-max Seat #1 is the button
+Seat 1: ann (200 in chips)
+Seat 2: ben (200 in chips)
+Seat 3: cat (200 in chips)
+Seat 4: dan (200 in chips) is sitting out
+ben: posts small blind 1
+cat: posts big blind 2
+*** HOLE CARDS ***
+ann: raises 4 to 6
+ben: raises 12 to 18
+cat: folds
+ann: calls 12
+*** FLOP *** [2c 7d 9s]
+ben: bets 20
+ann: calls 20
+*** TURN *** [2c 7d 9s] [Th]
+ben: bets 40
+ann: folds
+Uncalled bet (40) returned to ben
+ben collected 77 from pot
+*** SUMMARY ***
+
+PokerStars Hand #2: Hold'em No Limit (1/2) - 2020/01/01 12:01:00 ET
+Table 'Gamma' 6-max Seat #2 is the button
+Seat 1: ann (162 in chips)
+Seat 2: ben (239 in chips)
+Seat 3: cat (198 in chips)
+cat: posts small blind 1
+ann: posts big blind 2
+*** HOLE CARDS ***
+ben: calls 2
+cat: calls 1
+ann: checks
+*** FLOP *** [Ks 8c 3d]
+cat: checks
+ann: checks
+ben: checks
+*** TURN *** [Ks 8c 3d] [4h]
+cat: checks
+ann: checks
+ben: checks
+*** RIVER *** [Ks 8c 3d 4h] [4c]
+cat: checks
+ann: checks
+ben: checks
+*** SHOW DOWN ***
+ben collected 6 from pot
+*** SUMMARY ***
+";
+
+    #[test]
+    fn test_player_stats() {
+        let hands = parse_history(HANDS).unwrap();
+        assert_eq!(positions(&hands[0]).unwrap()[0], ("ann".to_string(), Position::Button));
+        let stats = player_stats(&hands);
+        assert!(!stats.contains_key("dan"));
+
+        let ann = &stats["ann"];
+        assert_eq!((ann.hands, ann.vpip, ann.pfr), (2, 1, 1));
+        assert_eq!((ann.three_bet_chances, ann.three_bets), (0, 0));
+        assert_eq!((ann.saw_flop, ann.showdowns, ann.calls), (2, 1, 1));
+        assert_eq!(ann.by_position[&Position::Button], (1, 1));
+        assert_eq!(ann.by_position[&Position::BigBlind], (1, 0));
+
+        let ben = &stats["ben"];
+        assert_eq!((ben.vpip(), ben.pfr()), (100.0, 50.0));
+        assert_eq!((ben.three_bet_chances, ben.three_bets, ben.three_bet()), (1, 1, 100.0));
+        assert_eq!(ben.aggression(), 2.0 / 0.0);
+        // cat faced a 3-bet, not a single raise
+        assert_eq!(stats["cat"].three_bet_chances, 0);
+        assert_eq!(stats["cat"].wtsd(), 100.0);
+    }
+}
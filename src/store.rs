@@ -0,0 +1,179 @@
+//! Local content-addressed store for expensive results (matchup tables, solves, reports)
+//! Artifacts are saved under the sha256 of their kind and inputs, so repeating an
+//! identical computation in a later session just reads the previous result back
+
+use std::{fs, io::{self, Write}, path::{Path, PathBuf}};
+use sha2::{Digest, Sha256};
+
+
+/// Something worth persisting between runs
+pub trait Artifact: Sized {
+    /// Short name for the kind of artifact, hashed in with the inputs
+    const KIND: &'static str;
+
+    fn to_bytes(&self) -> Vec<u8>;
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+/// Hex sha256 of an artifact's kind and the inputs it was computed from
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Key(String);
+
+impl Key {
+    pub fn new(kind: &str, inputs: &[u8]) -> Key {
+        let mut hasher = Sha256::new();
+        hasher.update(kind.as_bytes());
+        hasher.update([0]);
+        hasher.update(inputs);
+        Key(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Line of the store's index file
+#[derive(Debug, PartialEq, Clone)]
+pub struct IndexEntry {
+    pub key: Key,
+    pub kind: String,
+    pub size: usize,
+    pub description: String,
+}
+
+/// Directory holding objects/<key> files and an index of what they are
+pub struct Store {
+    dir: PathBuf,
+}
+
+impl Store {
+    /// $POKER_STORE if set, otherwise .poker-store in the working directory
+    pub fn default_dir() -> PathBuf {
+        std::env::var_os("POKER_STORE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(".poker-store"))
+    }
+
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Store> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(dir.join("objects"))?;
+        Ok(Store { dir })
+    }
+
+    fn object_path(&self, key: &Key) -> PathBuf {
+        self.dir.join("objects").join(key.as_str())
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("index")
+    }
+
+    pub fn get<T: Artifact>(&self, inputs: &[u8]) -> Option<T> {
+        let bytes = fs::read(self.object_path(&Key::new(T::KIND, inputs))).ok()?;
+        T::from_bytes(&bytes)
+    }
+
+    /// Save (or overwrite) the artifact computed from inputs
+    pub fn put<T: Artifact>(&self, inputs: &[u8], description: &str, artifact: &T) -> io::Result<Key> {
+        let key = Key::new(T::KIND, inputs);
+        let bytes = artifact.to_bytes();
+
+        // write then rename so a crash never leaves a half written object behind
+        let tmp = self.object_path(&key).with_extension("tmp");
+        fs::write(&tmp, &bytes)?;
+        fs::rename(&tmp, self.object_path(&key))?;
+
+        let mut entries = self.index()?;
+        entries.retain(|entry| entry.key != key);
+        entries.push(IndexEntry {
+            key: key.clone(),
+            kind: T::KIND.to_string(),
+            size: bytes.len(),
+            description: description.replace(['\t', '\n'], " "),
+        });
+        self.write_index(&entries)?;
+        Ok(key)
+    }
+
+    /// Previously stored result for these inputs, or compute and store it
+    pub fn get_or_compute<T: Artifact>(&self, inputs: &[u8], description: &str, compute: impl FnOnce() -> T) -> io::Result<T> {
+        if let Some(artifact) = self.get(inputs) {
+            return Ok(artifact);
+        }
+        let artifact = compute();
+        self.put(inputs, description, &artifact)?;
+        Ok(artifact)
+    }
+
+    pub fn index(&self) -> io::Result<Vec<IndexEntry>> {
+        let contents = match fs::read_to_string(self.index_path()) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(contents
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(4, '\t');
+                Some(IndexEntry {
+                    key: Key(fields.next()?.to_string()),
+                    kind: fields.next()?.to_string(),
+                    size: fields.next()?.parse().ok()?,
+                    description: fields.next().unwrap_or("").to_string(),
+                })
+            })
+            .collect())
+    }
+
+    fn write_index(&self, entries: &[IndexEntry]) -> io::Result<()> {
+        let tmp = self.index_path().with_extension("tmp");
+        let mut file = fs::File::create(&tmp)?;
+        for entry in entries {
+            writeln!(file, "{}\t{}\t{}\t{}", entry.key.as_str(), entry.kind, entry.size, entry.description)?;
+        }
+        fs::rename(&tmp, self.index_path())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Artifact for Vec<u64> {
+        const KIND: &'static str = "test-u64s";
+
+        fn to_bytes(&self) -> Vec<u8> {
+            self.iter().flat_map(|x| x.to_le_bytes()).collect()
+        }
+
+        fn from_bytes(bytes: &[u8]) -> Option<Self> {
+            Some(bytes.chunks_exact(8).map(|c| u64::from_le_bytes(c.try_into().unwrap())).collect())
+        }
+    }
+
+    #[test]
+    fn test_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("poker-store-test-{}", std::process::id()));
+        let store = Store::open(&dir).unwrap();
+
+        assert_eq!(store.get::<Vec<u64>>(b"inputs"), None);
+        let computed = store.get_or_compute(b"inputs", "first", || vec![1, 2, 3]).unwrap();
+        assert_eq!(computed, vec![1, 2, 3]);
+
+        // same inputs reuse the stored value instead of computing again
+        let reused = store.get_or_compute(b"inputs", "second", || -> Vec<u64> { unreachable!() }).unwrap();
+        assert_eq!(reused, vec![1, 2, 3]);
+
+        store.put(b"inputs", "overwritten", &vec![4]).unwrap();
+        store.put(b"other inputs", "other", &vec![5, 6]).unwrap();
+        let index = store.index().unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index[0].description, "overwritten");
+        assert_eq!(index[0].size, 8);
+        assert_eq!(index[1].key, Key::new("test-u64s", b"other inputs"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
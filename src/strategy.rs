@@ -0,0 +1,280 @@
+use crate::class::*;
+use crate::game::*;
+use crate::preflop::*;
+use crate::push_fold::*;
+use crate::street::*;
+use crate::strength::*;
+use crate::solver::*;
+use crate::table::*;
+use std::{fmt::{Display, Formatter}, str::FromStr};
+use rand::Rng;
+
+
+/// Something that plays hands: a bot, or a person behind some interface
+pub trait Strategy {
+    fn act(&mut self, state: &GameView) -> Action;
+}
+
+/// Never folds or raises, calling any bet and checking otherwise
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AlwaysCall;
+
+impl Strategy for AlwaysCall {
+    fn act(&mut self, state: &GameView) -> Action {
+        if state.to_call == 0 { Action::Check } else { Action::Call }
+    }
+}
+
+/// Equity heads up against a random hand: from the table preflop, and the hand strength as it
+/// stands after the flop, which is quick
+fn quick_equity(state: &GameView) -> f64 {
+    match state.street {
+        Street::Preflop => preflop_equity(HandClass::from_pair(&state.hole)),
+        _ => hand_strength(&state.hole, state.board),
+    }
+}
+
+/// The equity a bot needs to put its stack at risk: the chip threshold in a cash game, and in a
+/// tournament paying these payouts the threshold raised by the bubble factor against the biggest
+/// stack still in the hand. Only the players at the table count towards the prizes
+fn icm_adjusted(state: &GameView, threshold: f64, payouts: Option<&[f64]>) -> f64 {
+    let Some(payouts) = payouts else {
+        return threshold;
+    };
+    let stacks: Vec<f64> = state.stacks.iter().zip(&state.contributed).map(|(&stack, &put_in)| (stack + put_in) as f64).collect();
+    let villain = (0..stacks.len())
+        .filter(|&i| i != state.seat && !state.folded[i])
+        .max_by(|&a, &b| stacks[a].total_cmp(&stacks[b]));
+    match villain {
+        Some(villain) => icm_threshold(threshold, bubble_factor(&stacks, payouts, state.seat, villain)),
+        None => threshold,
+    }
+}
+
+/// Moves all in with at least the threshold equity against a random hand, and otherwise checks
+/// or folds
+#[derive(Debug, Clone)]
+pub struct PushFold {
+    pub threshold: f64,
+    /// Tournament payouts from first place, to play tighter near the bubble; None plays for chips
+    pub payouts: Option<Vec<f64>>,
+}
+
+impl Strategy for PushFold {
+    fn act(&mut self, state: &GameView) -> Action {
+        if quick_equity(state) >= icm_adjusted(state, self.threshold, self.payouts.as_deref()) {
+            Action::AllIn
+        } else if state.to_call == 0 {
+            Action::Check
+        } else {
+            Action::Fold
+        }
+    }
+}
+
+/// Bets by effective hand strength: raises the size of the pot with at least raise_above,
+/// otherwise calls when the pot odds are good enough and folds when they're not.
+/// Against several opponents the strength is raised to the power of how many there are, as if
+/// it had to beat each of them on its own. Rolling out the flop takes a moment
+#[derive(Debug, Clone)]
+pub struct EhsBettor {
+    pub raise_above: f64,
+    /// Tournament payouts from first place, to raise and call tighter near the bubble; None plays
+    /// for chips
+    pub payouts: Option<Vec<f64>>,
+}
+
+impl Default for EhsBettor {
+    fn default() -> Self {
+        EhsBettor { raise_above: 0.8, payouts: None }
+    }
+}
+
+impl Strategy for EhsBettor {
+    fn act(&mut self, state: &GameView) -> Action {
+        let strength = match state.street {
+            Street::Preflop => preflop_equity(HandClass::from_pair(&state.hole)),
+            _ => effective_hand_strength(&state.hole, state.board).ehs,
+        }
+        .powi(state.opponents().max(1) as i32);
+
+        let payouts = self.payouts.as_deref();
+        if strength >= icm_adjusted(state, self.raise_above, payouts)
+            && let Some(min) = state.min_raise_to
+        {
+            // the bet after calling plus the pot after calling
+            let bet = state.bets[state.seat] + state.to_call;
+            let pot = bet + state.pot + state.to_call;
+            return Action::Raise(pot.clamp(min, state.max_raise_to));
+        }
+        if state.to_call == 0 {
+            Action::Check
+        } else if strength >= icm_adjusted(state, state.pot_odds(), payouts) {
+            Action::Call
+        } else {
+            Action::Fold
+        }
+    }
+}
+
+/// Plays preflop from a push/fold chart (see PushFoldSolution::to_profile), picking at random
+/// as often as it says: all in or fold when the pot's unopened, and call or fold facing a
+/// raise. After the flop it checks or folds, which only matters if someone didn't push
+#[derive(Debug, Clone)]
+pub struct ChartBot {
+    pub profile: StrategyProfile,
+}
+
+impl Strategy for ChartBot {
+    fn act(&mut self, state: &GameView) -> Action {
+        let passive = if state.to_call == 0 { Action::Check } else { Action::Fold };
+        if state.street != Street::Preflop {
+            return passive;
+        }
+        let raised = state.bets.iter().any(|&bet| bet > state.big_blind);
+        let key = format!("{}:{}", if raised { "call" } else { "push" }, HandClass::from_pair(&state.hole));
+        // hands the chart leaves out fold
+        let p = self.profile.info_sets.get(&key).and_then(|strategy| strategy.get(1)).map_or(0.0, |&(_, p)| p);
+        match (rand::rng().random_bool(p.clamp(0.0, 1.0)), raised) {
+            (false, _) => passive,
+            (true, true) => Action::Call,
+            (true, false) => Action::AllIn,
+        }
+    }
+}
+
+/// The bots that come with the crate, to pick from by name
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Bot {
+    Call,
+    /// All in with 60% equity against a random hand
+    PushFold,
+    #[default]
+    Ehs,
+}
+
+impl Bot {
+    pub fn strategy(self) -> Box<dyn Strategy> {
+        match self {
+            Bot::Call => Box::new(AlwaysCall),
+            Bot::PushFold => Box::new(PushFold { threshold: 0.6, payouts: None }),
+            Bot::Ehs => Box::new(EhsBettor::default()),
+        }
+    }
+}
+
+impl Display for Bot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Bot::Call => "call",
+            Bot::PushFold => "push-fold",
+            Bot::Ehs => "ehs",
+        })
+    }
+}
+
+impl FromStr for Bot {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "call" => Ok(Bot::Call),
+            "push-fold" => Ok(Bot::PushFold),
+            "ehs" => Ok(Bot::Ehs),
+            _ => Err("Expected call, push-fold or ehs"),
+        }
+    }
+}
+
+/// Play a hand out to the end, asking the bot in each seat what to do
+pub fn play_hand(hand: &mut HandState, bots: &mut [Box<dyn Strategy>]) -> Result<(), &'static str> {
+    debug_assert_eq!(hand.seats.len(), bots.len());
+    while let Some(seat) = hand.to_act() {
+        let action = bots[seat].act(&hand.view(seat));
+        hand.act(action)?;
+    }
+    Ok(())
+}
+
+/// Play up to this many hands at the table, with a bot for every seat (empty ones included),
+/// stopping early once only one player has chips. Returns the number of hands played
+pub fn run_match<R: Rng + ?Sized>(table: &mut Table, bots: &mut [Box<dyn Strategy>], hands: usize, rng: &mut R) -> Result<usize, &'static str> {
+    debug_assert_eq!(table.seats.len(), bots.len());
+    for played in 0..hands {
+        if table.active().len() < 2 {
+            return Ok(played);
+        }
+        let active = table.active();
+        let mut hand = table.deal(rng)?;
+        // seat k of the hand is active[k] at the table
+        while let Some(seat) = hand.to_act() {
+            let action = bots[active[seat]].act(&hand.view(seat));
+            hand.act(action)?;
+        }
+        table.finish(&hand);
+    }
+    Ok(hands)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::*;
+
+    #[test]
+    fn test_agents() {
+        // seven deuce on the button folds and aces in the big blind shove
+        let deck = |holes: &str| {
+            let mut deck = parse_cards(holes).unwrap();
+            deck.extend(Card::DECK.into_iter().filter(|card| !deck.contains(card)).collect::<Vec<_>>());
+            deck
+        };
+        let mut hand = HandState::with_deck(&[100, 100], 0, 1, 2, deck("AsAh7c2d"));
+        assert_eq!(PushFold { threshold: 0.6, payouts: None }.act(&hand.view(0)), Action::Fold);
+        hand.act(Action::Call).unwrap();
+        assert_eq!(PushFold { threshold: 0.6, payouts: None }.act(&hand.view(1)), Action::AllIn);
+        // a pot sized raise on top of the 2 already in, with nothing to call, is to 6
+        assert_eq!(EhsBettor::default().act(&hand.view(1)), Action::Raise(6));
+        assert_eq!(AlwaysCall.act(&hand.view(1)), Action::Check);
+
+        // on the bubble, four left and three paid, a hand just good enough to push for chips folds
+        let bubble = HandState::with_deck(&[25; 4], 0, 1, 2, deck("2c3c4c5cAsKd"));
+        let threshold = preflop_equity("AKo".parse().unwrap());
+        assert_eq!(PushFold { threshold, payouts: None }.act(&bubble.view(3)), Action::AllIn);
+        assert_eq!(PushFold { threshold, payouts: Some(vec![0.5, 0.3, 0.2]) }.act(&bubble.view(3)), Action::Fold);
+
+        let mut bots: [Box<dyn Strategy>; 2] = [Box::new(AlwaysCall), Box::new(AlwaysCall)];
+        play_hand(&mut hand, &mut bots).unwrap();
+        assert!(hand.is_over() && hand.board.len() == 5);
+
+        // a chart that only pushes aces, which an always calling chart calls
+        let mut chart = StrategyProfile::default();
+        chart.info_sets.insert("push:AA".to_string(), vec![("fold".to_string(), 0.0), ("push".to_string(), 1.0)]);
+        chart.info_sets.insert("call:72o".to_string(), vec![("fold".to_string(), 0.0), ("call".to_string(), 1.0)]);
+        let mut hand = HandState::with_deck(&[100, 100], 0, 1, 2, deck("AsAh7c2d"));
+        let mut bot = ChartBot { profile: chart };
+        assert_eq!(bot.act(&hand.view(0)), Action::Fold);
+        hand.act(Action::Call).unwrap();
+        assert_eq!(bot.act(&hand.view(1)), Action::AllIn);
+        hand.act(Action::AllIn).unwrap();
+        assert_eq!(bot.act(&hand.view(0)), Action::Call);
+    }
+
+    #[test]
+    fn test_run_match() {
+        let mut table = Table::new(3, 1, 2);
+        for seat in 0..3 {
+            table.sit(seat, Player { name: seat.to_string(), stack: 50 }).unwrap();
+        }
+        let mut bots: Vec<Box<dyn Strategy>> = vec![
+            Box::new(PushFold { threshold: 0.55, payouts: None }),
+            Box::new(PushFold { threshold: 0.5, payouts: None }),
+            Box::new(AlwaysCall),
+        ];
+        let played = run_match(&mut table, &mut bots, 20, &mut rand::rng()).unwrap();
+        assert!(played > 0);
+        let chips: Chips = table.seats.iter().flatten().map(|player| player.stack).sum();
+        assert_eq!(chips, 150);
+    }
+}
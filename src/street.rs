@@ -0,0 +1,98 @@
+use crate::card::*;
+use std::{fmt::{Display, Formatter}, str::FromStr};
+
+
+/// Betting round, in the order they're played
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+}
+
+impl Street {
+    pub const ALL_STREETS: [Street; 4] = [Street::Preflop, Street::Flop, Street::Turn, Street::River];
+
+    /// Community cards out on this street
+    pub fn board_len(self) -> usize {
+        match self {
+            Street::Preflop => 0,
+            Street::Flop => 3,
+            Street::Turn => 4,
+            Street::River => 5,
+        }
+    }
+
+    /// Street a board of this many cards is on, None for 1, 2 or more than 5 cards
+    pub fn from_board_len(n: usize) -> Option<Street> {
+        Street::ALL_STREETS.into_iter().find(|street| street.board_len() == n)
+    }
+
+    pub fn of(board: &[Card]) -> Option<Street> {
+        Street::from_board_len(board.len())
+    }
+
+    /// None after the river
+    pub fn next(self) -> Option<Street> {
+        Street::ALL_STREETS.get(self as usize + 1).copied()
+    }
+
+    /// Cards dealt to the board going into this street
+    pub fn cards_dealt(self) -> usize {
+        match self {
+            Street::Preflop => 0,
+            Street::Flop => 3,
+            Street::Turn | Street::River => 1,
+        }
+    }
+}
+
+impl From<Street> for usize {
+    fn from(street: Street) -> Self {
+        street as usize
+    }
+}
+
+impl Display for Street {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Street::Preflop => "preflop",
+            Street::Flop => "flop",
+            Street::Turn => "turn",
+            Street::River => "river",
+        })
+    }
+}
+
+impl FromStr for Street {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Street::ALL_STREETS
+            .into_iter()
+            .find(|street| street.to_string().eq_ignore_ascii_case(s))
+            .ok_or("Expected preflop, flop, turn or river")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streets() {
+        assert!(Street::Preflop < Street::River);
+        assert_eq!(Street::Flop.next(), Some(Street::Turn));
+        assert_eq!(Street::River.next(), None);
+        assert_eq!(Street::of(&parse_cards("AhKh4s9c").unwrap()), Some(Street::Turn));
+        assert_eq!(Street::from_board_len(2), None);
+
+        let dealt: usize = Street::ALL_STREETS.iter().map(|street| street.cards_dealt()).sum();
+        assert_eq!(dealt, Street::River.board_len());
+        for street in Street::ALL_STREETS {
+            assert_eq!(street.to_string().parse(), Ok(street));
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use crate::card::*;
+use crate::combinatorics::*;
+use crate::eval::*;
+use crate::score::*;
+use crate::street::*;
+use itertools::Itertools;
+
+/// Raw hand strength (HS) on the current board, ignoring cards still to come:
+/// the fraction of all remaining two card combos the pair currently beats,
+/// with ties counted as half.
+/// Needs at least the flop on the board
+pub fn hand_strength(pair: &(Card, Card), board: &[Card]) -> f64 {
+    hand_strength_with_dead(pair, board, &[])
+}
+
+/// hand_strength where the evil pair can't hold any of the dead cards
+pub fn hand_strength_with_dead(pair: &(Card, Card), board: &[Card], dead: &[Card]) -> f64 {
+    debug_assert!(board.len() >= 3 && board.len() <= 5);
+
+    let deck = deck_without(&[board, dead, &[pair.0, pair.1]].concat());
+
+    let my_score = get_best_score(pair, board);
+
+    let mut ahead: usize = 0;
+    let mut tied: usize = 0;
+    let mut total: usize = 0;
+    for evil_pair in deck.iter().copied().tuple_combinations::<(Card, Card)>() {
+        let evil_score = get_best_score(&evil_pair, board);
+        if my_score < evil_score {
+            ahead += 1;
+        } else if my_score == evil_score {
+            tied += 1;
+        }
+        total += 1;
+    }
+
+    (ahead as f64 + tied as f64 / 2.0) / total as f64
+}
+
+
+/// Effective hand strength and the potentials it is built from
+/// - ppot: chance of ending up ahead by the river when currently behind or tied
+/// - npot: chance of ending up behind by the river when currently ahead or tied
+/// - ehs: hs + (1 - hs) * ppot - hs * npot
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Ehs {
+    pub hs: f64,
+    pub ppot: f64,
+    pub npot: f64,
+    pub ehs: f64,
+}
+
+const AHEAD: usize = 0;
+const TIED: usize = 1;
+const BEHIND: usize = 2;
+
+fn compare(my_score: Score, evil_score: Score) -> usize {
+    match my_score.cmp(&evil_score) {
+        std::cmp::Ordering::Less => AHEAD,
+        std::cmp::Ordering::Equal => TIED,
+        std::cmp::Ordering::Greater => BEHIND,
+    }
+}
+
+/// EHS on the current street, rolling out every remaining turn and river card
+/// against every remaining two card combo.
+/// On the river there is nothing left to come so both potentials are 0
+pub fn effective_hand_strength(pair: &(Card, Card), board: &[Card]) -> Ehs {
+    effective_hand_strength_with_dead(pair, board, &[])
+}
+
+/// effective_hand_strength with dead cards that neither the evil pair nor the runout can use
+pub fn effective_hand_strength_with_dead(pair: &(Card, Card), board: &[Card], dead: &[Card]) -> Ehs {
+    debug_assert!(board.len() >= 3 && board.len() <= 5);
+
+    let deck = deck_without(&[board, dead, &[pair.0, pair.1]].concat());
+
+    let evil_pairs: Vec<(Card, Card)> = deck
+        .iter()
+        .copied()
+        .tuple_combinations()
+        .collect();
+
+    let my_score = get_best_score(pair, board);
+    let now: Vec<usize> = evil_pairs
+        .iter()
+        .map(|evil_pair| compare(my_score, get_best_score(evil_pair, board)))
+        .collect();
+
+    // hp[now][river] counted once per (evil pair, runout)
+    let mut hp = [[0usize; 3]; 3];
+    let mut hp_total = [0usize; 3];
+
+    let n = board.len();
+
+    for_each_runout(board, &deck, &[&[pair.0, pair.1], board, dead], |community, remainder, weight| {
+        let my_river_score = get_best_score(pair, community);
+        for (evil_pair, &index) in evil_pairs.iter().zip(&now) {
+            if remainder.contains(&evil_pair.0) || remainder.contains(&evil_pair.1) {
+                continue;
+            }
+            let river_index = compare(my_river_score, get_best_score(evil_pair, community));
+            hp[index][river_index] += weight;
+            hp_total[index] += weight;
+        }
+    });
+
+    let hp = hp.map(|row| row.map(|x| x as f64));
+    let hp_total = hp_total.map(|x| x as f64);
+
+    let total: f64 = hp_total.iter().sum();
+    let hs = (hp_total[AHEAD] + hp_total[TIED] / 2.0) / total;
+
+    let ppot_den = hp_total[BEHIND] + hp_total[TIED] / 2.0;
+    let ppot = if ppot_den == 0.0 || n == 5 {
+        0.0
+    } else {
+        (hp[BEHIND][AHEAD] + hp[BEHIND][TIED] / 2.0 + hp[TIED][AHEAD] / 2.0) / ppot_den
+    };
+
+    let npot_den = hp_total[AHEAD] + hp_total[TIED] / 2.0;
+    let npot = if npot_den == 0.0 || n == 5 {
+        0.0
+    } else {
+        (hp[AHEAD][BEHIND] + hp[TIED][BEHIND] / 2.0 + hp[AHEAD][TIED] / 2.0) / npot_den
+    };
+
+    Ehs {
+        hs,
+        ppot,
+        npot,
+        ehs: hs + (1.0 - hs) * ppot - hs * npot,
+    }
+}
+
+/// EHS for every street the board has reached, starting from the flop.
+/// e.g. a full 5 card board gives [flop, turn, river]
+pub fn effective_hand_strength_by_street(pair: &(Card, Card), board: &[Card]) -> Vec<(Street, Ehs)> {
+    Street::ALL_STREETS
+        .into_iter()
+        .filter(|street| *street >= Street::Flop && street.board_len() <= board.len())
+        .map(|street| (street, effective_hand_strength(pair, &board[..street.board_len()])))
+        .collect()
+}
+
+/// Exact all-in equity against a random hand of every combo that can be dealt on the board
+/// (ties count half), all at once.
+/// Each runout scores every live combo once, then a sweep from worst to best counts how many
+/// weaker combos don't share a card with each one, so this costs about as much as a single
+/// combo's exhaustive equity. Needs at least the flop on the board
+pub fn equities_vs_random(board: &[Card], dead: &[Card]) -> Vec<((Card, Card), f64)> {
+    debug_assert!(board.len() >= 3 && board.len() <= 5);
+
+    let deck = deck_without(&[board, dead].concat());
+    let pairs: Vec<(Card, Card)> = deck.iter().copied().tuple_combinations().collect();
+
+    // 2 points a win, 1 a tie
+    let mut points = vec![0usize; pairs.len()];
+    let mut showdowns = vec![0usize; pairs.len()];
+
+    let mut community = board.to_vec();
+    for remainder in deck.iter().copied().combinations(5 - board.len()) {
+        community.extend_from_slice(&remainder);
+        // evil pairs each live pair can meet: any two of the cards left after both
+        let opponents = choose(deck.len() - remainder.len() - 2, 2);
+
+        let mut live: Vec<(Score, usize)> = pairs
+            .iter()
+            .enumerate()
+            .filter(|(_, pair)| !remainder.contains(&pair.0) && !remainder.contains(&pair.1))
+            .map(|(i, pair)| (get_best_score(pair, &community), i))
+            .collect();
+        live.sort_unstable_by_key(|&(score, _)| std::cmp::Reverse(score));
+
+        let mut beaten = 0;
+        let mut beaten_with = [0usize; 52];
+        for group in live.chunk_by(|a, b| a.0 == b.0) {
+            let mut tied_with = [0usize; 52];
+            for &(_, i) in group {
+                tied_with[usize::from(pairs[i].0)] += 1;
+                tied_with[usize::from(pairs[i].1)] += 1;
+            }
+            for &(_, i) in group {
+                let (a, b) = (usize::from(pairs[i].0), usize::from(pairs[i].1));
+                // the pair itself is the only combo holding both its cards
+                let wins = beaten - beaten_with[a] - beaten_with[b];
+                let ties = group.len() + 1 - tied_with[a] - tied_with[b];
+                points[i] += 2 * wins + ties;
+                showdowns[i] += opponents;
+            }
+            for &(_, i) in group {
+                beaten += 1;
+                beaten_with[usize::from(pairs[i].0)] += 1;
+                beaten_with[usize::from(pairs[i].1)] += 1;
+            }
+        }
+        community.truncate(board.len());
+    }
+
+    pairs
+        .into_iter()
+        .zip(points.iter().zip(&showdowns))
+        .map(|(pair, (&points, &showdowns))| (pair, points as f64 / (2 * showdowns) as f64))
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hand_strength_nuts() {
+        // royal flush on the river can't be beaten
+        let board = [Card::new(Rank::Ace, Suit::Spades),
+                     Card::new(Rank::King, Suit::Spades),
+                     Card::new(Rank::Queen, Suit::Spades),
+                     Card::new(Rank::Two, Suit::Hearts),
+                     Card::new(Rank::Three, Suit::Clubs)];
+        let pair = (Card::new(Rank::Jack, Suit::Spades), Card::new(Rank::Ten, Suit::Spades));
+        assert_eq!(hand_strength(&pair, &board), 1.0);
+    }
+
+    #[test]
+    fn test_hand_strength_board_plays() {
+        // everyone plays the royal flush on the board
+        let board = [Card::new(Rank::Ace, Suit::Spades),
+                     Card::new(Rank::King, Suit::Spades),
+                     Card::new(Rank::Queen, Suit::Spades),
+                     Card::new(Rank::Jack, Suit::Spades),
+                     Card::new(Rank::Ten, Suit::Spades)];
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Clubs));
+        assert_eq!(hand_strength(&pair, &board), 0.5);
+    }
+
+    #[test]
+    fn test_ehs() {
+        let board = [Card::new(Rank::Ace, Suit::Hearts),
+                     Card::new(Rank::King, Suit::Hearts),
+                     Card::new(Rank::Four, Suit::Spades),
+                     Card::new(Rank::Nine, Suit::Clubs)];
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+
+        let ehs = effective_hand_strength(&pair, &board);
+        // weak hand now, but a flush and straight draw
+        assert!(ehs.hs < 0.5);
+        assert!(ehs.ppot > 0.0);
+        assert!(ehs.ehs > ehs.hs);
+
+        // HS from the rollout agrees with the direct calculation
+        assert!((ehs.hs - hand_strength(&pair, &board)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ehs_river_has_no_potential() {
+        let board = [Card::new(Rank::Ace, Suit::Hearts),
+                     Card::new(Rank::King, Suit::Hearts),
+                     Card::new(Rank::Four, Suit::Spades),
+                     Card::new(Rank::Nine, Suit::Clubs),
+                     Card::new(Rank::Nine, Suit::Diamonds)];
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+
+        let ehs = effective_hand_strength(&pair, &board);
+        assert_eq!(ehs.ppot, 0.0);
+        assert_eq!(ehs.npot, 0.0);
+        assert_eq!(ehs.ehs, ehs.hs);
+    }
+
+    #[test]
+    fn test_equities_vs_random() {
+        let river = parse_cards("AhKh4s9c9d").unwrap();
+        let equities = equities_vs_random(&river, &[]);
+        assert_eq!(equities.len(), 1081);
+        for &(pair, equity) in equities.iter().step_by(97) {
+            assert!((equity - hand_strength(&pair, &river)).abs() < 1e-9);
+        }
+
+        let turn = &river[..4];
+        let equities = equities_vs_random(turn, &[]);
+        for &(pair, equity) in equities.iter().step_by(331) {
+            let exact = crate::monte_carlo::eval_exhaustive(&pair, &Villain::Random, turn, &[], 1, Default::default());
+            assert!((equity - exact.equity).abs() < 1e-9);
+        }
+    }
+}
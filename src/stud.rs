@@ -0,0 +1,151 @@
+use crate::card::*;
+use crate::eval::*;
+use crate::low::*;
+use crate::monte_carlo::*;
+use crate::score::*;
+use std::time::Instant;
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
+
+
+/// Cards each stud player ends up with: three down, four up (the last of them dealt down)
+pub const STUD_CARDS: usize = 7;
+
+/// Best five of a stud player's seven cards. There's no board, every card is the player's own
+pub fn best_stud_score(cards: &[Card]) -> Score {
+    debug_assert_eq!(cards.len(), STUD_CARDS);
+    Game::Holdem.best_score(&cards[..2], &cards[2..])
+}
+
+/// Outcome for the hero of one showdown against every opponent, all with seven cards scored
+/// lowest best. Winning means beating everyone, tying means sharing the best hand
+fn showdown<S: Ord>(hero: &[Card], opponents: &[Vec<Card>], score: impl Fn(&[Card]) -> S) -> Tally {
+    let mine = score(hero);
+    let best = opponents.iter().map(|cards| score(cards)).min().unwrap();
+    let mut tally = Tally::default();
+    match mine.cmp(&best) {
+        std::cmp::Ordering::Less => tally.wins += 1,
+        std::cmp::Ordering::Equal => tally.ties += 1,
+        std::cmp::Ordering::Greater => tally.losses += 1,
+    }
+    tally
+}
+
+fn is_valid_stud_deal(hero: &[Card], opponents: &[Vec<Card>], dead: &[Card]) -> bool {
+    let cards: Vec<Card> = [hero, dead, &opponents.concat()].concat();
+    let needed = STUD_CARDS * (1 + opponents.len());
+    !opponents.is_empty()
+        && hero.len() <= STUD_CARDS
+        && opponents.iter().all(|up| up.len() <= 4)
+        && cards.iter().all_unique()
+        && needed + dead.len() <= 52
+}
+
+/// Exact stud equity of the hero's cards (down and up) against opponents showing only their upcards,
+/// enumerating every unseen card each player could still hold or be dealt.
+/// Dead cards are upcards folded earlier, which no one can have.
+/// Only quick late in the hand, from sixth street heads up
+pub fn eval_stud_exact(hero: &[Card], opponents: &[Vec<Card>], dead: &[Card]) -> Estimate {
+    eval_exact_by(hero, opponents, dead, best_stud_score)
+}
+
+/// Monte carlo stud equity, as eval_stud_exact but dealing the unseen cards at random until the stop rule is met
+pub fn eval_stud<R: Rng + ?Sized>(hero: &[Card], opponents: &[Vec<Card>], dead: &[Card], rule: &StopRule, rng: &mut R) -> Estimate {
+    eval_by(hero, opponents, dead, rule, rng, best_stud_score)
+}
+
+/// eval_stud_exact for razz, where the best ace-to-five low wins
+pub fn eval_razz_exact(hero: &[Card], opponents: &[Vec<Card>], dead: &[Card]) -> Estimate {
+    eval_exact_by(hero, opponents, dead, LowScore::best)
+}
+
+/// eval_stud for razz, where the best ace-to-five low wins
+pub fn eval_razz<R: Rng + ?Sized>(hero: &[Card], opponents: &[Vec<Card>], dead: &[Card], rule: &StopRule, rng: &mut R) -> Estimate {
+    eval_by(hero, opponents, dead, rule, rng, LowScore::best)
+}
+
+fn eval_exact_by<S: Ord>(hero: &[Card], opponents: &[Vec<Card>], dead: &[Card], score: impl Fn(&[Card]) -> S) -> Estimate {
+    debug_assert!(is_valid_stud_deal(hero, opponents, dead));
+    let deck = deck_without(&[hero, dead, &opponents.concat()].concat());
+    let mut hands: Vec<Vec<Card>> = [hero.to_vec()].into_iter().chain(opponents.iter().cloned()).collect();
+    let mut tally = Tally::default();
+    for_each_completion(&mut hands, STUD_CARDS, &deck, |hands| {
+        tally.add(showdown(&hands[0], &hands[1..], &score));
+    });
+    Estimate::exact(tally)
+}
+
+fn eval_by<R: Rng + ?Sized, S: Ord>(hero: &[Card], opponents: &[Vec<Card>], dead: &[Card], rule: &StopRule, rng: &mut R,
+                                    score: impl Fn(&[Card]) -> S) -> Estimate {
+    debug_assert!(is_valid_stud_deal(hero, opponents, dead));
+    let mut deck = deck_without(&[hero, dead, &opponents.concat()].concat());
+    let needed = STUD_CARDS * (1 + opponents.len()) - hero.len() - opponents.iter().map(Vec::len).sum::<usize>();
+
+    let start = Instant::now();
+    let mut tally = Tally::default();
+    let mut hands: Vec<Vec<Card>> = [hero.to_vec()].into_iter().chain(opponents.iter().cloned()).collect();
+    loop {
+        for _ in 0..(rule.max_samples - tally.samples()).min(256) {
+            let (cards, _) = deck.partial_shuffle(rng, needed);
+            let mut cards: &[Card] = cards;
+            for hand in hands.iter_mut() {
+                let (dealt, rest) = cards.split_at(STUD_CARDS - hand.len());
+                hand.extend_from_slice(dealt);
+                cards = rest;
+            }
+            tally.add(showdown(&hands[0], &hands[1..], &score));
+            for (hand, known) in hands.iter_mut().zip([hero].into_iter().chain(opponents.iter().map(Vec::as_slice))) {
+                hand.truncate(known.len());
+            }
+        }
+        let estimate = Estimate::wilson(tally, rule.confidence);
+        if rule.is_done(&estimate, start) {
+            return estimate;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_stud_exact() {
+        // quad aces only lose to the six of clubs making a straight flush
+        let hero = parse_cards("AsAhAdAcKdQdJd").unwrap();
+        let opponents = vec![parse_cards("2c3c4c5c").unwrap()];
+        let estimate = eval_stud_exact(&hero, &opponents, &[]);
+        assert_eq!(estimate.tally, Tally { wins: 9880, ties: 0, losses: 780 });
+
+        // unless it was folded
+        let estimate = eval_stud_exact(&hero, &opponents, &parse_cards("6c").unwrap());
+        assert_eq!(estimate.tally, Tally { wins: 9880, ties: 0, losses: 0 });
+    }
+
+    #[test]
+    fn test_eval_stud() {
+        let hero = parse_cards("AsAhAdAcKd").unwrap();
+        let opponents = vec![parse_cards("2c3c4c").unwrap(), parse_cards("7h8h").unwrap()];
+        let rule = StopRule { half_width: 0.0, max_samples: 500, ..StopRule::default() };
+        let estimate = eval_stud(&hero, &opponents, &[], &rule, &mut rand::rng());
+        assert_eq!(estimate.tally.samples(), 500);
+        assert!(estimate.equity > 0.9);
+    }
+
+    #[test]
+    fn test_eval_razz() {
+        // a made wheel can't lose, and four big upcards can't tie it
+        let hero = parse_cards("Ah2d3c4s5hKcQc").unwrap();
+        let opponents = vec![parse_cards("KdQsJhTh").unwrap()];
+        let estimate = eval_razz_exact(&hero, &opponents, &[]);
+        assert_eq!(estimate.tally, Tally { wins: 10660, ties: 0, losses: 0 });
+
+        // quad aces are the worst hand there is in razz
+        let hero = parse_cards("AsAhAdAcKd").unwrap();
+        let opponents = vec![parse_cards("2c3c4c").unwrap()];
+        let rule = StopRule { half_width: 0.0, max_samples: 500, ..StopRule::default() };
+        let estimate = eval_razz(&hero, &opponents, &[], &rule, &mut rand::rng());
+        assert!(estimate.equity < 0.1);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::game::*;
+use crate::position::*;
+use rand::Rng;
+
+
+/// Someone sitting at the table
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Player {
+    pub name: String,
+    pub stack: Chips,
+}
+
+/// A table of numbered seats, some of them empty, and a button that moves round from hand to
+/// hand. Seat numbers stay put while players come and go, unlike the seats of a HandState, which
+/// are just the players dealt in
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Table {
+    pub seats: Vec<Option<Player>>,
+    /// Seat with the button, which always has someone in it once two players are dealt in
+    pub button: usize,
+    pub small_blind: Chips,
+    pub big_blind: Chips,
+}
+
+impl Table {
+    /// An empty table with this many seats (2-9)
+    pub fn new(seats: usize, small_blind: Chips, big_blind: Chips) -> Table {
+        debug_assert!((2..=9).contains(&seats));
+        Table { seats: vec![None; seats], button: 0, small_blind, big_blind }
+    }
+
+    pub fn sit(&mut self, seat: usize, player: Player) -> Result<(), &'static str> {
+        match self.seats.get_mut(seat) {
+            None => Err("no such seat"),
+            Some(Some(_)) => Err("seat taken"),
+            Some(empty) => {
+                *empty = Some(player);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn leave(&mut self, seat: usize) -> Option<Player> {
+        self.seats.get_mut(seat)?.take()
+    }
+
+    /// Seats that get dealt in: taken, with chips. In seat order
+    pub fn active(&self) -> Vec<usize> {
+        (0..self.seats.len())
+            .filter(|&seat| self.seats[seat].as_ref().is_some_and(|player| player.stack > 0))
+            .collect()
+    }
+
+    /// The next active seat round the table after this one, if there is one
+    pub fn next_active(&self, seat: usize) -> Option<usize> {
+        let n = self.seats.len();
+        (1..=n).map(|i| (seat + i) % n).find(|seat| self.active().contains(seat))
+    }
+
+    /// Where an active seat sits for the next hand, None for anyone not dealt in
+    pub fn position(&self, seat: usize) -> Option<Position> {
+        let active = self.active();
+        let button = active.iter().position(|&s| s == self.button)?;
+        let i = active.iter().position(|&s| s == seat)?;
+        Some(Position::left_of_button((i + active.len() - button) % active.len(), active.len()))
+    }
+
+    /// Deal a hand to the active seats, moving the button first if its seat is no longer active.
+    /// Seat k of the hand is active()[k]
+    pub fn deal<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Result<HandState, &'static str> {
+        let (stacks, button) = self.dealt_in()?;
+        Ok(HandState::new(&stacks, button, self.small_blind, self.big_blind, rng))
+    }
+
+    /// The stacks of the active seats and which of them has the button, for dealing a hand some
+    /// other way than deal. Moves the button first just as deal does
+    pub fn dealt_in(&mut self) -> Result<(Vec<Chips>, usize), &'static str> {
+        if !self.active().contains(&self.button) {
+            self.button = self.next_active(self.button).ok_or("no one to deal to")?;
+        }
+        let active = self.active();
+        if active.len() < 2 {
+            return Err("need at least two players with chips");
+        }
+        let stacks: Vec<Chips> = active.iter().map(|&seat| self.seats[seat].as_ref().unwrap().stack).collect();
+        let button = active.iter().position(|&seat| seat == self.button).unwrap();
+        Ok((stacks, button))
+    }
+
+    /// Take the stacks from a finished hand dealt by deal, then move the button on a seat
+    pub fn finish(&mut self, hand: &HandState) {
+        debug_assert!(hand.is_over());
+        for (seat, hand_seat) in self.active().into_iter().zip(&hand.seats) {
+            self.seats[seat].as_mut().unwrap().stack = hand_seat.stack;
+        }
+        if let Some(button) = self.next_active(self.button) {
+            self.button = button;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str, stack: Chips) -> Player {
+        Player { name: name.to_string(), stack }
+    }
+
+    #[test]
+    fn test_table() {
+        let mut table = Table::new(6, 1, 2);
+        table.sit(1, player("a", 100)).unwrap();
+        table.sit(3, player("b", 100)).unwrap();
+        table.sit(4, player("c", 100)).unwrap();
+        assert_eq!(table.sit(3, player("d", 100)), Err("seat taken"));
+        assert_eq!(table.active(), vec![1, 3, 4]);
+
+        // the button is on an empty seat, so it moves to seat 1 before the deal
+        let mut hand = table.deal(&mut rand::rng()).unwrap();
+        assert_eq!(table.button, 1);
+        assert_eq!(table.position(3), Some(Position::SmallBlind));
+        assert_eq!(table.position(4), Some(Position::BigBlind));
+        assert_eq!(hand.position(0), Position::Button);
+
+        // everyone folds to the big blind
+        hand.act(Action::Fold).unwrap();
+        hand.act(Action::Fold).unwrap();
+        table.finish(&hand);
+        assert_eq!(table.seats[4].as_ref().unwrap().stack, 101);
+        assert_eq!(table.button, 3);
+
+        // heads up once someone leaves, the button posting the small blind
+        table.leave(1);
+        assert_eq!(table.position(3), Some(Position::SmallBlind));
+        let hand = table.deal(&mut rand::rng()).unwrap();
+        assert_eq!(hand.seats[0].bet, 1);
+        assert_eq!(table.position(1), None);
+    }
+}
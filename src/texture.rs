@@ -0,0 +1,138 @@
+//! Board texture and the nuts, the structural questions asked of a board over and over (every
+//! redraw of a screen, every flop of a report). Neither depends on which suit is which, so
+//! both are worked out once for each canonical board (see iso) and kept
+
+use crate::card::*;
+use crate::category::*;
+use crate::eval::*;
+use crate::iso::*;
+use std::{collections::HashMap, fmt::{Display, Formatter}, sync::{LazyLock, Mutex}};
+use itertools::Itertools;
+
+
+/// The shape of a board, what decides which hands it favours
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Texture {
+    /// Most board cards of one suit
+    pub suited: usize,
+    /// Most board cards of one rank
+    pub paired: usize,
+    /// Whether two hole cards can make a straight with it
+    pub straight_possible: bool,
+    pub high: Rank,
+}
+
+impl Texture {
+    pub fn of(board: &[Card]) -> Texture {
+        analysis(board).0
+    }
+
+    pub fn flush_possible(&self) -> bool {
+        self.suited >= 3
+    }
+
+    fn classify(board: &[Card]) -> Texture {
+        let most = |counts: Vec<usize>| counts.into_iter().max().unwrap_or(0);
+        let ranks: Vec<usize> = board.iter().map(|card| usize::from(card.rank)).collect();
+        // the ace plays low too, as -1 under the two
+        let low_ranks: Vec<isize> = ranks.iter().flat_map(|&rank| if rank == 12 { vec![-1, 12] } else { vec![rank as isize] }).collect();
+        let straight_possible = (-1..=8).any(|low| (low..low + 5).filter(|rank| low_ranks.contains(rank)).count() >= 3);
+        Texture {
+            suited: most(board.iter().counts_by(|card| card.suit).into_values().collect()),
+            paired: most(board.iter().counts_by(|card| card.rank).into_values().collect()),
+            straight_possible,
+            high: board.iter().map(|card| card.rank).max().expect("an empty board"),
+        }
+    }
+}
+
+impl Display for Texture {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let suits = match self.suited {
+            1 => "rainbow".to_string(),
+            2 => "two tone".to_string(),
+            n => format!("{} of a suit", n),
+        };
+        let pairs = match self.paired {
+            1 => "unpaired",
+            2 => "paired",
+            3 => "trips",
+            _ => "quads",
+        };
+        let straights = if self.straight_possible { "connected" } else { "disconnected" };
+        write!(f, "{}, {}, {}, {} high", suits, pairs, straights, self.high.symbol())
+    }
+}
+
+/// The best hand anyone can hold on a board
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Nuts {
+    pub category: Category,
+    /// Hole card combos that make it
+    pub combos: usize,
+}
+
+impl Nuts {
+    pub fn of(board: &[Card]) -> Nuts {
+        analysis(board).1
+    }
+
+    fn find(board: &[Card]) -> Nuts {
+        let deck: Vec<Card> = Card::get_deck().into_iter().filter(|card| !board.contains(card)).collect();
+        let pairs: Vec<(Card, Card)> = deck.iter().copied().tuple_combinations().collect();
+        let scores: Vec<_> = pairs.iter().map(|pair| get_best_score(pair, board)).collect();
+        let best = *scores.iter().min().unwrap();
+        let nut_pair = pairs[scores.iter().position(|&score| score == best).unwrap()];
+        Nuts {
+            category: Category::of(&nut_pair, board),
+            combos: scores.iter().filter(|&&score| score == best).count(),
+        }
+    }
+}
+
+/// What's kept of a board
+type Analysis = (Texture, Nuts);
+
+/// Texture and nuts of every board asked about so far, by canonical board
+static ANALYSES: LazyLock<Mutex<HashMap<Vec<Card>, Analysis>>> = LazyLock::new(Default::default);
+
+/// Texture and nuts of a flop, turn or river, from the cache if an isomorphic board has been
+/// seen before
+fn analysis(board: &[Card]) -> Analysis {
+    assert!((3..=5).contains(&board.len()), "a flop, turn or river");
+    let canonical = canonicalize(board);
+    if let Some(&analysis) = ANALYSES.lock().unwrap().get(&canonical) {
+        return analysis;
+    }
+    // worked out without the lock held, so other boards aren't kept waiting
+    let analysis = (Texture::classify(&canonical), Nuts::find(&canonical));
+    ANALYSES.lock().unwrap().insert(canonical, analysis);
+    analysis
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_texture() {
+        let board = parse_cards("AhKhTc").unwrap();
+        let texture = Texture::of(&board);
+        assert_eq!(texture, Texture { suited: 2, paired: 1, straight_possible: true, high: Rank::Ace });
+        assert_eq!(texture.to_string(), "two tone, unpaired, connected, A high");
+        // any queen and jack make broadway
+        assert_eq!(Nuts::of(&board), Nuts { category: Category::Straight, combos: 16 });
+
+        // the same board with the suits relabelled comes out of the cache
+        let isomorphic = parse_cards("TdKsAs").unwrap();
+        assert!(ANALYSES.lock().unwrap().contains_key(&canonicalize(&isomorphic)));
+        assert_eq!((Texture::of(&isomorphic), Nuts::of(&isomorphic)), (texture, Nuts::of(&board)));
+
+        let board = parse_cards("9s9d4s7sTc").unwrap();
+        assert_eq!(Texture::of(&board).to_string(), "3 of a suit, paired, connected, T high");
+        // no two spades make a straight flush, so it's quads with the other two nines
+        assert_eq!(Nuts::of(&board), Nuts { category: Category::Quads, combos: 1 });
+        assert!(!Texture::of(&parse_cards("AcKd2h").unwrap()).straight_possible);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::card::*;
+use crate::category::*;
+use crate::eval::*;
+use std::{collections::BTreeMap, fmt::{Display, Formatter}};
+use rand::{seq::IndexedRandom, Rng};
+
+
+/// What a holding has on the flop: its made hand and any draw to something better
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct FlopState {
+    pub made: Category,
+    pub draw: Draw,
+}
+
+impl FlopState {
+    pub fn of(pair: &(Card, Card), flop: &[Card]) -> FlopState {
+        FlopState {
+            made: Category::of(pair, flop),
+            draw: Draw::of(pair, flop),
+        }
+    }
+}
+
+impl Display for FlopState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.draw {
+            Draw::Nothing => write!(f, "{}", self.made),
+            draw => write!(f, "{} + {}", self.made, draw),
+        }
+    }
+}
+
+/// Counts of (flop state -> river category) over holdings, flops and runouts
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TransitionMatrix {
+    counts: BTreeMap<FlopState, [usize; 9]>,
+}
+
+impl TransitionMatrix {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, from: FlopState, to: Category) {
+        self.record_n(from, to, 1);
+    }
+
+    pub fn record_n(&mut self, from: FlopState, to: Category, n: usize) {
+        self.counts.entry(from).or_insert([0; 9])[usize::from(to)] += n;
+    }
+
+    /// Record every turn and river for the pair on this flop
+    pub fn add_flop(&mut self, pair: &(Card, Card), flop: &[Card]) {
+        debug_assert_eq!(flop.len(), 3);
+        let from = FlopState::of(pair, flop);
+
+        let mut deck: Vec<Card> = Card::get_deck();
+        deck.retain(|card| !flop.contains(card) && *card != pair.0 && *card != pair.1);
+
+        for_each_runout(flop, &deck, &[&[pair.0, pair.1], flop], |board, _, weight| {
+            self.record_n(from, Category::from_score(get_best_score(pair, board)), weight);
+        });
+    }
+
+    /// Flop states seen, best made hand first
+    pub fn states(&self) -> impl Iterator<Item = FlopState> + '_ {
+        self.counts.keys().rev().copied()
+    }
+
+    /// Share of all observations that started in this state
+    pub fn frequency(&self, from: FlopState) -> f64 {
+        let total: usize = self.counts.values().flatten().sum();
+        let count: usize = self.counts.get(&from).map_or(0, |row| row.iter().sum());
+        if total == 0 { 0.0 } else { count as f64 / total as f64 }
+    }
+
+    /// Probability of each river category (indexed by Category) given the flop state
+    pub fn row(&self, from: FlopState) -> Option<[f64; 9]> {
+        let row = self.counts.get(&from)?;
+        let total: usize = row.iter().sum();
+        Some(row.map(|count| count as f64 / total as f64))
+    }
+
+    pub fn probability(&self, from: FlopState, to: Category) -> f64 {
+        self.row(from).map_or(0.0, |row| row[usize::from(to)])
+    }
+
+    /// Probability of finishing with to or better
+    pub fn improve_probability(&self, from: FlopState, to: Category) -> f64 {
+        self.row(from).map_or(0.0, |row| row[usize::from(to)..].iter().sum())
+    }
+}
+
+impl Display for TransitionMatrix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for from in self.states() {
+            writeln!(f, "{} ({:.1}%)", from, self.frequency(from) * 100.0)?;
+            let row = self.row(from).unwrap();
+            for to in Category::ALL_CATEGORIES.iter().rev() {
+                if row[usize::from(*to)] > 0.0 {
+                    writeln!(f, "    -> {:<16} {:>5.1}%", to.to_string(), row[usize::from(*to)] * 100.0)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Transitions for a holding (or every combo of a range) on a known flop
+/// holdings that clash with the flop are skipped
+pub fn flop_transitions(holdings: &[(Card, Card)], flop: &[Card]) -> TransitionMatrix {
+    let mut matrix = TransitionMatrix::new();
+    for pair in holdings {
+        if flop.contains(&pair.0) || flop.contains(&pair.1) {
+            continue;
+        }
+        matrix.add_flop(pair, flop);
+    }
+    matrix
+}
+
+/// Transitions for a holding (or range) over n random flops, each with every turn and river
+pub fn sample_transitions<R: Rng + ?Sized>(holdings: &[(Card, Card)], n: usize, rng: &mut R) -> TransitionMatrix {
+    let mut matrix = TransitionMatrix::new();
+    if holdings.is_empty() {
+        return matrix;
+    }
+    for _ in 0..n {
+        let pair = holdings.choose(rng).unwrap();
+        let mut deck: Vec<Card> = Card::get_deck();
+        deck.retain(|card| *card != pair.0 && *card != pair.1);
+        let flop: Vec<Card> = deck.choose_multiple(rng, 3).copied().collect();
+        matrix.add_flop(pair, &flop);
+    }
+    matrix
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combinatorics::*;
+
+    #[test]
+    fn test_flop_transitions() {
+        let flop = [Card::new(Rank::Ace, Suit::Hearts),
+                    Card::new(Rank::King, Suit::Hearts),
+                    Card::new(Rank::Four, Suit::Spades)];
+        let pair = (Card::new(Rank::Two, Suit::Hearts), Card::new(Rank::Three, Suit::Hearts));
+        let matrix = flop_transitions(&[pair], &flop);
+
+        let from = FlopState { made: Category::HighCard, draw: Draw::Combo };
+        assert_eq!(matrix.states().collect::<Vec<_>>(), vec![from]);
+        assert_eq!(matrix.frequency(from), 1.0);
+
+        let row = matrix.row(from).unwrap();
+        assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        // at least one of the 9 hearts in 2 cards
+        let flush_or_better = hit_probability(9, 47, 2);
+        assert!(matrix.improve_probability(from, Category::Flush) >= flush_or_better - 1e-9);
+        assert!(matrix.probability(from, Category::Straight) > 0.0);
+        assert_eq!(matrix.probability(from, Category::Quads), 0.0);
+    }
+}
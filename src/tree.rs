@@ -0,0 +1,421 @@
+//! Postflop betting trees for two players, built from a config of `key = value` lines, e.g.
+//!
+//! ```text
+//! street = turn
+//! pot = 100
+//! stack = 400
+//! bets = 0.5, 1
+//! raises = 1
+//! raise-cap = 2
+//! all-in = true
+//! ```
+
+use crate::bucket::*;
+use crate::card::*;
+use crate::eval::*;
+use crate::game::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::street::*;
+use std::fmt::{Display, Formatter};
+
+
+/// How a tree is built: the pot and effective stack where it starts, and the bet and raise sizes
+/// allowed on every street
+#[derive(Debug, PartialEq, Clone)]
+pub struct TreeConfig {
+    pub street: Street,
+    pub pot: Chips,
+    /// Behind, for each player
+    pub stack: Chips,
+    /// Bets as fractions of the pot
+    pub bet_sizes: Vec<f64>,
+    /// Raises as fractions of the pot after calling
+    pub raise_sizes: Vec<f64>,
+    /// Raises allowed a street after the first bet
+    pub raise_cap: usize,
+    /// Whether moving all in is always one of the choices
+    pub all_in: bool,
+}
+
+impl Default for TreeConfig {
+    fn default() -> Self {
+        TreeConfig {
+            street: Street::River,
+            pot: 100,
+            stack: 200,
+            bet_sizes: vec![0.5, 1.0],
+            raise_sizes: vec![1.0],
+            raise_cap: 1,
+            all_in: true,
+        }
+    }
+}
+
+impl TreeConfig {
+    /// Parse `key = value` lines (street, pot, stack, bets, raises, raise-cap, all-in) over the
+    /// defaults, ignoring blanks and # comments
+    pub fn parse(text: &str) -> Result<TreeConfig, String> {
+        let mut config = TreeConfig::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {}: expected key = value", number + 1));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = |e: &dyn Display| format!("{}: {}", key, e);
+            let sizes = |value: &str| value
+                .split(',')
+                .map(|size| size.trim().parse::<f64>().map_err(|e| invalid(&e)))
+                .collect::<Result<Vec<f64>, String>>();
+            match key {
+                "street" => config.street = value.parse().map_err(|e| invalid(&e))?,
+                "pot" => config.pot = value.parse().map_err(|e| invalid(&e))?,
+                "stack" => config.stack = value.parse().map_err(|e| invalid(&e))?,
+                "bets" => config.bet_sizes = sizes(value)?,
+                "raises" => config.raise_sizes = sizes(value)?,
+                "raise-cap" => config.raise_cap = value.parse().map_err(|e| invalid(&e))?,
+                "all-in" => config.all_in = value.parse().map_err(|e| invalid(&e))?,
+                _ => return Err(format!("unknown key {}", key)),
+            }
+        }
+        if config.street == Street::Preflop {
+            return Err("street: trees start after the flop".to_string());
+        }
+        Ok(config)
+    }
+}
+
+/// A choice in the tree, with amounts in chips: bets are how much, raises what to
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum TreeAction {
+    Fold,
+    Check,
+    Call,
+    Bet(Chips),
+    Raise(Chips),
+}
+
+impl Display for TreeAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TreeAction::Fold => write!(f, "f"),
+            TreeAction::Check => write!(f, "x"),
+            TreeAction::Call => write!(f, "c"),
+            TreeAction::Bet(amount) => write!(f, "b{}", amount),
+            TreeAction::Raise(to) => write!(f, "r{}", to),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NodeKind {
+    /// Each choice with the index of the node it leads to
+    Decision { player: usize, actions: Vec<(TreeAction, usize)> },
+    /// The next board card comes, then the tree goes on at the index
+    Deal(usize),
+    /// This player folded
+    Fold(usize),
+    Showdown,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TreeNode {
+    pub kind: NodeKind,
+    pub street: Street,
+    /// What each player has put in over the hand, counting half of the starting pot each
+    pub put_in: [Chips; 2],
+    /// The actions to get here, streets split by /
+    pub history: String,
+}
+
+impl TreeNode {
+    pub fn pot(&self) -> Chips {
+        self.put_in[0] + self.put_in[1]
+    }
+}
+
+/// Every way the betting can go from the config's street to the river, player 0 acting first
+/// each street. Node 0 is the root
+#[derive(Debug, PartialEq, Clone)]
+pub struct GameTree {
+    pub config: TreeConfig,
+    pub nodes: Vec<TreeNode>,
+}
+
+/// Where the building has got to on a street
+#[derive(Debug, Clone)]
+struct Betting {
+    street: Street,
+    put_in: [Chips; 2],
+    bets: [Chips; 2],
+    /// Bets and raises so far this street
+    raises: usize,
+    player: usize,
+    history: String,
+}
+
+impl GameTree {
+    pub fn new(config: TreeConfig) -> GameTree {
+        let mut tree = GameTree { config, nodes: Vec::new() };
+        let put_in = [tree.share(0), tree.share(1)];
+        tree.decision(Betting { street: tree.config.street, put_in, bets: [0; 2], raises: 0, player: 0, history: String::new() });
+        tree
+    }
+
+    pub fn root(&self) -> &TreeNode {
+        &self.nodes[0]
+    }
+
+    fn push(&mut self, kind: NodeKind, street: Street, put_in: [Chips; 2], history: &str) -> usize {
+        self.nodes.push(TreeNode { kind, street, put_in, history: history.to_string() });
+        self.nodes.len() - 1
+    }
+
+    /// Each player's half of the starting pot, the odd chip to player 1
+    fn share(&self, player: usize) -> Chips {
+        if player == 0 { self.config.pot / 2 } else { self.config.pot - self.config.pot / 2 }
+    }
+
+    fn behind(&self, put_in: [Chips; 2], player: usize) -> Chips {
+        self.config.stack + self.share(player) - put_in[player]
+    }
+
+    /// The street is over: on to the next one, or the showdown once the river's done or
+    /// someone's all in
+    fn street_over(&mut self, betting: &Betting) -> usize {
+        let Some(next) = betting.street.next() else {
+            return self.push(NodeKind::Showdown, betting.street, betting.put_in, &betting.history);
+        };
+        let index = self.push(NodeKind::Deal(0), betting.street, betting.put_in, &betting.history);
+        let history = format!("{}/", betting.history);
+        let child = if self.behind(betting.put_in, 0) == 0 || self.behind(betting.put_in, 1) == 0 {
+            // nothing left to bet, so just deal out the board
+            self.street_over(&Betting { street: next, history, ..betting.clone() })
+        } else {
+            self.decision(Betting { street: next, bets: [0; 2], raises: 0, player: 0, history, ..betting.clone() })
+        };
+        self.nodes[index].kind = NodeKind::Deal(child);
+        index
+    }
+
+    fn decision(&mut self, betting: Betting) -> usize {
+        let index = self.push(NodeKind::Showdown, betting.street, betting.put_in, &betting.history);
+        let player = betting.player;
+        let to_call = betting.bets[1 - player] - betting.bets[player];
+        let behind = self.behind(betting.put_in, player);
+        let pot = betting.put_in[0] + betting.put_in[1];
+
+        // each choice as what it puts in, and whether it ends the street
+        let mut choices: Vec<(TreeAction, Chips)> = Vec::new();
+        if to_call == 0 {
+            choices.push((TreeAction::Check, 0));
+            let mut amounts: Vec<Chips> = self.config.bet_sizes.iter().map(|size| ((size * pot as f64).round() as Chips).clamp(1, behind)).collect();
+            if self.config.all_in {
+                amounts.push(behind);
+            }
+            amounts.sort_unstable();
+            amounts.dedup();
+            choices.extend(amounts.into_iter().filter(|&amount| amount > 0).map(|amount| (TreeAction::Bet(amount), amount)));
+        } else {
+            choices.push((TreeAction::Fold, 0));
+            choices.push((TreeAction::Call, to_call.min(behind)));
+            let opponent_behind = self.behind(betting.put_in, 1 - player);
+            if betting.raises <= self.config.raise_cap && behind > to_call && opponent_behind > 0 {
+                let after_call = (pot + to_call) as f64;
+                let mut amounts: Vec<Chips> = self.config.raise_sizes
+                    .iter()
+                    .map(|size| (to_call + (size * after_call).round() as Chips).min(behind))
+                    .collect();
+                if self.config.all_in {
+                    amounts.push(behind);
+                }
+                amounts.sort_unstable();
+                amounts.dedup();
+                choices.extend(amounts.into_iter().map(|amount| (TreeAction::Raise(betting.bets[player] + amount), amount)));
+            }
+        }
+
+        let mut actions = Vec::new();
+        for (action, amount) in choices {
+            let mut next = betting.clone();
+            next.put_in[player] += amount;
+            next.bets[player] += amount;
+            next.player = 1 - player;
+            next.history.push_str(&action.to_string());
+            let child = match action {
+                TreeAction::Fold => self.push(NodeKind::Fold(player), next.street, next.put_in, &next.history),
+                TreeAction::Call => self.street_over(&next),
+                // checking behind
+                TreeAction::Check if player == 1 => self.street_over(&next),
+                TreeAction::Check => self.decision(next),
+                TreeAction::Bet(_) | TreeAction::Raise(_) => {
+                    next.raises += 1;
+                    self.decision(next)
+                }
+            };
+            actions.push((action, child));
+        }
+        self.nodes[index].kind = NodeKind::Decision { player, actions };
+        index
+    }
+
+    /// Decision points for each player
+    pub fn decisions(&self) -> [usize; 2] {
+        let mut count = [0; 2];
+        for node in &self.nodes {
+            if let NodeKind::Decision { player, .. } = node.kind {
+                count[player] += 1;
+            }
+        }
+        count
+    }
+}
+
+/// A tree with cards: each player is dealt a hand from their range, the board runs out as the
+/// tree says, and the hands are shown down at the end. The solver can work on it directly,
+/// though it's only quick enough starting on the river
+#[derive(Debug, Clone)]
+pub struct TreeGame {
+    pub tree: GameTree,
+    pub board: Vec<Card>,
+    pub ranges: [HandRange; 2],
+    /// Buckets for each player's hands on the starting board, so hands in the same bucket
+    /// share information sets (and a strategy) rather than each having their own
+    pub abstractions: [Option<Abstraction>; 2],
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TreeState {
+    pub node: usize,
+    pub holes: Option<[(Card, Card); 2]>,
+    pub board: Vec<Card>,
+}
+
+impl TreeGame {
+    pub fn new(tree: GameTree, board: Vec<Card>, ranges: [HandRange; 2]) -> Self {
+        debug_assert_eq!(board.len(), tree.config.street.board_len());
+        TreeGame { tree, board, ranges, abstractions: [None, None] }
+    }
+
+    pub fn with_abstraction(mut self, player: usize, abstraction: Abstraction) -> Self {
+        debug_assert_eq!(abstraction.board, self.board);
+        self.abstractions[player] = Some(abstraction);
+        self
+    }
+}
+
+impl ExtensiveGame for TreeGame {
+    type State = TreeState;
+
+    fn root(&self) -> TreeState {
+        TreeState { node: 0, holes: None, board: self.board.clone() }
+    }
+
+    fn node(&self, state: &TreeState) -> Node<TreeState> {
+        let Some(holes) = state.holes else {
+            let mut deals = Vec::new();
+            for (a, weight_a) in self.ranges[0].without(&self.board).combos() {
+                for (b, weight_b) in self.ranges[1].without(&[&self.board[..], &[a.0, a.1]].concat()).combos() {
+                    deals.push((weight_a * weight_b, TreeState { holes: Some([a, b]), ..state.clone() }));
+                }
+            }
+            let total: f64 = deals.iter().map(|(weight, _)| weight).sum();
+            return Node::Chance(deals.into_iter().map(|(weight, state)| (weight / total, state)).collect());
+        };
+
+        let node = &self.tree.nodes[state.node];
+        match &node.kind {
+            NodeKind::Fold(player) => {
+                let lost = node.put_in[*player] as f64;
+                Node::Terminal(if *player == 0 { -lost } else { lost })
+            }
+            NodeKind::Showdown => {
+                let scores = holes.map(|pair| get_best_score(&pair, &state.board));
+                // lower scores are better
+                Node::Terminal(match scores[0].cmp(&scores[1]) {
+                    std::cmp::Ordering::Less => node.put_in[1] as f64,
+                    std::cmp::Ordering::Greater => -(node.put_in[0] as f64),
+                    std::cmp::Ordering::Equal => 0.0,
+                })
+            }
+            &NodeKind::Deal(child) => {
+                let used: Vec<Card> = [&state.board[..], &[holes[0].0, holes[0].1, holes[1].0, holes[1].1]].concat();
+                let deck = deck_without(&used);
+                let p = 1.0 / deck.len() as f64;
+                Node::Chance(deck.into_iter().map(|card| (p, TreeState { node: child, holes: state.holes, board: [&state.board[..], &[card]].concat() })).collect())
+            }
+            NodeKind::Decision { player, actions } => {
+                let (a, b) = holes[*player];
+                let hand = match self.abstractions[*player].as_ref().and_then(|abstraction| abstraction.bucket(&(a, b))) {
+                    Some(bucket) => format!("#{}", bucket),
+                    None => format!("{}{}", a, b),
+                };
+                let board: String = state.board.iter().map(|card| card.to_string()).collect();
+                Node::Decision {
+                    player: *player,
+                    info_set: format!("{}|{}|{}", hand, board, node.history),
+                    actions: actions.iter().map(|(action, child)| (action.to_string(), TreeState { node: *child, ..state.clone() })).collect(),
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_tree() {
+        let config = TreeConfig::parse("street = river\npot = 100\nstack = 100 # behind\nbets = 0.5\nraises = 1\nraise-cap = 1").unwrap();
+        let tree = GameTree::new(config);
+        // check or bet 50 or 100 (all in), then check behind, bet, or fold, call or raise
+        let NodeKind::Decision { actions, .. } = &tree.root().kind else { panic!() };
+        assert_eq!(actions.iter().map(|(action, _)| *action).collect::<Vec<_>>(), [TreeAction::Check, TreeAction::Bet(50), TreeAction::Bet(100)]);
+        let (_, half) = actions[1];
+        let NodeKind::Decision { player: 1, actions } = &tree.nodes[half].kind else { panic!() };
+        // a pot raise on top of the half pot bet is all in
+        assert_eq!(actions.iter().map(|(action, _)| *action).collect::<Vec<_>>(), [TreeAction::Fold, TreeAction::Call, TreeAction::Raise(100)]);
+        assert_eq!(tree.nodes[actions[2].1].pot(), 250);
+        assert!(tree.nodes.iter().all(|node| node.put_in.iter().all(|&put_in| put_in <= 150)));
+        assert!(TreeConfig::parse("street = preflop").is_err());
+        assert!(TreeConfig::parse("bets = big").is_err());
+
+        // deeper trees deal the cards between streets
+        let turn = GameTree::new(TreeConfig { street: Street::Turn, ..TreeConfig::default() });
+        assert!(turn.nodes.iter().any(|node| matches!(node.kind, NodeKind::Deal(_))));
+        assert!(turn.decisions()[0] > tree.decisions()[0]);
+
+        // the nuts always bets into a bluff catcher
+        let board = parse_cards("AsKsQs2d3c").unwrap();
+        let nuts = (Card::new(Rank::Jack, Suit::Spades), Card::new(Rank::Ten, Suit::Spades));
+        let air = (Card::new(Rank::Seven, Suit::Hearts), Card::new(Rank::Six, Suit::Hearts));
+        let catcher = (Card::new(Rank::Ace, Suit::Hearts), Card::new(Rank::Two, Suit::Hearts));
+        let game = TreeGame::new(tree, board.clone(), [HandRange::from_combos([nuts, air]), HandRange::from_combos([catcher])]);
+        let mut cfr = Cfr::new(game);
+        for _ in 0..500 {
+            cfr.iterate();
+        }
+        let board: String = board.iter().map(|card| card.to_string()).collect();
+        let profile = cfr.average_strategy();
+        assert!(profile.probabilities(&format!("{}{}|{}|", nuts.0, nuts.1, board), 3)[0] < 0.05);
+        // and the catcher calls a shove about half the time, to keep the bluffs honest
+        let calls = profile.probabilities(&format!("{}{}|{}|b100", catcher.0, catcher.1, board), 2)[1];
+        assert!(calls > 0.3 && calls < 0.7);
+
+        // in one bucket the nuts and the air have to play the same way
+        let mut game = cfr.game.clone();
+        let abstraction = Abstraction { board: game.board.clone(), buckets: [(nuts, 0), (air, 0)].into_iter().collect(), centres: vec![vec![1.0]] };
+        game = game.with_abstraction(0, abstraction);
+        let mut cfr = Cfr::new(game);
+        cfr.iterate();
+        let profile = cfr.average_strategy();
+        assert!(profile.info_sets.contains_key(&format!("#0|{}|", board)));
+        assert!(!profile.info_sets.keys().any(|key| key.starts_with(&format!("{}{}", nuts.0, nuts.1))));
+    }
+}
@@ -0,0 +1,83 @@
+//! Entry points for a browser equity calculator. Everything goes in and out as strings, JSON for
+//! results and errors alike ({"error": ...}), and nothing here needs threads or a clock, which
+//! the browser doesn't give a wasm module without help: equity is sampled for a fixed number of
+//! deals from a seed. Nothing is exported to JavaScript yet, a wasm build has to bind these itself
+
+use crate::card::*;
+use crate::eval::*;
+use crate::json::*;
+use crate::monte_carlo::*;
+use crate::range::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+
+/// The cards in a string like "AhKd" as a list, e.g. {"cards": ["Ah", "Kd"]}
+pub fn parse_cards_json(s: &str) -> String {
+    match parse_cards(s) {
+        Ok(cards) => Json::object([("cards", cards.iter().map(Card::symbol).collect::<Vec<_>>().into())]),
+        Err(e) => error(e),
+    }.to_string()
+}
+
+/// Equity of a hand on a board (empty preflop) against opponents each dealt from the range
+/// (empty for any two cards), from samples deals
+pub fn equity_json(hand: &str, board: &str, range: &str, opponents: usize, samples: usize, seed: u64) -> String {
+    match equity(hand, board, range, opponents, samples, seed) {
+        Ok(json) => json,
+        Err(e) => error(e),
+    }.to_string()
+}
+
+fn equity(hand: &str, board: &str, range: &str, opponents: usize, samples: usize, seed: u64) -> Result<Json, &'static str> {
+    let pair = match parse_cards(hand)?[..] {
+        [a, b] => (a, b),
+        _ => return Err("Expected two hole cards"),
+    };
+    let board = parse_cards(board)?;
+    let range = if range.trim().is_empty() { HandRange::full() } else { range.parse()? };
+    if !is_valid_deal(&board, &[pair]) || !matches!(board.len(), 0 | 3 | 4 | 5) {
+        return Err("Invalid deal");
+    }
+    if !(1..=9).contains(&opponents) || samples == 0 {
+        return Err("Opponents has to be 1-9 and samples at least 1");
+    }
+    if range.without(&[&board[..], &[pair.0, pair.1]].concat()).is_empty() {
+        return Err("Every combo in the range is blocked");
+    }
+
+    let samplers = vec![RangeSampler::new(&range); opponents];
+    let fixed = [&board[..], &[pair.0, pair.1]].concat();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (mut sum, mut sum_squares) = (0.0, 0.0);
+    for _ in 0..samples {
+        let share = deal_vs_ranges(&pair, &samplers, &board, &fixed, &mut rng).ok_or("The ranges can't all be dealt together")?;
+        sum += share;
+        sum_squares += share * share;
+    }
+    let n = samples as f64;
+    let equity = sum / n;
+    let std_error = ((sum_squares / n - equity * equity).max(0.0) / n).sqrt();
+    Ok(Json::object([("equity", equity.into()), ("std_error", std_error.into()), ("samples", samples.into())]))
+}
+
+fn error(e: &str) -> Json {
+    Json::object([("error", e.into())])
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_web() {
+        assert_eq!(parse_cards_json("AhKd"), r#"{"cards":["Ah","Kd"]}"#);
+        assert_eq!(parse_cards_json("Ahx"), r#"{"error":"Invalid card list"}"#);
+        let result = Json::parse(&equity_json("AhAd", "", "", 1, 2000, 7)).unwrap();
+        assert!((result.get("equity").and_then(Json::as_f64).unwrap() - 0.85).abs() < 0.03);
+        // the same seed deals the same cards
+        assert_eq!(equity_json("AhAd", "", "", 1, 2000, 7), equity_json("AhAd", "", "", 1, 2000, 7));
+        assert!(equity_json("AhAh", "", "", 1, 100, 7).contains("error"));
+        assert!(equity_json("AhAd", "AsAc2d", "AA", 1, 100, 7).contains("blocked"));
+    }
+}
@@ -0,0 +1,199 @@
+use crate::card::*;
+use crate::category::*;
+use crate::eval::*;
+use crate::hand::*;
+use crate::monte_carlo::*;
+use crate::score::*;
+use std::fmt::{Display, Formatter};
+use itertools::Itertools;
+use rand::{seq::IndexedRandom, Rng};
+
+
+/// A card from a deck that may have jokers in it
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum WildCard {
+    Card(Card),
+    Joker,
+}
+
+impl Display for WildCard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WildCard::Card(card) => write!(f, "{}", card),
+            WildCard::Joker => write!(f, "Jo"),
+        }
+    }
+}
+
+/// parse_cards with "Jo" for a joker, e.g. "AhJoKd"
+pub fn parse_wild_cards(s: &str) -> Result<Vec<WildCard>, &'static str> {
+    let chars: Vec<char> = s.chars().filter(|c| !c.is_whitespace() && *c != ',').collect();
+    if !chars.len().is_multiple_of(2) {
+        return Err("Invalid card list");
+    }
+    chars.chunks(2)
+        .map(|c| match c {
+            ['J' | 'j', 'o' | 'O'] => Ok(WildCard::Joker),
+            _ => Ok(WildCard::Card(Card::new(Rank::try_from(c[0])?, Suit::try_from(c[1])?))),
+        })
+        .collect()
+}
+
+/// What's wild: jokers added to the deck, and any ranks that play as wild (deuces wild)
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Wilds {
+    pub jokers: usize,
+    pub ranks: Vec<Rank>,
+}
+
+impl Wilds {
+    pub fn is_wild(&self, card: WildCard) -> bool {
+        match card {
+            WildCard::Card(card) => self.ranks.contains(&card.rank),
+            WildCard::Joker => true,
+        }
+    }
+
+    /// The full deck with the jokers
+    pub fn deck(&self) -> Vec<WildCard> {
+        Card::DECK
+            .into_iter()
+            .map(WildCard::Card)
+            .chain(std::iter::repeat_n(WildCard::Joker, self.jokers))
+            .collect()
+    }
+}
+
+/// Score of a hand with wild cards in it: five of a kind (aces best) beats everything, then
+/// the usual scores. Lower is better, as with Score
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct WildScore(u16);
+
+impl WildScore {
+    fn five_of_a_kind(rank: Rank) -> WildScore {
+        WildScore((usize::from(Rank::Ace) - usize::from(rank)) as u16)
+    }
+
+    fn made(score: Score) -> WildScore {
+        WildScore((Rank::ALL_RANKS.len() + score.index()) as u16)
+    }
+
+    pub fn is_five_of_a_kind(self) -> bool {
+        (self.0 as usize) < Rank::ALL_RANKS.len()
+    }
+
+    /// The usual score, unless it's five of a kind
+    pub fn score(self) -> Option<Score> {
+        (self.0 as usize).checked_sub(Rank::ALL_RANKS.len()).and_then(Score::from_index)
+    }
+
+    /// The made hand, unless it's five of a kind
+    pub fn category(self) -> Option<Category> {
+        self.score().map(|score| score.category())
+    }
+}
+
+/// Best score of exactly five cards, with the wilds becoming whatever cards make it best
+pub fn wild_score(cards: &[WildCard], wilds: &Wilds) -> WildScore {
+    debug_assert_eq!(cards.len(), 5);
+    let naturals: Vec<Card> = cards
+        .iter()
+        .filter(|&&card| !wilds.is_wild(card))
+        .map(|card| match card {
+            WildCard::Card(card) => *card,
+            WildCard::Joker => unreachable!(),
+        })
+        .collect();
+    if naturals.iter().map(|card| card.rank).all_equal() {
+        return WildScore::five_of_a_kind(naturals.first().map_or(Rank::Ace, |card| card.rank));
+    }
+
+    // suits only matter for a flush, which takes the naturals all being one suit
+    let flush_suit = naturals.iter().map(|card| card.suit).all_equal_value().ok();
+    let other_suit = Suit::ALL_SUITS.into_iter().find(|&suit| Some(suit) != flush_suit).unwrap();
+    let stand_ins: Vec<Card> = Rank::ALL_RANKS
+        .into_iter()
+        .flat_map(|rank| flush_suit.into_iter().chain([other_suit]).map(move |suit| Card::new(rank, suit)))
+        .collect();
+    let mut best: Option<Score> = None;
+    for picks in (0..stand_ins.len()).combinations_with_replacement(5 - naturals.len()) {
+        let hand = Hand::from_cards(naturals.iter().copied().chain(picks.iter().map(|&i| stand_ins[i])));
+        // stand ins copying a natural card can make hands that aren't in the table, but they're never the best
+        if let Some(&score) = SCORES.get(&hand) {
+            best = Some(best.map_or(score, |best| best.min(score)));
+        }
+    }
+    WildScore::made(best.unwrap())
+}
+
+/// Best score of any five of the cards. A wild can always copy a natural card, so the best hand
+/// uses as many wilds as it can
+pub fn best_wild_score(cards: &[WildCard], wilds: &Wilds) -> WildScore {
+    let (wild, natural): (Vec<WildCard>, Vec<WildCard>) = cards.iter().partition(|&&card| wilds.is_wild(card));
+    let used = wild.len().min(5);
+    natural
+        .into_iter()
+        .combinations(5 - used)
+        .map(|naturals| wild_score(&[&wild[..used], &naturals].concat(), wilds))
+        .min()
+        .unwrap()
+}
+
+/// Hold'em equity heads up against a random hand with wild cards, from n random deals
+pub fn eval_wild<R: Rng + ?Sized>(hole: &[WildCard; 2], board: &[WildCard], wilds: &Wilds, n: usize, rng: &mut R) -> Tally {
+    let mut deck = wilds.deck();
+    for card in [&hole[..], board].concat() {
+        let i = deck.iter().position(|&dealt| dealt == card).expect("card not in the deck, or dealt twice");
+        deck.swap_remove(i);
+    }
+
+    let mut tally = Tally::default();
+    for _ in 0..n {
+        let cards: Vec<WildCard> = deck.choose_multiple(rng, 2 + 5 - board.len()).copied().collect();
+        let community = [board, &cards[2..]].concat();
+        let score = |hole: &[WildCard]| best_wild_score(&[hole, &community].concat(), wilds);
+        match score(hole).cmp(&score(&cards[..2])) {
+            std::cmp::Ordering::Less => tally.wins += 1,
+            std::cmp::Ordering::Equal => tally.ties += 1,
+            std::cmp::Ordering::Greater => tally.losses += 1,
+        }
+    }
+    tally
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wild_score() {
+        let jokers = Wilds { jokers: 1, ..Wilds::default() };
+        let score = |s: &str, wilds: &Wilds| best_wild_score(&parse_wild_cards(s).unwrap(), wilds);
+        let royal = score("AhKhQhJhTh", &Wilds::default());
+        assert_eq!(royal.category(), Some(Category::StraightFlush));
+        assert_eq!(score("KhQhJhThJo", &jokers), royal);
+
+        let aces = score("AhAdAsAcJo", &jokers);
+        assert!(aces.is_five_of_a_kind() && aces < royal && aces.category().is_none());
+        assert!(score("AhAdAsKcJo", &jokers) > royal);
+
+        // deuces wild: the deuce fills in the six of hearts
+        let deuces = Wilds { ranks: vec![Rank::Two], ..Wilds::default() };
+        assert_eq!(score("2c3h4h5h7h", &deuces), score("3h4h5h6h7h", &Wilds::default()));
+        // and with a joker too, the best of seven uses both wilds
+        let both = Wilds { jokers: 1, ranks: vec![Rank::Two] };
+        assert_eq!(score("AhAd2cJo7s9d3c", &both), score("AhAdAsAc9d", &Wilds::default()));
+        assert!(score("KhKd2c2sJo", &both).is_five_of_a_kind());
+    }
+
+    #[test]
+    fn test_eval_wild() {
+        let wilds = Wilds { jokers: 2, ..Wilds::default() };
+        assert_eq!(wilds.deck().len(), 54);
+        let hole: [WildCard; 2] = parse_wild_cards("JoJo").unwrap().try_into().unwrap();
+        let tally = eval_wild(&hole, &[], &wilds, 200, &mut rand::rng());
+        assert_eq!(tally.samples(), 200);
+        assert!(tally.equity() > 0.8);
+    }
+}